@@ -56,6 +56,19 @@ sgx_status_t sgx_dcap_ra_get_ga(
     sgx_ra_context_t context,
     sgx_ec256_public_t *g_a);
 
+/*
+ * The sgx_dcap_mra_get_msg1 function fills in msg1 of the mutual remote
+ * attestation with the initiator's public key g_a. sgx_dcap_ra_msg1_t has
+ * no version field, so the C API only speaks protocol version 1.
+ *
+ * @param context   Context returned by sgx_mra_initiator_init.
+ * @param msg1      The msg1 returned, inside the enclave.
+ * @return sgx_status_t
+ */
+sgx_status_t sgx_dcap_mra_get_msg1(
+    sgx_ra_context_t context,
+    sgx_dcap_ra_msg1_t *msg1);
+
 sgx_status_t sgx_dcap_ura_proc_msg2(
     sgx_ra_context_t context,
     const sgx_dcap_ura_msg2_t *msg2,
@@ -84,6 +97,16 @@ sgx_status_t sgx_dcap_ra_get_msg3(
     sgx_dcap_ra_msg3_t *msg3,
     uint32_t msg3_size);
 
+/*
+ * The sgx_dcap_mra_get_msg3 function is the same as sgx_dcap_ra_get_msg3.
+ * msg3_size must be sizeof(sgx_dcap_ra_msg3_t) plus msg3->quote_size.
+ */
+sgx_status_t sgx_dcap_mra_get_msg3(
+    sgx_ra_context_t context,
+    const sgx_report_t* qe_report,
+    sgx_dcap_ra_msg3_t *msg3,
+    uint32_t msg3_size);
+
 /*
  * The sgx_ra_initiator_get_keys function is used to get the negotiated keys of a remote
  * attestation and key exchange session. This function should only be called after
@@ -114,6 +137,18 @@ sgx_status_t SGXAPI sgx_mra_initiator_get_peer_identity(
     sgx_ql_qv_result_t *quote_verification_result,
     sgx_dcap_enclave_identity_t *responder_identity);
 
+/*
+ * The sgx_ra_initiator_rekey function replaces SK and MK with keys derived
+ * from the current ones. The responder must call sgx_mra_responder_rekey the
+ * same number of times; data protected under the old keys must be finished
+ * before calling.
+ *
+ * @param context   Context returned by sgx_mra_initiator_init or sgx_ura_initiator_init.
+ * @return sgx_status_t
+ */
+sgx_status_t SGXAPI sgx_ra_initiator_rekey(
+    sgx_ra_context_t context);
+
 /*
  * Call the sgx_ra_initiator_close function to release the remote attestation and key
  * exchange context after the process is done and the context isn't needed
@@ -136,6 +171,11 @@ sgx_status_t SGXAPI sgx_ra_initiator_close(
 sgx_status_t SGXAPI sgx_mra_responder_init(
     sgx_ra_context_t *p_context);
 
+/*
+ * The sgx_dcap_mra_proc_msg1 function handles a protocol version 1 msg1. A
+ * responder whose policy requires a later version rejects it with
+ * SGX_ERROR_INVALID_VERSION.
+ */
 sgx_status_t sgx_dcap_mra_proc_msg1(
     sgx_ra_context_t context,
     const sgx_dcap_ra_msg1_t *msg1,
@@ -144,11 +184,45 @@ sgx_status_t sgx_dcap_mra_proc_msg1(
     sgx_report_t *report,
     sgx_quote_nonce_t *nonce);
 
+/*
+ * The sgx_dcap_mra_proc_msg1_ex function behaves like sgx_dcap_mra_proc_msg1
+ * but uses the caller-supplied nonce instead of a random one, so test vectors
+ * are reproducible. It is only built with the test-determinism feature and
+ * must never ship in a release enclave; define SGX_DCAP_TEST_DETERMINISM to
+ * declare it.
+ */
+#ifdef SGX_DCAP_TEST_DETERMINISM
+sgx_status_t sgx_dcap_mra_proc_msg1_ex(
+    sgx_ra_context_t context,
+    const sgx_dcap_ra_msg1_t *msg1,
+    const sgx_target_info_t *qe_target,
+    const sgx_quote_nonce_t *nonce,
+    sgx_ec256_public_t *g_b,
+    sgx_report_t *report);
+#endif
+
+/*
+ * The sgx_dcap_mra_get_msg2 function builds msg2 around the quote the QE
+ * produced for the report from sgx_dcap_mra_proc_msg1. Call it with p_msg2
+ * set to NULL first to learn the required size in *p_msg2_size, then again
+ * with a buffer of exactly that size. The quote is copied into msg2 by the
+ * enclave.
+ *
+ * @param context      Context returned by sgx_mra_responder_init.
+ * @param qe_report    The QE report returned with the quote.
+ * @param p_quote      The quote returned by the QE.
+ * @param quote_size   Size of the quote in bytes.
+ * @param p_msg2       The msg2 buffer, or NULL to query its size.
+ * @param p_msg2_size  The size of p_msg2 on input, the required size on output.
+ * @return sgx_status_t
+ */
 sgx_status_t sgx_dcap_mra_get_msg2(
     sgx_ra_context_t context,
-    const sgx_report_t* qe_report,
-    sgx_dcap_mra_msg2_t *msg2,
-    uint32_t msg2_size);
+    const sgx_report_t *qe_report,
+    const uint8_t *p_quote,
+    uint32_t quote_size,
+    sgx_dcap_mra_msg2_t *p_msg2,
+    uint32_t *p_msg2_size);
 
 sgx_status_t sgx_dcap_mra_proc_msg3(
     sgx_ra_context_t context,
@@ -191,6 +265,114 @@ sgx_status_t SGXAPI sgx_mra_responder_get_peer_identity(
     sgx_ql_qv_result_t *quote_verification_result,
     sgx_dcap_enclave_identity_t *initiator_identity);
 
+/*
+ * The sgx_mra_responder_get_collateral_status function returns the expiration
+ * time and collateral expiration status that the initiator's quote was
+ * verified with in sgx_dcap_mra_proc_msg3, so that a cached peer identity can
+ * be re-attested before its collateral expires.
+ *
+ * @param context                         Context returned by sgx_mra_responder_init.
+ * @param expiration_time                 The expiration time passed to the QvE.
+ * @param collateral_expiration_status    The collateral expiration status from the QvE.
+ * @return sgx_status_t
+ */
+sgx_status_t SGXAPI sgx_mra_responder_get_collateral_status(
+    sgx_ra_context_t context,
+    time_t *expiration_time,
+    uint32_t *collateral_expiration_status);
+
+/*
+ * The sgx_mra_responder_get_peer_quote function copies the quote the
+ * initiator was attested with in sgx_dcap_mra_proc_msg3. Call it with a null
+ * p_buf first to get the size in p_quote_len. It fails with
+ * SGX_ERROR_INVALID_STATE before sgx_dcap_mra_proc_msg3 succeeds.
+ *
+ * @param context       Context returned by sgx_mra_responder_init.
+ * @param p_buf         Buffer for the quote, or NULL to query the size.
+ * @param buf_size      Size of p_buf.
+ * @param p_quote_len   Size of the quote.
+ * @return sgx_status_t
+ */
+sgx_status_t SGXAPI sgx_mra_responder_get_peer_quote(
+    sgx_ra_context_t context,
+    uint8_t *p_buf,
+    uint32_t buf_size,
+    uint32_t *p_quote_len);
+
+/*
+ * The sgx_mra_responder_set_policy function restricts the quote verification
+ * results sgx_dcap_mra_proc_msg3 accepts. A msg3 whose result is not listed
+ * fails with SGX_ERROR_NO_PRIVILEGE. Without a policy every result is accepted.
+ *
+ * @param context               Context returned by sgx_mra_responder_init.
+ * @param accepted_qv_results   The accepted results, inside the enclave.
+ * @param accepted_count        Number of entries in accepted_qv_results.
+ * @return sgx_status_t
+ */
+sgx_status_t SGXAPI sgx_mra_responder_set_policy(
+    sgx_ra_context_t context,
+    const sgx_ql_qv_result_t *accepted_qv_results,
+    uint32_t accepted_count);
+
+/*
+ * The sgx_mra_responder_export function seals the keys and peer identity of an
+ * established session so that it can be restored with sgx_mra_responder_import
+ * after the enclave is reloaded. The blob is sealed to MRENCLAVE, so only the
+ * same enclave build can import it. Call it with a null p_blob to get the size.
+ *
+ * @param context     Context returned by sgx_mra_responder_init.
+ * @param p_blob      The sealed session returned, or NULL.
+ * @param blob_size   Size of the p_blob buffer.
+ * @param p_blob_len  Size of the sealed session.
+ * @return sgx_status_t
+ */
+sgx_status_t SGXAPI sgx_mra_responder_export(
+    sgx_ra_context_t context,
+    uint8_t *p_blob,
+    uint32_t blob_size,
+    uint32_t *p_blob_len);
+
+/*
+ * The sgx_mra_responder_import function creates an established responder
+ * context from a blob written by sgx_mra_responder_export. A blob from an
+ * incompatible version fails with SGX_ERROR_INVALID_VERSION, and a blob this
+ * enclave instance has already imported fails with SGX_ERROR_INVALID_STATE.
+ * Replays into a freshly loaded enclave are not detected.
+ *
+ * @param p_blob      The sealed session.
+ * @param blob_size   Size of p_blob.
+ * @param p_context   The output context.
+ * @return sgx_status_t
+ */
+sgx_status_t SGXAPI sgx_mra_responder_import(
+    const uint8_t *p_blob,
+    uint32_t blob_size,
+    sgx_ra_context_t *p_context);
+
+/*
+ * The sgx_mra_responder_rekey function replaces SK and MK of an established
+ * session with keys derived from the current ones, so keys obtained earlier
+ * from sgx_mra_responder_get_keys no longer match. The initiator must call
+ * sgx_ra_initiator_rekey the same number of times, and data protected under
+ * the old keys must be finished before calling.
+ *
+ * @param context   Context returned by sgx_mra_responder_init.
+ * @return sgx_status_t
+ */
+sgx_status_t SGXAPI sgx_mra_responder_rekey(
+    sgx_ra_context_t context);
+
+/*
+ * The sgx_mra_responder_abort function ends the key exchange immediately. The
+ * session secrets are cleared and every later call on the context except
+ * sgx_mra_responder_close fails with SGX_ERROR_INVALID_STATE.
+ *
+ * @param context   Context returned by sgx_mra_responder_init.
+ * @return sgx_status_t
+ */
+sgx_status_t SGXAPI sgx_mra_responder_abort(
+    sgx_ra_context_t context);
+
 /*
  * Call the sgx_mra_responder_close function to release the remote attestation and key
  * exchange context after the process is done and the context isn't needed
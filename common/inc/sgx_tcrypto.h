@@ -47,11 +47,13 @@
 #define SGX_SHA1_HASH_SIZE              20
 #define SGX_SHA256_HASH_SIZE            32
 #define SGX_SHA384_HASH_SIZE            48
+#define SGX_SHA512_HASH_SIZE            64
 #define SGX_ECP256_KEY_SIZE             32
 #define SGX_NISTP_ECP256_KEY_SIZE       (SGX_ECP256_KEY_SIZE/sizeof(uint32_t))
 #define SGX_AESGCM_IV_SIZE              12
 #define SGX_AESCBC_IV_SIZE              16
 #define SGX_AESGCM_KEY_SIZE             16
+#define SGX_AESGCM_256_KEY_SIZE         32
 #define SGX_AESCBC_KEY_SIZE             16
 #define SGX_AESGCM_MAC_SIZE             16
 #define SGX_AESCCM_IV_SIZE              12
@@ -139,9 +141,11 @@ typedef void* sgx_sm4_state_handle_t;
 typedef uint8_t sgx_sha1_hash_t[SGX_SHA1_HASH_SIZE];
 typedef uint8_t sgx_sha256_hash_t[SGX_SHA256_HASH_SIZE];
 typedef uint8_t sgx_sha384_hash_t[SGX_SHA384_HASH_SIZE];
+typedef uint8_t sgx_sha512_hash_t[SGX_SHA512_HASH_SIZE];
 typedef uint8_t sgx_sm3_hash_t[SGX_SM3_HASH_SIZE];
 
 typedef uint8_t sgx_aes_gcm_128bit_key_t[SGX_AESGCM_KEY_SIZE];
+typedef uint8_t sgx_aes_gcm_256bit_key_t[SGX_AESGCM_256_KEY_SIZE];
 typedef uint8_t sgx_aes_gcm_128bit_tag_t[SGX_AESGCM_MAC_SIZE];
 typedef uint8_t sgx_hmac_256bit_key_t[SGX_HMAC256_KEY_SIZE];
 typedef uint8_t sgx_hmac_256bit_tag_t[SGX_HMAC256_MAC_SIZE];
@@ -260,6 +264,7 @@ extern "C" {
     *           uint32_t src_len - Length of the input stream to be hashed
     *   Output: sgx_sha#_hash_t *p_hash - Resultant hash from operation
     */
+    sgx_status_t SGXAPI sgx_sha512_msg(const uint8_t *p_src, uint32_t src_len, sgx_sha512_hash_t *p_hash);
     sgx_status_t SGXAPI sgx_sha384_msg(const uint8_t *p_src, uint32_t src_len, sgx_sha384_hash_t *p_hash);
     sgx_status_t SGXAPI sgx_sha256_msg(const uint8_t *p_src, uint32_t src_len, sgx_sha256_hash_t *p_hash);
     sgx_status_t SGXAPI sgx_sha1_msg(const uint8_t *p_src, uint32_t src_len, sgx_sha1_hash_t *p_hash);
@@ -270,6 +275,7 @@ extern "C" {
     *   Return: sgx_status_t  - SGX_SUCCESS or failure as defined in sgx_error.h
     *   Output: sgx_sha_state_handle_t *p_sha_handle - Pointer to the handle of the SHA state
     */
+    sgx_status_t SGXAPI sgx_sha512_init(sgx_sha_state_handle_t* p_sha_handle);
     sgx_status_t SGXAPI sgx_sha384_init(sgx_sha_state_handle_t* p_sha_handle);
     sgx_status_t SGXAPI sgx_sha256_init(sgx_sha_state_handle_t* p_sha_handle);
     sgx_status_t SGXAPI sgx_sha1_init(sgx_sha_state_handle_t* p_sha_handle);
@@ -282,6 +288,7 @@ extern "C" {
     *           uint8_t *p_src - Pointer to the input stream to be hashed
     *           uint32_t src_len - Length of the input stream to be hashed
     */
+    sgx_status_t SGXAPI sgx_sha512_update(const uint8_t *p_src, uint32_t src_len, sgx_sha_state_handle_t sha_handle);
     sgx_status_t SGXAPI sgx_sha384_update(const uint8_t *p_src, uint32_t src_len, sgx_sha_state_handle_t sha_handle);
     sgx_status_t SGXAPI sgx_sha256_update(const uint8_t *p_src, uint32_t src_len, sgx_sha_state_handle_t sha_handle);
     sgx_status_t SGXAPI sgx_sha1_update(const uint8_t *p_src, size_t src_len, sgx_sha_state_handle_t sha_handle);
@@ -293,6 +300,7 @@ extern "C" {
     *   Input:  sgx_sha_state_handle_t sha_handle - Handle to the SHA state
     *   Output: sgx_sha#_hash_t *p_hash - Resultant hash from operation
     */
+    sgx_status_t SGXAPI sgx_sha512_get_hash(sgx_sha_state_handle_t sha_handle, sgx_sha512_hash_t *p_hash);
     sgx_status_t SGXAPI sgx_sha384_get_hash(sgx_sha_state_handle_t sha_handle, sgx_sha384_hash_t *p_hash);
     sgx_status_t SGXAPI sgx_sha256_get_hash(sgx_sha_state_handle_t sha_handle, sgx_sha256_hash_t *p_hash);
     sgx_status_t SGXAPI sgx_sha1_get_hash(sgx_sha_state_handle_t sha_handle, sgx_sha1_hash_t *p_hash);
@@ -303,11 +311,12 @@ extern "C" {
     *   Return: sgx_status_t  - SGX_SUCCESS or failure as defined in sgx_error.h
     *   Input:  sgx_sha_state_handle_t sha_handle - Handle to the SHA state
     */
+    sgx_status_t SGXAPI sgx_sha512_close(sgx_sha_state_handle_t sha_handle);
     sgx_status_t SGXAPI sgx_sha384_close(sgx_sha_state_handle_t sha_handle);
     sgx_status_t SGXAPI sgx_sha256_close(sgx_sha_state_handle_t sha_handle);
     sgx_status_t SGXAPI sgx_sha1_close(sgx_sha_state_handle_t sha_handle);
 
-   /**Rijndael AES-GCM - Only 128-bit key AES-GCM Encryption/Decryption is supported
+   /**Rijndael AES-GCM - 128-bit and 256-bit key AES-GCM Encryption/Decryption
     *
     * The Galois/Counter Mode (GCM) is a mode of operation of the AES algorithm.
     * GCM [NIST SP 800-38D] uses a variation of the Counter mode of operation for encryption.
@@ -371,6 +380,29 @@ extern "C" {
                                                 uint32_t aad_len,
                                                 const sgx_aes_gcm_128bit_tag_t *p_in_mac);
 
+   /** sgx_rijndael256GCM_encrypt and sgx_rijndael256GCM_decrypt are the 256-bit key
+    * counterparts of the functions above, with the same parameters and return values
+    * except that p_key points to a sgx_aes_gcm_256bit_key_t.
+    */
+    sgx_status_t SGXAPI sgx_rijndael256GCM_encrypt(const sgx_aes_gcm_256bit_key_t *p_key,
+                                                const uint8_t *p_src,
+                                                uint32_t src_len,
+                                                uint8_t *p_dst,
+                                                const uint8_t *p_iv,
+                                                uint32_t iv_len,
+                                                const uint8_t *p_aad,
+                                                uint32_t aad_len,
+                                                sgx_aes_gcm_128bit_tag_t *p_out_mac);
+    sgx_status_t SGXAPI sgx_rijndael256GCM_decrypt(const sgx_aes_gcm_256bit_key_t *p_key,
+                                                const uint8_t *p_src,
+                                                uint32_t src_len,
+                                                uint8_t *p_dst,
+                                                const uint8_t *p_iv,
+                                                uint32_t iv_len,
+                                                const uint8_t *p_aad,
+                                                uint32_t aad_len,
+                                                const sgx_aes_gcm_128bit_tag_t *p_in_mac);
+
    /** Message Authentication Rijndael 128 CMAC - Only 128-bit key size is supported.
     * NOTE: Use sgx_rijndael128_cmac_msg if the src ptr contains the complete msg to perform hash (Option 1)
     *       Else use the Init, Update, Update, ..., Final, Close procedure (Option 2)
@@ -52,6 +52,17 @@ mod bindings {
         pub fn sgx_sha384_get_hash(sha_handle: ShaHandle, p_hash: *mut Sha384Hash) -> SgxStatus;
         pub fn sgx_sha384_close(sha_handle: ShaHandle) -> SgxStatus;
 
+        pub fn sgx_sha512_msg(p_src: *const u8, src_len: u32, p_hash: *mut Sha512Hash)
+            -> SgxStatus;
+        pub fn sgx_sha512_init(p_sha_handle: *mut ShaHandle) -> SgxStatus;
+        pub fn sgx_sha512_update(
+            p_src: *const u8,
+            src_len: u32,
+            sha_handle: ShaHandle,
+        ) -> SgxStatus;
+        pub fn sgx_sha512_get_hash(sha_handle: ShaHandle, p_hash: *mut Sha512Hash) -> SgxStatus;
+        pub fn sgx_sha512_close(sha_handle: ShaHandle) -> SgxStatus;
+
         pub fn sgx_sha256_msg(p_src: *const u8, src_len: u32, p_hash: *mut Sha256Hash)
             -> SgxStatus;
         pub fn sgx_sha256_init(p_sha_handle: *mut ShaHandle) -> SgxStatus;
@@ -94,6 +105,30 @@ mod bindings {
             p_in_mac: *const Mac128bit,
         ) -> SgxStatus;
 
+        pub fn sgx_rijndael256GCM_encrypt(
+            p_key: *const Key256bit,
+            p_src: *const u8,
+            src_len: u32,
+            p_dst: *mut u8,
+            p_iv: *const u8,
+            iv_len: u32,
+            p_aad: *const u8,
+            aad_len: u32,
+            p_out_mac: *mut Mac128bit,
+        ) -> SgxStatus;
+
+        pub fn sgx_rijndael256GCM_decrypt(
+            p_key: *const Key256bit,
+            p_src: *const u8,
+            src_len: u32,
+            p_dst: *mut u8,
+            p_iv: *const u8,
+            iv_len: u32,
+            p_aad: *const u8,
+            aad_len: u32,
+            p_in_mac: *const Mac128bit,
+        ) -> SgxStatus;
+
         pub fn sgx_rijndael128_cmac_msg(
             p_key: *const Key128bit,
             p_src: *const u8,
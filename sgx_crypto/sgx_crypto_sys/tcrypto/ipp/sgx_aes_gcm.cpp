@@ -50,9 +50,9 @@
 *   Output: uint8_t *p_dst - Pointer to cipher text. Size of buffer should be >= src_len.
 *           sgx_aes_gcm_128bit_tag_t *p_out_mac - Pointer to MAC generated from encryption process
 * NOTE: Wrapper is responsible for confirming decryption tag matches encryption tag */
-sgx_status_t sgx_rijndael128GCM_encrypt(const sgx_aes_gcm_128bit_key_t *p_key, const uint8_t *p_src, uint32_t src_len,
-                                        uint8_t *p_dst, const uint8_t *p_iv, uint32_t iv_len, const uint8_t *p_aad, uint32_t aad_len,
-                                        sgx_aes_gcm_128bit_tag_t *p_out_mac)
+static sgx_status_t aes_gcm_encrypt(const uint8_t *p_key, int key_size, const uint8_t *p_src, uint32_t src_len,
+                                    uint8_t *p_dst, const uint8_t *p_iv, uint32_t iv_len, const uint8_t *p_aad, uint32_t aad_len,
+                                    sgx_aes_gcm_128bit_tag_t *p_out_mac)
 {
     IppStatus error_code = ippStsNoErr;
     IppsAES_GCMState* pState = NULL;
@@ -74,7 +74,7 @@ sgx_status_t sgx_rijndael128GCM_encrypt(const sgx_aes_gcm_128bit_key_t *p_key, c
     {
         return SGX_ERROR_OUT_OF_MEMORY;
     }
-    error_code = ippsAES_GCMInit((const Ipp8u *)p_key, SGX_AESGCM_KEY_SIZE, pState, ippStateSize);
+    error_code = ippsAES_GCMInit(p_key, key_size, pState, ippStateSize);
     if (error_code != ippStsNoErr)
     {
         // Clear temp State before free.
@@ -135,9 +135,9 @@ sgx_status_t sgx_rijndael128GCM_encrypt(const sgx_aes_gcm_128bit_key_t *p_key, c
     return SGX_SUCCESS;
 }
 
-sgx_status_t sgx_rijndael128GCM_decrypt(const sgx_aes_gcm_128bit_key_t *p_key, const uint8_t *p_src,
-                                        uint32_t src_len, uint8_t *p_dst, const uint8_t *p_iv, uint32_t iv_len,
-                                        const uint8_t *p_aad, uint32_t aad_len, const sgx_aes_gcm_128bit_tag_t *p_in_mac)
+static sgx_status_t aes_gcm_decrypt(const uint8_t *p_key, int key_size, const uint8_t *p_src,
+                                    uint32_t src_len, uint8_t *p_dst, const uint8_t *p_iv, uint32_t iv_len,
+                                    const uint8_t *p_aad, uint32_t aad_len, const sgx_aes_gcm_128bit_tag_t *p_in_mac)
 {
     IppStatus error_code = ippStsNoErr;
     uint8_t l_tag[SGX_AESGCM_MAC_SIZE];
@@ -163,7 +163,7 @@ sgx_status_t sgx_rijndael128GCM_decrypt(const sgx_aes_gcm_128bit_key_t *p_key, c
     {
         return SGX_ERROR_OUT_OF_MEMORY;
     }
-    error_code = ippsAES_GCMInit((const Ipp8u *)p_key, SGX_AESGCM_KEY_SIZE, pState, ippStateSize);
+    error_code = ippsAES_GCMInit(p_key, key_size, pState, ippStateSize);
     if (error_code != ippStsNoErr)
     {
         // Clear temp State before free.
@@ -234,6 +234,38 @@ sgx_status_t sgx_rijndael128GCM_decrypt(const sgx_aes_gcm_128bit_key_t *p_key, c
     return SGX_SUCCESS;
 }
 
+sgx_status_t sgx_rijndael128GCM_encrypt(const sgx_aes_gcm_128bit_key_t *p_key, const uint8_t *p_src, uint32_t src_len,
+                                        uint8_t *p_dst, const uint8_t *p_iv, uint32_t iv_len, const uint8_t *p_aad, uint32_t aad_len,
+                                        sgx_aes_gcm_128bit_tag_t *p_out_mac)
+{
+    return aes_gcm_encrypt((const uint8_t *)p_key, SGX_AESGCM_KEY_SIZE, p_src, src_len, p_dst, p_iv, iv_len,
+                           p_aad, aad_len, p_out_mac);
+}
+
+sgx_status_t sgx_rijndael128GCM_decrypt(const sgx_aes_gcm_128bit_key_t *p_key, const uint8_t *p_src,
+                                        uint32_t src_len, uint8_t *p_dst, const uint8_t *p_iv, uint32_t iv_len,
+                                        const uint8_t *p_aad, uint32_t aad_len, const sgx_aes_gcm_128bit_tag_t *p_in_mac)
+{
+    return aes_gcm_decrypt((const uint8_t *)p_key, SGX_AESGCM_KEY_SIZE, p_src, src_len, p_dst, p_iv, iv_len,
+                           p_aad, aad_len, p_in_mac);
+}
+
+sgx_status_t sgx_rijndael256GCM_encrypt(const sgx_aes_gcm_256bit_key_t *p_key, const uint8_t *p_src, uint32_t src_len,
+                                        uint8_t *p_dst, const uint8_t *p_iv, uint32_t iv_len, const uint8_t *p_aad, uint32_t aad_len,
+                                        sgx_aes_gcm_128bit_tag_t *p_out_mac)
+{
+    return aes_gcm_encrypt((const uint8_t *)p_key, SGX_AESGCM_256_KEY_SIZE, p_src, src_len, p_dst, p_iv, iv_len,
+                           p_aad, aad_len, p_out_mac);
+}
+
+sgx_status_t sgx_rijndael256GCM_decrypt(const sgx_aes_gcm_256bit_key_t *p_key, const uint8_t *p_src,
+                                        uint32_t src_len, uint8_t *p_dst, const uint8_t *p_iv, uint32_t iv_len,
+                                        const uint8_t *p_aad, uint32_t aad_len, const sgx_aes_gcm_128bit_tag_t *p_in_mac)
+{
+    return aes_gcm_decrypt((const uint8_t *)p_key, SGX_AESGCM_256_KEY_SIZE, p_src, src_len, p_dst, p_iv, iv_len,
+                           p_aad, aad_len, p_in_mac);
+}
+
 sgx_status_t sgx_aes_gcm128_init(const uint8_t *key, const uint8_t *iv, uint32_t iv_len, const uint8_t *aad,
     uint32_t aad_len, sgx_aes_state_handle_t* aes_gcm_state)
 {
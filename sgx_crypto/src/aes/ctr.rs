@@ -15,6 +15,7 @@
 // specific language governing permissions and limitations
 // under the License..
 
+use alloc::vec::Vec;
 use core::array::TryFromSliceError;
 use core::convert::{From, TryFrom, TryInto};
 use sgx_crypto_sys::*;
@@ -22,6 +23,9 @@ use sgx_types::error::{SgxResult, SgxStatus};
 use sgx_types::marker::{BytewiseEquality, ContiguousMemory};
 use sgx_types::types::{AlignKey128bit, Key128bit, AESCTR_CTR_SIZE};
 
+#[cfg(feature = "unit_test")]
+mod tests;
+
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub struct AesCtr {
     key: AlignKey128bit,
@@ -117,6 +121,53 @@ impl Default for AesCtr {
     }
 }
 
+/// Encrypts `data` with AES-128 in CTR mode, starting at `counter` and
+/// leaving it advanced by one per 16-byte block, a trailing partial block
+/// included, so consecutive calls continue the keystream.
+///
+/// CTR mode provides no integrity, so the output must be covered by a
+/// separate MAC. The caller must never use the same (key, counter) pair
+/// twice: two messages encrypted under the same keystream reveal the XOR
+/// of their plaintexts.
+pub fn aes128_ctr_encrypt(
+    key: &Key128bit,
+    counter: &mut [u8; AESCTR_CTR_SIZE],
+    data: &[u8],
+) -> SgxResult<Vec<u8>> {
+    ensure!(
+        (1..i32::MAX as usize).contains(&data.len()),
+        SgxStatus::InvalidParameter
+    );
+
+    let mut ctr = *counter;
+    let mut dst = vec![0_u8; data.len()];
+    let status = unsafe {
+        sgx_aes_ctr_encrypt(
+            key as *const Key128bit,
+            data.as_ptr(),
+            data.len() as u32,
+            ctr.as_mut_ptr(),
+            AesCtr::CTR_INC_BITS,
+            dst.as_mut_ptr(),
+        )
+    };
+    ensure!(status.is_success(), status);
+
+    *counter = ctr;
+    Ok(dst)
+}
+
+/// CTR decryption is the same operation as encryption; see
+/// `aes128_ctr_encrypt`.
+#[inline]
+pub fn aes128_ctr_decrypt(
+    key: &Key128bit,
+    counter: &mut [u8; AESCTR_CTR_SIZE],
+    data: &[u8],
+) -> SgxResult<Vec<u8>> {
+    aes128_ctr_encrypt(key, counter, data)
+}
+
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub struct Counter([u8; AESCTR_CTR_SIZE]);
 
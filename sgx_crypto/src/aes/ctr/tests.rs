@@ -0,0 +1,103 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License..
+use super::{aes128_ctr_decrypt, aes128_ctr_encrypt};
+use alloc::vec::Vec;
+use sgx_test_utils::test_case;
+use sgx_types::error::SgxStatus;
+
+fn unhex(hex: &str) -> Vec<u8> {
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap())
+        .collect()
+}
+
+// NIST SP 800-38A, F.5.1 and F.5.2 (CTR-AES128).
+const KEY: [u8; 16] = [
+    0x2b, 0x7e, 0x15, 0x16, 0x28, 0xae, 0xd2, 0xa6, 0xab, 0xf7, 0x15, 0x88, 0x09, 0xcf, 0x4f, 0x3c,
+];
+const INIT_COUNTER: [u8; 16] = [
+    0xf0, 0xf1, 0xf2, 0xf3, 0xf4, 0xf5, 0xf6, 0xf7, 0xf8, 0xf9, 0xfa, 0xfb, 0xfc, 0xfd, 0xfe, 0xff,
+];
+const PLAINTEXT: &str = "6bc1bee22e409f96e93d7e117393172aae2d8a571e03ac9c9eb76fac45af8e51\
+                         30c81c46a35ce411e5fbc1191a0a52eff69f2445df4f9b17ad2b417be66c3710";
+const CIPHERTEXT: &str = "874d6191b620e3261bef6864990db6ce9806f66b7970fdff8617187bb9fffdff\
+                          5ae4df3edbd5d35e5b4f09020db03eab1e031dda2fbe03d1792170a0f3009cee";
+
+fn counter_plus(n: u8) -> [u8; 16] {
+    let mut ctr = INIT_COUNTER;
+    let (low, carry) = ctr[15].overflowing_add(n);
+    ctr[15] = low;
+    if carry {
+        ctr[14] += 1;
+    }
+    ctr
+}
+
+#[test_case]
+fn ctr_sp800_38a_vectors() {
+    let plaintext = unhex(PLAINTEXT);
+    let ciphertext = unhex(CIPHERTEXT);
+
+    let mut counter = INIT_COUNTER;
+    assert_eq!(
+        aes128_ctr_encrypt(&KEY, &mut counter, &plaintext).unwrap(),
+        ciphertext
+    );
+    assert_eq!(counter, counter_plus(4));
+
+    let mut counter = INIT_COUNTER;
+    assert_eq!(
+        aes128_ctr_decrypt(&KEY, &mut counter, &ciphertext).unwrap(),
+        plaintext
+    );
+    assert_eq!(counter, counter_plus(4));
+}
+
+#[test_case]
+fn ctr_chunks_continue_the_keystream() {
+    let plaintext = unhex(PLAINTEXT);
+    let ciphertext = unhex(CIPHERTEXT);
+
+    let mut counter = INIT_COUNTER;
+    let mut out = aes128_ctr_encrypt(&KEY, &mut counter, &plaintext[..32]).unwrap();
+    out.extend(aes128_ctr_encrypt(&KEY, &mut counter, &plaintext[32..]).unwrap());
+    assert_eq!(out, ciphertext);
+
+    // Random access: block 2 decrypts on its own from counter + 2.
+    let mut counter = counter_plus(2);
+    assert_eq!(
+        aes128_ctr_decrypt(&KEY, &mut counter, &ciphertext[32..48]).unwrap(),
+        &plaintext[32..48]
+    );
+
+    // A partial block still consumes a whole counter value.
+    let mut counter = INIT_COUNTER;
+    let out = aes128_ctr_encrypt(&KEY, &mut counter, &plaintext[..20]).unwrap();
+    assert_eq!(out, &ciphertext[..20]);
+    assert_eq!(counter, counter_plus(2));
+}
+
+#[test_case]
+fn ctr_rejects_empty_input() {
+    let mut counter = INIT_COUNTER;
+    assert_eq!(
+        aes128_ctr_encrypt(&KEY, &mut counter, &[]),
+        Err(SgxStatus::InvalidParameter)
+    );
+    assert_eq!(counter, INIT_COUNTER);
+}
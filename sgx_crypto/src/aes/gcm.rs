@@ -15,17 +15,25 @@
 // specific language governing permissions and limitations
 // under the License..
 
+use crate::zeroize;
+use alloc::vec::Vec;
 use core::array::TryFromSliceError;
 use core::convert::{From, TryFrom, TryInto};
+use core::fmt;
+use core::mem;
 use core::ptr;
 use sgx_crypto_sys::*;
 use sgx_types::error::{SgxResult, SgxStatus};
 use sgx_types::marker::{BytewiseEquality, ContiguousMemory};
 use sgx_types::memeq::ConstTimeEq;
 use sgx_types::types::{
-    AesHandle, AlignKey128bit, AlignMac128bit, Key128bit, Mac128bit, AESGCM_IV_SIZE,
+    AesHandle, AlignKey128bit, AlignKey256bit, AlignMac128bit, Key128bit, Key256bit, Mac128bit,
+    AESGCM_IV_SIZE,
 };
 
+#[cfg(feature = "unit_test")]
+mod tests;
+
 #[derive(Debug)]
 pub struct AesGcm<A: AsRef<[u8]>> {
     key: AlignKey128bit,
@@ -287,6 +295,95 @@ impl<A: AsRef<[u8]>> AesGcm<A> {
     }
 }
 
+impl<A: AsRef<[u8]>> AesGcm<A> {
+    /// Encrypts the next chunk of a stream started by `new`. The tag from
+    /// `finish` covers every chunk passed here, in order.
+    pub fn update(&mut self, chunk: &[u8]) -> SgxResult<Vec<u8>> {
+        let mut ciphertext = vec![0_u8; chunk.len()];
+        if !chunk.is_empty() {
+            self.enc_update(chunk, ciphertext.as_mut_slice())?;
+        }
+        Ok(ciphertext)
+    }
+
+    /// Ends the stream and returns any remaining ciphertext with the tag.
+    /// GCM emits ciphertext as it goes, so the remainder is always empty.
+    pub fn finish(mut self) -> SgxResult<(Vec<u8>, Mac128bit)> {
+        if self.handle.is_null() {
+            self.init()?;
+        }
+        Ok((Vec::new(), self.enc_get_mac()?))
+    }
+}
+
+/// Incremental AES-128-GCM decryption. Plaintext stays inside until
+/// `finish` has verified the tag over the whole stream, and is zeroed if
+/// verification fails or the stream is dropped unfinished.
+pub struct AesGcmDecrypt<A: AsRef<[u8]>> {
+    gcm: AesGcm<A>,
+    plaintext: Vec<u8>,
+}
+
+impl<A: AsRef<[u8]>> AesGcmDecrypt<A> {
+    pub fn new(key: &Key128bit, iv: Nonce, aad: Aad<A>) -> SgxResult<AesGcmDecrypt<A>> {
+        Ok(AesGcmDecrypt {
+            gcm: AesGcm::new(key, iv, aad)?,
+            plaintext: Vec::new(),
+        })
+    }
+
+    pub fn update(&mut self, chunk: &[u8]) -> SgxResult {
+        if chunk.is_empty() {
+            return Ok(());
+        }
+
+        let start = self.plaintext.len();
+        let len = start
+            .checked_add(chunk.len())
+            .ok_or(SgxStatus::InvalidParameter)?;
+        self.reserve(len);
+        self.plaintext.resize(len, 0);
+        // Unverified plaintext never leaves `self` before `finish`.
+        unsafe { self.gcm.dec_update(chunk, &mut self.plaintext[start..]) }
+    }
+
+    pub fn finish(mut self, mac: &Mac128bit) -> SgxResult<Vec<u8>> {
+        if self.gcm.handle.is_null() {
+            self.gcm.init()?;
+        }
+        self.gcm.dec_verify_mac(mac)?;
+        Ok(mem::take(&mut self.plaintext))
+    }
+
+    // Grows the buffer by hand so a reallocation does not leave a copy of
+    // the plaintext behind in freed memory.
+    fn reserve(&mut self, len: usize) {
+        if len <= self.plaintext.capacity() {
+            return;
+        }
+
+        let mut grown = Vec::with_capacity(len.max(self.plaintext.capacity() * 2));
+        grown.extend_from_slice(&self.plaintext);
+        zeroize(&mut self.plaintext);
+        self.plaintext = grown;
+    }
+}
+
+// Neither the key nor the unverified plaintext is shown.
+impl<A: AsRef<[u8]>> fmt::Debug for AesGcmDecrypt<A> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AesGcmDecrypt")
+            .field("buffered", &self.plaintext.len())
+            .finish_non_exhaustive()
+    }
+}
+
+impl<A: AsRef<[u8]>> Drop for AesGcmDecrypt<A> {
+    fn drop(&mut self) {
+        zeroize(&mut self.plaintext);
+    }
+}
+
 impl Default for AesGcm<[u8; 0]> {
     fn default() -> AesGcm<[u8; 0]> {
         let mut key = AlignKey128bit::default();
@@ -309,6 +406,125 @@ impl<A: AsRef<[u8]>> Drop for AesGcm<A> {
     }
 }
 
+/// One-shot AES-256-GCM with the same interface as the one-shot
+/// `AesGcm` calls, so switching is a matter of the key type.
+#[derive(Debug)]
+pub struct AesGcm256<A: AsRef<[u8]>> {
+    key: AlignKey256bit,
+    iv: Nonce,
+    aad: Aad<A>,
+}
+
+impl<A: AsRef<[u8]>> AesGcm256<A> {
+    pub fn new(key: &Key256bit, iv: Nonce, aad: Aad<A>) -> SgxResult<AesGcm256<A>> {
+        ensure!(
+            aad.as_ref().len() < i32::MAX as usize,
+            SgxStatus::InvalidParameter
+        );
+
+        Ok(AesGcm256 {
+            key: AlignKey256bit::from(key),
+            iv,
+            aad,
+        })
+    }
+
+    pub fn encrypt(&mut self, src: &[u8], dst: &mut [u8]) -> SgxResult<Mac128bit> {
+        let src_len = src.len();
+        let aad = self.aad.as_ref();
+
+        ensure!(src_len < i32::MAX as usize, SgxStatus::InvalidParameter);
+        ensure!(src_len == dst.len(), SgxStatus::InvalidParameter);
+
+        let p_aad = if !aad.is_empty() {
+            aad.as_ptr()
+        } else {
+            ptr::null()
+        };
+
+        let (p_src, p_dst) = if !src.is_empty() {
+            (src.as_ptr(), dst.as_mut_ptr())
+        } else {
+            (ptr::null(), ptr::null_mut())
+        };
+
+        let mut mac = AlignMac128bit::default();
+        let status = unsafe {
+            sgx_rijndael256GCM_encrypt(
+                &self.key.key as *const Key256bit,
+                p_src,
+                src_len as u32,
+                p_dst,
+                self.iv.as_ref().as_ptr(),
+                self.iv.as_ref().len() as u32,
+                p_aad,
+                aad.len() as u32,
+                &mut mac.mac as *mut Mac128bit,
+            )
+        };
+
+        ensure!(status.is_success(), status);
+        Ok(mac.mac)
+    }
+
+    pub fn encrypt_in_place(&mut self, in_out: &mut [u8]) -> SgxResult<Mac128bit> {
+        let mut dst = vec![0_u8; in_out.len()];
+        let mac = self.encrypt(in_out, dst.as_mut_slice())?;
+        in_out.clone_from_slice(dst.as_slice());
+        Ok(mac)
+    }
+
+    pub fn decrypt(&mut self, src: &[u8], dst: &mut [u8], mac: &Mac128bit) -> SgxResult {
+        let src_len = src.len();
+        let aad = self.aad.as_ref();
+
+        ensure!(src_len < i32::MAX as usize, SgxStatus::InvalidParameter);
+        ensure!(src_len == dst.len(), SgxStatus::InvalidParameter);
+
+        let p_aad = if !aad.is_empty() {
+            aad.as_ptr()
+        } else {
+            ptr::null()
+        };
+
+        let (p_src, p_dst) = if !src.is_empty() {
+            (src.as_ptr(), dst.as_mut_ptr())
+        } else {
+            (ptr::null(), ptr::null_mut())
+        };
+
+        let status = unsafe {
+            sgx_rijndael256GCM_decrypt(
+                &self.key.key as *const Key256bit,
+                p_src,
+                src_len as u32,
+                p_dst,
+                self.iv.as_ref().as_ptr(),
+                self.iv.as_ref().len() as u32,
+                p_aad,
+                aad.len() as u32,
+                mac as *const Mac128bit,
+            )
+        };
+
+        ensure!(status.is_success(), status);
+        Ok(())
+    }
+
+    pub fn decrypt_in_place(&mut self, in_out: &mut [u8], mac: &Mac128bit) -> SgxResult {
+        let mut dst = vec![0_u8; in_out.len()];
+        self.decrypt(in_out, dst.as_mut_slice(), mac)?;
+        in_out.clone_from_slice(dst.as_slice());
+        Ok(())
+    }
+}
+
+impl<A: AsRef<[u8]>> Drop for AesGcm256<A> {
+    fn drop(&mut self) {
+        self.key.key.fill(0);
+    }
+}
+
 pub struct Aad<A>(A);
 
 impl<A: AsRef<[u8]>> Aad<A> {
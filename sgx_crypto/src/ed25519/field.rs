@@ -0,0 +1,229 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License..
+//! Arithmetic in GF(2^255 - 19), five 51-bit limbs per element. Shared
+//! with X25519.
+
+const MASK: u64 = (1 << 51) - 1;
+
+#[derive(Clone, Copy)]
+pub(crate) struct Fe(pub(crate) [u64; 5]);
+
+impl Fe {
+    pub(crate) const ZERO: Fe = Fe([0, 0, 0, 0, 0]);
+    pub(crate) const ONE: Fe = Fe([1, 0, 0, 0, 0]);
+
+    /// Decodes 32 little-endian bytes, ignoring the top bit.
+    pub(crate) fn from_bytes(bytes: &[u8; 32]) -> Fe {
+        let load = |i: usize| {
+            let mut word = [0_u8; 8];
+            word.copy_from_slice(&bytes[i..i + 8]);
+            u64::from_le_bytes(word)
+        };
+        Fe([
+            load(0) & MASK,
+            (load(6) >> 3) & MASK,
+            (load(12) >> 6) & MASK,
+            (load(19) >> 1) & MASK,
+            (load(24) >> 12) & MASK,
+        ])
+    }
+
+    /// Encodes the canonical representative as 32 little-endian bytes.
+    pub(crate) fn to_bytes(self) -> [u8; 32] {
+        let mut l = Fe::weak_reduce(self.0);
+
+        // q is 1 iff the value is at least p.
+        let mut q = (l[0] + 19) >> 51;
+        q = (l[1] + q) >> 51;
+        q = (l[2] + q) >> 51;
+        q = (l[3] + q) >> 51;
+        q = (l[4] + q) >> 51;
+
+        l[0] += 19 * q;
+        l[1] += l[0] >> 51;
+        l[0] &= MASK;
+        l[2] += l[1] >> 51;
+        l[1] &= MASK;
+        l[3] += l[2] >> 51;
+        l[2] &= MASK;
+        l[4] += l[3] >> 51;
+        l[3] &= MASK;
+        l[4] &= MASK;
+
+        let mut out = [0_u8; 32];
+        let mut acc = 0_u128;
+        let mut bits = 0;
+        let mut i = 0;
+        for limb in l {
+            acc |= (limb as u128) << bits;
+            bits += 51;
+            while bits >= 8 {
+                out[i] = acc as u8;
+                acc >>= 8;
+                bits -= 8;
+                i += 1;
+            }
+        }
+        out[i] = acc as u8;
+        out
+    }
+
+    fn weak_reduce(mut l: [u64; 5]) -> [u64; 5] {
+        let c0 = l[0] >> 51;
+        let c1 = l[1] >> 51;
+        let c2 = l[2] >> 51;
+        let c3 = l[3] >> 51;
+        let c4 = l[4] >> 51;
+        l[0] &= MASK;
+        l[1] &= MASK;
+        l[2] &= MASK;
+        l[3] &= MASK;
+        l[4] &= MASK;
+        l[0] += c4 * 19;
+        l[1] += c0;
+        l[2] += c1;
+        l[3] += c2;
+        l[4] += c3;
+        l
+    }
+
+    pub(crate) fn add(&self, rhs: &Fe) -> Fe {
+        let (a, b) = (&self.0, &rhs.0);
+        Fe(Fe::weak_reduce([
+            a[0] + b[0],
+            a[1] + b[1],
+            a[2] + b[2],
+            a[3] + b[3],
+            a[4] + b[4],
+        ]))
+    }
+
+    pub(crate) fn sub(&self, rhs: &Fe) -> Fe {
+        // Adding 16p keeps every limb non-negative.
+        let (a, b) = (&self.0, &rhs.0);
+        Fe(Fe::weak_reduce([
+            (a[0] + 36028797018963664) - b[0],
+            (a[1] + 36028797018963952) - b[1],
+            (a[2] + 36028797018963952) - b[2],
+            (a[3] + 36028797018963952) - b[3],
+            (a[4] + 36028797018963952) - b[4],
+        ]))
+    }
+
+    pub(crate) fn neg(&self) -> Fe {
+        Fe::ZERO.sub(self)
+    }
+
+    pub(crate) fn mul(&self, rhs: &Fe) -> Fe {
+        #[inline(always)]
+        fn m(x: u64, y: u64) -> u128 {
+            (x as u128) * (y as u128)
+        }
+
+        let (a, b) = (&self.0, &rhs.0);
+        let b1_19 = b[1] * 19;
+        let b2_19 = b[2] * 19;
+        let b3_19 = b[3] * 19;
+        let b4_19 = b[4] * 19;
+
+        let c0 = m(a[0], b[0]) + m(a[4], b1_19) + m(a[3], b2_19) + m(a[2], b3_19) + m(a[1], b4_19);
+        let mut c1 =
+            m(a[1], b[0]) + m(a[0], b[1]) + m(a[4], b2_19) + m(a[3], b3_19) + m(a[2], b4_19);
+        let mut c2 =
+            m(a[2], b[0]) + m(a[1], b[1]) + m(a[0], b[2]) + m(a[4], b3_19) + m(a[3], b4_19);
+        let mut c3 = m(a[3], b[0]) + m(a[2], b[1]) + m(a[1], b[2]) + m(a[0], b[3]) + m(a[4], b4_19);
+        let mut c4 = m(a[4], b[0]) + m(a[3], b[1]) + m(a[2], b[2]) + m(a[1], b[3]) + m(a[0], b[4]);
+
+        let mask = MASK as u128;
+        c1 += c0 >> 51;
+        let mut l0 = (c0 & mask) as u64;
+        c2 += c1 >> 51;
+        let l1 = (c1 & mask) as u64;
+        c3 += c2 >> 51;
+        let l2 = (c2 & mask) as u64;
+        c4 += c3 >> 51;
+        let l3 = (c3 & mask) as u64;
+        let carry = (c4 >> 51) as u64;
+        let l4 = (c4 & mask) as u64;
+
+        l0 += carry * 19;
+        Fe([l0 & MASK, l1 + (l0 >> 51), l2, l3, l4])
+    }
+
+    pub(crate) fn square(&self) -> Fe {
+        self.mul(self)
+    }
+
+    fn pow2k(&self, k: u32) -> Fe {
+        let mut r = *self;
+        for _ in 0..k {
+            r = r.square();
+        }
+        r
+    }
+
+    /// Returns (z^(2^250 - 1), z^11).
+    fn pow22501(&self) -> (Fe, Fe) {
+        let z2 = self.square();
+        let z9 = self.mul(&z2.pow2k(2));
+        let z11 = z2.mul(&z9);
+        let z_5_0 = z9.mul(&z11.square());
+        let z_10_0 = z_5_0.pow2k(5).mul(&z_5_0);
+        let z_20_0 = z_10_0.pow2k(10).mul(&z_10_0);
+        let z_40_0 = z_20_0.pow2k(20).mul(&z_20_0);
+        let z_50_0 = z_40_0.pow2k(10).mul(&z_10_0);
+        let z_100_0 = z_50_0.pow2k(50).mul(&z_50_0);
+        let z_200_0 = z_100_0.pow2k(100).mul(&z_100_0);
+        let z_250_0 = z_200_0.pow2k(50).mul(&z_50_0);
+        (z_250_0, z11)
+    }
+
+    /// z^(p - 2).
+    pub(crate) fn invert(&self) -> Fe {
+        let (z_250_0, z11) = self.pow22501();
+        z_250_0.pow2k(5).mul(&z11)
+    }
+
+    /// z^((p - 5) / 8).
+    pub(crate) fn pow_p58(&self) -> Fe {
+        let (z_250_0, _) = self.pow22501();
+        z_250_0.pow2k(2).mul(self)
+    }
+
+    pub(crate) fn is_negative(&self) -> u8 {
+        self.to_bytes()[0] & 1
+    }
+
+    pub(crate) fn ct_eq(&self, rhs: &Fe) -> bool {
+        let (a, b) = (self.to_bytes(), rhs.to_bytes());
+        let diff = a
+            .iter()
+            .zip(b.iter())
+            .fold(0_u8, |acc, (x, y)| acc | (x ^ y));
+        diff == 0
+    }
+
+    /// Swaps `a` and `b` when `choice` is 1, without branching on it.
+    pub(crate) fn cswap(a: &mut Fe, b: &mut Fe, choice: u8) {
+        let mask = 0_u64.wrapping_sub(choice as u64);
+        for i in 0..5 {
+            let t = mask & (a.0[i] ^ b.0[i]);
+            a.0[i] ^= t;
+            b.0[i] ^= t;
+        }
+    }
+}
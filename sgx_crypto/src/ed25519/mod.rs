@@ -0,0 +1,304 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License..
+//! Ed25519 signatures (RFC 8032), implemented in Rust on top of the
+//! tcrypto SHA-512. Scalar multiplication uses a Montgomery ladder and
+//! branch-free field arithmetic, so signing runs in constant time.
+
+use crate::sha::Sha512;
+use sgx_types::error::SgxResult;
+
+#[cfg(any(feature = "tserialize", feature = "userialize"))]
+use sgx_serialize::{Deserialize, Serialize};
+
+pub(crate) mod field;
+mod point;
+mod scalar;
+
+#[cfg(feature = "unit_test")]
+mod tests;
+
+use point::Point;
+
+pub const ED25519_KEY_SIZE: usize = 32;
+pub const ED25519_SIGNATURE_SIZE: usize = 64;
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(
+    any(feature = "tserialize", feature = "userialize"),
+    derive(Deserialize, Serialize)
+)]
+pub struct Ed25519KeyPair {
+    private: Ed25519PrivateKey,
+    public: Ed25519PublicKey,
+}
+
+impl Ed25519KeyPair {
+    pub fn create() -> SgxResult<Ed25519KeyPair> {
+        let private = Ed25519PrivateKey::create();
+        let public = private.export_public_key()?;
+        Ok(Ed25519KeyPair { private, public })
+    }
+
+    pub fn from_private_key(private: Ed25519PrivateKey) -> SgxResult<Ed25519KeyPair> {
+        let public = private.export_public_key()?;
+        Ok(Ed25519KeyPair { private, public })
+    }
+
+    pub fn sign(&self, message: &[u8]) -> SgxResult<Ed25519Signature> {
+        self.private.sign_with_public(message, &self.public)
+    }
+
+    #[inline]
+    pub fn private_key(&self) -> Ed25519PrivateKey {
+        self.private
+    }
+
+    #[inline]
+    pub fn public_key(&self) -> Ed25519PublicKey {
+        self.public
+    }
+
+    #[inline]
+    pub fn clear(&mut self) {
+        self.private.clear();
+        self.public.clear();
+    }
+}
+
+/// The 32-byte Ed25519 seed from which the signing scalar and nonce
+/// prefix are derived.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(
+    any(feature = "tserialize", feature = "userialize"),
+    derive(Deserialize, Serialize)
+)]
+pub struct Ed25519PrivateKey([u8; ED25519_KEY_SIZE]);
+
+impl Ed25519PrivateKey {
+    /// Draws a fresh seed from the enclave RNG.
+    pub fn create() -> Ed25519PrivateKey {
+        let mut seed = [0_u8; ED25519_KEY_SIZE];
+        crate::aes::rand(&mut seed);
+        Ed25519PrivateKey(seed)
+    }
+
+    pub fn sign(&self, message: &[u8]) -> SgxResult<Ed25519Signature> {
+        let public = self.export_public_key()?;
+        self.sign_with_public(message, &public)
+    }
+
+    pub fn export_public_key(&self) -> SgxResult<Ed25519PublicKey> {
+        let (mut a, mut prefix) = self.expand()?;
+        let public = Point::BASE.mul(&a).compress();
+        a.fill(0);
+        prefix.fill(0);
+        Ok(Ed25519PublicKey(public))
+    }
+
+    #[inline]
+    pub fn private_key(&self) -> [u8; ED25519_KEY_SIZE] {
+        self.0
+    }
+
+    #[inline]
+    pub fn clear(&mut self) {
+        self.0.fill(0);
+    }
+
+    /// Hashes the seed and clamps the lower half into the signing scalar;
+    /// the upper half is the nonce prefix.
+    fn expand(&self) -> SgxResult<([u8; 32], [u8; 32])> {
+        let mut hash = Sha512::digest(&self.0)?;
+        let mut a = [0_u8; 32];
+        let mut prefix = [0_u8; 32];
+        a.copy_from_slice(&hash[..32]);
+        prefix.copy_from_slice(&hash[32..]);
+        hash.fill(0);
+
+        a[0] &= 248;
+        a[31] &= 127;
+        a[31] |= 64;
+        Ok((a, prefix))
+    }
+
+    fn sign_with_public(
+        &self,
+        message: &[u8],
+        public: &Ed25519PublicKey,
+    ) -> SgxResult<Ed25519Signature> {
+        let (mut a, mut prefix) = self.expand()?;
+        let r = hash_to_scalar(&[&prefix, message]);
+        prefix.fill(0);
+        let mut r = match r {
+            Ok(r) => r,
+            Err(e) => {
+                a.fill(0);
+                return Err(e);
+            }
+        };
+
+        let big_r = Point::BASE.mul(&r).compress();
+        let k = hash_to_scalar(&[&big_r, &public.0, message]);
+        let s = k.map(|k| scalar::mul_add(&k, &a, &r));
+        a.fill(0);
+        r.fill(0);
+
+        let mut signature = [0_u8; ED25519_SIGNATURE_SIZE];
+        signature[..32].copy_from_slice(&big_r);
+        signature[32..].copy_from_slice(&s?);
+        Ok(Ed25519Signature(signature))
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(
+    any(feature = "tserialize", feature = "userialize"),
+    derive(Deserialize, Serialize)
+)]
+pub struct Ed25519PublicKey([u8; ED25519_KEY_SIZE]);
+
+impl Ed25519PublicKey {
+    /// Checks `signature` over `message` with the cofactorless equation
+    /// [S]B = R + [k]A. Malformed keys, non-canonical S and mismatches all
+    /// yield `Ok(false)`; errors only come from the hash.
+    pub fn verify(&self, message: &[u8], signature: &Ed25519Signature) -> SgxResult<bool> {
+        let mut big_r = [0_u8; 32];
+        let mut s = [0_u8; 32];
+        big_r.copy_from_slice(&signature.0[..32]);
+        s.copy_from_slice(&signature.0[32..]);
+
+        if !scalar::is_canonical(&s) {
+            return Ok(false);
+        }
+        let a = match Point::decompress(&self.0) {
+            Some(a) => a,
+            None => return Ok(false),
+        };
+
+        let k = hash_to_scalar(&[&big_r, &self.0, message])?;
+        let check = Point::BASE.mul(&s).add(&a.neg().mul(&k)).compress();
+        Ok(check == big_r)
+    }
+
+    #[inline]
+    pub fn public_key(&self) -> [u8; ED25519_KEY_SIZE] {
+        self.0
+    }
+
+    #[inline]
+    pub fn clear(&mut self) {
+        self.0.fill(0);
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(
+    any(feature = "tserialize", feature = "userialize"),
+    derive(Deserialize, Serialize)
+)]
+pub struct Ed25519Signature([u8; ED25519_SIGNATURE_SIZE]);
+
+impl Ed25519Signature {
+    #[inline]
+    pub fn signature(&self) -> [u8; ED25519_SIGNATURE_SIZE] {
+        self.0
+    }
+}
+
+impl Default for Ed25519Signature {
+    fn default() -> Ed25519Signature {
+        Ed25519Signature([0; ED25519_SIGNATURE_SIZE])
+    }
+}
+
+/// SHA-512 over the concatenated parts, reduced modulo L.
+fn hash_to_scalar(parts: &[&[u8]]) -> SgxResult<[u8; 32]> {
+    let mut sha = Sha512::new()?;
+    for part in parts.iter().filter(|part| !part.is_empty()) {
+        sha.update(*part)?;
+    }
+    let mut hash = sha.finalize()?;
+    let scalar = scalar::reduce(&hash.hash);
+    hash.fill(0);
+    Ok(scalar)
+}
+
+impl From<[u8; ED25519_KEY_SIZE]> for Ed25519PrivateKey {
+    fn from(key: [u8; ED25519_KEY_SIZE]) -> Ed25519PrivateKey {
+        Ed25519PrivateKey(key)
+    }
+}
+
+impl From<&[u8; ED25519_KEY_SIZE]> for Ed25519PrivateKey {
+    fn from(key: &[u8; ED25519_KEY_SIZE]) -> Ed25519PrivateKey {
+        Ed25519PrivateKey(*key)
+    }
+}
+
+impl AsRef<[u8; ED25519_KEY_SIZE]> for Ed25519PrivateKey {
+    fn as_ref(&self) -> &[u8; ED25519_KEY_SIZE] {
+        &self.0
+    }
+}
+
+impl From<[u8; ED25519_KEY_SIZE]> for Ed25519PublicKey {
+    fn from(key: [u8; ED25519_KEY_SIZE]) -> Ed25519PublicKey {
+        Ed25519PublicKey(key)
+    }
+}
+
+impl From<&[u8; ED25519_KEY_SIZE]> for Ed25519PublicKey {
+    fn from(key: &[u8; ED25519_KEY_SIZE]) -> Ed25519PublicKey {
+        Ed25519PublicKey(*key)
+    }
+}
+
+impl AsRef<[u8; ED25519_KEY_SIZE]> for Ed25519PublicKey {
+    fn as_ref(&self) -> &[u8; ED25519_KEY_SIZE] {
+        &self.0
+    }
+}
+
+impl From<Ed25519PublicKey> for [u8; ED25519_KEY_SIZE] {
+    fn from(key: Ed25519PublicKey) -> [u8; ED25519_KEY_SIZE] {
+        key.0
+    }
+}
+
+impl From<[u8; ED25519_SIGNATURE_SIZE]> for Ed25519Signature {
+    fn from(signature: [u8; ED25519_SIGNATURE_SIZE]) -> Ed25519Signature {
+        Ed25519Signature(signature)
+    }
+}
+
+impl From<&[u8; ED25519_SIGNATURE_SIZE]> for Ed25519Signature {
+    fn from(signature: &[u8; ED25519_SIGNATURE_SIZE]) -> Ed25519Signature {
+        Ed25519Signature(*signature)
+    }
+}
+
+impl AsRef<[u8; ED25519_SIGNATURE_SIZE]> for Ed25519Signature {
+    fn as_ref(&self) -> &[u8; ED25519_SIGNATURE_SIZE] {
+        &self.0
+    }
+}
+
+impl From<Ed25519Signature> for [u8; ED25519_SIGNATURE_SIZE] {
+    fn from(signature: Ed25519Signature) -> [u8; ED25519_SIGNATURE_SIZE] {
+        signature.0
+    }
+}
@@ -0,0 +1,181 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License..
+//! Points on edwards25519 in extended coordinates (X : Y : Z : T), with
+//! x = X/Z, y = Y/Z and xy = T/Z.
+
+use super::field::Fe;
+
+const D: Fe = Fe([
+    929955233495203,
+    466365720129213,
+    1662059464998953,
+    2033849074728123,
+    1442794654840575,
+]);
+const D2: Fe = Fe([
+    1859910466990425,
+    932731440258426,
+    1072319116312658,
+    1815898335770999,
+    633789495995903,
+]);
+const SQRT_M1: Fe = Fe([
+    1718705420411056,
+    234908883556509,
+    2233514472574048,
+    2117202627021982,
+    765476049583133,
+]);
+
+#[derive(Clone, Copy)]
+pub(super) struct Point {
+    x: Fe,
+    y: Fe,
+    z: Fe,
+    t: Fe,
+}
+
+impl Point {
+    const IDENTITY: Point = Point {
+        x: Fe::ZERO,
+        y: Fe::ONE,
+        z: Fe::ONE,
+        t: Fe::ZERO,
+    };
+
+    pub(super) const BASE: Point = Point {
+        x: Fe([
+            1738742601995546,
+            1146398526822698,
+            2070867633025821,
+            562264141797630,
+            587772402128613,
+        ]),
+        y: Fe([
+            1801439850948184,
+            1351079888211148,
+            450359962737049,
+            900719925474099,
+            1801439850948198,
+        ]),
+        z: Fe::ONE,
+        t: Fe([
+            1841354044333475,
+            16398895984059,
+            755974180946558,
+            900171276175154,
+            1821297809914039,
+        ]),
+    };
+
+    /// Decodes a point as in RFC 8032, section 5.1.3. Non-canonical y
+    /// coordinates and encodings of points not on the curve are rejected.
+    pub(super) fn decompress(bytes: &[u8; 32]) -> Option<Point> {
+        let sign = bytes[31] >> 7;
+        let y = Fe::from_bytes(bytes);
+        let mut canonical = *bytes;
+        canonical[31] &= 0x7f;
+        if y.to_bytes() != canonical {
+            return None;
+        }
+
+        let yy = y.square();
+        let u = yy.sub(&Fe::ONE);
+        let v = yy.mul(&D).add(&Fe::ONE);
+        let v3 = v.square().mul(&v);
+        let v7 = v3.square().mul(&v);
+        let mut x = u.mul(&v3).mul(&u.mul(&v7).pow_p58());
+
+        let vxx = v.mul(&x.square());
+        if !vxx.ct_eq(&u) {
+            if !vxx.ct_eq(&u.neg()) {
+                return None;
+            }
+            x = x.mul(&SQRT_M1);
+        }
+        if x.ct_eq(&Fe::ZERO) && sign == 1 {
+            return None;
+        }
+        if x.is_negative() != sign {
+            x = x.neg();
+        }
+
+        Some(Point {
+            x,
+            y,
+            z: Fe::ONE,
+            t: x.mul(&y),
+        })
+    }
+
+    pub(super) fn compress(&self) -> [u8; 32] {
+        let zinv = self.z.invert();
+        let x = self.x.mul(&zinv);
+        let y = self.y.mul(&zinv);
+        let mut bytes = y.to_bytes();
+        bytes[31] |= x.is_negative() << 7;
+        bytes
+    }
+
+    pub(super) fn add(&self, rhs: &Point) -> Point {
+        let a = self.y.sub(&self.x).mul(&rhs.y.sub(&rhs.x));
+        let b = self.y.add(&self.x).mul(&rhs.y.add(&rhs.x));
+        let c = self.t.mul(&D2).mul(&rhs.t);
+        let d = self.z.add(&self.z).mul(&rhs.z);
+        let e = b.sub(&a);
+        let f = d.sub(&c);
+        let g = d.add(&c);
+        let h = b.add(&a);
+        Point {
+            x: e.mul(&f),
+            y: g.mul(&h),
+            z: f.mul(&g),
+            t: e.mul(&h),
+        }
+    }
+
+    pub(super) fn neg(&self) -> Point {
+        Point {
+            x: self.x.neg(),
+            y: self.y,
+            z: self.z,
+            t: self.t.neg(),
+        }
+    }
+
+    fn cswap(a: &mut Point, b: &mut Point, choice: u8) {
+        Fe::cswap(&mut a.x, &mut b.x, choice);
+        Fe::cswap(&mut a.y, &mut b.y, choice);
+        Fe::cswap(&mut a.z, &mut b.z, choice);
+        Fe::cswap(&mut a.t, &mut b.t, choice);
+    }
+
+    /// Multiplies by a little-endian 256-bit scalar with a Montgomery
+    /// ladder, so the sequence of operations does not depend on the scalar.
+    pub(super) fn mul(&self, scalar: &[u8; 32]) -> Point {
+        let mut r0 = Point::IDENTITY;
+        let mut r1 = *self;
+        for i in (0..256).rev() {
+            let bit = (scalar[i / 8] >> (i % 8)) & 1;
+            Point::cswap(&mut r0, &mut r1, bit);
+            r1 = r0.add(&r1);
+            r0 = r0.add(&r0);
+            Point::cswap(&mut r0, &mut r1, bit);
+        }
+        r0
+    }
+}
@@ -0,0 +1,115 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License..
+//! Arithmetic modulo the group order
+//! L = 2^252 + 27742317777372353535851937790883648493.
+
+const L: [u64; 4] = [
+    0x5812631a5cf5d3ed,
+    0x14def9dea2f79cd6,
+    0,
+    0x1000000000000000,
+];
+
+fn to_limbs<const N: usize>(bytes: &[u8]) -> [u64; N] {
+    let mut limbs = [0_u64; N];
+    for (limb, chunk) in limbs.iter_mut().zip(bytes.chunks(8)) {
+        let mut word = [0_u8; 8];
+        word.copy_from_slice(chunk);
+        *limb = u64::from_le_bytes(word);
+    }
+    limbs
+}
+
+fn to_bytes(limbs: &[u64; 4]) -> [u8; 32] {
+    let mut bytes = [0_u8; 32];
+    for (chunk, limb) in bytes.chunks_mut(8).zip(limbs.iter()) {
+        chunk.copy_from_slice(&limb.to_le_bytes());
+    }
+    bytes
+}
+
+/// Returns `a - L` and the final borrow.
+fn sub_l(a: &[u64; 4]) -> ([u64; 4], u64) {
+    let mut out = [0_u64; 4];
+    let mut borrow = 0_u64;
+    for i in 0..4 {
+        let (d1, b1) = a[i].overflowing_sub(L[i]);
+        let (d2, b2) = d1.overflowing_sub(borrow);
+        out[i] = d2;
+        borrow = (b1 | b2) as u64;
+    }
+    (out, borrow)
+}
+
+/// Reduces a little-endian 512-bit integer modulo L, one bit at a time
+/// from the top, with a branch-free conditional subtraction per bit.
+fn reduce_limbs(wide: &[u64; 8]) -> [u64; 4] {
+    let mut acc = [0_u64; 4];
+    for i in (0..512).rev() {
+        let bit = (wide[i / 64] >> (i % 64)) & 1;
+        // acc < L < 2^253, so doubling cannot overflow 256 bits.
+        acc[3] = (acc[3] << 1) | (acc[2] >> 63);
+        acc[2] = (acc[2] << 1) | (acc[1] >> 63);
+        acc[1] = (acc[1] << 1) | (acc[0] >> 63);
+        acc[0] = (acc[0] << 1) | bit;
+
+        let (diff, borrow) = sub_l(&acc);
+        let keep = 0_u64.wrapping_sub(borrow);
+        for j in 0..4 {
+            acc[j] = (acc[j] & keep) | (diff[j] & !keep);
+        }
+    }
+    acc
+}
+
+/// Reduces a 64-byte little-endian integer, such as a SHA-512 digest.
+pub(super) fn reduce(wide: &[u8; 64]) -> [u8; 32] {
+    to_bytes(&reduce_limbs(&to_limbs(wide)))
+}
+
+/// Computes (a * b + c) mod L.
+pub(super) fn mul_add(a: &[u8; 32], b: &[u8; 32], c: &[u8; 32]) -> [u8; 32] {
+    let a: [u64; 4] = to_limbs(a);
+    let b: [u64; 4] = to_limbs(b);
+    let c: [u64; 4] = to_limbs(c);
+
+    let mut wide = [0_u64; 8];
+    for i in 0..4 {
+        let mut carry = 0_u128;
+        for j in 0..4 {
+            let t = (a[i] as u128) * (b[j] as u128) + (wide[i + j] as u128) + carry;
+            wide[i + j] = t as u64;
+            carry = t >> 64;
+        }
+        wide[i + 4] = carry as u64;
+    }
+
+    let mut carry = 0_u128;
+    for (i, limb) in wide.iter_mut().enumerate() {
+        let t = (*limb as u128) + (c.get(i).copied().unwrap_or(0) as u128) + carry;
+        *limb = t as u64;
+        carry = t >> 64;
+    }
+
+    to_bytes(&reduce_limbs(&wide))
+}
+
+/// Whether a 32-byte little-endian integer is already less than L.
+pub(super) fn is_canonical(s: &[u8; 32]) -> bool {
+    let (_, borrow) = sub_l(&to_limbs(s));
+    borrow == 1
+}
@@ -0,0 +1,127 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License..
+use super::{Ed25519KeyPair, Ed25519PrivateKey, Ed25519PublicKey, Ed25519Signature};
+use alloc::vec::Vec;
+use sgx_test_utils::test_case;
+
+fn unhex(hex: &str) -> Vec<u8> {
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap())
+        .collect()
+}
+
+// RFC 8032, section 7.1: TEST 1, TEST 2, TEST 3 and TEST SHA(abc), as
+// (secret key, public key, message, signature).
+const RFC8032_VECTORS: [(&str, &str, &str, &str); 4] = [
+    (
+        "9d61b19deffd5a60ba844af492ec2cc44449c5697b326919703bac031cae7f60",
+        "d75a980182b10ab7d54bfed3c964073a0ee172f3daa62325af021a68f707511a",
+        "",
+        "e5564300c360ac729086e2cc806e828a84877f1eb8e5d974d873e06522490155\
+         5fb8821590a33bacc61e39701cf9b46bd25bf5f0595bbe24655141438e7a100b",
+    ),
+    (
+        "4ccd089b28ff96da9db6c346ec114e0f5b8a319f35aba624da8cf6ed4fb8a6fb",
+        "3d4017c3e843895a92b70aa74d1b7ebc9c982ccf2ec4968cc0cd55f12af4660c",
+        "72",
+        "92a009a9f0d4cab8720e820b5f642540a2b27b5416503f8fb3762223ebdb69da\
+         085ac1e43e15996e458f3613d0f11d8c387b2eaeb4302aeeb00d291612bb0c00",
+    ),
+    (
+        "c5aa8df43f9f837bedb7442f31dcb7b166d38535076f094b85ce3a2e0b4458f7",
+        "fc51cd8e6218a1a38da47ed00230f0580816ed13ba3303ac5deb911548908025",
+        "af82",
+        "6291d657deec24024827e69c3abe01a30ce548a284743a445e3680d7db5ac3ac\
+         18ff9b538d16f290ae67f760984dc6594a7c15e9716ed28dc027beceea1ec40a",
+    ),
+    (
+        "833fe62409237b9d62ec77587520911e9a759cec1d19755b7da901b96dca3d42",
+        "ec172b93ad5e563bf4932c70e1245034c35467ef2efd4d64ebf819683467e2bf",
+        "ddaf35a193617abacc417349ae20413112e6fa4e89a97ea20a9eeee64b55d39a\
+         2192992a274fc1a836ba3c23a3feebbd454d4423643ce80e2a9ac94fa54ca49f",
+        "dc2a4459e7369633a52b1bf277839a00201009a3efbf3ecb69bea2186c26b589\
+         09351fc9ac90b3ecfdfbc7c66431e0303dca179c138ac17ad9bef1177331a704",
+    ),
+];
+
+#[test_case]
+fn ed25519_rfc8032_vectors() {
+    for (secret, public, message, signature) in RFC8032_VECTORS {
+        let secret: [u8; 32] = unhex(secret).try_into().unwrap();
+        let private = Ed25519PrivateKey::from(secret);
+        let public_key = private.export_public_key().unwrap();
+        assert_eq!(public_key.public_key().as_slice(), unhex(public).as_slice());
+
+        let message = unhex(message);
+        let sig = private.sign(&message).unwrap();
+        assert_eq!(sig.signature().as_slice(), unhex(signature).as_slice());
+        assert!(public_key.verify(&message, &sig).unwrap());
+    }
+}
+
+#[test_case]
+fn ed25519_rejects_tampering() {
+    let (secret, public, message, signature) = RFC8032_VECTORS[2];
+    let public: [u8; 32] = unhex(public).try_into().unwrap();
+    let public = Ed25519PublicKey::from(public);
+    let message = unhex(message);
+    let signature: [u8; 64] = unhex(signature).try_into().unwrap();
+
+    // Flip a bit in R, in S, and in the message.
+    for pos in [5, 40] {
+        let mut bad = signature;
+        bad[pos] ^= 1;
+        assert!(!public.verify(&message, &bad.into()).unwrap());
+    }
+    let mut bad_message = message.clone();
+    bad_message[0] ^= 1;
+    assert!(!public.verify(&bad_message, &signature.into()).unwrap());
+
+    // An S at or above the group order is rejected.
+    let mut high_s = signature;
+    high_s[63] |= 0xe0;
+    assert!(!public.verify(&message, &high_s.into()).unwrap());
+
+    // A different key does not verify the signature.
+    let secret: [u8; 32] = unhex(secret).try_into().unwrap();
+    let mut other = secret;
+    other[0] ^= 1;
+    let other = Ed25519PrivateKey::from(other).export_public_key().unwrap();
+    assert!(!other.verify(&message, &signature.into()).unwrap());
+}
+
+#[test_case]
+fn ed25519_rejects_invalid_public_key() {
+    // y = p is not a canonical encoding.
+    let mut non_canonical = [0xff_u8; 32];
+    non_canonical[0] = 0xed;
+    non_canonical[31] = 0x7f;
+    let public = Ed25519PublicKey::from(non_canonical);
+    assert!(!public
+        .verify(b"message", &Ed25519Signature::default())
+        .unwrap());
+}
+
+#[test_case]
+fn ed25519_keypair_sign_verify() {
+    let key_pair = Ed25519KeyPair::create().unwrap();
+    let sig = key_pair.sign(b"audit record").unwrap();
+    assert_eq!(sig, key_pair.private_key().sign(b"audit record").unwrap());
+    assert!(key_pair.public_key().verify(b"audit record", &sig).unwrap());
+    assert!(!key_pair.public_key().verify(b"audit recorD", &sig).unwrap());
+}
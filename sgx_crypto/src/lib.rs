@@ -50,8 +50,29 @@ extern crate sgx_userialize as sgx_serialize;
 
 pub mod aes;
 pub mod ecc;
+pub mod ed25519;
 pub mod mac;
+mod memeq;
 pub mod rsa;
 pub mod sha;
 mod sm;
+mod x25519;
+pub use memeq::*;
 pub use sm::*;
+pub use x25519::*;
+
+// Clears key material with volatile stores, so that zeroing right before a
+// free is not optimized away.
+#[inline]
+pub(crate) fn zeroize(buf: &mut [u8]) {
+    cfg_if! {
+        if #[cfg(feature = "tcrypto")] {
+            sgx_trts::memory::volatile_zeroize(buf);
+        } else {
+            for byte in buf.iter_mut() {
+                unsafe { core::ptr::write_volatile(byte, 0) };
+            }
+            core::sync::atomic::compiler_fence(core::sync::atomic::Ordering::SeqCst);
+        }
+    }
+}
@@ -23,6 +23,9 @@ use sgx_types::marker::ContiguousMemory;
 use sgx_types::memeq::ConstTimeEq;
 use sgx_types::types::{AlignKey128bit, AlignMac128bit, CMacHandle, Key128bit, Mac128bit};
 
+#[cfg(feature = "unit_test")]
+mod tests;
+
 pub struct AesCMac {
     handle: CMacHandle,
     key: AlignKey128bit,
@@ -0,0 +1,89 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License..
+use super::AesCMac;
+use alloc::vec::Vec;
+use sgx_test_utils::test_case;
+use sgx_types::error::SgxStatus;
+
+fn unhex(hex: &str) -> Vec<u8> {
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap())
+        .collect()
+}
+
+// RFC 4493, section 4, examples 2 to 4. Example 1 is the empty message,
+// which the handle rejects.
+const KEY: [u8; 16] = [
+    0x2b, 0x7e, 0x15, 0x16, 0x28, 0xae, 0xd2, 0xa6, 0xab, 0xf7, 0x15, 0x88, 0x09, 0xcf, 0x4f, 0x3c,
+];
+const MESSAGE: &str = "6bc1bee22e409f96e93d7e117393172aae2d8a571e03ac9c9eb76fac45af8e51\
+                       30c81c46a35ce411e5fbc1191a0a52eff69f2445df4f9b17ad2b417be66c3710";
+const MACS: [(usize, &str); 3] = [
+    (16, "070a16b46b4d4144f79bdd9dd04a287c"),
+    (40, "dfa66747de9ae63030ca32611497c827"),
+    (64, "51f0bebf7e3b9d92fc49741779363cfe"),
+];
+
+#[test_case]
+fn cmac_rfc4493_vectors() {
+    let message = unhex(MESSAGE);
+    for (len, mac) in MACS {
+        let expected = unhex(mac);
+        let one_shot = AesCMac::cmac(&KEY, &message[..len]).unwrap();
+        assert_eq!(one_shot.as_slice(), expected.as_slice());
+    }
+}
+
+#[test_case]
+fn cmac_split_input_matches_one_shot() {
+    let message = unhex(MESSAGE);
+    let one_shot = AesCMac::cmac(&KEY, message.as_slice()).unwrap();
+
+    // Splits inside a block, on block boundaries, and single bytes at
+    // either end.
+    for splits in [
+        &[1][..],
+        &[15, 16, 17][..],
+        &[16, 32, 48][..],
+        &[7, 40, 63][..],
+        &[3, 5, 8, 13, 21, 34, 55][..],
+    ] {
+        let mut cmac = AesCMac::new(&KEY).unwrap();
+        let mut start = 0;
+        for &end in splits.iter().chain([message.len()].iter()) {
+            cmac.update(&message[start..end]).unwrap();
+            start = end;
+        }
+        assert_eq!(cmac.finalize().unwrap(), one_shot);
+    }
+
+    // Byte at a time, then reset and reuse.
+    let mut cmac = AesCMac::new(&KEY).unwrap();
+    for byte in message.iter() {
+        cmac.update(byte).unwrap();
+    }
+    assert_eq!(cmac.finalize_reset().unwrap(), one_shot);
+    cmac.update(message.as_slice()).unwrap();
+    cmac.verify(&one_shot).unwrap();
+}
+
+#[test_case]
+fn cmac_rejects_empty_update() {
+    let mut cmac = AesCMac::new(&KEY).unwrap();
+    assert_eq!(cmac.update(&[] as &[u8]), Err(SgxStatus::InvalidParameter));
+}
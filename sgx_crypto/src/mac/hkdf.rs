@@ -0,0 +1,75 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License..
+use super::{HMac, HashType};
+use alloc::vec::Vec;
+use sgx_types::error::{SgxResult, SgxStatus};
+use sgx_types::types::{Mac256bit, MAC_256BIT_SIZE};
+
+#[cfg(feature = "unit_test")]
+mod tests;
+
+/// Largest output `hkdf_sha256_expand` can produce, 255 blocks.
+pub const HKDF_SHA256_MAX_OUTPUT_LEN: usize = 255 * MAC_256BIT_SIZE;
+
+/// HKDF-Extract (RFC 5869) with SHA-256. An empty `salt` stands for a
+/// block of zeros, as the RFC specifies.
+pub fn hkdf_sha256_extract(salt: &[u8], ikm: &[u8]) -> SgxResult<Mac256bit> {
+    let zeros = [0_u8; MAC_256BIT_SIZE];
+    let salt = if salt.is_empty() { &zeros[..] } else { salt };
+
+    let mut hmac = HMac::new(salt, HashType::Sha256)?;
+    if !ikm.is_empty() {
+        hmac.update(ikm)?;
+    }
+    hmac.finalize()
+}
+
+/// HKDF-Expand (RFC 5869) with SHA-256. `prk` must be at least one hash
+/// long and `out_len` at most `HKDF_SHA256_MAX_OUTPUT_LEN`, otherwise
+/// `InvalidParameter` is returned.
+pub fn hkdf_sha256_expand(prk: &[u8], info: &[u8], out_len: usize) -> SgxResult<Vec<u8>> {
+    ensure!(prk.len() >= MAC_256BIT_SIZE, SgxStatus::InvalidParameter);
+    ensure!(
+        out_len <= HKDF_SHA256_MAX_OUTPUT_LEN,
+        SgxStatus::InvalidParameter
+    );
+
+    let mut okm = Vec::with_capacity(out_len);
+    let mut block: Option<Mac256bit> = None;
+    for counter in 1..=out_len.div_ceil(MAC_256BIT_SIZE) as u8 {
+        let mut hmac = HMac::new(prk, HashType::Sha256)?;
+        if let Some(prev) = block.as_ref() {
+            hmac.update(prev)?;
+        }
+        if !info.is_empty() {
+            hmac.update(info)?;
+        }
+        hmac.update(&counter)?;
+        let next = hmac.finalize()?;
+
+        let take = MAC_256BIT_SIZE.min(out_len - okm.len());
+        okm.extend_from_slice(&next[..take]);
+        if let Some(prev) = block.as_mut() {
+            prev.fill(0);
+        }
+        block = Some(next);
+    }
+    if let Some(prev) = block.as_mut() {
+        prev.fill(0);
+    }
+    Ok(okm)
+}
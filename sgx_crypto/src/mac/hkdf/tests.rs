@@ -0,0 +1,89 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License..
+use super::{hkdf_sha256_expand, hkdf_sha256_extract, HKDF_SHA256_MAX_OUTPUT_LEN};
+use alloc::vec::Vec;
+use sgx_test_utils::test_case;
+use sgx_types::error::SgxStatus;
+
+fn unhex(hex: &str) -> Vec<u8> {
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap())
+        .collect()
+}
+
+fn check(ikm: &[u8], salt: &[u8], info: &[u8], prk: &str, okm: &str) {
+    let okm = unhex(okm);
+    let actual_prk = hkdf_sha256_extract(salt, ikm).unwrap();
+    assert_eq!(actual_prk.as_slice(), unhex(prk).as_slice());
+    let actual_okm = hkdf_sha256_expand(&actual_prk, info, okm.len()).unwrap();
+    assert_eq!(actual_okm, okm);
+}
+
+// RFC 5869, appendix A.1.
+#[test_case]
+fn rfc5869_case_1() {
+    let salt: Vec<u8> = (0x00..=0x0c).collect();
+    let info: Vec<u8> = (0xf0..=0xf9).collect();
+    check(
+        &[0x0b; 22],
+        &salt,
+        &info,
+        "077709362c2e32df0ddc3f0dc47bba6390b6c73bb50f9c3122ec844ad7c2b3e5",
+        "3cb25f25faacd57a90434f64d0362f2a2d2d0a90cf1a5a4c5db02d56ecc4c5bf34007208d5b887185865",
+    );
+}
+
+// RFC 5869, appendix A.2.
+#[test_case]
+fn rfc5869_case_2() {
+    let ikm: Vec<u8> = (0x00..=0x4f).collect();
+    let salt: Vec<u8> = (0x60..=0xaf).collect();
+    let info: Vec<u8> = (0xb0..=0xff).collect();
+    check(
+        &ikm,
+        &salt,
+        &info,
+        "06a6b88c5853361a06104c9ceb35b45cef760014904671014a193f40c15fc244",
+        "b11e398dc80327a1c8e7f78c596a49344f012eda2d4efad8a050cc4c19afa97c\
+         59045a99cac7827271cb41c65e590e09da3275600c2f09b8367793a9aca3db71\
+         cc30c58179ec3e87c14c01d5c1f3434f1d87",
+    );
+}
+
+// RFC 5869, appendix A.3: empty salt and info.
+#[test_case]
+fn rfc5869_case_3() {
+    check(
+        &[0x0b; 22],
+        &[],
+        &[],
+        "19ef24a32c717b167f33a91d6f648bdf96596776afdb6377ac434c1c293ccb04",
+        "8da4e775a563c18f715f802a063c5a31b8a11f5c5ee1879ec3454e5f3c738d2d9d201395faa4b61a96c8",
+    );
+}
+
+#[test_case]
+fn expand_rejects_oversized_output() {
+    let prk = [0x42; 32];
+    assert_eq!(
+        hkdf_sha256_expand(&prk, b"info", HKDF_SHA256_MAX_OUTPUT_LEN + 1),
+        Err(SgxStatus::InvalidParameter)
+    );
+    let okm = hkdf_sha256_expand(&prk, b"info", HKDF_SHA256_MAX_OUTPUT_LEN).unwrap();
+    assert_eq!(okm.len(), HKDF_SHA256_MAX_OUTPUT_LEN);
+}
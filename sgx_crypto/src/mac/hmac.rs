@@ -25,6 +25,9 @@ use sgx_types::marker::ContiguousMemory;
 use sgx_types::memeq::ConstTimeEq;
 use sgx_types::types::{AlignMac256bit, HMacHandle, Mac256bit, MAC_256BIT_SIZE};
 
+#[cfg(feature = "unit_test")]
+mod tests;
+
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub enum HashType {
     Sha256,
@@ -0,0 +1,110 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License..
+use super::{HMac, HashType};
+use alloc::vec::Vec;
+use sgx_test_utils::test_case;
+use sgx_types::error::SgxStatus;
+use sgx_types::types::Mac256bit;
+
+fn unhex(hex: &str) -> Vec<u8> {
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap())
+        .collect()
+}
+
+// RFC 4231, section 4, HMAC-SHA-256 outputs. Case 5 is checked on its
+// 128-bit truncation, as in the RFC.
+fn rfc4231_cases() -> Vec<(Vec<u8>, Vec<u8>, &'static str)> {
+    vec![
+        (
+            vec![0x0b; 20],
+            b"Hi There".to_vec(),
+            "b0344c61d8db38535ca8afceaf0bf12b881dc200c9833da726e9376c2e32cff7",
+        ),
+        (
+            b"Jefe".to_vec(),
+            b"what do ya want for nothing?".to_vec(),
+            "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843",
+        ),
+        (
+            vec![0xaa; 20],
+            vec![0xdd; 50],
+            "773ea91e36800e46854db8ebd09181a72959098b3ef8c122d9635514ced565fe",
+        ),
+        (
+            (0x01..=0x19).collect(),
+            vec![0xcd; 50],
+            "82558a389a443c0ea4cc819899f2083a85f0faa3e578f8077a2e3ff46729665b",
+        ),
+        (
+            vec![0x0c; 20],
+            b"Test With Truncation".to_vec(),
+            "a3b6167473100ee06e0c796c2955552b",
+        ),
+        (
+            vec![0xaa; 131],
+            b"Test Using Larger Than Block-Size Key - Hash Key First".to_vec(),
+            "60e431591ee0b67f0d8a26aacbf5b77f8e0bc6213728c5140546040f0ee37f54",
+        ),
+        (
+            vec![0xaa; 131],
+            b"This is a test using a larger than block-size key and a larger \
+          than block-size data. The key needs to be hashed before being \
+          used by the HMAC algorithm."
+                .to_vec(),
+            "9b09ffa71b942fcb27635fbcd5b0e944bfdc63644f0713938a7f51535c3a35e2",
+        ),
+    ]
+}
+
+#[test_case]
+fn hmac_sha256_rfc4231() {
+    for (key, data, expected) in rfc4231_cases() {
+        let expected = unhex(expected);
+        let mac = HMac::hmac(&key, HashType::Sha256, data.as_slice()).unwrap();
+        assert_eq!(&mac[..expected.len()], expected.as_slice());
+    }
+}
+
+#[test_case]
+fn hmac_sha256_incremental_matches_one_shot() {
+    for (key, data, _) in rfc4231_cases() {
+        let one_shot = HMac::hmac(&key, HashType::Sha256, data.as_slice()).unwrap();
+
+        let (head, tail) = data.split_at(data.len() / 2);
+        let mut hmac = HMac::new(&key, HashType::Sha256).unwrap();
+        hmac.update(head).unwrap();
+        hmac.update(tail).unwrap();
+        assert_eq!(hmac.finalize_reset().unwrap(), one_shot);
+
+        // The handle is usable again after a reset.
+        hmac.update(data.as_slice()).unwrap();
+        hmac.verify(&one_shot).unwrap();
+    }
+}
+
+#[test_case]
+fn hmac_sha256_verify_rejects_tampered_mac() {
+    let (key, data, expected) = rfc4231_cases().swap_remove(0);
+    let mut mac: Mac256bit = unhex(expected).as_slice().try_into().unwrap();
+    mac[31] ^= 1;
+
+    let mut hmac = HMac::new(&key, HashType::Sha256).unwrap();
+    hmac.update(data.as_slice()).unwrap();
+    assert_eq!(hmac.verify(&mac), Err(SgxStatus::MacMismatch));
+}
@@ -16,7 +16,9 @@
 // under the License..
 
 mod cmac;
+mod hkdf;
 mod hmac;
 
 pub use cmac::*;
+pub use hkdf::*;
 pub use hmac::*;
@@ -0,0 +1,28 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License..
+use sgx_types::memeq::ConstTimeEq;
+
+#[cfg(feature = "unit_test")]
+mod tests;
+
+/// Compares two byte slices in time that depends only on their length.
+/// Slices of different lengths compare unequal straight away, as the length
+/// is not treated as secret.
+#[inline]
+pub fn consttime_memequal(a: &[u8], b: &[u8]) -> bool {
+    a.ct_eq(b)
+}
@@ -15,8 +15,10 @@
 // specific language governing permissions and limitations
 // under the License..
 
+mod pkcs1;
 mod rsa2048;
 mod rsa3072;
+pub use pkcs1::*;
 pub use rsa2048::*;
 pub use rsa3072::*;
 
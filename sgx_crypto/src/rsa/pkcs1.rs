@@ -0,0 +1,267 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License..
+//! RSA-3072 PKCS#1 v1.5 signature verification over a SHA-256 digest,
+//! for keys that arrive as DER or as big-endian modulus and exponent.
+//! Everything handled here is public, so the arithmetic is not
+//! constant-time.
+
+use core::cmp::Ordering;
+use sgx_types::error::{SgxResult, SgxStatus};
+use sgx_types::types::{RSA3072_KEY_SIZE, SHA256_HASH_SIZE};
+
+#[cfg(feature = "unit_test")]
+mod tests;
+
+const LIMBS: usize = RSA3072_KEY_SIZE / 8;
+
+/// DER DigestInfo header for SHA-256 (RFC 8017, section 9.2).
+const SHA256_DIGEST_INFO: [u8; 19] = [
+    0x30, 0x31, 0x30, 0x0d, 0x06, 0x09, 0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x02, 0x01, 0x05,
+    0x00, 0x04, 0x20,
+];
+
+/// DER encoding of the rsaEncryption OID, 1.2.840.113549.1.1.1.
+const RSA_ENCRYPTION_OID: [u8; 9] = [0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x01, 0x01, 0x01];
+
+/// The public key passed to `rsa3072_verify`.
+#[derive(Clone, Copy, Debug)]
+pub enum Rsa3072VerifyKey<'a> {
+    /// A DER `SubjectPublicKeyInfo` or PKCS#1 `RSAPublicKey`.
+    Der(&'a [u8]),
+    /// Big-endian modulus and public exponent.
+    Raw {
+        modulus: &'a [u8],
+        exponent: &'a [u8],
+    },
+}
+
+/// Verifies an RSASSA-PKCS1-v1_5 signature over a SHA-256 digest with a
+/// 3072-bit key.
+///
+/// Returns `Ok(false)` when the signature is well formed but covers a
+/// different digest. A signature whose encoded message is not a valid
+/// PKCS#1 v1.5 SHA-256 block, or that is not smaller than the modulus,
+/// fails with `InvalidSignature`; a key that does not parse as a 3072-bit
+/// RSA key fails with `InvalidParameter`.
+pub fn rsa3072_verify(
+    public_key: Rsa3072VerifyKey<'_>,
+    digest: &[u8; SHA256_HASH_SIZE],
+    signature: &[u8],
+) -> SgxResult<bool> {
+    let (modulus, exponent) = match public_key {
+        Rsa3072VerifyKey::Der(der) => parse_der_key(der)?,
+        Rsa3072VerifyKey::Raw { modulus, exponent } => (modulus, exponent),
+    };
+    let modulus = strip_zeros(modulus);
+    let exponent = strip_zeros(exponent);
+    ensure!(
+        modulus.len() == RSA3072_KEY_SIZE && modulus[RSA3072_KEY_SIZE - 1] & 1 == 1,
+        SgxStatus::InvalidParameter
+    );
+    ensure!(
+        !exponent.is_empty() && exponent.len() <= 8 && exponent[exponent.len() - 1] & 1 == 1,
+        SgxStatus::InvalidParameter
+    );
+    ensure!(
+        signature.len() == RSA3072_KEY_SIZE,
+        SgxStatus::InvalidSignature
+    );
+
+    let n = from_be_bytes(modulus);
+    let s = from_be_bytes(signature);
+    ensure!(cmp(&s, &n) == Ordering::Less, SgxStatus::InvalidSignature);
+
+    let mut e = [0_u8; 8];
+    e[8 - exponent.len()..].copy_from_slice(exponent);
+    let em = to_be_bytes(&mod_exp(&s, u64::from_be_bytes(e), &n));
+
+    // 0x00 0x01 PS 0x00 DigestInfo H, with PS all 0xff.
+    let digest_at = RSA3072_KEY_SIZE - SHA256_HASH_SIZE;
+    let info_at = digest_at - SHA256_DIGEST_INFO.len();
+    let well_formed = em[0] == 0x00
+        && em[1] == 0x01
+        && em[2..info_at - 1].iter().all(|&b| b == 0xff)
+        && em[info_at - 1] == 0x00
+        && em[info_at..digest_at] == SHA256_DIGEST_INFO;
+    ensure!(well_formed, SgxStatus::InvalidSignature);
+
+    Ok(em[digest_at..] == digest[..])
+}
+
+/// Extracts the big-endian modulus and exponent from either a
+/// `SubjectPublicKeyInfo` or a bare PKCS#1 `RSAPublicKey`.
+fn parse_der_key(der: &[u8]) -> SgxResult<(&[u8], &[u8])> {
+    let (outer, rest) = der_read(der, 0x30)?;
+    ensure!(rest.is_empty(), SgxStatus::InvalidParameter);
+
+    let rsa_public_key = if outer.first() == Some(&0x30) {
+        // SubjectPublicKeyInfo: AlgorithmIdentifier, then a BIT STRING.
+        let (algorithm, rest) = der_read(outer, 0x30)?;
+        let (oid, params) = der_read(algorithm, 0x06)?;
+        ensure!(oid == RSA_ENCRYPTION_OID, SgxStatus::InvalidParameter);
+        ensure!(
+            params.is_empty() || params == [0x05, 0x00],
+            SgxStatus::InvalidParameter
+        );
+        let (bits, rest) = der_read(rest, 0x03)?;
+        ensure!(rest.is_empty(), SgxStatus::InvalidParameter);
+        ensure!(bits.first() == Some(&0x00), SgxStatus::InvalidParameter);
+        let (key, rest) = der_read(&bits[1..], 0x30)?;
+        ensure!(rest.is_empty(), SgxStatus::InvalidParameter);
+        key
+    } else {
+        outer
+    };
+
+    let (modulus, rest) = der_read(rsa_public_key, 0x02)?;
+    let (exponent, rest) = der_read(rest, 0x02)?;
+    ensure!(rest.is_empty(), SgxStatus::InvalidParameter);
+    Ok((modulus, exponent))
+}
+
+/// Reads one DER element with the given tag, returning its contents and
+/// the bytes after it.
+fn der_read(input: &[u8], tag: u8) -> SgxResult<(&[u8], &[u8])> {
+    ensure!(
+        input.len() >= 2 && input[0] == tag,
+        SgxStatus::InvalidParameter
+    );
+    let (len, header) = match input[1] {
+        len @ 0..=0x7f => (len as usize, 2),
+        0x81 => {
+            ensure!(input.len() >= 3, SgxStatus::InvalidParameter);
+            (input[2] as usize, 3)
+        }
+        0x82 => {
+            ensure!(input.len() >= 4, SgxStatus::InvalidParameter);
+            (u16::from_be_bytes([input[2], input[3]]) as usize, 4)
+        }
+        _ => bail!(SgxStatus::InvalidParameter),
+    };
+    ensure!(input.len() - header >= len, SgxStatus::InvalidParameter);
+    Ok(input[header..].split_at(len))
+}
+
+fn strip_zeros(bytes: &[u8]) -> &[u8] {
+    let start = bytes.iter().position(|&b| b != 0).unwrap_or(bytes.len());
+    &bytes[start..]
+}
+
+fn from_be_bytes(bytes: &[u8]) -> [u64; LIMBS] {
+    let mut limbs = [0_u64; LIMBS];
+    for (limb, chunk) in limbs.iter_mut().zip(bytes.rchunks(8)) {
+        let mut word = [0_u8; 8];
+        word[8 - chunk.len()..].copy_from_slice(chunk);
+        *limb = u64::from_be_bytes(word);
+    }
+    limbs
+}
+
+fn to_be_bytes(limbs: &[u64; LIMBS]) -> [u8; RSA3072_KEY_SIZE] {
+    let mut bytes = [0_u8; RSA3072_KEY_SIZE];
+    for (chunk, limb) in bytes.rchunks_mut(8).zip(limbs.iter()) {
+        chunk.copy_from_slice(&limb.to_be_bytes());
+    }
+    bytes
+}
+
+fn cmp(a: &[u64; LIMBS], b: &[u64; LIMBS]) -> Ordering {
+    a.iter().rev().cmp(b.iter().rev())
+}
+
+/// a -= b, returning the borrow.
+fn sub_assign(a: &mut [u64; LIMBS], b: &[u64; LIMBS]) -> bool {
+    let mut borrow = false;
+    for (x, &y) in a.iter_mut().zip(b.iter()) {
+        let (d1, b1) = x.overflowing_sub(y);
+        let (d2, b2) = d1.overflowing_sub(borrow as u64);
+        *x = d2;
+        borrow = b1 | b2;
+    }
+    borrow
+}
+
+/// Montgomery product a * b * R^-1 mod n with R = 2^3072 (CIOS method).
+fn mont_mul(a: &[u64; LIMBS], b: &[u64; LIMBS], n: &[u64; LIMBS], n0inv: u64) -> [u64; LIMBS] {
+    let mut t = [0_u64; LIMBS + 2];
+    for &bi in b.iter() {
+        let mut carry = 0_u128;
+        for j in 0..LIMBS {
+            let v = t[j] as u128 + (a[j] as u128) * (bi as u128) + carry;
+            t[j] = v as u64;
+            carry = v >> 64;
+        }
+        let v = t[LIMBS] as u128 + carry;
+        t[LIMBS] = v as u64;
+        t[LIMBS + 1] = (v >> 64) as u64;
+
+        let m = t[0].wrapping_mul(n0inv);
+        let mut carry = (t[0] as u128 + (m as u128) * (n[0] as u128)) >> 64;
+        for j in 1..LIMBS {
+            let v = t[j] as u128 + (m as u128) * (n[j] as u128) + carry;
+            t[j - 1] = v as u64;
+            carry = v >> 64;
+        }
+        let v = t[LIMBS] as u128 + carry;
+        t[LIMBS - 1] = v as u64;
+        t[LIMBS] = t[LIMBS + 1] + (v >> 64) as u64;
+    }
+
+    let mut out = [0_u64; LIMBS];
+    out.copy_from_slice(&t[..LIMBS]);
+    if t[LIMBS] != 0 || cmp(&out, n) != Ordering::Less {
+        sub_assign(&mut out, n);
+    }
+    out
+}
+
+/// s^e mod n for an odd n with its top bit set.
+fn mod_exp(s: &[u64; LIMBS], e: u64, n: &[u64; LIMBS]) -> [u64; LIMBS] {
+    // -n^-1 mod 2^64 by Newton iteration.
+    let mut inv = 1_u64;
+    for _ in 0..6 {
+        inv = inv.wrapping_mul(2_u64.wrapping_sub(n[0].wrapping_mul(inv)));
+    }
+    let n0inv = inv.wrapping_neg();
+
+    // R^2 mod n by doubling 1 modulo n 2 * 3072 times.
+    let mut r2 = [0_u64; LIMBS];
+    r2[0] = 1;
+    for _ in 0..2 * 64 * LIMBS {
+        let top = r2[LIMBS - 1] >> 63;
+        for j in (1..LIMBS).rev() {
+            r2[j] = (r2[j] << 1) | (r2[j - 1] >> 63);
+        }
+        r2[0] <<= 1;
+        if top == 1 || cmp(&r2, n) != Ordering::Less {
+            sub_assign(&mut r2, n);
+        }
+    }
+
+    let base = mont_mul(s, &r2, n, n0inv);
+    let mut acc = base;
+    for bit in (0..63 - e.leading_zeros()).rev() {
+        acc = mont_mul(&acc, &acc, n, n0inv);
+        if (e >> bit) & 1 == 1 {
+            acc = mont_mul(&acc, &base, n, n0inv);
+        }
+    }
+
+    let mut one = [0_u64; LIMBS];
+    one[0] = 1;
+    mont_mul(&acc, &one, n, n0inv)
+}
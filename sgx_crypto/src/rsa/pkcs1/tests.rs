@@ -0,0 +1,184 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License..
+use super::{rsa3072_verify, Rsa3072VerifyKey};
+use alloc::vec::Vec;
+use sgx_test_utils::test_case;
+use sgx_types::error::SgxStatus;
+
+fn unhex(hex: &str) -> Vec<u8> {
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap())
+        .collect()
+}
+
+// A 3072-bit key with e = 65537 and a SHA-256 PKCS#1 v1.5 signature over
+// "tcb info collateral", generated with the Python cryptography package.
+// BAD_PADDING_SIG is the same key's private operation applied to an
+// otherwise correct block with type 0x02 instead of 0x01.
+const MODULUS: &str = "\
+    d01be56c816652bb7aad2582b24f9a70ce98d432607589aa60f44feea064a72f\
+    b5902bb94547245f8140fe70d2466d6cf7e2a993a4c4c8a739fbc2b9ad364980\
+    a80b72722c7a8dfd808f2e596820b5372a3d93719d19634e81637fc39dcfadc0\
+    4ea5e8177687366d0efe0795e52b2c9296ee2b7115a6a9882b73ea9498d33d17\
+    9ab5795f239208c4f41009f3f878c32275508426542b0e48d8cc7066f638605e\
+    6d232c42c17efcdca4b42415097413c248420cde4a796af6a7f07bfd9c9573c6\
+    9bd36c3d15bb258a56c8b310a5061c14a205b23cd140e86f30478e02406da580\
+    32b2a0cf8b4361b9b4a0ba4d16453207785a7773d9b5c2ddb372527f2a5ab266\
+    20ae81c4789431dd78dae02e099f23dbc38192c0d5f16833895a5242e5cc6de4\
+    670837d79a8b500c00656be505e8cb4697447c1ebfeca18403b4c813520c127d\
+    2cccc9a960a0c129c946647bfadec26c1fedcd02d82aa8703c26881e6384d378\
+    1cd95e624ac26f0b79993ac8ae47789a3901cbefff935f17b41f46a4095b0279";
+
+const SPKI_DER: &str = "\
+    308201a2300d06092a864886f70d01010105000382018f003082018a02820181\
+    00d01be56c816652bb7aad2582b24f9a70ce98d432607589aa60f44feea064a7\
+    2fb5902bb94547245f8140fe70d2466d6cf7e2a993a4c4c8a739fbc2b9ad3649\
+    80a80b72722c7a8dfd808f2e596820b5372a3d93719d19634e81637fc39dcfad\
+    c04ea5e8177687366d0efe0795e52b2c9296ee2b7115a6a9882b73ea9498d33d\
+    179ab5795f239208c4f41009f3f878c32275508426542b0e48d8cc7066f63860\
+    5e6d232c42c17efcdca4b42415097413c248420cde4a796af6a7f07bfd9c9573\
+    c69bd36c3d15bb258a56c8b310a5061c14a205b23cd140e86f30478e02406da5\
+    8032b2a0cf8b4361b9b4a0ba4d16453207785a7773d9b5c2ddb372527f2a5ab2\
+    6620ae81c4789431dd78dae02e099f23dbc38192c0d5f16833895a5242e5cc6d\
+    e4670837d79a8b500c00656be505e8cb4697447c1ebfeca18403b4c813520c12\
+    7d2cccc9a960a0c129c946647bfadec26c1fedcd02d82aa8703c26881e6384d3\
+    781cd95e624ac26f0b79993ac8ae47789a3901cbefff935f17b41f46a4095b02\
+    790203010001";
+
+const SIGNATURE: &str = "\
+    7f8525f62728ce6fa7cd1977c8ab644710ff7f8e4a5736a3c85acf073703d8f5\
+    f267456836e88f05c6b321749ac81f44cac3cf1f84f40490781dd96d14874d8b\
+    1e4bd5656ac960ffe7dc14ac5c5e4743ee914438daba782abc09dd0c538edce9\
+    2368a628a6a86f832bd131671fd1e88792dde881ed316a72bf8442180dc27cc8\
+    d90ee0bd3604f76cc1a27a2eade58da2c8c5bc5e0a5add5ba578836f95454af3\
+    7aee77c0d55deee026bc3b620c3d62a8c2a08cb1f1aeb4298b5a7edaed67e420\
+    d7a2a129a4ac7feca6b9d0b743b0619db4bacfe6e03e76250124d951d9ff3af8\
+    2dfc113f88a0de5917d3508c3fc7476d72994f9bc3a56836b7dc1f17f6fb4db0\
+    5f7b6623a0f7d28c6f7f6324173679de4c7f4e334d54e8d60a573899814b075c\
+    781681c40e27879f7af59516e7a4586c63599012495a0501623786afe7e55f0a\
+    d760bc7a5ca7713cde3267066488786e62b706fa1a56be650037550abf4241e4\
+    d20dfc76a2c4aa57ad1dfbf63ed33de3edd9288b8efe72d4cdee06590eb7cd7a";
+
+const BAD_PADDING_SIG: &str = "\
+    36f76d248c62b287884e49b99efd0dc765dbcbd91b7d4848d3a881e42582ee7d\
+    739a0bf8438839b72e73e3847b5a98732a44ce454bb9a861dbb3ffc33e98624c\
+    aec8ad75a838478408ffa36bf3df2a18982d6614e1d9d3a2a32d27c5f0199b2a\
+    272e3867379d937221ee8ea079eaf92b0dc4c1ef855c5364228790c311578208\
+    6300b7092369dc3e1c93b6e0b5007cd4ce6ed56204b54afe0833ef39af0fcea4\
+    58920392c7c52eeba665424fc3dc4064dcb8d874720155c06cc5538539dfb30e\
+    945fcfe39a0db57a1b3e0d6f2b1778f62f3e886a7b2471cfb46f6e06b8d4cb1a\
+    b552869b08b925a2cc45f5d457e9bb56fa44d1e3d84185619d624627fcfc10c4\
+    60ee1a2b92b868d945fd5d02974a7faf355aa73350b50aeae9b0721db5407707\
+    252415c3eba4809e91b101215f187dd637f8dd041185c0d39412de2b13ccd122\
+    5d067ab2d4ebfccbbcca3556d02530b8171f764a8b40bd1f165675a6f707a79b\
+    56b5c64e079c5cc0a3de3025b726e7ea3f2bb7699aeb321e5a0f6df385a7d525";
+
+const DIGEST: &str = "2cf13a5eebb8e95ea9f0d1e528e89f6b5130d77ce41cfcb880f0ada309ea4ce0";
+
+// The PKCS#1 RSAPublicKey is the tail of the SubjectPublicKeyInfo.
+const SPKI_HEADER_LEN: usize = 24;
+const EXPONENT: [u8; 3] = [0x01, 0x00, 0x01];
+
+fn digest() -> [u8; 32] {
+    unhex(DIGEST).try_into().unwrap()
+}
+
+#[test_case]
+fn rsa3072_verify_accepts_all_key_forms() {
+    let modulus = unhex(MODULUS);
+    let spki = unhex(SPKI_DER);
+    let signature = unhex(SIGNATURE);
+    let keys = [
+        Rsa3072VerifyKey::Raw {
+            modulus: &modulus,
+            exponent: &EXPONENT,
+        },
+        Rsa3072VerifyKey::Der(&spki),
+        Rsa3072VerifyKey::Der(&spki[SPKI_HEADER_LEN..]),
+    ];
+    for key in keys {
+        assert_eq!(rsa3072_verify(key, &digest(), &signature), Ok(true));
+    }
+}
+
+#[test_case]
+fn rsa3072_verify_rejects_other_digest() {
+    let spki = unhex(SPKI_DER);
+    let mut other = digest();
+    other[0] ^= 1;
+    assert_eq!(
+        rsa3072_verify(Rsa3072VerifyKey::Der(&spki), &other, &unhex(SIGNATURE)),
+        Ok(false)
+    );
+}
+
+#[test_case]
+fn rsa3072_verify_reports_malformed_padding() {
+    let spki = unhex(SPKI_DER);
+    let key = Rsa3072VerifyKey::Der(&spki);
+    assert_eq!(
+        rsa3072_verify(key, &digest(), &unhex(BAD_PADDING_SIG)),
+        Err(SgxStatus::InvalidSignature)
+    );
+
+    // A corrupted signature decrypts to noise rather than a padded block.
+    let mut corrupted = unhex(SIGNATURE);
+    corrupted[100] ^= 1;
+    assert_eq!(
+        rsa3072_verify(key, &digest(), &corrupted),
+        Err(SgxStatus::InvalidSignature)
+    );
+
+    // Signatures must be exactly the modulus size and below the modulus.
+    let signature = unhex(SIGNATURE);
+    assert_eq!(
+        rsa3072_verify(key, &digest(), &signature[1..]),
+        Err(SgxStatus::InvalidSignature)
+    );
+    assert_eq!(
+        rsa3072_verify(key, &digest(), &unhex(MODULUS)),
+        Err(SgxStatus::InvalidSignature)
+    );
+}
+
+#[test_case]
+fn rsa3072_verify_rejects_bad_keys() {
+    let signature = unhex(SIGNATURE);
+    let spki = unhex(SPKI_DER);
+    assert_eq!(
+        rsa3072_verify(
+            Rsa3072VerifyKey::Der(&spki[..spki.len() - 1]),
+            &digest(),
+            &signature
+        ),
+        Err(SgxStatus::InvalidParameter)
+    );
+
+    let modulus = unhex(MODULUS);
+    assert_eq!(
+        rsa3072_verify(
+            Rsa3072VerifyKey::Raw {
+                modulus: &modulus[1..],
+                exponent: &EXPONENT,
+            },
+            &digest(),
+            &signature
+        ),
+        Err(SgxStatus::InvalidParameter)
+    );
+}
@@ -18,7 +18,9 @@
 mod sha1;
 mod sha256;
 mod sha384;
+mod sha512;
 
 pub use sha1::*;
 pub use sha256::*;
 pub use sha384::*;
+pub use sha512::*;
@@ -22,6 +22,9 @@ use sgx_types::error::{SgxResult, SgxStatus};
 use sgx_types::marker::ContiguousMemory;
 use sgx_types::types::{Sha384Hash, ShaHandle};
 
+#[cfg(feature = "unit_test")]
+mod tests;
+
 pub struct Sha384 {
     handle: ShaHandle,
 }
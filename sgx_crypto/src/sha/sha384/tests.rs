@@ -0,0 +1,76 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License..
+use super::Sha384;
+use alloc::vec::Vec;
+use sgx_test_utils::test_case;
+
+fn unhex(hex: &str) -> Vec<u8> {
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap())
+        .collect()
+}
+
+const TWO_BLOCK_MSG: &[u8] = b"abcdefghbcdefghicdefghijdefghijkefghijklfghijklmghijklmn\
+    hijklmnoijklmnopjklmnopqklmnopqrlmnopqrsmnopqrstnopqrstu";
+
+// Short-message examples from FIPS 180-2, appendix C.
+#[test_case]
+fn sha384_abc() {
+    let hash = Sha384::digest(b"abc").unwrap();
+    assert_eq!(
+        hash.as_slice(),
+        unhex("cb00753f45a35e8bb5a03d699ac65007272c32ab0eded1631a8b605a43ff5bed8086072ba1e7cc2358baeca134c825a7").as_slice()
+    );
+}
+
+#[test_case]
+fn sha384_empty() {
+    let hash = Sha384::new().unwrap().finalize().unwrap();
+    assert_eq!(
+        hash.as_slice(),
+        unhex("38b060a751ac96384cd9327eb1b1e36a21fdb71114be07434c0cc7bf63f6e1da274edebfe76f65fbd51ad2f14898b95b").as_slice()
+    );
+}
+
+#[test_case]
+fn sha384_two_blocks() {
+    assert_eq!(TWO_BLOCK_MSG.len(), 112);
+    let hash = Sha384::digest(TWO_BLOCK_MSG).unwrap();
+    assert_eq!(
+        hash.as_slice(),
+        unhex("09330c33f71147e83d192fc782cd1b4753111b173b3b05d22fa08086e3b0f712fcc7c71a557e2db966c3e9fa91746039").as_slice()
+    );
+}
+
+// One million repetitions of 'a', fed through the incremental interface.
+#[test_case]
+fn sha384_million_a() {
+    let chunk = [b'a'; 1000];
+    let mut sha = Sha384::new().unwrap();
+    for _ in 0..1000 {
+        sha.update(&chunk).unwrap();
+    }
+    let hash = sha.finalize_reset().unwrap();
+    assert_eq!(
+        hash.as_slice(),
+        unhex("9d0e1809716474cb086e834e310a4a1ced149e9c00f248527972cec5704c2a5b07b8b3dc38ecc4ebae97ddd87f3d8985").as_slice()
+    );
+
+    sha.update(b"abc").unwrap();
+    assert_eq!(sha.finalize().unwrap(), Sha384::digest(b"abc").unwrap());
+}
@@ -0,0 +1,92 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License..
+use super::Sha512;
+use alloc::vec::Vec;
+use sgx_test_utils::test_case;
+
+fn unhex(hex: &str) -> Vec<u8> {
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap())
+        .collect()
+}
+
+const TWO_BLOCK_MSG: &[u8] = b"abcdefghbcdefghicdefghijdefghijkefghijklfghijklmghijklmn\
+    hijklmnoijklmnopjklmnopqklmnopqrlmnopqrsmnopqrstnopqrstu";
+
+// Short-message examples from FIPS 180-2, appendix C.
+#[test_case]
+fn sha512_abc() {
+    let hash = Sha512::digest(b"abc").unwrap();
+    assert_eq!(
+        hash.as_slice(),
+        unhex(
+            "ddaf35a193617abacc417349ae20413112e6fa4e89a97ea20a9eeee64b55d39a\
+             2192992a274fc1a836ba3c23a3feebbd454d4423643ce80e2a9ac94fa54ca49f"
+        )
+        .as_slice()
+    );
+}
+
+#[test_case]
+fn sha512_empty() {
+    let hash = Sha512::new().unwrap().finalize().unwrap();
+    assert_eq!(
+        hash.as_slice(),
+        unhex(
+            "cf83e1357eefb8bdf1542850d66d8007d620e4050b5715dc83f4a921d36ce9ce\
+             47d0d13c5d85f2b0ff8318d2877eec2f63b931bd47417a81a538327af927da3e"
+        )
+        .as_slice()
+    );
+}
+
+#[test_case]
+fn sha512_two_blocks() {
+    assert_eq!(TWO_BLOCK_MSG.len(), 112);
+    let hash = Sha512::digest(TWO_BLOCK_MSG).unwrap();
+    assert_eq!(
+        hash.as_slice(),
+        unhex(
+            "8e959b75dae313da8cf4f72814fc143f8f7779c6eb9f7fa17299aeadb6889018\
+             501d289e4900f7e4331b99dec4b5433ac7d329eeb6dd26545e96e55b874be909"
+        )
+        .as_slice()
+    );
+}
+
+// One million repetitions of 'a', fed through the incremental interface.
+#[test_case]
+fn sha512_million_a() {
+    let chunk = [b'a'; 1000];
+    let mut sha = Sha512::new().unwrap();
+    for _ in 0..1000 {
+        sha.update(&chunk).unwrap();
+    }
+    let hash = sha.finalize_reset().unwrap();
+    assert_eq!(
+        hash.as_slice(),
+        unhex(
+            "e718483d0ce769644e2e42c7bc15b4638e1f98b13b2044285632a803afa973eb\
+             de0ff244877ea60a4cb0432ce577c31beb009c5c2c49aa2e4eadb217ad8cc09b"
+        )
+        .as_slice()
+    );
+
+    sha.update(b"abc").unwrap();
+    assert_eq!(sha.finalize().unwrap(), Sha512::digest(b"abc").unwrap());
+}
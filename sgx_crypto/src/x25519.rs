@@ -0,0 +1,168 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License..
+//! X25519 Diffie-Hellman (RFC 7748), sharing the field arithmetic of the
+//! Ed25519 implementation.
+
+use crate::ed25519::field::Fe;
+use crate::zeroize;
+use sgx_types::error::{SgxResult, SgxStatus};
+
+#[cfg(feature = "unit_test")]
+mod tests;
+
+pub const X25519_KEY_SIZE: usize = 32;
+
+const BASE_POINT: [u8; X25519_KEY_SIZE] = {
+    let mut u = [0_u8; X25519_KEY_SIZE];
+    u[0] = 9;
+    u
+};
+
+/// An X25519 private scalar. It is zeroed when dropped.
+pub struct X25519PrivateKey([u8; X25519_KEY_SIZE]);
+
+impl X25519PrivateKey {
+    /// Draws a fresh scalar from the enclave RNG.
+    pub fn create() -> X25519PrivateKey {
+        let mut key = [0_u8; X25519_KEY_SIZE];
+        crate::aes::rand(&mut key);
+        X25519PrivateKey(key)
+    }
+
+    pub fn public_key(&self) -> X25519PublicKey {
+        X25519PublicKey(x25519(&self.0, &BASE_POINT))
+    }
+
+    #[inline]
+    pub fn private_key(&self) -> &[u8; X25519_KEY_SIZE] {
+        &self.0
+    }
+}
+
+impl Drop for X25519PrivateKey {
+    fn drop(&mut self) {
+        zeroize(&mut self.0);
+    }
+}
+
+impl From<[u8; X25519_KEY_SIZE]> for X25519PrivateKey {
+    fn from(key: [u8; X25519_KEY_SIZE]) -> X25519PrivateKey {
+        X25519PrivateKey(key)
+    }
+}
+
+impl From<&[u8; X25519_KEY_SIZE]> for X25519PrivateKey {
+    fn from(key: &[u8; X25519_KEY_SIZE]) -> X25519PrivateKey {
+        X25519PrivateKey(*key)
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct X25519PublicKey([u8; X25519_KEY_SIZE]);
+
+impl X25519PublicKey {
+    #[inline]
+    pub fn public_key(&self) -> [u8; X25519_KEY_SIZE] {
+        self.0
+    }
+}
+
+impl From<[u8; X25519_KEY_SIZE]> for X25519PublicKey {
+    fn from(key: [u8; X25519_KEY_SIZE]) -> X25519PublicKey {
+        X25519PublicKey(key)
+    }
+}
+
+impl From<&[u8; X25519_KEY_SIZE]> for X25519PublicKey {
+    fn from(key: &[u8; X25519_KEY_SIZE]) -> X25519PublicKey {
+        X25519PublicKey(*key)
+    }
+}
+
+impl AsRef<[u8; X25519_KEY_SIZE]> for X25519PublicKey {
+    fn as_ref(&self) -> &[u8; X25519_KEY_SIZE] {
+        &self.0
+    }
+}
+
+impl From<X25519PublicKey> for [u8; X25519_KEY_SIZE] {
+    fn from(key: X25519PublicKey) -> [u8; X25519_KEY_SIZE] {
+        key.0
+    }
+}
+
+pub fn x25519_keypair() -> (X25519PrivateKey, X25519PublicKey) {
+    let private = X25519PrivateKey::create();
+    let public = private.public_key();
+    (private, public)
+}
+
+/// Computes the shared secret with `peer_public`. An all-zero result, which
+/// a low-order peer key forces regardless of our key, is rejected with
+/// `InvalidParameter`.
+pub fn x25519_shared_secret(
+    private: &X25519PrivateKey,
+    peer_public: &X25519PublicKey,
+) -> SgxResult<[u8; X25519_KEY_SIZE]> {
+    let shared = x25519(&private.0, &peer_public.0);
+    let acc = shared.iter().fold(0_u8, |acc, byte| acc | byte);
+    ensure!(acc != 0, SgxStatus::InvalidParameter);
+    Ok(shared)
+}
+
+/// The X25519 function of RFC 7748, section 5.
+fn x25519(scalar: &[u8; X25519_KEY_SIZE], u: &[u8; X25519_KEY_SIZE]) -> [u8; X25519_KEY_SIZE] {
+    let mut k = *scalar;
+    k[0] &= 248;
+    k[31] &= 127;
+    k[31] |= 64;
+
+    let a24 = Fe([121665, 0, 0, 0, 0]);
+    let x1 = Fe::from_bytes(u);
+    let mut x2 = Fe::ONE;
+    let mut z2 = Fe::ZERO;
+    let mut x3 = x1;
+    let mut z3 = Fe::ONE;
+    let mut swap = 0_u8;
+
+    for t in (0..255).rev() {
+        let bit = (k[t / 8] >> (t % 8)) & 1;
+        swap ^= bit;
+        Fe::cswap(&mut x2, &mut x3, swap);
+        Fe::cswap(&mut z2, &mut z3, swap);
+        swap = bit;
+
+        let a = x2.add(&z2);
+        let aa = a.square();
+        let b = x2.sub(&z2);
+        let bb = b.square();
+        let e = aa.sub(&bb);
+        let c = x3.add(&z3);
+        let d = x3.sub(&z3);
+        let da = d.mul(&a);
+        let cb = c.mul(&b);
+        x3 = da.add(&cb).square();
+        z3 = x1.mul(&da.sub(&cb).square());
+        x2 = aa.mul(&bb);
+        z2 = e.mul(&aa.add(&a24.mul(&e)));
+    }
+    Fe::cswap(&mut x2, &mut x3, swap);
+    Fe::cswap(&mut z2, &mut z3, swap);
+    k.fill(0);
+
+    x2.mul(&z2.invert()).to_bytes()
+}
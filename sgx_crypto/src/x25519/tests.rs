@@ -0,0 +1,122 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License..
+use super::{
+    x25519, x25519_keypair, x25519_shared_secret, X25519PrivateKey, X25519PublicKey, BASE_POINT,
+};
+use alloc::vec::Vec;
+use sgx_test_utils::test_case;
+use sgx_types::error::SgxStatus;
+
+fn unhex32(hex: &str) -> [u8; 32] {
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap())
+        .collect::<Vec<u8>>()
+        .try_into()
+        .unwrap()
+}
+
+// RFC 7748, section 5.2.
+#[test_case]
+fn x25519_rfc7748_vectors() {
+    let vectors = [
+        (
+            "a546e36bf0527c9d3b16154b82465edd62144c0ac1fc5a18506a2244ba449ac4",
+            "e6db6867583030db3594c1a424b15f7c726624ec26b3353b10a903a6d0ab1c4c",
+            "c3da55379de9c6908e94ea4df28d084f32eccf03491c71f754b4075577a28552",
+        ),
+        (
+            "4b66e9d4d1b4673c5ad22691957d6af5c11b6421e0ea01d42ca4169e7918ba0d",
+            "e5210f12786811d3f4b7959d0538ae2c31dbe7106fc03c3efc4cd549c715a493",
+            "95cbde9476e8907d7aade45cb4b873f88b595a68799fa152e6f8f7647aac7957",
+        ),
+    ];
+    for (scalar, u, expected) in vectors {
+        assert_eq!(x25519(&unhex32(scalar), &unhex32(u)), unhex32(expected));
+    }
+}
+
+// RFC 7748, section 5.2: k and u start at 9 and each round computes
+// k, u = x25519(k, u), k.
+#[test_case]
+fn x25519_rfc7748_iterated() {
+    let mut k = BASE_POINT;
+    let mut u = BASE_POINT;
+    for round in 1..=1000 {
+        let next = x25519(&k, &u);
+        u = k;
+        k = next;
+        if round == 1 {
+            assert_eq!(
+                k,
+                unhex32("422c8e7a6227d7bca1350b3e2bb7279f7897b87bb6854b783c60e80311ae3079")
+            );
+        }
+    }
+    assert_eq!(
+        k,
+        unhex32("684cf59ba83309552800ef566f2f4d3c1c3887c49360e3875f2eb94d99532c51")
+    );
+}
+
+// RFC 7748, section 6.1.
+#[test_case]
+fn x25519_rfc7748_diffie_hellman() {
+    let alice = X25519PrivateKey::from(unhex32(
+        "77076d0a7318a57d3c16c17251b26645df4c2f87ebc0992ab177fba51db92c2a",
+    ));
+    let bob = X25519PrivateKey::from(unhex32(
+        "5dab087e624a8a4b79e17f8b83800ee66f3bb1292618b6fd1c2f8b27ff88e0eb",
+    ));
+    let alice_public = alice.public_key();
+    let bob_public = bob.public_key();
+    assert_eq!(
+        alice_public.public_key(),
+        unhex32("8520f0098930a754748b7ddcb43ef75a0dbf3a0d26381af4eba4a98eaa9b4e6a")
+    );
+    assert_eq!(
+        bob_public.public_key(),
+        unhex32("de9edb7d7b7dc1b4d35b61c2ece435373f8343c85b78674dadfc7e146f882b4f")
+    );
+
+    let shared = unhex32("4a5d9d5ba4ce2de1728e3bf480350f25e07e21c947d19e3376f09b3c1e161742");
+    assert_eq!(x25519_shared_secret(&alice, &bob_public).unwrap(), shared);
+    assert_eq!(x25519_shared_secret(&bob, &alice_public).unwrap(), shared);
+}
+
+#[test_case]
+fn x25519_rejects_low_order_peer() {
+    let (private, _) = x25519_keypair();
+    let mut one = [0_u8; 32];
+    one[0] = 1;
+    for peer in [[0_u8; 32], one] {
+        assert_eq!(
+            x25519_shared_secret(&private, &X25519PublicKey::from(peer)),
+            Err(SgxStatus::InvalidParameter)
+        );
+    }
+}
+
+#[test_case]
+fn x25519_keypairs_agree() {
+    let (a, a_public) = x25519_keypair();
+    let (b, b_public) = x25519_keypair();
+    assert_eq!(
+        x25519_shared_secret(&a, &b_public).unwrap(),
+        x25519_shared_secret(&b, &a_public).unwrap()
+    );
+}
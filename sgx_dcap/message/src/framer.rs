@@ -0,0 +1,140 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License..
+
+use crate::message::DcapRaMsg3;
+use alloc::vec::Vec;
+use core::mem;
+use sgx_types::error::{SgxResult, SgxStatus};
+
+#[cfg(feature = "unit_test")]
+mod tests;
+
+pub const FRAME_HEADER_LEN: usize = mem::size_of::<u32>();
+
+/// Reassembles `u32 length || body` frames from a byte stream that may
+/// deliver them piecemeal, e.g. a non-blocking socket. Partial input is
+/// kept between `read_from` calls.
+#[derive(Debug)]
+pub struct MsgFramer {
+    max_len: usize,
+    header: [u8; FRAME_HEADER_LEN],
+    header_len: usize,
+    body: Vec<u8>,
+    body_len: usize,
+}
+
+impl MsgFramer {
+    pub fn new(max_len: usize) -> MsgFramer {
+        MsgFramer {
+            max_len: max_len.min(u32::MAX as usize),
+            header: [0; FRAME_HEADER_LEN],
+            header_len: 0,
+            body: Vec::new(),
+            body_len: 0,
+        }
+    }
+
+    pub fn frame(msg: &[u8]) -> SgxResult<Vec<u8>> {
+        ensure!(
+            !msg.is_empty() && msg.len() <= u32::MAX as usize,
+            SgxStatus::InvalidParameter
+        );
+
+        let mut frame = Vec::new();
+        frame
+            .try_reserve_exact(FRAME_HEADER_LEN + msg.len())
+            .map_err(|_| SgxStatus::OutOfMemory)?;
+        frame.extend_from_slice(&(msg.len() as u32).to_le_bytes());
+        frame.extend_from_slice(msg);
+        Ok(frame)
+    }
+
+    /// True when no partial frame is buffered.
+    #[inline]
+    pub fn is_idle(&self) -> bool {
+        self.header_len == 0
+    }
+
+    /// Pulls bytes through `read` until a frame is complete.
+    ///
+    /// `read` returns how many bytes it wrote, with 0 meaning nothing is
+    /// available yet. `Ok(None)` means the frame is still incomplete and the
+    /// call should be repeated once more input arrives. A malformed length
+    /// or a failing `read` discards the partial frame.
+    pub fn read_from<F>(&mut self, mut read: F) -> SgxResult<Option<Vec<u8>>>
+    where
+        F: FnMut(&mut [u8]) -> SgxResult<usize>,
+    {
+        let result = self.pull(&mut read);
+        if !matches!(result, Ok(None)) {
+            self.reset();
+        }
+        result
+    }
+
+    /// `read_from` followed by parsing the frame as a msg3.
+    pub fn read_msg3_from<F>(&mut self, read: F) -> SgxResult<Option<DcapRaMsg3>>
+    where
+        F: FnMut(&mut [u8]) -> SgxResult<usize>,
+    {
+        self.read_from(read)?
+            .map(|frame| DcapRaMsg3::from_slice(&frame))
+            .transpose()
+    }
+
+    fn pull<F>(&mut self, read: &mut F) -> SgxResult<Option<Vec<u8>>>
+    where
+        F: FnMut(&mut [u8]) -> SgxResult<usize>,
+    {
+        while self.header_len < FRAME_HEADER_LEN {
+            let dst = &mut self.header[self.header_len..];
+            let n = read(dst)?;
+            if n == 0 {
+                return Ok(None);
+            }
+            ensure!(n <= dst.len(), SgxStatus::Unexpected);
+            self.header_len += n;
+
+            if self.header_len == FRAME_HEADER_LEN {
+                let len = u32::from_le_bytes(self.header) as usize;
+                ensure!(len != 0 && len <= self.max_len, SgxStatus::InvalidParameter);
+                self.body
+                    .try_reserve_exact(len)
+                    .map_err(|_| SgxStatus::OutOfMemory)?;
+                self.body.resize(len, 0);
+            }
+        }
+
+        while self.body_len < self.body.len() {
+            let dst = &mut self.body[self.body_len..];
+            let n = read(dst)?;
+            if n == 0 {
+                return Ok(None);
+            }
+            ensure!(n <= dst.len(), SgxStatus::Unexpected);
+            self.body_len += n;
+        }
+
+        Ok(Some(mem::take(&mut self.body)))
+    }
+
+    fn reset(&mut self) {
+        self.header_len = 0;
+        self.body = Vec::new();
+        self.body_len = 0;
+    }
+}
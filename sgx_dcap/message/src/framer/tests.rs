@@ -0,0 +1,102 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License..
+
+use super::{MsgFramer, FRAME_HEADER_LEN};
+use crate::message::{DcapRaMsg3, MIN_QUOTE_LEN};
+use sgx_test_utils::test_case;
+use sgx_types::error::SgxStatus;
+
+// Serves `data` one byte per call, reporting "nothing available" on every
+// other call like a non-blocking socket.
+struct Trickle<'a> {
+    data: &'a [u8],
+    pos: usize,
+    ready: bool,
+}
+
+impl Trickle<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize, SgxStatus> {
+        self.ready = !self.ready;
+        if !self.ready || self.pos == self.data.len() || buf.is_empty() {
+            return Ok(0);
+        }
+        buf[0] = self.data[self.pos];
+        self.pos += 1;
+        Ok(1)
+    }
+}
+
+#[test_case]
+fn msg3_one_byte_at_a_time() {
+    let msg3 = DcapRaMsg3 {
+        quote: vec![0x5a_u8; MIN_QUOTE_LEN + 1].into_boxed_slice(),
+        ..Default::default()
+    };
+    let frame = MsgFramer::frame(&msg3.to_bytes().unwrap()).unwrap();
+    let mut source = Trickle {
+        data: &frame,
+        pos: 0,
+        ready: false,
+    };
+
+    let mut framer = MsgFramer::new(frame.len());
+    let mut calls = 0;
+    let parsed = loop {
+        calls += 1;
+        if let Some(msg) = framer.read_msg3_from(|buf| source.read(buf)).unwrap() {
+            break msg;
+        }
+        assert!(calls <= 2 * frame.len());
+    };
+    assert_eq!(parsed.quote, msg3.quote);
+    assert!(framer.is_idle());
+    assert_eq!(source.pos, frame.len());
+}
+
+#[test_case]
+fn frames_back_to_back() {
+    let mut stream = MsgFramer::frame(b"first").unwrap();
+    stream.extend_from_slice(&MsgFramer::frame(b"second").unwrap());
+
+    let mut framer = MsgFramer::new(64);
+    let mut pos = 0;
+    let mut read = |buf: &mut [u8]| {
+        let n = buf.len().min(stream.len() - pos);
+        buf[..n].copy_from_slice(&stream[pos..pos + n]);
+        pos += n;
+        Ok(n)
+    };
+    assert_eq!(framer.read_from(&mut read).unwrap().unwrap(), b"first");
+    assert_eq!(framer.read_from(&mut read).unwrap().unwrap(), b"second");
+    assert_eq!(framer.read_from(&mut read).unwrap(), None);
+}
+
+#[test_case]
+fn oversized_frame_rejected() {
+    let frame = MsgFramer::frame(&[0_u8; 32]).unwrap();
+    let mut framer = MsgFramer::new(16);
+    let mut pos = 0;
+    let result = framer.read_from(|buf| {
+        let n = buf.len().min(frame.len() - pos);
+        buf[..n].copy_from_slice(&frame[pos..pos + n]);
+        pos += n;
+        Ok(n)
+    });
+    assert_eq!(result.unwrap_err(), SgxStatus::InvalidParameter);
+    assert_eq!(pos, FRAME_HEADER_LEN);
+    assert!(framer.is_idle());
+}
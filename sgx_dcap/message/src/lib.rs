@@ -43,5 +43,9 @@ extern crate sgx_tserialize as sgx_serialize;
 #[cfg(feature = "userialize")]
 extern crate sgx_userialize as sgx_serialize;
 
+mod framer;
+mod mac;
 mod message;
+pub use framer::*;
+pub use mac::*;
 pub use message::*;
@@ -0,0 +1,91 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License..
+
+use core::fmt;
+use sgx_crypto::consttime_memequal;
+use sgx_types::types::{Mac, MAC_SIZE};
+
+#[cfg(any(feature = "tserialize", feature = "userialize"))]
+use sgx_serialize::{Deserialize, Serialize};
+
+#[cfg(feature = "unit_test")]
+mod tests;
+
+/// A 128-bit message MAC. Equality is constant-time and `Debug` never prints
+/// the MAC bytes.
+#[derive(Clone, Copy, Default)]
+#[cfg_attr(
+    any(feature = "tserialize", feature = "userialize"),
+    derive(Deserialize, Serialize)
+)]
+#[repr(transparent)]
+pub struct Mac128(Mac);
+
+impl Mac128 {
+    #[inline]
+    pub const fn new(mac: Mac) -> Mac128 {
+        Mac128(mac)
+    }
+
+    #[inline]
+    pub fn as_bytes(&self) -> &[u8; MAC_SIZE] {
+        &self.0
+    }
+}
+
+impl PartialEq for Mac128 {
+    #[inline]
+    fn eq(&self, other: &Mac128) -> bool {
+        consttime_memequal(&self.0, &other.0)
+    }
+}
+
+impl Eq for Mac128 {}
+
+impl fmt::Debug for Mac128 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Mac128(<redacted>)")
+    }
+}
+
+impl From<Mac> for Mac128 {
+    #[inline]
+    fn from(mac: Mac) -> Mac128 {
+        Mac128(mac)
+    }
+}
+
+impl From<&Mac> for Mac128 {
+    #[inline]
+    fn from(mac: &Mac) -> Mac128 {
+        Mac128(*mac)
+    }
+}
+
+impl From<Mac128> for Mac {
+    #[inline]
+    fn from(mac: Mac128) -> Mac {
+        mac.0
+    }
+}
+
+impl AsRef<[u8; MAC_SIZE]> for Mac128 {
+    #[inline]
+    fn as_ref(&self) -> &[u8; MAC_SIZE] {
+        &self.0
+    }
+}
@@ -0,0 +1,40 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License..
+
+use super::Mac128;
+use alloc::format;
+use sgx_test_utils::test_case;
+
+#[test_case]
+fn mac128_eq() {
+    let mac = Mac128::new([0x5a; 16]);
+    assert_eq!(mac, Mac128::from([0x5a; 16]));
+
+    for i in 0..16 {
+        let mut bytes = [0x5a; 16];
+        bytes[i] ^= 0x01;
+        assert_ne!(mac, Mac128::from(bytes));
+    }
+}
+
+#[test_case]
+fn mac128_debug_redacted() {
+    let mac = Mac128::new([0xab; 16]);
+    let debug = format!("{:?}", mac);
+    assert_eq!(debug, "Mac128(<redacted>)");
+    assert!(!debug.contains("171"));
+}
@@ -15,18 +15,19 @@
 // specific language governing permissions and limitations
 // under the License..
 
+use crate::mac::Mac128;
 use alloc::alloc::Global;
 use alloc::boxed::Box;
 use alloc::vec::{self, Vec};
 use core::alloc::Allocator;
 use core::mem;
-use core::slice;
+use core::sync::atomic::{AtomicUsize, Ordering};
+use sgx_crypto::consttime_memequal;
 use sgx_crypto::ecc::{EcPrivateKey, EcPublicKey, EcSignature};
 use sgx_crypto::mac::AesCMac;
 #[cfg(feature = "tmsg")]
 use sgx_trts::trts::{is_within_enclave, is_within_host, EnclaveRange};
 use sgx_types::error::{SgxResult, SgxStatus};
-use sgx_types::memeq::ConstTimeEq;
 use sgx_types::types::{
     AlignKey128bit, CDcapMRaMsg2, CDcapRaMsg1, CDcapRaMsg3, CDcapURaMsg2, Ec256PublicKey, Mac,
     QlAuthData, QlCertificationData, QlEcdsaSigData, Quote3,
@@ -35,13 +36,42 @@ use sgx_types::types::{
 #[cfg(any(feature = "tserialize", feature = "userialize"))]
 use sgx_serialize::{Deserialize, Serialize};
 
-#[derive(Clone, Copy, Debug, Default)]
+#[cfg(feature = "unit_test")]
+mod tests;
+
+/// Default upper bound for an embedded quote. Quotes are copied into the
+/// enclave heap, so the bound should stay well below the enclave's heap budget.
+pub const DEFAULT_MAX_QUOTE_LEN: usize = 64 * 1024;
+
+pub(crate) const MIN_QUOTE_LEN: usize = mem::size_of::<Quote3>()
+    + mem::size_of::<QlEcdsaSigData>()
+    + mem::size_of::<QlAuthData>()
+    + mem::size_of::<QlCertificationData>();
+
+static MRA_MSG2_MAX_QUOTE_LEN: AtomicUsize = AtomicUsize::new(DEFAULT_MAX_QUOTE_LEN);
+static RA_MSG3_MAX_QUOTE_LEN: AtomicUsize = AtomicUsize::new(DEFAULT_MAX_QUOTE_LEN);
+
+/// Protocol version of the original msg1 layout, which carries no version
+/// byte on the wire.
+pub const DCAP_RA_PROTOCOL_VERSION: u8 = 1;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
 #[cfg_attr(
     any(feature = "tserialize", feature = "userialize"),
     derive(Deserialize, Serialize)
 )]
 pub struct DcapRaMsg1 {
     pub pub_key_a: EcPublicKey,
+    pub protocol_version: u8,
+}
+
+impl Default for DcapRaMsg1 {
+    fn default() -> DcapRaMsg1 {
+        DcapRaMsg1 {
+            pub_key_a: EcPublicKey::default(),
+            protocol_version: DCAP_RA_PROTOCOL_VERSION,
+        }
+    }
 }
 
 impl_struct_ContiguousMemory! {
@@ -76,12 +106,22 @@ impl_asref_array! {
     derive(Deserialize, Serialize)
 )]
 pub struct DcapMRaMsg2 {
-    pub mac: Mac,
+    pub mac: Mac128,
     pub pub_key_b: EcPublicKey,
     pub kdf_id: u32,
     pub quote: Box<[u8]>,
 }
 
+/// A msg2 parsed in place by `DcapMRaMsg2::borrow_from_slice`. The quote is
+/// a view into the parsed buffer, so the value cannot outlive it.
+#[derive(Clone, Copy, Debug)]
+pub struct DcapMRaMsg2Ref<'a> {
+    mac: Mac128,
+    pub_key_b: EcPublicKey,
+    kdf_id: u32,
+    quote: &'a [u8],
+}
+
 #[derive(Clone, Debug, Default)]
 #[cfg_attr(
     any(feature = "tserialize", feature = "userialize"),
@@ -91,6 +131,11 @@ pub struct DcapRaMsg3 {
     pub mac: Mac,
     pub pub_key_a: EcPublicKey,
     pub quote: Box<[u8]>,
+    /// Optional secret encrypted by the initiator under a key derived from
+    /// SK, sent after the quote as `u32 length || bytes` and covered by the
+    /// CMAC. `None` omits the
+    /// section; `Some` of an empty slice sends it with a zero length.
+    pub sealed: Option<Box<[u8]>>,
 }
 
 impl DcapRaMsg1 {
@@ -109,19 +154,36 @@ impl DcapRaMsg1 {
         self.to_bytes_in(alloc)
     }
 
+    /// Later protocol versions append the version byte to the `CDcapRaMsg1`
+    /// layout; version 1 messages keep the original size.
     pub fn to_bytes_in<A: Allocator>(&self, alloc: A) -> SgxResult<Vec<u8, A>> {
-        let raw_msg: CDcapRaMsg1 = self.into();
-        Ok(raw_msg.as_ref().to_vec_in(alloc))
+        let raw_msg = CDcapRaMsg1 {
+            g_a: self.pub_key_a.into(),
+        };
+        let mut bytes = raw_msg.as_ref().to_vec_in(alloc);
+        if self.protocol_version != DCAP_RA_PROTOCOL_VERSION {
+            bytes.push(self.protocol_version);
+        }
+        Ok(bytes)
+    }
+
+    #[inline]
+    pub fn raw_size(&self) -> usize {
+        if self.protocol_version == DCAP_RA_PROTOCOL_VERSION {
+            mem::size_of::<CDcapRaMsg1>()
+        } else {
+            mem::size_of::<CDcapRaMsg1>() + 1
+        }
     }
 
     pub fn copy_to_slice(&self, bytes: &mut [u8]) -> SgxResult {
-        ensure!(
-            bytes.len() == mem::size_of::<CDcapRaMsg1>(),
-            SgxStatus::InvalidParameter
-        );
+        ensure!(bytes.len() == self.raw_size(), SgxStatus::InvalidParameter);
 
         let raw_msg = unsafe { &mut *(bytes.as_mut_ptr() as *mut CDcapRaMsg1) };
         raw_msg.g_a = self.pub_key_a.into();
+        if self.protocol_version != DCAP_RA_PROTOCOL_VERSION {
+            bytes[mem::size_of::<CDcapRaMsg1>()] = self.protocol_version;
+        }
         Ok(())
     }
 
@@ -131,8 +193,17 @@ impl DcapRaMsg1 {
     }
 
     pub fn from_slice(bytes: &[u8]) -> SgxResult<DcapRaMsg1> {
+        let header_len = mem::size_of::<CDcapRaMsg1>();
+        let protocol_version = match bytes.len() {
+            len if len == header_len => DCAP_RA_PROTOCOL_VERSION,
+            len if len == header_len + 1 => bytes[header_len],
+            _ => return Err(SgxStatus::InvalidParameter),
+        };
+
         let raw_msg = unsafe { &*(bytes.as_ptr() as *const CDcapRaMsg1) };
-        Ok(raw_msg.into())
+        let mut msg: DcapRaMsg1 = raw_msg.into();
+        msg.protocol_version = protocol_version;
+        Ok(msg)
     }
 }
 
@@ -143,7 +214,7 @@ impl DcapMRaMsg2 {
         cmac.update(&self.kdf_id)?;
         cmac.update(&self.quote.len())?;
         cmac.update(&self.quote[..])?;
-        self.mac = cmac.finalize()?;
+        self.mac = cmac.finalize()?.into();
 
         Ok(())
     }
@@ -156,7 +227,7 @@ impl DcapMRaMsg2 {
         cmac.update(&self.quote[..])?;
         let mac = cmac.finalize()?;
 
-        ensure!(mac.ct_eq(&self.mac), SgxStatus::MacMismatch);
+        ensure!(self.mac == mac.into(), SgxStatus::MacMismatch);
         Ok(())
     }
 
@@ -183,7 +254,7 @@ impl DcapMRaMsg2 {
         let header_len = mem::size_of::<CDcapMRaMsg2>() as u32;
 
         let raw_msg = unsafe { &mut *(bytes.as_mut_ptr() as *mut CDcapMRaMsg2) };
-        raw_msg.mac = self.mac;
+        raw_msg.mac = self.mac.into();
         raw_msg.g_b = self.pub_key_b.into();
         raw_msg.kdf_id = self.kdf_id;
         raw_msg.quote_size = self.quote.len() as u32;
@@ -200,7 +271,7 @@ impl DcapMRaMsg2 {
         let header_len = mem::size_of::<CDcapMRaMsg2>() as u32;
         let raw_msg = unsafe { &mut *(bytes.as_mut_ptr() as *mut CDcapMRaMsg2) };
 
-        raw_msg.mac = self.mac;
+        raw_msg.mac = self.mac.into();
         raw_msg.g_b = self.pub_key_b.into();
         raw_msg.kdf_id = self.kdf_id;
         raw_msg.quote_size = self.quote.len() as u32;
@@ -215,6 +286,22 @@ impl DcapMRaMsg2 {
     }
 
     pub fn from_slice(bytes: &[u8]) -> SgxResult<DcapMRaMsg2> {
+        let msg2 = Self::borrow_from_slice(bytes)?;
+
+        let mut quote = vec![0_u8; msg2.quote.len()];
+        quote.as_mut_slice().copy_from_slice(msg2.quote);
+
+        Ok(DcapMRaMsg2 {
+            mac: msg2.mac,
+            pub_key_b: msg2.pub_key_b,
+            kdf_id: msg2.kdf_id,
+            quote: quote.into_boxed_slice(),
+        })
+    }
+
+    /// Parses `bytes` with the checks of `from_slice` but without copying the
+    /// quote; the returned view borrows it from `bytes`.
+    pub fn borrow_from_slice(bytes: &[u8]) -> SgxResult<DcapMRaMsg2Ref<'_>> {
         let raw_msg_len = bytes.len();
         ensure!(
             raw_msg_len > mem::size_of::<CDcapMRaMsg2>(),
@@ -234,16 +321,11 @@ impl DcapMRaMsg2 {
             SgxStatus::InvalidParameter
         );
 
-        let mut quote = vec![0_u8; quote_len];
-        quote.as_mut_slice().copy_from_slice(unsafe {
-            slice::from_raw_parts(&raw_msg.quote as *const _ as *const u8, quote_len)
-        });
-
-        Ok(DcapMRaMsg2 {
-            mac: raw_msg.mac,
+        Ok(DcapMRaMsg2Ref {
+            mac: raw_msg.mac.into(),
             pub_key_b: raw_msg.g_b.into(),
             kdf_id: raw_msg.kdf_id,
-            quote: quote.into_boxed_slice(),
+            quote: &bytes[header_len..],
         })
     }
 
@@ -259,12 +341,54 @@ impl DcapMRaMsg2 {
 
     #[inline]
     pub fn check_quote_len(quote_len: usize) -> bool {
-        quote_len <= (u32::MAX as usize) - mem::size_of::<CDcapMRaMsg2>()
-            && quote_len
-                > (mem::size_of::<Quote3>()
-                    + mem::size_of::<QlEcdsaSigData>()
-                    + mem::size_of::<QlAuthData>()
-                    + mem::size_of::<QlCertificationData>())
+        quote_len <= Self::max_quote_len() && quote_len > MIN_QUOTE_LEN
+    }
+
+    /// Sets the largest quote accepted in a msg2. The value should be set
+    /// below the enclave's heap budget and is capped by what fits in the
+    /// 32-bit `quote_size` field.
+    pub fn set_max_quote_len(max_quote_len: usize) {
+        let limit = (u32::MAX as usize) - mem::size_of::<CDcapMRaMsg2>();
+        MRA_MSG2_MAX_QUOTE_LEN.store(max_quote_len.min(limit), Ordering::Relaxed);
+    }
+
+    #[inline]
+    pub fn max_quote_len() -> usize {
+        MRA_MSG2_MAX_QUOTE_LEN.load(Ordering::Relaxed)
+    }
+}
+
+impl<'a> DcapMRaMsg2Ref<'a> {
+    #[inline]
+    pub fn mac(&self) -> Mac128 {
+        self.mac
+    }
+
+    #[inline]
+    pub fn g_b(&self) -> EcPublicKey {
+        self.pub_key_b
+    }
+
+    #[inline]
+    pub fn kdf_id(&self) -> u32 {
+        self.kdf_id
+    }
+
+    #[inline]
+    pub fn quote(&self) -> &'a [u8] {
+        self.quote
+    }
+
+    pub fn verify_cmac(&self, cmac_key: &AlignKey128bit) -> SgxResult {
+        let mut cmac = AesCMac::new(&cmac_key.key)?;
+        cmac.update(&self.pub_key_b)?;
+        cmac.update(&self.kdf_id)?;
+        cmac.update(&self.quote.len())?;
+        cmac.update(self.quote)?;
+        let mac = cmac.finalize()?;
+
+        ensure!(self.mac == mac.into(), SgxStatus::MacMismatch);
+        Ok(())
     }
 }
 
@@ -306,7 +430,7 @@ impl DcapURaMsg2 {
         cmac.update(&self.sign_gb_ga)?;
         let mac = cmac.finalize()?;
 
-        ensure!(mac.ct_eq(&self.mac), SgxStatus::MacMismatch);
+        ensure!(consttime_memequal(&mac, &self.mac), SgxStatus::MacMismatch);
         Ok(())
     }
 
@@ -360,6 +484,10 @@ impl DcapRaMsg3 {
         cmac.update(&self.pub_key_a)?;
         cmac.update(&self.quote.len())?;
         cmac.update(&self.quote[..])?;
+        if let Some(sealed) = self.sealed.as_deref() {
+            cmac.update(&sealed.len())?;
+            cmac.update(sealed)?;
+        }
         self.mac = cmac.finalize()?;
 
         Ok(())
@@ -370,9 +498,13 @@ impl DcapRaMsg3 {
         cmac.update(&self.pub_key_a)?;
         cmac.update(&self.quote.len())?;
         cmac.update(&self.quote[..])?;
+        if let Some(sealed) = self.sealed.as_deref() {
+            cmac.update(&sealed.len())?;
+            cmac.update(sealed)?;
+        }
         let mac = cmac.finalize()?;
 
-        ensure!(mac.ct_eq(&self.mac), SgxStatus::MacMismatch);
+        ensure!(consttime_memequal(&mac, &self.mac), SgxStatus::MacMismatch);
         Ok(())
     }
 
@@ -402,7 +534,7 @@ impl DcapRaMsg3 {
         raw_msg.mac = self.mac;
         raw_msg.g_a = self.pub_key_a.into();
         raw_msg.quote_size = self.quote.len() as u32;
-        bytes[header_len as usize..].copy_from_slice(&self.quote);
+        self.copy_payload(&mut bytes[header_len as usize..]);
 
         Ok(bytes)
     }
@@ -418,11 +550,28 @@ impl DcapRaMsg3 {
         raw_msg.mac = self.mac;
         raw_msg.g_a = self.pub_key_a.into();
         raw_msg.quote_size = self.quote.len() as u32;
-        bytes[header_len as usize..].copy_from_slice(&self.quote);
+        self.copy_payload(&mut bytes[header_len as usize..]);
 
         Ok(())
     }
 
+    // Writes the quote and, if present, the length-prefixed sealed secret.
+    fn copy_payload(&self, payload: &mut [u8]) {
+        let (quote, rest) = payload.split_at_mut(self.quote.len());
+        quote.copy_from_slice(&self.quote);
+        if let Some(sealed) = self.sealed.as_deref() {
+            rest[..4].copy_from_slice(&(sealed.len() as u32).to_le_bytes());
+            rest[4..].copy_from_slice(sealed);
+        }
+    }
+
+    /// `None` if the message had no sealed section, `Some(&[])` if it had
+    /// one of zero length.
+    #[inline]
+    pub fn sealed_secret(&self) -> Option<&[u8]> {
+        self.sealed.as_deref()
+    }
+
     #[inline]
     pub fn from_bytes<A: Allocator>(bytes: Vec<u8, A>) -> SgxResult<DcapRaMsg3> {
         Self::from_slice(bytes.as_slice())
@@ -438,70 +587,150 @@ impl DcapRaMsg3 {
         let header_len = mem::size_of::<CDcapRaMsg3>();
         let raw_msg = unsafe { &*(bytes.as_ptr() as *const CDcapRaMsg3) };
 
-        let quote_len = raw_msg_len - header_len;
+        let payload = &bytes[header_len..];
+        let quote_len = raw_msg.quote_size as usize;
         ensure!(
-            Self::check_quote_len(quote_len),
-            SgxStatus::InvalidParameter
-        );
-        ensure!(
-            quote_len == raw_msg.quote_size as usize,
+            Self::check_quote_len(quote_len) && quote_len <= payload.len(),
             SgxStatus::InvalidParameter
         );
+        let (quote, rest) = payload.split_at(quote_len);
 
-        let mut quote = vec![0_u8; quote_len];
-        quote.as_mut_slice().copy_from_slice(unsafe {
-            slice::from_raw_parts(&raw_msg.quote as *const _ as *const u8, quote_len)
-        });
+        let sealed = if rest.is_empty() {
+            None
+        } else {
+            ensure!(rest.len() >= 4, SgxStatus::InvalidParameter);
+            let (sealed_len, sealed) = rest.split_at(4);
+            let sealed_len = u32::from_le_bytes(sealed_len.try_into().unwrap()) as usize;
+            ensure!(sealed_len == sealed.len(), SgxStatus::InvalidParameter);
+            Some(sealed.into())
+        };
 
         Ok(DcapRaMsg3 {
             mac: raw_msg.mac,
             pub_key_a: raw_msg.g_a.into(),
-            quote: quote.into_boxed_slice(),
+            quote: quote.into(),
+            sealed,
         })
     }
 
     pub fn get_raw_ize(&self) -> Option<u32> {
         let quote_len = self.quote.len();
+        if !Self::check_quote_len(quote_len) {
+            return None;
+        }
 
-        if Self::check_quote_len(quote_len) {
-            Some((mem::size_of::<CDcapRaMsg3>() + quote_len) as u32)
-        } else {
-            None
+        let raw_len = mem::size_of::<CDcapRaMsg3>() + quote_len;
+        match self.sealed.as_deref() {
+            None => Some(raw_len as u32),
+            Some(sealed) => raw_len
+                .checked_add(4 + sealed.len())
+                .and_then(|len| u32::try_from(len).ok()),
         }
     }
 
     #[inline]
     pub fn check_quote_len(quote_len: usize) -> bool {
-        quote_len <= (u32::MAX as usize) - mem::size_of::<CDcapRaMsg3>()
-            && quote_len
-                > (mem::size_of::<Quote3>()
-                    + mem::size_of::<QlEcdsaSigData>()
-                    + mem::size_of::<QlAuthData>()
-                    + mem::size_of::<QlCertificationData>())
+        quote_len <= Self::max_quote_len() && quote_len > MIN_QUOTE_LEN
+    }
+
+    /// Sets the largest quote accepted in a msg3, see
+    /// [`DcapMRaMsg2::set_max_quote_len`].
+    pub fn set_max_quote_len(max_quote_len: usize) {
+        let limit = (u32::MAX as usize) - mem::size_of::<CDcapRaMsg3>();
+        RA_MSG3_MAX_QUOTE_LEN.store(max_quote_len.min(limit), Ordering::Relaxed);
+    }
+
+    #[inline]
+    pub fn max_quote_len() -> usize {
+        RA_MSG3_MAX_QUOTE_LEN.load(Ordering::Relaxed)
     }
 }
 
-impl From<DcapRaMsg1> for CDcapRaMsg1 {
-    fn from(msg: DcapRaMsg1) -> CDcapRaMsg1 {
-        CDcapRaMsg1 {
-            g_a: msg.pub_key_a.into(),
+/// Serializes a msg3 straight from borrowed parts, filling in `quote_size`
+/// from the quote so the header cannot disagree with it.
+#[derive(Clone, Copy, Debug)]
+pub struct DcapRaMsg3Builder<'a> {
+    mac: Mac,
+    pub_key_a: EcPublicKey,
+    quote: &'a [u8],
+    sealed: Option<&'a [u8]>,
+}
+
+impl<'a> DcapRaMsg3Builder<'a> {
+    pub fn new(mac: Mac, pub_key_a: EcPublicKey, quote: &'a [u8]) -> DcapRaMsg3Builder<'a> {
+        DcapRaMsg3Builder {
+            mac,
+            pub_key_a,
+            quote,
+            sealed: None,
+        }
+    }
+
+    /// Appends a sealed secret section, see [`DcapRaMsg3::sealed`].
+    pub fn sealed(mut self, sealed: &'a [u8]) -> DcapRaMsg3Builder<'a> {
+        self.sealed = Some(sealed);
+        self
+    }
+
+    /// Fails with `InvalidParameter`, before allocating, if the quote is
+    /// outside the bounds of [`DcapRaMsg3::check_quote_len`].
+    pub fn build(&self) -> SgxResult<Vec<u8>> {
+        ensure!(
+            DcapRaMsg3::check_quote_len(self.quote.len()),
+            SgxStatus::InvalidParameter
+        );
+        let header_len = mem::size_of::<CDcapRaMsg3>();
+        let mut raw_len = header_len + self.quote.len();
+        if let Some(sealed) = self.sealed {
+            raw_len = raw_len
+                .checked_add(4 + sealed.len())
+                .filter(|&len| u32::try_from(len).is_ok())
+                .ok_or(SgxStatus::InvalidParameter)?;
+        }
+
+        let mut bytes = vec![0_u8; raw_len];
+        let raw_msg = unsafe { &mut *(bytes.as_mut_ptr() as *mut CDcapRaMsg3) };
+        raw_msg.mac = self.mac;
+        raw_msg.g_a = self.pub_key_a.into();
+        raw_msg.quote_size = self.quote.len() as u32;
+
+        let (quote, rest) = bytes[header_len..].split_at_mut(self.quote.len());
+        quote.copy_from_slice(self.quote);
+        if let Some(sealed) = self.sealed {
+            rest[..4].copy_from_slice(&(sealed.len() as u32).to_le_bytes());
+            rest[4..].copy_from_slice(sealed);
         }
+        Ok(bytes)
+    }
+}
+
+// `CDcapRaMsg1` is the version 1 layout and has no version field, so only
+// version 1 messages convert to it, and messages from it are version 1.
+impl TryFrom<DcapRaMsg1> for CDcapRaMsg1 {
+    type Error = SgxStatus;
+
+    fn try_from(msg: DcapRaMsg1) -> SgxResult<CDcapRaMsg1> {
+        CDcapRaMsg1::try_from(&msg)
     }
 }
 
-impl From<&DcapRaMsg1> for CDcapRaMsg1 {
-    fn from(msg: &DcapRaMsg1) -> CDcapRaMsg1 {
-        CDcapRaMsg1 {
+impl TryFrom<&DcapRaMsg1> for CDcapRaMsg1 {
+    type Error = SgxStatus;
+
+    fn try_from(msg: &DcapRaMsg1) -> SgxResult<CDcapRaMsg1> {
+        ensure!(
+            msg.protocol_version == DCAP_RA_PROTOCOL_VERSION,
+            SgxStatus::InvalidVersion
+        );
+        Ok(CDcapRaMsg1 {
             g_a: msg.pub_key_a.into(),
-        }
+        })
     }
 }
 
 impl From<CDcapRaMsg1> for DcapRaMsg1 {
     fn from(msg: CDcapRaMsg1) -> DcapRaMsg1 {
-        DcapRaMsg1 {
-            pub_key_a: msg.g_a.into(),
-        }
+        DcapRaMsg1::from(&msg)
     }
 }
 
@@ -509,6 +738,7 @@ impl From<&CDcapRaMsg1> for DcapRaMsg1 {
     fn from(msg: &CDcapRaMsg1) -> DcapRaMsg1 {
         DcapRaMsg1 {
             pub_key_a: msg.g_a.into(),
+            protocol_version: DCAP_RA_PROTOCOL_VERSION,
         }
     }
 }
@@ -589,6 +819,11 @@ impl EnclaveRange for DcapRaMsg3 {
         if self.quote.len() > 0 && !is_within_enclave(self.quote.as_ptr(), self.quote.len()) {
             return false;
         }
+        if let Some(sealed) = self.sealed.as_deref() {
+            if !sealed.is_empty() && !is_within_enclave(sealed.as_ptr(), sealed.len()) {
+                return false;
+            }
+        }
         true
     }
 
@@ -599,6 +834,11 @@ impl EnclaveRange for DcapRaMsg3 {
         if self.quote.len() > 0 && !is_within_host(self.quote.as_ptr(), self.quote.len()) {
             return false;
         }
+        if let Some(sealed) = self.sealed.as_deref() {
+            if !sealed.is_empty() && !is_within_host(sealed.as_ptr(), sealed.len()) {
+                return false;
+            }
+        }
         true
     }
 }
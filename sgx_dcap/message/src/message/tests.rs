@@ -0,0 +1,253 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License..
+
+use super::DCAP_RA_PROTOCOL_VERSION;
+use super::{DcapMRaMsg2, DcapRaMsg1, DcapRaMsg3, DcapRaMsg3Builder};
+use super::{DEFAULT_MAX_QUOTE_LEN, MIN_QUOTE_LEN};
+use core::mem;
+use sgx_crypto::ecc::EcPublicKey;
+use sgx_test_utils::test_case;
+use sgx_trts::rand::Rng;
+use sgx_types::error::SgxStatus;
+use sgx_types::types::{AlignKey128bit, CDcapRaMsg1};
+
+#[test_case]
+fn msg2_quote_len_default_bound() {
+    assert_eq!(DcapMRaMsg2::max_quote_len(), DEFAULT_MAX_QUOTE_LEN);
+    assert!(!DcapMRaMsg2::check_quote_len(MIN_QUOTE_LEN));
+    assert!(DcapMRaMsg2::check_quote_len(MIN_QUOTE_LEN + 1));
+    assert!(DcapMRaMsg2::check_quote_len(DEFAULT_MAX_QUOTE_LEN));
+    assert!(!DcapMRaMsg2::check_quote_len(DEFAULT_MAX_QUOTE_LEN + 1));
+}
+
+#[test_case]
+fn msg2_quote_len_configured_bound() {
+    let max = MIN_QUOTE_LEN + 128;
+    DcapMRaMsg2::set_max_quote_len(max);
+    assert!(DcapMRaMsg2::check_quote_len(max));
+    assert!(!DcapMRaMsg2::check_quote_len(max + 1));
+    assert!(DcapRaMsg3::check_quote_len(max + 1));
+
+    DcapMRaMsg2::set_max_quote_len(usize::MAX);
+    assert!(DcapMRaMsg2::max_quote_len() < u32::MAX as usize);
+
+    DcapMRaMsg2::set_max_quote_len(DEFAULT_MAX_QUOTE_LEN);
+}
+
+#[test_case]
+fn msg3_quote_len_configured_bound() {
+    let max = MIN_QUOTE_LEN + 128;
+    DcapRaMsg3::set_max_quote_len(max);
+    assert!(DcapRaMsg3::check_quote_len(max));
+    assert!(!DcapRaMsg3::check_quote_len(max + 1));
+
+    DcapRaMsg3::set_max_quote_len(DEFAULT_MAX_QUOTE_LEN);
+}
+
+fn msg3_with(sealed: Option<&[u8]>) -> DcapRaMsg3 {
+    DcapRaMsg3 {
+        quote: vec![0x33_u8; MIN_QUOTE_LEN + 1].into_boxed_slice(),
+        sealed: sealed.map(Into::into),
+        ..Default::default()
+    }
+}
+
+#[test_case]
+fn msg3_sealed_secret_roundtrip() {
+    let key = AlignKey128bit::default();
+    let mut msg3 = msg3_with(Some(b"sealed secret"));
+    msg3.gen_cmac(&key).unwrap();
+
+    let bytes = msg3.to_bytes().unwrap();
+    let parsed = DcapRaMsg3::from_slice(&bytes).unwrap();
+    assert_eq!(parsed.sealed_secret(), Some(&b"sealed secret"[..]));
+    assert_eq!(parsed.quote, msg3.quote);
+    assert!(parsed.verify_cmac(&key).is_ok());
+
+    let mut plain = msg3_with(None);
+    plain.gen_cmac(&key).unwrap();
+    let parsed = DcapRaMsg3::from_slice(&plain.to_bytes().unwrap()).unwrap();
+    assert_eq!(parsed.sealed_secret(), None);
+    assert!(parsed.verify_cmac(&key).is_ok());
+}
+
+#[test_case]
+fn msg3_sealed_secret_covered_by_cmac() {
+    let key = AlignKey128bit::default();
+    let mut msg3 = msg3_with(Some(b"sealed secret"));
+    msg3.gen_cmac(&key).unwrap();
+
+    msg3.sealed = None;
+    assert_eq!(msg3.verify_cmac(&key).unwrap_err(), SgxStatus::MacMismatch);
+}
+
+#[test_case]
+fn msg3_sealed_secret_bad_length() {
+    let bytes = msg3_with(Some(b"sealed secret")).to_bytes().unwrap();
+    assert!(DcapRaMsg3::from_slice(&bytes[..bytes.len() - 1]).is_err());
+
+    let bytes = msg3_with(None).to_bytes().unwrap();
+    let mut short = bytes.clone();
+    short.extend_from_slice(&[0, 0, 0]);
+    assert!(DcapRaMsg3::from_slice(&short).is_err());
+}
+
+#[test_case]
+fn msg3_empty_vs_absent_sealed_secret() {
+    let key = AlignKey128bit::default();
+
+    let mut absent = msg3_with(None);
+    absent.gen_cmac(&key).unwrap();
+    let absent_bytes = absent.to_bytes().unwrap();
+    let parsed = DcapRaMsg3::from_slice(&absent_bytes).unwrap();
+    assert_eq!(parsed.sealed_secret(), None);
+
+    let mut empty = msg3_with(Some(&[]));
+    empty.gen_cmac(&key).unwrap();
+    let empty_bytes = empty.to_bytes().unwrap();
+    assert_eq!(empty_bytes.len(), absent_bytes.len() + 4);
+    assert_eq!(empty_bytes[absent_bytes.len()..], [0, 0, 0, 0]);
+    let parsed = DcapRaMsg3::from_slice(&empty_bytes).unwrap();
+    assert_eq!(parsed.sealed_secret(), Some(&[][..]));
+    assert!(parsed.verify_cmac(&key).is_ok());
+
+    // The two encodings are distinct under the CMAC as well.
+    assert_ne!(absent.mac, empty.mac);
+}
+
+#[test_case]
+fn msg1_bytes_roundtrip() {
+    let mut rng = Rng::new();
+    let mut pub_key = [0_u8; 64];
+    rng.fill_bytes(&mut pub_key);
+
+    for protocol_version in [DCAP_RA_PROTOCOL_VERSION, DCAP_RA_PROTOCOL_VERSION + 1] {
+        let msg1 = DcapRaMsg1 {
+            pub_key_a: EcPublicKey::from(pub_key),
+            protocol_version,
+        };
+        let bytes = msg1.to_bytes().unwrap();
+        assert_eq!(bytes.len(), msg1.raw_size());
+        assert_eq!(DcapRaMsg1::from_slice(&bytes).unwrap(), msg1);
+        assert_eq!(DcapRaMsg1::from_bytes(bytes).unwrap(), msg1);
+    }
+}
+
+#[test_case]
+fn msg1_rejects_truncated_bytes() {
+    let msg1 = DcapRaMsg1 {
+        protocol_version: DCAP_RA_PROTOCOL_VERSION + 1,
+        ..Default::default()
+    };
+    let bytes = msg1.to_bytes().unwrap();
+    for len in 0..mem::size_of::<CDcapRaMsg1>() {
+        assert_eq!(
+            DcapRaMsg1::from_slice(&bytes[..len]).unwrap_err(),
+            SgxStatus::InvalidParameter
+        );
+    }
+    assert!(DcapRaMsg1::from_slice(&[0; mem::size_of::<CDcapRaMsg1>() + 2]).is_err());
+}
+
+#[test_case]
+fn msg1_c_layout_is_version_1() {
+    let mut msg1 = DcapRaMsg1::default();
+    let raw_msg = CDcapRaMsg1::try_from(&msg1).unwrap();
+    assert_eq!(DcapRaMsg1::from(&raw_msg), msg1);
+
+    msg1.protocol_version = DCAP_RA_PROTOCOL_VERSION + 1;
+    assert_eq!(
+        CDcapRaMsg1::try_from(msg1).unwrap_err(),
+        SgxStatus::InvalidVersion
+    );
+}
+
+#[test_case]
+fn msg2_borrow_matches_owned() {
+    let key = AlignKey128bit::default();
+    let mut msg2 = DcapMRaMsg2 {
+        kdf_id: 0x0001,
+        quote: vec![0x22_u8; MIN_QUOTE_LEN + 1].into_boxed_slice(),
+        ..Default::default()
+    };
+    msg2.gen_cmac(&key).unwrap();
+    let mut bytes = msg2.to_bytes().unwrap();
+
+    let view = DcapMRaMsg2::borrow_from_slice(&bytes).unwrap();
+    assert_eq!(view.quote(), &*msg2.quote);
+    assert!(bytes.as_ptr_range().contains(&view.quote().as_ptr()));
+    assert_eq!(view.g_b(), msg2.pub_key_b);
+    assert_eq!(view.kdf_id(), msg2.kdf_id);
+    assert!(view.mac() == msg2.mac);
+    assert!(view.verify_cmac(&key).is_ok());
+
+    let owned = DcapMRaMsg2::from_slice(&bytes).unwrap();
+    assert_eq!(owned.quote, msg2.quote);
+
+    *bytes.last_mut().unwrap() ^= 1;
+    let view = DcapMRaMsg2::borrow_from_slice(&bytes).unwrap();
+    assert_eq!(view.verify_cmac(&key), Err(SgxStatus::MacMismatch));
+
+    bytes.pop();
+    assert_eq!(
+        DcapMRaMsg2::borrow_from_slice(&bytes).unwrap_err(),
+        SgxStatus::InvalidParameter
+    );
+}
+
+#[test_case]
+fn msg3_builder_roundtrip() {
+    let key = AlignKey128bit::default();
+    let mut msg3 = msg3_with(Some(b"sealed"));
+    msg3.gen_cmac(&key).unwrap();
+
+    let bytes = DcapRaMsg3Builder::new(msg3.mac, msg3.pub_key_a, &msg3.quote)
+        .sealed(b"sealed")
+        .build()
+        .unwrap();
+    assert_eq!(bytes, msg3.to_bytes().unwrap());
+
+    let parsed = DcapRaMsg3::from_slice(&bytes).unwrap();
+    assert_eq!(parsed.quote, msg3.quote);
+    assert_eq!(parsed.sealed_secret(), Some(&b"sealed"[..]));
+    assert!(parsed.verify_cmac(&key).is_ok());
+
+    let bytes = DcapRaMsg3Builder::new(msg3.mac, msg3.pub_key_a, &msg3.quote)
+        .build()
+        .unwrap();
+    assert_eq!(
+        DcapRaMsg3::from_slice(&bytes).unwrap().sealed_secret(),
+        None
+    );
+}
+
+#[test_case]
+fn msg3_builder_checks_quote_len() {
+    let quote = vec![0x33_u8; MIN_QUOTE_LEN + 16];
+    DcapRaMsg3::set_max_quote_len(MIN_QUOTE_LEN + 8);
+    let result = DcapRaMsg3Builder::new(Default::default(), Default::default(), &quote).build();
+    DcapRaMsg3::set_max_quote_len(DEFAULT_MAX_QUOTE_LEN);
+    assert_eq!(result.unwrap_err(), SgxStatus::InvalidParameter);
+
+    let result = DcapRaMsg3Builder::new(
+        Default::default(),
+        Default::default(),
+        &quote[..MIN_QUOTE_LEN],
+    )
+    .build();
+    assert_eq!(result.unwrap_err(), SgxStatus::InvalidParameter);
+}
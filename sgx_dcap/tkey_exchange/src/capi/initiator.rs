@@ -15,6 +15,7 @@
 // specific language governing permissions and limitations
 // under the License..
 
+use super::write_key_or_zero;
 use crate::session::Initiator;
 use crate::QveReportInfo;
 use core::mem::{self, ManuallyDrop};
@@ -24,8 +25,8 @@ use sgx_trts::trts::{is_within_enclave, is_within_host};
 use sgx_types::error::SgxStatus;
 use sgx_types::types::time_t;
 use sgx_types::types::{
-    CDcapMRaMsg2, CDcapRaMsg3, CDcapURaMsg2, CEnclaveIdentity, Ec256PublicKey, Key128bit,
-    QlQvResult, Quote3, QuoteNonce, RaContext, RaKeyType, Report, TargetInfo,
+    CDcapMRaMsg2, CDcapRaMsg1, CDcapRaMsg3, CDcapURaMsg2, CEnclaveIdentity, Ec256PublicKey,
+    Key128bit, QlQvResult, Quote3, QuoteNonce, RaContext, RaKeyType, Report, TargetInfo,
 };
 
 /// # Safety
@@ -82,7 +83,10 @@ pub unsafe extern "C" fn sgx_dcap_ra_get_ga(
         return SgxStatus::InvalidParameter;
     }
 
-    let initiator = ManuallyDrop::new(Initiator::from_raw(context));
+    let initiator = match Initiator::from_raw(context) {
+        Ok(initiator) => ManuallyDrop::new(initiator),
+        Err(e) => return e,
+    };
     let key = match initiator.get_ga() {
         Ok(key) => key,
         Err(e) => return e,
@@ -92,6 +96,33 @@ pub unsafe extern "C" fn sgx_dcap_ra_get_ga(
     SgxStatus::Success
 }
 
+/// # Safety
+#[no_mangle]
+pub unsafe extern "C" fn sgx_dcap_mra_get_msg1(
+    context: RaContext,
+    msg1: *mut CDcapRaMsg1,
+) -> SgxStatus {
+    if msg1.is_null() {
+        return SgxStatus::InvalidParameter;
+    }
+
+    if !is_within_enclave(msg1 as *const u8, mem::size_of::<CDcapRaMsg1>()) {
+        return SgxStatus::InvalidParameter;
+    }
+
+    let initiator = match Initiator::from_raw(context) {
+        Ok(initiator) => ManuallyDrop::new(initiator),
+        Err(e) => return e,
+    };
+    let key = match initiator.get_ga() {
+        Ok(key) => key,
+        Err(e) => return e,
+    };
+
+    (*msg1).g_a = key.into();
+    SgxStatus::Success
+}
+
 /// # Safety
 #[no_mangle]
 pub unsafe extern "C" fn sgx_dcap_mra_proc_msg2(
@@ -153,6 +184,14 @@ pub unsafe extern "C" fn sgx_dcap_mra_proc_msg2(
         return SgxStatus::InvalidParameter;
     }
 
+    let quote_size = (*msg2).quote_size;
+    if !DcapMRaMsg2::check_quote_len(quote_size as usize) {
+        return SgxStatus::InvalidParameter;
+    }
+    if msg2_size != mem::size_of::<CDcapMRaMsg2>() as u32 + quote_size {
+        return SgxStatus::InvalidParameter;
+    }
+
     let qve_nonce = *qve_nonce;
     let qve_report = &*qve_report;
     let qe_target = &*qe_target;
@@ -172,16 +211,19 @@ pub unsafe extern "C" fn sgx_dcap_mra_proc_msg2(
         None
     };
 
-    let qve_report_info = QveReportInfo {
+    let qve_report_info = QveReportInfo::new(
         qve_report,
         expiration_time,
         collateral_expiration_status,
         quote_verification_result,
         qve_nonce,
         supplemental_data,
-    };
+    );
 
-    let initiator = ManuallyDrop::new(Initiator::from_raw(context));
+    let initiator = match Initiator::from_raw(context) {
+        Ok(initiator) => ManuallyDrop::new(initiator),
+        Err(e) => return e,
+    };
     let (rpt, rand, _) = match initiator.process_mra_msg2(&msg2, qe_target, &qve_report_info) {
         Ok(r) => r,
         Err(e) => return e,
@@ -216,7 +258,10 @@ pub unsafe extern "C" fn sgx_dcap_ura_proc_msg2(
     let qe_target = &*qe_target;
     let msg2 = (&*msg2).into();
 
-    let initiator = ManuallyDrop::new(Initiator::from_raw(context));
+    let initiator = match Initiator::from_raw(context) {
+        Ok(initiator) => ManuallyDrop::new(initiator),
+        Err(e) => return e,
+    };
     let (rpt, rand) = match initiator.process_ura_msg2(&msg2, qe_target) {
         Ok(r) => r,
         Err(e) => return e,
@@ -268,7 +313,10 @@ pub unsafe extern "C" fn sgx_dcap_ra_get_msg3(
 
     let quote = slice::from_raw_parts(&c_msg3.quote as *const _ as *const u8, quote_size as usize);
 
-    let initiator = ManuallyDrop::new(Initiator::from_raw(context));
+    let initiator = match Initiator::from_raw(context) {
+        Ok(initiator) => ManuallyDrop::new(initiator),
+        Err(e) => return e,
+    };
     let msg3 = match initiator.generate_msg3(qe_report, quote) {
         Ok(msg) => msg,
         Err(e) => return e,
@@ -279,6 +327,19 @@ pub unsafe extern "C" fn sgx_dcap_ra_get_msg3(
     SgxStatus::Success
 }
 
+/// Same as `sgx_dcap_ra_get_msg3`, named after the other mutual RA calls.
+///
+/// # Safety
+#[no_mangle]
+pub unsafe extern "C" fn sgx_dcap_mra_get_msg3(
+    context: RaContext,
+    qe_report: *const Report,
+    msg3: *mut CDcapRaMsg3,
+    msg3_size: u32,
+) -> SgxStatus {
+    sgx_dcap_ra_get_msg3(context, qe_report, msg3, msg3_size)
+}
+
 /// # Safety
 #[no_mangle]
 pub unsafe extern "C" fn sgx_mra_initiator_get_peer_identity(
@@ -303,7 +364,10 @@ pub unsafe extern "C" fn sgx_mra_initiator_get_peer_identity(
         return SgxStatus::InvalidParameter;
     }
 
-    let initiator = ManuallyDrop::new(Initiator::from_raw(context));
+    let initiator = match Initiator::from_raw(context) {
+        Ok(initiator) => ManuallyDrop::new(initiator),
+        Err(e) => return e,
+    };
     let (qv_result, identity) = match initiator.get_peer_identity() {
         Ok(identity) => identity,
         Err(e) => return e,
@@ -321,28 +385,33 @@ pub unsafe extern "C" fn sgx_ra_initiator_get_keys(
     key_type: RaKeyType,
     key: *mut Key128bit,
 ) -> SgxStatus {
-    if key.is_null() {
-        return SgxStatus::InvalidParameter;
-    }
-
-    if !is_within_enclave(key as *const u8, mem::size_of::<Key128bit>()) {
-        return SgxStatus::InvalidParameter;
-    }
+    write_key_or_zero(key, || {
+        let initiator = ManuallyDrop::new(Initiator::from_raw(context)?);
+        initiator.get_keys(key_type).map(|key| key.key)
+    })
+}
 
-    let initiator = ManuallyDrop::new(Initiator::from_raw(context));
-    let ra_key = match initiator.get_keys(key_type) {
-        Ok(key) => key.key,
+/// # Safety
+#[no_mangle]
+pub unsafe extern "C" fn sgx_ra_initiator_rekey(context: RaContext) -> SgxStatus {
+    let initiator = match Initiator::from_raw(context) {
+        Ok(initiator) => ManuallyDrop::new(initiator),
         Err(e) => return e,
     };
-
-    *key = ra_key;
-    SgxStatus::Success
+    match initiator.rekey() {
+        Ok(()) => SgxStatus::Success,
+        Err(e) => e,
+    }
 }
 
 /// # Safety
 #[no_mangle]
 pub unsafe extern "C" fn sgx_ra_initiator_close(context: RaContext) -> SgxStatus {
-    let initiator = Initiator::from_raw(context);
-    drop(initiator);
-    SgxStatus::Success
+    match Initiator::from_raw(context) {
+        Ok(initiator) => {
+            drop(initiator);
+            SgxStatus::Success
+        }
+        Err(e) => e,
+    }
 }
@@ -15,8 +15,67 @@
 // specific language governing permissions and limitations
 // under the License..
 
+use core::mem;
+use core::ptr;
+use sgx_trts::trts::is_within_enclave_val;
+use sgx_types::error::SgxStatus;
+use sgx_types::types::Key128bit;
+
 mod initiator;
 mod responder;
 
 pub use initiator::*;
 pub use responder::*;
+
+#[cfg(feature = "unit_test")]
+mod tests;
+
+fn is_key_buffer(dst: *const Key128bit) -> bool {
+    !dst.is_null() && is_within_enclave_val(dst)
+}
+
+/// Writes `key` to a caller-supplied buffer in one copy from a local.
+///
+/// `dst` must be non-null and lie entirely within the enclave; otherwise
+/// nothing is written. The local copy is zeroed before returning.
+///
+/// # Safety
+///
+/// `dst` must be valid for writes of a `Key128bit` when it passes the checks.
+pub(crate) unsafe fn write_key_checked(dst: *mut Key128bit, key: &Key128bit) -> SgxStatus {
+    if !is_key_buffer(dst) {
+        return SgxStatus::InvalidParameter;
+    }
+
+    let mut local: Key128bit = *key;
+    ptr::copy_nonoverlapping(&local, dst, 1);
+    ptr::write_volatile(&mut local, [0; mem::size_of::<Key128bit>()]);
+    SgxStatus::Success
+}
+
+/// Fetches a key through `get` and writes it to `dst`. The caller buffer is
+/// zeroed if the key cannot be produced, so it never holds stale material.
+///
+/// # Safety
+///
+/// Same requirements as [`write_key_checked`].
+pub(crate) unsafe fn write_key_or_zero<F>(dst: *mut Key128bit, get: F) -> SgxStatus
+where
+    F: FnOnce() -> Result<Key128bit, SgxStatus>,
+{
+    if !is_key_buffer(dst) {
+        return SgxStatus::InvalidParameter;
+    }
+
+    match get() {
+        Ok(mut key) => {
+            let status = write_key_checked(dst, &key);
+            ptr::write_volatile(&mut key, [0; mem::size_of::<Key128bit>()]);
+            status
+        }
+        Err(e) => {
+            let _ = write_key_checked(dst, &[0; mem::size_of::<Key128bit>()]);
+            e
+        }
+    }
+}
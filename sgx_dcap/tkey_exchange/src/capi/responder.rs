@@ -15,13 +15,21 @@
 // specific language governing permissions and limitations
 // under the License..
 
+use super::write_key_or_zero;
+use crate::ecall::copy_from_host;
 use crate::session::Responder;
 use crate::QveReportInfo;
 use core::mem::{self, ManuallyDrop};
+use core::ptr;
 use core::slice;
-use sgx_dcap_ra_msg::{DcapMRaMsg2, DcapRaMsg3};
-use sgx_trts::trts::{is_within_enclave, is_within_host};
-use sgx_types::error::SgxStatus;
+use sgx_crypto::ecc::EcPublicKey;
+use sgx_dcap_ra_msg::{DcapMRaMsg2, DcapRaMsg1, DcapRaMsg3};
+use sgx_trts::fence;
+use sgx_trts::trts::{
+    check_enclave_ptrs, is_within_enclave, is_within_enclave_slice, is_within_enclave_val,
+    is_within_host,
+};
+use sgx_types::error::{SgxResult, SgxStatus};
 use sgx_types::types::time_t;
 use sgx_types::types::{
     CDcapMRaMsg2, CDcapRaMsg1, CDcapRaMsg3, CEnclaveIdentity, Ec256PublicKey, Key128bit,
@@ -54,88 +62,175 @@ pub unsafe extern "C" fn sgx_dcap_mra_proc_msg1(
     report: *mut Report,
     nonce: *mut QuoteNonce,
 ) -> SgxStatus {
-    if msg1.is_null()
-        || qe_target.is_null()
-        || pub_key_b.is_null()
-        || report.is_null()
-        || nonce.is_null()
-    {
+    if nonce.is_null() || !is_within_enclave_val(nonce) {
         return SgxStatus::InvalidParameter;
     }
 
-    if !is_within_enclave(msg1 as *const u8, mem::size_of::<CDcapRaMsg1>())
-        || !is_within_enclave(qe_target as *const u8, mem::size_of::<TargetInfo>())
-        || !is_within_enclave(pub_key_b as *const u8, mem::size_of::<Ec256PublicKey>())
-        || !is_within_enclave(report as *const u8, mem::size_of::<Report>())
-        || !is_within_enclave(nonce as *const u8, mem::size_of::<QuoteNonce>())
-    {
+    match proc_msg1(
+        context,
+        msg1,
+        qe_target,
+        pub_key_b,
+        report,
+        |responder, msg1, qe_target| responder.process_msg1(msg1, qe_target),
+    ) {
+        Ok(rand) => {
+            *nonce = rand;
+            SgxStatus::Success
+        }
+        Err(e) => e,
+    }
+}
+
+/// `sgx_dcap_mra_proc_msg1` with a caller-supplied nonce, for reproducible
+/// test vectors. Only built with the `test-determinism` feature.
+///
+/// # Safety
+#[cfg(feature = "test-determinism")]
+#[no_mangle]
+pub unsafe extern "C" fn sgx_dcap_mra_proc_msg1_ex(
+    context: RaContext,
+    msg1: *const CDcapRaMsg1,
+    qe_target: *const TargetInfo,
+    nonce: *const QuoteNonce,
+    pub_key_b: *mut Ec256PublicKey,
+    report: *mut Report,
+) -> SgxStatus {
+    if nonce.is_null() || !is_within_enclave_val(nonce) {
         return SgxStatus::InvalidParameter;
     }
 
+    let nonce = *nonce;
+    match proc_msg1(
+        context,
+        msg1,
+        qe_target,
+        pub_key_b,
+        report,
+        |responder, msg1, qe_target| responder.process_msg1_with_nonce(msg1, qe_target, nonce),
+    ) {
+        Ok(_) => SgxStatus::Success,
+        Err(e) => e,
+    }
+}
+
+unsafe fn proc_msg1<F>(
+    context: RaContext,
+    msg1: *const CDcapRaMsg1,
+    qe_target: *const TargetInfo,
+    pub_key_b: *mut Ec256PublicKey,
+    report: *mut Report,
+    process: F,
+) -> SgxResult<QuoteNonce>
+where
+    F: FnOnce(&Responder, &DcapRaMsg1, &TargetInfo) -> SgxResult<(EcPublicKey, Report, QuoteNonce)>,
+{
+    if msg1.is_null() || qe_target.is_null() || pub_key_b.is_null() || report.is_null() {
+        return Err(SgxStatus::InvalidParameter);
+    }
+
+    if !check_enclave_ptrs(&[
+        (msg1 as *const u8, mem::size_of::<CDcapRaMsg1>()),
+        (qe_target as *const u8, mem::size_of::<TargetInfo>()),
+        (pub_key_b as *const u8, mem::size_of::<Ec256PublicKey>()),
+        (report as *const u8, mem::size_of::<Report>()),
+    ]) {
+        return Err(SgxStatus::InvalidParameter);
+    }
+
     let qe_target = &*qe_target;
     let msg1 = (&*msg1).into();
 
-    let responder = ManuallyDrop::new(Responder::from_raw(context));
-    let (pub_key, rpt, rand) = match responder.process_msg1(&msg1, qe_target) {
-        Ok(r) => r,
-        Err(e) => return e,
-    };
+    let responder = ManuallyDrop::new(Responder::from_raw(context)?);
+    let (pub_key, rpt, rand) = process(&responder, &msg1, qe_target)?;
 
     *pub_key_b = pub_key.into();
     *report = rpt;
-    *nonce = rand;
-    SgxStatus::Success
+    Ok(rand)
 }
 
+/// Builds msg2 around the QE quote at `quote`. With a null `msg2`, only the
+/// msg2 size for the quote is written to `msg2_size`, without touching the
+/// session; otherwise `*msg2_size` must equal that size and msg2, quote
+/// included, is written to `msg2`.
+///
 /// # Safety
 #[no_mangle]
 pub unsafe extern "C" fn sgx_dcap_mra_get_msg2(
     context: RaContext,
     qe_report: *const Report,
+    quote: *const u8,
+    quote_size: u32,
     msg2: *mut CDcapMRaMsg2,
-    msg2_size: u32,
+    msg2_size: *mut u32,
 ) -> SgxStatus {
-    if qe_report.is_null() || msg2.is_null() {
+    if qe_report.is_null() || quote.is_null() || msg2_size.is_null() {
         return SgxStatus::InvalidParameter;
     }
 
-    if usize::MAX - (msg2 as usize) < msg2_size as usize
-        || msg2_size < (mem::size_of::<CDcapMRaMsg2>() + mem::size_of::<Quote3>()) as u32
-    {
+    if !is_within_enclave_val(qe_report) || !is_within_enclave_val(msg2_size) {
         return SgxStatus::InvalidParameter;
     }
 
-    if !(is_within_enclave(msg2 as *const u8, msg2_size as usize)
-        || is_within_host(msg2 as *const u8, msg2_size as usize))
+    if !DcapMRaMsg2::check_quote_len(quote_size as usize) {
+        return SgxStatus::InvalidParameter;
+    }
+
+    if !(is_within_enclave(quote, quote_size as usize)
+        || is_within_host(quote, quote_size as usize))
     {
         return SgxStatus::InvalidParameter;
     }
 
-    if !is_within_enclave(qe_report as *const u8, mem::size_of::<Report>()) {
+    let responder = match Responder::from_raw(context) {
+        Ok(responder) => ManuallyDrop::new(responder),
+        Err(e) => return e,
+    };
+
+    // The size is that of the quote as its header describes it, so a
+    // `quote_size` that disagrees with the quote is caught by the query.
+    let header = ptr::read_unaligned(quote as *const Quote3);
+    if mem::size_of::<Quote3>() as u64 + header.signature_len as u64 != quote_size as u64 {
         return SgxStatus::InvalidParameter;
     }
+    let required = mem::size_of::<CDcapMRaMsg2>() as u32 + quote_size;
 
-    let qe_report = &*qe_report;
-    let c_msg2 = &mut *msg2;
-    let quote_size = c_msg2.quote_size;
+    if msg2.is_null() {
+        *msg2_size = required;
+        return SgxStatus::Success;
+    }
 
-    if !DcapMRaMsg2::check_quote_len(quote_size as usize) {
+    if *msg2_size != required {
+        *msg2_size = required;
         return SgxStatus::InvalidParameter;
     }
-    if msg2_size != mem::size_of::<CDcapMRaMsg2>() as u32 + quote_size {
+
+    if usize::MAX - (msg2 as usize) < required as usize {
+        return SgxStatus::InvalidParameter;
+    }
+
+    if !(is_within_enclave(msg2 as *const u8, required as usize)
+        || is_within_host(msg2 as *const u8, required as usize))
+    {
         return SgxStatus::InvalidParameter;
     }
 
-    let quote = slice::from_raw_parts(&c_msg2.quote as *const _ as *const u8, quote_size as usize);
-    let responder = ManuallyDrop::new(Responder::from_raw(context));
-    let msg2 = match responder.generate_msg2(qe_report, quote) {
+    let quote = slice::from_raw_parts(quote, quote_size as usize);
+    let msg2_out = match responder.generate_msg2(&*qe_report, quote) {
         Ok(msg) => msg,
         Err(e) => return e,
     };
 
-    c_msg2.mac = msg2.mac;
-    c_msg2.g_b = msg2.pub_key_b.into();
-    c_msg2.kdf_id = msg2.kdf_id;
+    let c_msg2 = &mut *msg2;
+    c_msg2.mac = msg2_out.mac.into();
+    c_msg2.g_b = msg2_out.pub_key_b.into();
+    c_msg2.kdf_id = msg2_out.kdf_id;
+    c_msg2.quote_size = quote_size;
+    ptr::copy_nonoverlapping(
+        msg2_out.quote.as_ptr(),
+        &mut c_msg2.quote as *mut _ as *mut u8,
+        quote_size as usize,
+    );
     SgxStatus::Success
 }
 
@@ -170,15 +265,12 @@ pub unsafe extern "C" fn sgx_dcap_mra_proc_msg3(
         return SgxStatus::InvalidParameter;
     }
 
-    if !(is_within_enclave(msg3 as *const u8, msg3_size as usize)
-        || is_within_host(msg3 as *const u8, msg3_size as usize))
-    {
+    let host_msg3 = is_within_host(msg3 as *const u8, msg3_size as usize);
+    if !(host_msg3 || is_within_enclave(msg3 as *const u8, msg3_size as usize)) {
         return SgxStatus::InvalidParameter;
     }
 
-    if !is_within_enclave(qve_nonce as *const u8, mem::size_of::<QuoteNonce>())
-        || !is_within_enclave(qve_report as *const u8, mem::size_of::<Report>())
-    {
+    if !is_within_enclave_val(qve_nonce) || !is_within_enclave_val(qve_report) {
         return SgxStatus::InvalidParameter;
     }
 
@@ -188,10 +280,35 @@ pub unsafe extern "C" fn sgx_dcap_mra_proc_msg3(
         return SgxStatus::InvalidParameter;
     }
 
+    fence::lfence();
+
     let qve_nonce = *qve_nonce;
     let qve_report = &*qve_report;
 
-    let msg3_slice = slice::from_raw_parts(msg3 as *const u8, msg3_size as usize);
+    let responder = match Responder::from_raw(context) {
+        Ok(responder) => ManuallyDrop::new(responder),
+        Err(e) => return e,
+    };
+    // Flagged before parsing, so a host msg3 that fails or is retried
+    // still counts.
+    if host_msg3 {
+        if let Err(e) = responder.set_used_host_buffers() {
+            return e;
+        }
+    }
+
+    // A host msg3 is parsed from an enclave copy, as in
+    // `sgx_dcap_mra_proc_msg3_trusted`.
+    let msg3_local;
+    let msg3_slice = if host_msg3 {
+        msg3_local = match copy_from_host(msg3 as *const u8, msg3_size as usize) {
+            Ok(msg) => msg,
+            Err(e) => return e,
+        };
+        msg3_local.as_slice()
+    } else {
+        slice::from_raw_parts(msg3 as *const u8, msg3_size as usize)
+    };
     let msg3 = match DcapRaMsg3::from_slice(msg3_slice) {
         Ok(msg) => msg,
         Err(e) => return e,
@@ -206,16 +323,15 @@ pub unsafe extern "C" fn sgx_dcap_mra_proc_msg3(
         None
     };
 
-    let qve_report_info = QveReportInfo {
+    let qve_report_info = QveReportInfo::new(
         qve_report,
         expiration_time,
         collateral_expiration_status,
         quote_verification_result,
         qve_nonce,
         supplemental_data,
-    };
+    );
 
-    let responder = ManuallyDrop::new(Responder::from_raw(context));
     let _ = match responder.process_msg3(&msg3, &qve_report_info) {
         Ok(identity) => identity,
         Err(e) => return e,
@@ -235,20 +351,15 @@ pub unsafe extern "C" fn sgx_mra_responder_get_peer_identity(
         return SgxStatus::InvalidParameter;
     }
 
-    if !is_within_enclave(
-        quote_verification_result as *const u8,
-        mem::size_of::<QlQvResult>(),
-    ) {
-        return SgxStatus::InvalidParameter;
-    }
-    if !is_within_enclave(
-        enclave_identity as *const u8,
-        mem::size_of::<CEnclaveIdentity>(),
-    ) {
+    if !is_within_enclave_val(quote_verification_result) || !is_within_enclave_val(enclave_identity)
+    {
         return SgxStatus::InvalidParameter;
     }
 
-    let responder = ManuallyDrop::new(Responder::from_raw(context));
+    let responder = match Responder::from_raw(context) {
+        Ok(responder) => ManuallyDrop::new(responder),
+        Err(e) => return e,
+    };
     let (qv_result, identity) = match responder.get_peer_identity() {
         Ok(identity) => identity,
         Err(e) => return e,
@@ -259,6 +370,83 @@ pub unsafe extern "C" fn sgx_mra_responder_get_peer_identity(
     SgxStatus::Success
 }
 
+/// # Safety
+#[no_mangle]
+pub unsafe extern "C" fn sgx_mra_responder_get_collateral_status(
+    context: RaContext,
+    expiration_time: *mut time_t,
+    collateral_expiration_status: *mut u32,
+) -> SgxStatus {
+    if expiration_time.is_null() || collateral_expiration_status.is_null() {
+        return SgxStatus::InvalidParameter;
+    }
+
+    if !is_within_enclave_val(expiration_time)
+        || !is_within_enclave_val(collateral_expiration_status)
+    {
+        return SgxStatus::InvalidParameter;
+    }
+
+    let responder = match Responder::from_raw(context) {
+        Ok(responder) => ManuallyDrop::new(responder),
+        Err(e) => return e,
+    };
+    let (time, status) = match responder.get_collateral_status() {
+        Ok(r) => r,
+        Err(e) => return e,
+    };
+
+    *expiration_time = time;
+    *collateral_expiration_status = status;
+    SgxStatus::Success
+}
+
+/// Copies the initiator's quote into `buf`. With a null `buf` only
+/// `quote_len` is written, so callers can size the buffer first.
+///
+/// # Safety
+#[no_mangle]
+pub unsafe extern "C" fn sgx_mra_responder_get_peer_quote(
+    context: RaContext,
+    buf: *mut u8,
+    buf_size: u32,
+    quote_len: *mut u32,
+) -> SgxStatus {
+    if quote_len.is_null() || !is_within_enclave_val(quote_len) {
+        return SgxStatus::InvalidParameter;
+    }
+
+    if !buf.is_null() {
+        if usize::MAX - (buf as usize) < buf_size as usize {
+            return SgxStatus::InvalidParameter;
+        }
+        if !(is_within_enclave(buf, buf_size as usize) || is_within_host(buf, buf_size as usize)) {
+            return SgxStatus::InvalidParameter;
+        }
+    }
+
+    let responder = match Responder::from_raw(context) {
+        Ok(responder) => ManuallyDrop::new(responder),
+        Err(e) => return e,
+    };
+    let quote = match responder.peer_quote() {
+        Ok(quote) => quote,
+        Err(e) => return e,
+    };
+
+    *quote_len = quote.len() as u32;
+    if buf.is_null() {
+        return SgxStatus::Success;
+    }
+    if (buf_size as usize) < quote.len() {
+        return SgxStatus::InvalidParameter;
+    }
+
+    let dst = slice::from_raw_parts_mut(buf, quote.len());
+    dst.copy_from_slice(&quote);
+    SgxStatus::Success
+}
+
 /// # Safety
 #[no_mangle]
 pub unsafe extern "C" fn sgx_mra_responder_get_keys(
@@ -266,28 +454,153 @@ pub unsafe extern "C" fn sgx_mra_responder_get_keys(
     key_type: RaKeyType,
     key: *mut Key128bit,
 ) -> SgxStatus {
-    if key.is_null() {
+    write_key_or_zero(key, || {
+        let responder = ManuallyDrop::new(Responder::from_raw(context)?);
+        responder.get_keys(key_type).map(|key| key.key)
+    })
+}
+
+/// # Safety
+#[no_mangle]
+pub unsafe extern "C" fn sgx_mra_responder_set_policy(
+    context: RaContext,
+    accepted_qv_results: *const QlQvResult,
+    accepted_count: u32,
+) -> SgxStatus {
+    if accepted_qv_results.is_null() || accepted_count == 0 {
+        return SgxStatus::InvalidParameter;
+    }
+
+    if !is_within_enclave_slice(accepted_qv_results, accepted_count as usize) {
+        return SgxStatus::InvalidParameter;
+    }
+
+    let accepted = slice::from_raw_parts(accepted_qv_results, accepted_count as usize);
+    let responder = match Responder::from_raw(context) {
+        Ok(responder) => ManuallyDrop::new(responder),
+        Err(e) => return e,
+    };
+    match responder.set_accepted_qv_results(accepted) {
+        Ok(()) => SgxStatus::Success,
+        Err(e) => e,
+    }
+}
+
+/// Writes the sealed session to `blob` and its size to `blob_len`. With a
+/// null `blob` only the size is written.
+///
+/// # Safety
+#[no_mangle]
+pub unsafe extern "C" fn sgx_mra_responder_export(
+    context: RaContext,
+    blob: *mut u8,
+    blob_size: u32,
+    blob_len: *mut u32,
+) -> SgxStatus {
+    if blob_len.is_null() || !is_within_enclave_val(blob_len) {
+        return SgxStatus::InvalidParameter;
+    }
+
+    if !blob.is_null() {
+        if usize::MAX - (blob as usize) < blob_size as usize {
+            return SgxStatus::InvalidParameter;
+        }
+        if !(is_within_enclave(blob, blob_size as usize)
+            || is_within_host(blob, blob_size as usize))
+        {
+            return SgxStatus::InvalidParameter;
+        }
+    }
+
+    let responder = match Responder::from_raw(context) {
+        Ok(responder) => ManuallyDrop::new(responder),
+        Err(e) => return e,
+    };
+    let sealed = match responder.export_sealed() {
+        Ok(sealed) => sealed,
+        Err(e) => return e,
+    };
+
+    *blob_len = sealed.len() as u32;
+    if blob.is_null() {
+        return SgxStatus::Success;
+    }
+    if (blob_size as usize) < sealed.len() {
+        return SgxStatus::InvalidParameter;
+    }
+
+    let dst = slice::from_raw_parts_mut(blob, sealed.len());
+    dst.copy_from_slice(&sealed);
+    SgxStatus::Success
+}
+
+/// # Safety
+#[no_mangle]
+pub unsafe extern "C" fn sgx_mra_responder_import(
+    blob: *const u8,
+    blob_size: u32,
+    context: *mut RaContext,
+) -> SgxStatus {
+    if blob.is_null() || blob_size == 0 || context.is_null() {
+        return SgxStatus::InvalidParameter;
+    }
+
+    if usize::MAX - (blob as usize) < blob_size as usize {
         return SgxStatus::InvalidParameter;
     }
 
-    if !is_within_enclave(key as *const u8, mem::size_of::<Key128bit>()) {
+    if !(is_within_enclave(blob, blob_size as usize) || is_within_host(blob, blob_size as usize)) {
         return SgxStatus::InvalidParameter;
     }
 
-    let responder = ManuallyDrop::new(Responder::from_raw(context));
-    let ra_key = match responder.get_keys(key_type) {
-        Ok(key) => key.key,
+    if !is_within_enclave_val(context) {
+        return SgxStatus::InvalidParameter;
+    }
+
+    let blob = slice::from_raw_parts(blob, blob_size as usize);
+    let responder = match Responder::import_sealed(blob) {
+        Ok(responder) => responder,
         Err(e) => return e,
     };
 
-    *key = ra_key;
+    *context = responder.into_raw();
     SgxStatus::Success
 }
 
+/// # Safety
+#[no_mangle]
+pub unsafe extern "C" fn sgx_mra_responder_rekey(context: RaContext) -> SgxStatus {
+    let responder = match Responder::from_raw(context) {
+        Ok(responder) => ManuallyDrop::new(responder),
+        Err(e) => return e,
+    };
+    match responder.rekey() {
+        Ok(()) => SgxStatus::Success,
+        Err(e) => e,
+    }
+}
+
+/// # Safety
+#[no_mangle]
+pub unsafe extern "C" fn sgx_mra_responder_abort(context: RaContext) -> SgxStatus {
+    let responder = match Responder::from_raw(context) {
+        Ok(responder) => ManuallyDrop::new(responder),
+        Err(e) => return e,
+    };
+    match responder.abort() {
+        Ok(()) => SgxStatus::Success,
+        Err(e) => e,
+    }
+}
+
 /// # Safety
 #[no_mangle]
 pub unsafe extern "C" fn sgx_mra_responder_close(context: RaContext) -> SgxStatus {
-    let responder = Responder::from_raw(context);
-    drop(responder);
-    SgxStatus::Success
+    match Responder::from_raw(context) {
+        Ok(responder) => {
+            drop(responder);
+            SgxStatus::Success
+        }
+        Err(e) => e,
+    }
 }
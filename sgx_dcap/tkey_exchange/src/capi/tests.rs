@@ -0,0 +1,389 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License..
+
+use super::{
+    sgx_dcap_mra_get_msg1, sgx_dcap_mra_get_msg2, sgx_dcap_mra_proc_msg2, sgx_dcap_mra_proc_msg3,
+    sgx_dcap_ra_get_ga, sgx_mra_initiator_init, sgx_mra_responder_abort, sgx_mra_responder_close,
+    sgx_mra_responder_get_keys, sgx_mra_responder_set_policy, sgx_ra_initiator_close,
+    write_key_checked, write_key_or_zero,
+};
+use crate::session::{Initiator, Responder};
+use alloc::vec;
+use core::mem;
+use core::num::NonZeroUsize;
+use core::ptr;
+use core::slice;
+use sgx_crypto::sha::Sha256;
+use sgx_dcap_ra_msg::DcapRaMsg1;
+use sgx_test_utils::test_case;
+use sgx_trts::trts::OcBuffer;
+use sgx_tse::{EnclaveReport, EnclaveTarget};
+use sgx_types::error::SgxStatus;
+use sgx_types::types::{
+    CDcapMRaMsg2, CDcapRaMsg1, CDcapRaMsg3, Ec256PublicKey, Key128bit, QlAuthData,
+    QlCertificationData, QlEcdsaSigData, QlQvResult, Quote3, QuoteNonce, RaContext, RaKeyType,
+    Report, ReportData, TargetInfo, SHA256_HASH_SIZE,
+};
+
+#[test_case]
+fn write_key_checked_copies() {
+    let key: Key128bit = [0x5a; 16];
+    let mut dst: Key128bit = [0; 16];
+    let status = unsafe { write_key_checked(&mut dst, &key) };
+    assert_eq!(status, SgxStatus::Success);
+    assert_eq!(dst, key);
+
+    // `Key128bit` is a byte array, so C buffers at any offset are accepted.
+    let mut buf = [0_u8; 17];
+    let offset = unsafe { buf.as_mut_ptr().add(1) } as *mut Key128bit;
+    let status = unsafe { write_key_checked(offset, &key) };
+    assert_eq!(status, SgxStatus::Success);
+    assert_eq!(&buf[1..], &key[..]);
+}
+
+#[test_case]
+fn write_key_checked_rejects_bad_dst() {
+    let key: Key128bit = [0x5a; 16];
+    let status = unsafe { write_key_checked(ptr::null_mut(), &key) };
+    assert_eq!(status, SgxStatus::InvalidParameter);
+
+    let mut host =
+        OcBuffer::alloc(NonZeroUsize::new(mem::size_of::<Key128bit>()).unwrap()).unwrap();
+    host.fill(0xee);
+    let status = unsafe { write_key_checked(host.as_mut_ptr() as *mut Key128bit, &key) };
+    assert_eq!(status, SgxStatus::InvalidParameter);
+    assert!(host.iter().all(|&b| b == 0xee));
+}
+
+#[test_case]
+fn write_key_or_zero_clears_on_failure() {
+    let mut dst: Key128bit = [0xaa; 16];
+    let status = unsafe { write_key_or_zero(&mut dst, || Err(SgxStatus::InvalidState)) };
+    assert_eq!(status, SgxStatus::InvalidState);
+    assert_eq!(dst, [0; 16]);
+}
+
+#[test_case]
+fn get_keys_zeroes_before_established() {
+    let responder = Responder::new().unwrap();
+    let context = responder.into_raw();
+
+    let mut dst: Key128bit = [0xaa; 16];
+    let status = unsafe { sgx_mra_responder_get_keys(context, RaKeyType::SK, &mut dst) };
+    assert_eq!(status, SgxStatus::InvalidState);
+    assert_eq!(dst, [0; 16]);
+
+    drop(unsafe { Responder::from_raw(context) }.unwrap());
+}
+
+#[test_case]
+fn responder_abort_then_close() {
+    let context = Responder::new().unwrap().into_raw();
+
+    assert_eq!(
+        unsafe { sgx_mra_responder_abort(context) },
+        SgxStatus::Success
+    );
+    let mut dst: Key128bit = [0xaa; 16];
+    let status = unsafe { sgx_mra_responder_get_keys(context, RaKeyType::SK, &mut dst) };
+    assert_eq!(status, SgxStatus::InvalidState);
+    assert_eq!(
+        unsafe { sgx_mra_responder_abort(context) },
+        SgxStatus::InvalidState
+    );
+
+    assert_eq!(
+        unsafe { sgx_mra_responder_close(context) },
+        SgxStatus::Success
+    );
+}
+
+#[test_case]
+fn double_close_rejected() {
+    let context = Responder::new().unwrap().into_raw();
+    assert_eq!(
+        unsafe { sgx_mra_responder_close(context) },
+        SgxStatus::Success
+    );
+    assert_eq!(
+        unsafe { sgx_mra_responder_close(context) },
+        SgxStatus::InvalidParameter
+    );
+    assert_eq!(
+        unsafe { sgx_mra_responder_abort(context) },
+        SgxStatus::InvalidParameter
+    );
+
+    let mut context: RaContext = 0;
+    assert_eq!(
+        unsafe { sgx_mra_initiator_init(&mut context) },
+        SgxStatus::Success
+    );
+    assert_eq!(
+        unsafe { sgx_ra_initiator_close(context) },
+        SgxStatus::Success
+    );
+    assert_eq!(
+        unsafe { sgx_ra_initiator_close(context) },
+        SgxStatus::InvalidParameter
+    );
+}
+
+#[test_case]
+fn initiator_get_msg1_carries_ga() {
+    let mut context = RaContext::default();
+    assert_eq!(
+        unsafe { sgx_mra_initiator_init(&mut context) },
+        SgxStatus::Success
+    );
+
+    let mut msg1 = CDcapRaMsg1::default();
+    let mut g_a = Ec256PublicKey::default();
+    assert_eq!(
+        unsafe { sgx_dcap_mra_get_msg1(context, &mut msg1) },
+        SgxStatus::Success
+    );
+    assert_eq!(
+        unsafe { sgx_dcap_ra_get_ga(context, &mut g_a) },
+        SgxStatus::Success
+    );
+    assert_eq!(msg1.g_a.gx, g_a.gx);
+    assert_eq!(msg1.g_a.gy, g_a.gy);
+    assert_eq!(
+        unsafe { sgx_dcap_mra_get_msg1(context, ptr::null_mut()) },
+        SgxStatus::InvalidParameter
+    );
+
+    assert_eq!(
+        unsafe { sgx_ra_initiator_close(context) },
+        SgxStatus::Success
+    );
+}
+
+#[test_case]
+fn initiator_proc_msg2_checks_quote_size() {
+    let mut context = RaContext::default();
+    assert_eq!(
+        unsafe { sgx_mra_initiator_init(&mut context) },
+        SgxStatus::Success
+    );
+
+    let quote_len = mem::size_of::<Quote3>() + 64;
+    let msg2_size = mem::size_of::<CDcapMRaMsg2>() + quote_len;
+    let mut buf = vec![0_u32; msg2_size.div_ceil(4)];
+    let msg2 = buf.as_mut_ptr() as *mut CDcapMRaMsg2;
+    let qve_nonce = QuoteNonce::default();
+    let qve_report = Report::default();
+    let qe_target = TargetInfo::default();
+    let mut report = Report::default();
+    let mut nonce = QuoteNonce::default();
+
+    // A declared quote size that disagrees with the buffer is refused.
+    for quote_size in [quote_len + 4, 0] {
+        unsafe { (*msg2).quote_size = quote_size as u32 };
+        let status = unsafe {
+            sgx_dcap_mra_proc_msg2(
+                context,
+                msg2,
+                msg2_size as u32,
+                0,
+                0,
+                QlQvResult::Ok,
+                &qve_nonce,
+                &qve_report,
+                ptr::null(),
+                0,
+                &qe_target,
+                &mut report,
+                &mut nonce,
+            )
+        };
+        assert_eq!(status, SgxStatus::InvalidParameter);
+    }
+
+    assert_eq!(
+        unsafe { sgx_ra_initiator_close(context) },
+        SgxStatus::Success
+    );
+}
+
+#[test_case]
+fn get_msg2_queries_size_from_quote() {
+    let responder = Responder::new().unwrap();
+    let msg1 = DcapRaMsg1 {
+        pub_key_a: Initiator::new().unwrap().get_ga().unwrap(),
+        ..Default::default()
+    };
+    let (pub_key_b, report, nonce) = responder
+        .process_msg1(&msg1, &TargetInfo::for_self().unwrap())
+        .unwrap();
+
+    let quote_len = mem::size_of::<Quote3>()
+        + mem::size_of::<QlEcdsaSigData>()
+        + mem::size_of::<QlAuthData>()
+        + mem::size_of::<QlCertificationData>()
+        + 64;
+    let mut quote = vec![0_u8; quote_len];
+    let mut quote3 = Quote3::default();
+    quote3.header.version = 3;
+    quote3.report_body = report.body;
+    quote3.signature_len = (quote_len - mem::size_of::<Quote3>()) as u32;
+    unsafe { ptr::write_unaligned(quote.as_mut_ptr() as *mut Quote3, quote3) };
+
+    let mut sha = Sha256::new().unwrap();
+    sha.update(&nonce).unwrap();
+    sha.update(quote.as_slice()).unwrap();
+    let mut report_data = ReportData::default();
+    report_data.d[..SHA256_HASH_SIZE].copy_from_slice(&sha.finalize().unwrap());
+    let qe_report = Report::for_target(&TargetInfo::for_self().unwrap(), &report_data).unwrap();
+    let context = responder.into_raw();
+
+    // A quote size that disagrees with the quote header is refused.
+    let mut msg2_size = 0_u32;
+    let status = unsafe {
+        sgx_dcap_mra_get_msg2(
+            context,
+            &qe_report,
+            quote.as_ptr(),
+            quote_len as u32 - 1,
+            ptr::null_mut(),
+            &mut msg2_size,
+        )
+    };
+    assert_eq!(status, SgxStatus::InvalidParameter);
+
+    let status = unsafe {
+        sgx_dcap_mra_get_msg2(
+            context,
+            &qe_report,
+            quote.as_ptr(),
+            quote_len as u32,
+            ptr::null_mut(),
+            &mut msg2_size,
+        )
+    };
+    assert_eq!(status, SgxStatus::Success);
+    assert_eq!(
+        msg2_size as usize,
+        mem::size_of::<CDcapMRaMsg2>() + quote_len
+    );
+
+    let mut buf = vec![0_u32; (msg2_size as usize).div_ceil(4)];
+    let msg2 = buf.as_mut_ptr() as *mut CDcapMRaMsg2;
+    let mut short_size = msg2_size - 1;
+    let status = unsafe {
+        sgx_dcap_mra_get_msg2(
+            context,
+            &qe_report,
+            quote.as_ptr(),
+            quote_len as u32,
+            msg2,
+            &mut short_size,
+        )
+    };
+    assert_eq!(status, SgxStatus::InvalidParameter);
+    assert_eq!(short_size, msg2_size);
+
+    let status = unsafe {
+        sgx_dcap_mra_get_msg2(
+            context,
+            &qe_report,
+            quote.as_ptr(),
+            quote_len as u32,
+            msg2,
+            &mut msg2_size,
+        )
+    };
+    assert_eq!(status, SgxStatus::Success);
+
+    let c_msg2 = unsafe { &*msg2 };
+    let g_b: Ec256PublicKey = pub_key_b.into();
+    assert_eq!(c_msg2.g_b.gx, g_b.gx);
+    assert_eq!(c_msg2.g_b.gy, g_b.gy);
+    assert_eq!(c_msg2.quote_size as usize, quote_len);
+    let msg2_quote =
+        unsafe { slice::from_raw_parts(&c_msg2.quote as *const _ as *const u8, quote_len) };
+    assert_eq!(msg2_quote, quote.as_slice());
+
+    assert_eq!(
+        unsafe { sgx_mra_responder_close(context) },
+        SgxStatus::Success
+    );
+}
+
+#[test_case]
+fn responder_set_policy_checks_input() {
+    let context = Responder::new().unwrap().into_raw();
+    let accepted = [QlQvResult::Ok, QlQvResult::SWHardeningNeeded];
+
+    assert_eq!(
+        unsafe { sgx_mra_responder_set_policy(context, accepted.as_ptr(), 2) },
+        SgxStatus::Success
+    );
+    assert_eq!(
+        unsafe { sgx_mra_responder_set_policy(context, accepted.as_ptr(), 0) },
+        SgxStatus::InvalidParameter
+    );
+    assert_eq!(
+        unsafe { sgx_mra_responder_set_policy(context, ptr::null(), 2) },
+        SgxStatus::InvalidParameter
+    );
+    let max = [QlQvResult::Max];
+    assert_eq!(
+        unsafe { sgx_mra_responder_set_policy(context, max.as_ptr(), 1) },
+        SgxStatus::InvalidParameter
+    );
+
+    assert_eq!(
+        unsafe { sgx_mra_responder_close(context) },
+        SgxStatus::Success
+    );
+}
+
+// Hands `msg3` to a fresh responder, which refuses it before msg2, and
+// reports whether the session was flagged.
+unsafe fn flagged_after_msg3(msg3: *const u8, len: usize) -> bool {
+    let context = Responder::new().unwrap().into_raw();
+    let status = sgx_dcap_mra_proc_msg3(
+        context,
+        msg3 as *const CDcapRaMsg3,
+        len as u32,
+        0,
+        0,
+        QlQvResult::Ok,
+        &QuoteNonce::default(),
+        &Report::default(),
+        ptr::null(),
+        0,
+    );
+    assert_ne!(status, SgxStatus::Success);
+    Responder::from_raw(context)
+        .unwrap()
+        .used_host_buffers()
+        .unwrap()
+}
+
+#[test_case]
+fn failed_msg3_flagged_by_placement() {
+    let len = mem::size_of::<CDcapRaMsg3>() + mem::size_of::<Quote3>();
+    let enclave = vec![0_u8; len];
+    assert!(!unsafe { flagged_after_msg3(enclave.as_ptr(), len) });
+
+    let mut host = OcBuffer::alloc(NonZeroUsize::new(len).unwrap()).unwrap();
+    host.fill(0);
+    assert!(unsafe { flagged_after_msg3(host.as_ptr(), len) });
+}
@@ -15,6 +15,7 @@
 // specific language governing permissions and limitations
 // under the License..
 
+use super::copy_from_host;
 use crate::session::Initiator;
 use crate::QveReportInfo;
 use core::mem::{self, ManuallyDrop};
@@ -39,7 +40,10 @@ pub unsafe extern "C" fn sgx_dcap_ra_get_ga_trusted(
         return SgxStatus::InvalidParameter;
     }
 
-    let initiator = ManuallyDrop::new(Initiator::from_raw(context));
+    let initiator = match Initiator::from_raw(context) {
+        Ok(initiator) => ManuallyDrop::new(initiator),
+        Err(e) => return e,
+    };
     let key = match initiator.get_ga() {
         Ok(key) => key,
         Err(e) => return e,
@@ -99,8 +103,11 @@ pub unsafe extern "C" fn sgx_dcap_mra_proc_msg2_trusted(
     let qve_report = &*qve_report;
     let qe_target = &*qe_target;
 
-    let msg2_slice = slice::from_raw_parts(msg2 as *const u8, msg2_size as usize);
-    let msg2 = match DcapMRaMsg2::from_slice(msg2_slice) {
+    let msg2_local = match copy_from_host(msg2 as *const u8, msg2_size as usize) {
+        Ok(msg) => msg,
+        Err(e) => return e,
+    };
+    let msg2 = match DcapMRaMsg2::from_slice(&msg2_local) {
         Ok(msg) => msg,
         Err(e) => return e,
     };
@@ -114,16 +121,19 @@ pub unsafe extern "C" fn sgx_dcap_mra_proc_msg2_trusted(
         None
     };
 
-    let qve_report_info = QveReportInfo {
+    let qve_report_info = QveReportInfo::new(
         qve_report,
         expiration_time,
         collateral_expiration_status,
         quote_verification_result,
         qve_nonce,
         supplemental_data,
-    };
+    );
 
-    let initiator = ManuallyDrop::new(Initiator::from_raw(context));
+    let initiator = match Initiator::from_raw(context) {
+        Ok(initiator) => ManuallyDrop::new(initiator),
+        Err(e) => return e,
+    };
     let (rpt, rand, _) = match initiator.process_mra_msg2(&msg2, qe_target, &qve_report_info) {
         Ok(r) => r,
         Err(e) => return e,
@@ -150,7 +160,10 @@ pub unsafe extern "C" fn sgx_dcap_ura_proc_msg2_trusted(
     let qe_target = &*qe_target;
     let msg2 = (&*msg2).into();
 
-    let initiator = ManuallyDrop::new(Initiator::from_raw(context));
+    let initiator = match Initiator::from_raw(context) {
+        Ok(initiator) => ManuallyDrop::new(initiator),
+        Err(e) => return e,
+    };
     let (rpt, rand) = match initiator.process_ura_msg2(&msg2, qe_target) {
         Ok(r) => r,
         Err(e) => return e,
@@ -198,7 +211,10 @@ pub unsafe extern "C" fn sgx_dcap_ra_get_msg3_trusted(
 
     let quote = slice::from_raw_parts(&c_msg3.quote as *const _ as *const u8, quote_size as usize);
 
-    let initiator = ManuallyDrop::new(Initiator::from_raw(context));
+    let initiator = match Initiator::from_raw(context) {
+        Ok(initiator) => ManuallyDrop::new(initiator),
+        Err(e) => return e,
+    };
     let msg3 = match initiator.generate_msg3(qe_report, quote) {
         Ok(msg) => msg,
         Err(e) => return e,
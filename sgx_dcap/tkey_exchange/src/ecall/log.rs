@@ -0,0 +1,44 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License..
+
+use crate::session::dump_log;
+use core::slice;
+use sgx_trts::fence;
+use sgx_trts::trts::is_within_host;
+use sgx_types::error::SgxStatus;
+
+/// # Safety
+#[no_mangle]
+pub unsafe extern "C" fn sgx_mra_dump_log(buf: *mut u8, len: u32) -> SgxStatus {
+    if buf.is_null() || len == 0 {
+        return SgxStatus::InvalidParameter;
+    }
+
+    if usize::MAX - (buf as usize) < len as usize {
+        return SgxStatus::InvalidParameter;
+    }
+
+    if !is_within_host(buf as *const u8, len as usize) {
+        return SgxStatus::InvalidParameter;
+    }
+
+    fence::lfence();
+
+    let buf = slice::from_raw_parts_mut(buf, len as usize);
+    let _ = dump_log(buf);
+    SgxStatus::Success
+}
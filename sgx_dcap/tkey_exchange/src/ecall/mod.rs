@@ -15,8 +15,32 @@
 // specific language governing permissions and limitations
 // under the License..
 
+use alloc::vec::Vec;
+use core::slice;
+use sgx_types::error::{SgxResult, SgxStatus};
+
 mod initiator;
+mod log;
 mod responder;
 
 pub use initiator::*;
+pub use log::*;
 pub use responder::*;
+
+#[cfg(feature = "unit_test")]
+mod tests;
+
+/// Copies a host-memory message into the enclave with a single read of the
+/// host buffer, so that parsing never reads memory the host can change.
+///
+/// # Safety
+///
+/// `ptr` must be valid for reads of `len` bytes.
+pub(crate) unsafe fn copy_from_host(ptr: *const u8, len: usize) -> SgxResult<Vec<u8>> {
+    let mut local = Vec::new();
+    local
+        .try_reserve_exact(len)
+        .map_err(|_| SgxStatus::OutOfMemory)?;
+    local.extend_from_slice(slice::from_raw_parts(ptr, len));
+    Ok(local)
+}
@@ -15,6 +15,7 @@
 // specific language governing permissions and limitations
 // under the License..
 
+use super::copy_from_host;
 use crate::session::Responder;
 use crate::QveReportInfo;
 use core::mem::{self, ManuallyDrop};
@@ -51,7 +52,10 @@ pub unsafe extern "C" fn sgx_dcap_mra_proc_msg1_trusted(
     let qe_target = &*qe_target;
     let msg1 = (&*msg1).into();
 
-    let responder = ManuallyDrop::new(Responder::from_raw(context));
+    let responder = match Responder::from_raw(context) {
+        Ok(responder) => ManuallyDrop::new(responder),
+        Err(e) => return e,
+    };
     let (pub_key, rpt, rand) = match responder.process_msg1(&msg1, qe_target) {
         Ok(r) => r,
         Err(e) => return e,
@@ -99,13 +103,16 @@ pub unsafe extern "C" fn sgx_dcap_mra_get_msg2_trusted(
     }
 
     let quote = slice::from_raw_parts(&c_msg2.quote as *const _ as *const u8, quote_size as usize);
-    let responder = ManuallyDrop::new(Responder::from_raw(context));
+    let responder = match Responder::from_raw(context) {
+        Ok(responder) => ManuallyDrop::new(responder),
+        Err(e) => return e,
+    };
     let msg2 = match responder.generate_msg2(qe_report, quote) {
         Ok(msg) => msg,
         Err(e) => return e,
     };
 
-    c_msg2.mac = msg2.mac;
+    c_msg2.mac = msg2.mac.into();
     c_msg2.g_b = msg2.pub_key_b.into();
     c_msg2.kdf_id = msg2.kdf_id;
     SgxStatus::Success
@@ -151,8 +158,21 @@ pub unsafe extern "C" fn sgx_dcap_mra_proc_msg3_trusted(
     let qve_nonce = *qve_nonce;
     let qve_report = &*qve_report;
 
-    let msg3_slice = slice::from_raw_parts(msg3 as *const u8, msg3_size as usize);
-    let msg3 = match DcapRaMsg3::from_slice(msg3_slice) {
+    let responder = match Responder::from_raw(context) {
+        Ok(responder) => ManuallyDrop::new(responder),
+        Err(e) => return e,
+    };
+    // Flagged before parsing, so a msg3 that fails or is retried still
+    // counts.
+    if let Err(e) = responder.set_used_host_buffers() {
+        return e;
+    }
+
+    let msg3_local = match copy_from_host(msg3 as *const u8, msg3_size as usize) {
+        Ok(msg) => msg,
+        Err(e) => return e,
+    };
+    let msg3 = match DcapRaMsg3::from_slice(&msg3_local) {
         Ok(msg) => msg,
         Err(e) => return e,
     };
@@ -166,20 +186,17 @@ pub unsafe extern "C" fn sgx_dcap_mra_proc_msg3_trusted(
         None
     };
 
-    let qve_report_info = QveReportInfo {
+    let qve_report_info = QveReportInfo::new(
         qve_report,
         expiration_time,
         collateral_expiration_status,
         quote_verification_result,
         qve_nonce,
         supplemental_data,
-    };
-
-    let responder = ManuallyDrop::new(Responder::from_raw(context));
-    let _ = match responder.process_msg3(&msg3, &qve_report_info) {
-        Ok(identity) => identity,
-        Err(e) => return e,
-    };
+    );
 
-    SgxStatus::Success
+    match responder.process_msg3(&msg3, &qve_report_info) {
+        Ok(_) => SgxStatus::Success,
+        Err(e) => e,
+    }
 }
@@ -0,0 +1,87 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License..
+
+use super::{copy_from_host, sgx_dcap_mra_proc_msg3_trusted};
+use crate::session::Responder;
+use core::mem;
+use core::num::NonZeroUsize;
+use core::ptr;
+use sgx_dcap_ra_msg::DcapRaMsg3;
+use sgx_test_utils::test_case;
+use sgx_trts::trts::{EnclaveRange, OcBuffer};
+use sgx_types::error::SgxStatus;
+use sgx_types::types::{
+    CDcapRaMsg3, QlAuthData, QlCertificationData, QlEcdsaSigData, QlQvResult, Quote3, QuoteNonce,
+    Report,
+};
+
+const QUOTE_LEN: usize = mem::size_of::<Quote3>()
+    + mem::size_of::<QlEcdsaSigData>()
+    + mem::size_of::<QlAuthData>()
+    + mem::size_of::<QlCertificationData>()
+    + 64;
+
+#[test_case]
+fn copy_from_host_reads_once() {
+    let msg3 = DcapRaMsg3 {
+        mac: Default::default(),
+        pub_key_a: Default::default(),
+        quote: alloc::vec![0x11_u8; QUOTE_LEN].into_boxed_slice(),
+        sealed: None,
+    };
+    let bytes = msg3.to_bytes().unwrap();
+
+    let mut host = OcBuffer::alloc(NonZeroUsize::new(bytes.len()).unwrap()).unwrap();
+    host.copy_from_slice(&bytes);
+
+    let local = unsafe { copy_from_host(host.as_ptr(), host.len()) }.unwrap();
+    assert!(local.as_slice().is_enclave_range());
+
+    // Later host writes must not be observed by the parser.
+    let len = host.len();
+    host[len - 1] = 0x22;
+    let parsed = DcapRaMsg3::from_slice(&local).unwrap();
+    assert_eq!(parsed.quote, msg3.quote);
+}
+
+#[test_case]
+fn failed_host_msg3_flagged() {
+    let context = Responder::new().unwrap().into_raw();
+    let len = mem::size_of::<CDcapRaMsg3>() + QUOTE_LEN;
+    let mut host = OcBuffer::alloc(NonZeroUsize::new(len).unwrap()).unwrap();
+    host.fill(0);
+
+    // The session has not produced msg2, so msg3 is refused, but it was
+    // still handed in through host memory.
+    let status = unsafe {
+        sgx_dcap_mra_proc_msg3_trusted(
+            context,
+            host.as_ptr() as *const CDcapRaMsg3,
+            len as u32,
+            0,
+            0,
+            QlQvResult::Ok,
+            &QuoteNonce::default(),
+            &Report::default(),
+            ptr::null(),
+            0,
+        )
+    };
+    assert_ne!(status, SgxStatus::Success);
+    let responder = unsafe { Responder::from_raw(context) }.unwrap();
+    assert_eq!(responder.used_host_buffers(), Ok(true));
+}
@@ -0,0 +1,65 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License..
+
+use sgx_types::types::{EnclaveIdentity, Measurement};
+
+#[cfg(feature = "unit_test")]
+mod tests;
+
+/// Which enclaves an identity check accepts.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum IdentityPolicy {
+    /// Exactly one enclave build.
+    MrEnclave { mr_enclave: Measurement },
+    /// One product from a signer, at `min_isv_svn` or newer.
+    MrSigner {
+        mr_signer: Measurement,
+        isv_prod_id: u16,
+        min_isv_svn: u16,
+    },
+    /// Every product from a signer at `min_isv_svn` or newer. The identity
+    /// carries no KSS family ID, so the signer stands for the family.
+    Family {
+        mr_signer: Measurement,
+        min_isv_svn: u16,
+    },
+}
+
+pub trait IdentityMatch {
+    fn matches(&self, policy: &IdentityPolicy) -> bool;
+}
+
+impl IdentityMatch for EnclaveIdentity {
+    fn matches(&self, policy: &IdentityPolicy) -> bool {
+        match *policy {
+            IdentityPolicy::MrEnclave { mr_enclave } => self.mr_enclave.eq(&mr_enclave),
+            IdentityPolicy::MrSigner {
+                mr_signer,
+                isv_prod_id,
+                min_isv_svn,
+            } => {
+                self.mr_signer.eq(&mr_signer)
+                    && self.isv_prod_id == isv_prod_id
+                    && self.isv_svn >= min_isv_svn
+            }
+            IdentityPolicy::Family {
+                mr_signer,
+                min_isv_svn,
+            } => self.mr_signer.eq(&mr_signer) && self.isv_svn >= min_isv_svn,
+        }
+    }
+}
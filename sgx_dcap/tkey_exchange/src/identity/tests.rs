@@ -0,0 +1,67 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License..
+
+use super::{IdentityMatch, IdentityPolicy};
+use sgx_test_utils::test_case;
+use sgx_types::types::{EnclaveIdentity, Measurement};
+
+fn identity(mr_enclave: u8, mr_signer: u8, isv_prod_id: u16, isv_svn: u16) -> EnclaveIdentity {
+    EnclaveIdentity {
+        mr_enclave: Measurement {
+            m: [mr_enclave; 32],
+        },
+        mr_signer: Measurement { m: [mr_signer; 32] },
+        isv_prod_id,
+        isv_svn,
+        ..Default::default()
+    }
+}
+
+#[test_case]
+fn mr_enclave_policy() {
+    let policy = IdentityPolicy::MrEnclave {
+        mr_enclave: Measurement { m: [1; 32] },
+    };
+    assert!(identity(1, 2, 1, 1).matches(&policy));
+    assert!(!identity(3, 2, 1, 1).matches(&policy));
+}
+
+#[test_case]
+fn mr_signer_policy() {
+    let policy = IdentityPolicy::MrSigner {
+        mr_signer: Measurement { m: [2; 32] },
+        isv_prod_id: 7,
+        min_isv_svn: 3,
+    };
+    assert!(identity(1, 2, 7, 3).matches(&policy));
+    assert!(identity(9, 2, 7, 4).matches(&policy));
+    assert!(!identity(1, 2, 7, 2).matches(&policy));
+    assert!(!identity(1, 2, 8, 3).matches(&policy));
+    assert!(!identity(1, 5, 7, 3).matches(&policy));
+}
+
+#[test_case]
+fn family_policy() {
+    let policy = IdentityPolicy::Family {
+        mr_signer: Measurement { m: [2; 32] },
+        min_isv_svn: 3,
+    };
+    assert!(identity(1, 2, 7, 3).matches(&policy));
+    assert!(identity(1, 2, 8, 5).matches(&policy));
+    assert!(!identity(1, 2, 8, 2).matches(&policy));
+    assert!(!identity(1, 5, 7, 3).matches(&policy));
+}
@@ -17,6 +17,7 @@
 
 #![no_std]
 #![cfg_attr(target_vendor = "teaclave", feature(rustc_private))]
+#![feature(allocator_api)]
 #![feature(extract_if)]
 #![allow(clippy::missing_safety_doc)]
 
@@ -28,15 +29,20 @@ extern crate sgx_dcap_tvl;
 extern crate sgx_sync;
 extern crate sgx_trts;
 extern crate sgx_tse;
+extern crate sgx_tseal;
 #[macro_use]
 extern crate sgx_types;
 
 mod ecall;
+mod identity;
+mod primitives;
 mod session;
 pub use ecall::*;
+pub use identity::*;
+pub use primitives::*;
 pub use session::*;
 
-pub use sgx_dcap_tvl::QveReportInfo;
+pub use sgx_dcap_tvl::{Cert, PlatformFlagsPolicy, QveReportInfo, SupplementalData};
 
 #[cfg(feature = "capi")]
 pub mod capi;
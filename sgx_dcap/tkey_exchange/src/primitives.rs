@@ -0,0 +1,69 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License..
+
+#[cfg(feature = "unit_test")]
+mod tests;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Curve {
+    P256,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Kdf {
+    /// AES-128-CMAC over the ECDH shared secret, as in the SGX RA key
+    /// derivation (SMK/SK/MK/VK).
+    AesCMac,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum HashAlg {
+    Sha256,
+}
+
+/// Crypto primitives compiled into this build, for negotiating with a peer.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Primitives {
+    pub curves: &'static [Curve],
+    pub kdfs: &'static [Kdf],
+    pub hashes: &'static [HashAlg],
+}
+
+impl Primitives {
+    pub fn supports_curve(&self, curve: Curve) -> bool {
+        self.curves.contains(&curve)
+    }
+
+    pub fn supports_kdf(&self, kdf: Kdf) -> bool {
+        self.kdfs.contains(&kdf)
+    }
+
+    pub fn supports_hash(&self, hash: HashAlg) -> bool {
+        self.hashes.contains(&hash)
+    }
+}
+
+const SUPPORTED_PRIMITIVES: Primitives = Primitives {
+    curves: &[Curve::P256],
+    kdfs: &[Kdf::AesCMac],
+    hashes: &[HashAlg::Sha256],
+};
+
+#[inline]
+pub fn supported_primitives() -> Primitives {
+    SUPPORTED_PRIMITIVES
+}
@@ -0,0 +1,101 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License..
+use super::quote::{check_qve_ran, classify_quote3_error, parse_quote3};
+use super::{Responder, QVE_ISVSVN_THRESHOLD};
+use crate::QveReportInfo;
+use alloc::vec::Vec;
+use sgx_dcap_ra_msg::{DcapRaMsg1, DcapRaMsg3};
+use sgx_types::error::{SgxResult, SgxStatus};
+use sgx_types::types::{QuoteNonce, Report, TargetInfo};
+
+/// What `dry_run_handshake` needs to produce msg2: the QE target info and a
+/// callback returning the QE report and quote, as for `handle_request`.
+pub struct Msg2Inputs<'a, F> {
+    pub qe_target: &'a TargetInfo,
+    pub get_quote: F,
+}
+
+/// Outcome of each responder step of a dry run. A step that could not run
+/// because an earlier one failed reports `InvalidState`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct HandshakeReport {
+    pub msg1: SgxResult,
+    pub msg2: SgxResult,
+    pub msg3: SgxResult,
+}
+
+impl HandshakeReport {
+    #[inline]
+    pub fn is_ok(&self) -> bool {
+        self.msg1.is_ok() && self.msg2.is_ok() && self.msg3.is_ok()
+    }
+}
+
+/// Checks whether recorded handshake messages would be accepted, using a
+/// throwaway responder session that is dropped afterwards.
+///
+/// msg1 and msg2 run through the responder as usual. A recorded msg3 is
+/// bound to the keys of the session it was recorded in, so its CMAC and
+/// report data cannot be checked here; the dry run checks its framing, its
+/// g_a against msg1, the quote layout and the QvE verdict.
+pub fn dry_run_handshake<F>(
+    msg1: &[u8],
+    msg2_inputs: Msg2Inputs<'_, F>,
+    msg3: &[u8],
+    qve_report_info: &QveReportInfo,
+) -> SgxResult<HandshakeReport>
+where
+    F: FnOnce(&Report, &QuoteNonce) -> SgxResult<(Report, Vec<u8>)>,
+{
+    let responder = Responder::new()?;
+
+    let msg1 = DcapRaMsg1::from_slice(msg1);
+    let msg1_result = msg1.as_ref().map_err(|e| *e).and_then(|msg1| {
+        responder
+            .do_process_msg1(msg1, msg2_inputs.qe_target, None)
+            .map(|(_, report, nonce)| (report, nonce))
+    });
+
+    let msg2_result = match msg1_result {
+        Ok((report, nonce)) => (msg2_inputs.get_quote)(&report, &nonce)
+            .and_then(|(qe_report, quote)| responder.do_generate_msg2(&qe_report, &quote))
+            .map(|_| ()),
+        Err(_) => Err(SgxStatus::InvalidState),
+    };
+
+    let msg3_result = match msg1 {
+        Ok(msg1) => check_msg3(&msg1, msg3, qve_report_info),
+        Err(_) => Err(SgxStatus::InvalidState),
+    };
+
+    Ok(HandshakeReport {
+        msg1: msg1_result.map(|_| ()),
+        msg2: msg2_result,
+        msg3: msg3_result,
+    })
+}
+
+fn check_msg3(msg1: &DcapRaMsg1, msg3: &[u8], qve_report_info: &QveReportInfo) -> SgxResult {
+    let msg3 = DcapRaMsg3::from_slice(msg3)?;
+    ensure!(msg3.pub_key_a == msg1.pub_key_a, SgxStatus::Unexpected);
+
+    parse_quote3(&msg3.quote)?;
+    check_qve_ran(qve_report_info)?;
+    qve_report_info
+        .verify_report_and_identity(&msg3.quote, QVE_ISVSVN_THRESHOLD)
+        .map_err(classify_quote3_error)
+}
@@ -0,0 +1,154 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License..
+
+use alloc::vec::Vec;
+use core::mem;
+use sgx_crypto::ecc::EcPublicKey;
+use sgx_crypto::mac::AesCMac;
+use sgx_dcap_ra_msg::Mac128;
+use sgx_types::error::{SgxResult, SgxStatus};
+use sgx_types::types::{AlignKey128bit, ECP256_KEY_SIZE, MAC_SIZE};
+
+pub const EVIDENCE_BUNDLE_MAGIC: [u8; 4] = *b"DCEV";
+pub const EVIDENCE_BUNDLE_VERSION: u16 = 1;
+
+const HEADER_SIZE: usize = 4 + 2 + 2 + ECP256_KEY_SIZE * 2;
+
+/// Attestation evidence for the session peer to check offline, laid out as
+///
+/// ```text
+/// magic "DCEV" | version: u16 | reserved: u16 | g_b: [u8; 64]
+/// quote_len: u32 | quote | collateral_len: u32 | collateral | mac: [u8; 16]
+/// ```
+///
+/// with little-endian integers and an AES-CMAC under the session MK over
+/// everything before the MAC.
+///
+/// The bundle is not third-party verifiable. Both ends of the session hold
+/// MK, so the MAC only tells the session peer that the bundle is intact.
+/// Anyone else has to verify the embedded quote on its own, which proves
+/// the enclave identity but not that the bundle belongs to a given session.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct EvidenceBundle {
+    pub version: u16,
+    pub pub_key: EcPublicKey,
+    pub quote: Vec<u8>,
+    pub collateral: Vec<u8>,
+    pub mac: Mac128,
+}
+
+impl EvidenceBundle {
+    pub fn new(pub_key: EcPublicKey, quote: Vec<u8>, collateral: Vec<u8>) -> EvidenceBundle {
+        EvidenceBundle {
+            version: EVIDENCE_BUNDLE_VERSION,
+            pub_key,
+            quote,
+            collateral,
+            mac: Mac128::default(),
+        }
+    }
+
+    pub fn gen_cmac(&mut self, mk_key: &AlignKey128bit) -> SgxResult {
+        let body = self.body()?;
+        self.mac = AesCMac::cmac(&mk_key.key, body.as_slice())?.into();
+        Ok(())
+    }
+
+    pub fn verify_cmac(&self, mk_key: &AlignKey128bit) -> SgxResult {
+        let body = self.body()?;
+        let mac = AesCMac::cmac(&mk_key.key, body.as_slice())?;
+        ensure!(self.mac == mac.into(), SgxStatus::MacMismatch);
+        Ok(())
+    }
+
+    pub fn to_bytes(&self) -> SgxResult<Vec<u8>> {
+        let mut bytes = self.body()?;
+        bytes.extend_from_slice(self.mac.as_bytes());
+        Ok(bytes)
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> SgxResult<EvidenceBundle> {
+        ensure!(
+            bytes.len() >= HEADER_SIZE + 2 * mem::size_of::<u32>() + MAC_SIZE,
+            SgxStatus::InvalidParameter
+        );
+        ensure!(
+            bytes[..4] == EVIDENCE_BUNDLE_MAGIC,
+            SgxStatus::InvalidParameter
+        );
+        let version = u16::from_le_bytes([bytes[4], bytes[5]]);
+        ensure!(
+            version == EVIDENCE_BUNDLE_VERSION,
+            SgxStatus::InvalidParameter
+        );
+
+        let mut pub_key = [0_u8; ECP256_KEY_SIZE * 2];
+        pub_key.copy_from_slice(&bytes[8..HEADER_SIZE]);
+
+        let (body, mac) = bytes.split_at(bytes.len() - MAC_SIZE);
+        let mut rest = &body[HEADER_SIZE..];
+        let quote = take_field(&mut rest)?;
+        let collateral = take_field(&mut rest)?;
+        ensure!(rest.is_empty(), SgxStatus::InvalidParameter);
+
+        let mut mac_bytes = [0_u8; MAC_SIZE];
+        mac_bytes.copy_from_slice(mac);
+
+        Ok(EvidenceBundle {
+            version,
+            pub_key: pub_key.into(),
+            quote: quote.into(),
+            collateral: collateral.into(),
+            mac: mac_bytes.into(),
+        })
+    }
+
+    fn body(&self) -> SgxResult<Vec<u8>> {
+        ensure!(
+            self.quote.len() <= u32::MAX as usize && self.collateral.len() <= u32::MAX as usize,
+            SgxStatus::InvalidParameter
+        );
+
+        let pub_key: [u8; ECP256_KEY_SIZE * 2] = self.pub_key.into();
+        let mut body = Vec::with_capacity(
+            HEADER_SIZE + 2 * mem::size_of::<u32>() + self.quote.len() + self.collateral.len(),
+        );
+        body.extend_from_slice(&EVIDENCE_BUNDLE_MAGIC);
+        body.extend_from_slice(&self.version.to_le_bytes());
+        body.extend_from_slice(&0_u16.to_le_bytes());
+        body.extend_from_slice(&pub_key);
+        body.extend_from_slice(&(self.quote.len() as u32).to_le_bytes());
+        body.extend_from_slice(&self.quote);
+        body.extend_from_slice(&(self.collateral.len() as u32).to_le_bytes());
+        body.extend_from_slice(&self.collateral);
+        Ok(body)
+    }
+}
+
+fn take_field<'a>(rest: &mut &'a [u8]) -> SgxResult<&'a [u8]> {
+    ensure!(
+        rest.len() >= mem::size_of::<u32>(),
+        SgxStatus::InvalidParameter
+    );
+    let len = u32::from_le_bytes([rest[0], rest[1], rest[2], rest[3]]) as usize;
+    let data = &rest[mem::size_of::<u32>()..];
+    ensure!(data.len() >= len, SgxStatus::InvalidParameter);
+
+    let (field, tail) = data.split_at(len);
+    *rest = tail;
+    Ok(field)
+}
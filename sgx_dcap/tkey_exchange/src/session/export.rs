@@ -0,0 +1,237 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License..
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use core::mem;
+use core::ptr;
+use sgx_crypto::ecc::EcPublicKey;
+use sgx_sync::SpinMutex;
+use sgx_trts::rand::Rng;
+use sgx_tseal::seal::{SealedData, UnsealedData};
+use sgx_types::error::{SgxResult, SgxStatus};
+use sgx_types::types::{
+    AlignKey128bit, KeyPolicy, QlQvResult, ReportBody, ECP256_KEY_SIZE, KEY_128BIT_SIZE,
+};
+
+#[cfg(feature = "unit_test")]
+mod tests;
+
+pub const SESSION_EXPORT_MAGIC: [u8; 4] = *b"DCSE";
+/// Bumped whenever the exported fields or the key schedule change, so that
+/// an older blob is refused instead of yielding the wrong keys.
+pub const SESSION_EXPORT_VERSION: u16 = 4;
+
+const PUB_KEY_SIZE: usize = ECP256_KEY_SIZE * 2;
+
+const EXPORT_NONCE_SIZE: usize = 16;
+
+const AAD_SIZE: usize = 4 + 2 + EXPORT_NONCE_SIZE;
+
+// g_a | g_b | sk | mk | vk | rekey_count: u32 | qv_result: u32 | peer report body
+// | has_collateral: u8 | expiration_time: i64 | collateral_expiration_status: u32
+// | confirm_required: u8 | confirmed: u8 | quote_len: u32, followed by the peer
+// quote. An empty quote stands for none.
+const FIXED_SIZE: usize = PUB_KEY_SIZE * 2
+    + KEY_128BIT_SIZE * 3
+    + 4
+    + 4
+    + mem::size_of::<ReportBody>()
+    + 1
+    + 8
+    + 4
+    + 1
+    + 1
+    + 4;
+
+// Nonces of the blobs this enclave instance has imported.
+static IMPORTED_NONCES: SpinMutex<Vec<[u8; EXPORT_NONCE_SIZE]>> = SpinMutex::new(Vec::new());
+
+/// The state of an established session that survives an enclave reload.
+/// It is sealed to MRENCLAVE with `magic | version: u16 | nonce` as AAD: the
+/// blob carries live session keys, so only the same enclave build may resume
+/// it, not any enclave from the same signer. The random nonce lets an enclave
+/// instance refuse a blob it has already imported; a replay into a freshly
+/// loaded enclave is not detected, as there is no counter that survives the
+/// reload.
+pub(super) struct SessionExport {
+    pub pub_key_a: EcPublicKey,
+    pub pub_key_b: EcPublicKey,
+    pub sk_key: AlignKey128bit,
+    pub mk_key: AlignKey128bit,
+    pub vk_key: AlignKey128bit,
+    pub qv_result: QlQvResult,
+    pub peer_report: ReportBody,
+    pub rekey_count: u32,
+    pub peer_quote: Option<Box<[u8]>>,
+    pub collateral_status: Option<(i64, u32)>,
+    pub confirm_required: bool,
+    pub confirmed: bool,
+    // Drawn afresh by each `seal`, and read back from the AAD by `unseal`.
+    pub nonce: [u8; EXPORT_NONCE_SIZE],
+}
+
+impl SessionExport {
+    pub fn seal(&self) -> SgxResult<Vec<u8>> {
+        let quote = self.peer_quote.as_deref().unwrap_or_default();
+        let quote_len = u32::try_from(quote.len()).map_err(|_| SgxStatus::InvalidParameter)?;
+        let (has_collateral, (expiration_time, collateral_expiration_status)) =
+            match self.collateral_status {
+                Some(status) => (1_u8, status),
+                None => (0_u8, (0, 0)),
+            };
+
+        let mut nonce = [0_u8; EXPORT_NONCE_SIZE];
+        Rng::new().fill_bytes(&mut nonce);
+
+        let mut payload = Vec::with_capacity(FIXED_SIZE + quote.len());
+        payload.extend_from_slice(self.pub_key_a.as_ref());
+        payload.extend_from_slice(self.pub_key_b.as_ref());
+        payload.extend_from_slice(&self.sk_key.key);
+        payload.extend_from_slice(&self.mk_key.key);
+        payload.extend_from_slice(&self.vk_key.key);
+        payload.extend_from_slice(&self.rekey_count.to_le_bytes());
+        payload.extend_from_slice(&u32::from(self.qv_result).to_le_bytes());
+        payload.extend_from_slice(self.peer_report.as_ref());
+        payload.push(has_collateral);
+        payload.extend_from_slice(&expiration_time.to_le_bytes());
+        payload.extend_from_slice(&collateral_expiration_status.to_le_bytes());
+        payload.push(self.confirm_required as u8);
+        payload.push(self.confirmed as u8);
+        payload.extend_from_slice(&quote_len.to_le_bytes());
+        payload.extend_from_slice(quote);
+
+        let sealed = SealedData::<[u8]>::seal_with_aad(
+            payload.as_slice(),
+            &aad(&nonce),
+            KeyPolicy::MRENCLAVE,
+        );
+        payload.fill(0);
+        sealed?.into_bytes()
+    }
+
+    pub fn unseal(blob: &[u8]) -> SgxResult<SessionExport> {
+        let unsealed = UnsealedData::<[u8]>::unseal_from_slice(blob)?;
+        let aad = unsealed.to_aad();
+        ensure!(
+            aad.len() == AAD_SIZE && aad[..AAD_SIZE - EXPORT_NONCE_SIZE] == aad_prefix(),
+            SgxStatus::InvalidVersion
+        );
+        let mut nonce = [0_u8; EXPORT_NONCE_SIZE];
+        nonce.copy_from_slice(&aad[AAD_SIZE - EXPORT_NONCE_SIZE..]);
+
+        let mut payload = unsealed.into_plaintext();
+        let export = Self::from_payload(&payload);
+        payload.fill(0);
+        let mut export = export?;
+        export.nonce = nonce;
+        Ok(export)
+    }
+
+    // Records the nonce of an unsealed blob, failing with `InvalidState` if
+    // this enclave instance has imported the blob before.
+    pub fn claim(&self) -> SgxResult {
+        let mut imported = IMPORTED_NONCES.lock();
+        ensure!(!imported.contains(&self.nonce), SgxStatus::InvalidState);
+        imported.push(self.nonce);
+        Ok(())
+    }
+
+    fn from_payload(payload: &[u8]) -> SgxResult<SessionExport> {
+        ensure!(payload.len() >= FIXED_SIZE, SgxStatus::InvalidParameter);
+
+        let (pub_key_a, rest) = payload.split_at(PUB_KEY_SIZE);
+        let (pub_key_b, rest) = rest.split_at(PUB_KEY_SIZE);
+        let (sk_key, rest) = rest.split_at(KEY_128BIT_SIZE);
+        let (mk_key, rest) = rest.split_at(KEY_128BIT_SIZE);
+        let (vk_key, rest) = rest.split_at(KEY_128BIT_SIZE);
+        let (rekey_count, rest) = rest.split_at(4);
+        let (qv_result, rest) = rest.split_at(4);
+        let (peer_report, rest) = rest.split_at(mem::size_of::<ReportBody>());
+        let (has_collateral, rest) = rest.split_at(1);
+        let (expiration_time, rest) = rest.split_at(8);
+        let (collateral_expiration_status, rest) = rest.split_at(4);
+        let (confirm_flags, rest) = rest.split_at(2);
+        let (quote_len, quote) = rest.split_at(4);
+        ensure!(
+            quote.len() == u32::from_le_bytes(quote_len.try_into().unwrap()) as usize,
+            SgxStatus::InvalidParameter
+        );
+
+        let collateral_status = match has_collateral[0] {
+            0 => None,
+            1 => Some((
+                i64::from_le_bytes(expiration_time.try_into().unwrap()),
+                u32::from_le_bytes(collateral_expiration_status.try_into().unwrap()),
+            )),
+            _ => bail!(SgxStatus::InvalidParameter),
+        };
+        let flag = |byte: u8| match byte {
+            0 => Ok(false),
+            1 => Ok(true),
+            _ => Err(SgxStatus::InvalidParameter),
+        };
+
+        let qv_result = u32::from_le_bytes(qv_result.try_into().unwrap());
+        let qv_result = QlQvResult::try_from(qv_result).map_err(|_| SgxStatus::InvalidParameter)?;
+        let key = |bytes: &[u8]| {
+            let mut key = AlignKey128bit::default();
+            key.key.copy_from_slice(bytes);
+            key
+        };
+        let pub_key =
+            |bytes: &[u8]| EcPublicKey::from(<[u8; PUB_KEY_SIZE]>::try_from(bytes).unwrap());
+
+        Ok(SessionExport {
+            pub_key_a: pub_key(pub_key_a),
+            pub_key_b: pub_key(pub_key_b),
+            sk_key: key(sk_key),
+            mk_key: key(mk_key),
+            vk_key: key(vk_key),
+            qv_result,
+            // The payload was sealed by this code, so the bits are as written.
+            peer_report: unsafe { ptr::read_unaligned(peer_report.as_ptr() as *const ReportBody) },
+            rekey_count: u32::from_le_bytes(rekey_count.try_into().unwrap()),
+            peer_quote: (!quote.is_empty()).then(|| quote.into()),
+            collateral_status,
+            confirm_required: flag(confirm_flags[0])?,
+            confirmed: flag(confirm_flags[1])?,
+            nonce: [0; EXPORT_NONCE_SIZE],
+        })
+    }
+}
+
+impl Drop for SessionExport {
+    fn drop(&mut self) {
+        self.sk_key.key.fill(0);
+        self.mk_key.key.fill(0);
+        self.vk_key.key.fill(0);
+    }
+}
+
+fn aad_prefix() -> [u8; 6] {
+    let mut prefix = [0_u8; 6];
+    prefix[..4].copy_from_slice(&SESSION_EXPORT_MAGIC);
+    prefix[4..].copy_from_slice(&SESSION_EXPORT_VERSION.to_le_bytes());
+    prefix
+}
+
+fn aad(nonce: &[u8; EXPORT_NONCE_SIZE]) -> [u8; AAD_SIZE] {
+    let mut aad = [0_u8; AAD_SIZE];
+    aad[..6].copy_from_slice(&aad_prefix());
+    aad[6..].copy_from_slice(nonce);
+    aad
+}
@@ -0,0 +1,110 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License..
+use super::{SessionExport, SESSION_EXPORT_MAGIC, SESSION_EXPORT_VERSION};
+use alloc::vec;
+use sgx_crypto::ecc::EcKeyPair;
+use sgx_test_utils::test_case;
+use sgx_tse::EnclaveReport;
+use sgx_tseal::seal::SealedData;
+use sgx_types::error::SgxStatus;
+use sgx_types::types::{AlignKey128bit, KeyPolicy, QlQvResult, Report};
+
+fn key(byte: u8) -> AlignKey128bit {
+    let mut key = AlignKey128bit::default();
+    key.key = [byte; 16];
+    key
+}
+
+fn sample() -> SessionExport {
+    SessionExport {
+        pub_key_a: EcKeyPair::create().unwrap().public_key(),
+        pub_key_b: EcKeyPair::create().unwrap().public_key(),
+        sk_key: key(1),
+        mk_key: key(2),
+        vk_key: key(3),
+        qv_result: QlQvResult::SWHardeningNeeded,
+        peer_report: Report::get_self().body,
+        rekey_count: 3,
+        peer_quote: Some(vec![0x5a; 600].into_boxed_slice()),
+        collateral_status: Some((1_700_000_000, 1)),
+        confirm_required: true,
+        confirmed: true,
+        nonce: Default::default(),
+    }
+}
+
+#[test_case]
+fn export_round_trip() {
+    let export = sample();
+    let blob = export.seal().unwrap();
+
+    let import = SessionExport::unseal(&blob).unwrap();
+    assert_eq!(import.pub_key_a, export.pub_key_a);
+    assert_eq!(import.pub_key_b, export.pub_key_b);
+    assert_eq!(import.sk_key.key, [1; 16]);
+    assert_eq!(import.mk_key.key, [2; 16]);
+    assert_eq!(import.vk_key.key, [3; 16]);
+    assert_eq!(import.qv_result, QlQvResult::SWHardeningNeeded);
+    assert_eq!(import.rekey_count, 3);
+    assert_eq!(import.peer_report.mr_enclave, export.peer_report.mr_enclave);
+    assert_eq!(import.peer_quote, export.peer_quote);
+    assert_eq!(import.collateral_status, Some((1_700_000_000, 1)));
+    assert!(import.confirm_required && import.confirmed);
+
+    let mut export = sample();
+    export.peer_quote = None;
+    export.collateral_status = None;
+    let import = SessionExport::unseal(&export.seal().unwrap()).unwrap();
+    assert_eq!(import.peer_quote, None);
+    assert_eq!(import.collateral_status, None);
+}
+
+#[test_case]
+fn export_sealed_to_mrenclave() {
+    let blob = sample().seal().unwrap();
+    // The key request leads the sealed header: key_name, then key_policy.
+    let key_policy = u16::from_le_bytes([blob[2], blob[3]]);
+    assert_eq!(key_policy, KeyPolicy::MRENCLAVE.bits());
+}
+
+#[test_case]
+fn export_claimed_once() {
+    let export = sample();
+    let first = SessionExport::unseal(&export.seal().unwrap()).unwrap();
+    let second = SessionExport::unseal(&export.seal().unwrap()).unwrap();
+    assert_ne!(first.nonce, second.nonce);
+
+    first.claim().unwrap();
+    assert_eq!(first.claim(), Err(SgxStatus::InvalidState));
+    second.claim().unwrap();
+}
+
+#[test_case]
+fn export_rejects_other_version() {
+    let mut aad = [0_u8; 6];
+    aad[..4].copy_from_slice(&SESSION_EXPORT_MAGIC);
+    aad[4..].copy_from_slice(&(SESSION_EXPORT_VERSION + 1).to_le_bytes());
+    let blob = SealedData::<[u8]>::seal(&[0_u8; 16][..], Some(&aad))
+        .unwrap()
+        .into_bytes()
+        .unwrap();
+
+    assert_eq!(
+        SessionExport::unseal(&blob).err(),
+        Some(SgxStatus::InvalidVersion)
+    );
+}
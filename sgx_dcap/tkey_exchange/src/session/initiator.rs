@@ -16,9 +16,11 @@
 // under the License..
 
 use super::manager::INITIATOR_SESSION_MAGAGER;
+use super::manager::{parse_identity, DropKey, DropPrivateKey, DropShareKey};
 use super::manager::{Context, InitiatorState, Role, Session};
-use super::manager::{DropKey, DropPrivateKey, DropShareKey};
-use super::QVE_ISVSVN_THRESHOLD;
+use super::quote::check_qve_ran;
+use super::secret::encrypt_secret;
+use super::{confirm_mac, derive_directional_keys, rekey, DirectionalKeys, QVE_ISVSVN_THRESHOLD};
 use core::mem;
 use sgx_crypto::ecc::{EcKeyPair, EcPublicKey};
 use sgx_crypto::sha::Sha256;
@@ -31,8 +33,8 @@ use sgx_tse::EnclaveReport;
 use sgx_types::error::{Quote3Error, SgxResult, SgxStatus};
 use sgx_types::types::SHA256_HASH_SIZE;
 use sgx_types::types::{
-    AlignKey128bit, EnclaveIdentity, QlQvResult, Quote3, QuoteNonce, RaContext, RaKeyType, Report,
-    ReportData, TargetInfo,
+    AlignKey128bit, EnclaveIdentity, Mac, QlQvResult, Quote3, QuoteNonce, RaContext, RaKeyType,
+    Report, ReportData, TargetInfo,
 };
 
 #[derive(Debug)]
@@ -131,6 +133,7 @@ impl Initiator {
         let vk_key = DropKey::new(vk_key);
 
         msg2.verify_cmac(&smk_key)?;
+        check_qve_ran(qve_report_info)?;
         qve_report_info
             .verify_report_and_identity(&msg2.quote, QVE_ISVSVN_THRESHOLD)
             .map_err(|e| match e {
@@ -158,7 +161,8 @@ impl Initiator {
             hash.eq(&quote3.report_body.report_data.d[..SHA256_HASH_SIZE]),
             SgxStatus::Unexpected
         );
-        let enclave_identity = quote3.report_body.into();
+        let peer_report = quote3.report_body;
+        let enclave_identity = parse_identity(&peer_report);
 
         let mut context = session.context.lock();
         ensure!(
@@ -173,6 +177,7 @@ impl Initiator {
         context.qe_target = *qe_target;
         context.quote_nonce = nonce;
         context.qv_result = Some(qve_report_info.quote_verification_result);
+        context.peer_report = Some(peer_report);
         context.enclave_identity = Some(enclave_identity);
         context.state = From::from(InitiatorState::Msg2Proced);
         drop(context);
@@ -258,6 +263,28 @@ impl Initiator {
     }
 
     pub fn generate_msg3(&self, qe_report: &Report, quote: &[u8]) -> SgxResult<DcapRaMsg3> {
+        self.do_generate_msg3(qe_report, quote, None)
+    }
+
+    /// Like `generate_msg3`, but also carries `secret` for the responder,
+    /// encrypted with AES-128-GCM under a key derived from SK and covered by
+    /// the msg3 CMAC. The responder reads it with `process_msg3_unseal`.
+    pub fn generate_msg3_with_secret(
+        &self,
+        qe_report: &Report,
+        quote: &[u8],
+        secret: &[u8],
+    ) -> SgxResult<DcapRaMsg3> {
+        ensure!(!secret.is_empty(), SgxStatus::InvalidParameter);
+        self.do_generate_msg3(qe_report, quote, Some(secret))
+    }
+
+    fn do_generate_msg3(
+        &self,
+        qe_report: &Report,
+        quote: &[u8],
+        secret: Option<&[u8]>,
+    ) -> SgxResult<DcapRaMsg3> {
         ensure!(qe_report.is_enclave_range(), SgxStatus::InvalidParameter);
         ensure!(!quote.is_empty(), SgxStatus::InvalidParameter);
         ensure!(
@@ -289,10 +316,12 @@ impl Initiator {
         let mr_enclave = context.qe_target.mr_enclave;
         let pub_key_a = context.pub_key_a;
         let mut smk_key = context.smk_key;
+        let mut sk_key = context.sk_key;
         let nonce = context.quote_nonce;
         drop(context);
 
         let smk_key = DropKey::new(&mut smk_key);
+        let sk_key = DropKey::new(&mut sk_key);
 
         ensure!(
             attributes.eq(&qe_report.body.attributes),
@@ -312,10 +341,15 @@ impl Initiator {
             SgxStatus::Unexpected
         );
 
+        let sealed = match secret {
+            Some(secret) => Some(encrypt_secret(&sk_key, secret)?.into_boxed_slice()),
+            None => None,
+        };
         let mut msg3 = DcapRaMsg3 {
             mac: Default::default(),
             pub_key_a,
             quote: quote.into(),
+            sealed,
         };
         msg3.gen_cmac(&smk_key)?;
 
@@ -332,6 +366,30 @@ impl Initiator {
         Ok(msg3)
     }
 
+    /// Returns the key confirmation MAC the responder checks in
+    /// `Responder::finalize`.
+    pub fn confirmation(&self) -> SgxResult<Mac> {
+        let session = INITIATOR_SESSION_MAGAGER
+            .read()
+            .find(self.rctx)
+            .ok_or(SgxStatus::InvalidParameter)?;
+
+        let context = session.context.lock();
+        ensure!(
+            context
+                .state
+                .check_initiator_state(InitiatorState::Established),
+            SgxStatus::InvalidState
+        );
+        let pub_key_a = context.pub_key_a;
+        let pub_key_b = context.pub_key_b;
+        let mut vk_key = context.vk_key;
+        drop(context);
+
+        let vk_key = DropKey::new(&mut vk_key);
+        confirm_mac(&vk_key, &pub_key_a, &pub_key_b)
+    }
+
     pub fn get_keys(&self, key_type: RaKeyType) -> SgxResult<AlignKey128bit> {
         let session = INITIATOR_SESSION_MAGAGER
             .read()
@@ -354,25 +412,89 @@ impl Initiator {
         Ok(key)
     }
 
-    pub fn get_peer_identity(&self) -> SgxResult<(QlQvResult, EnclaveIdentity)> {
+    /// Returns per-direction keys derived from SK, under the same
+    /// conditions as `get_keys`.
+    pub fn get_directional_keys(&self) -> SgxResult<DirectionalKeys> {
+        let mut sk_key = self.get_keys(RaKeyType::SK)?;
+        let sk_key = DropKey::new(&mut sk_key);
+        derive_directional_keys(&sk_key)
+    }
+
+    /// The initiator side of `Responder::rekey`; both ends must rekey the
+    /// same number of times to keep agreeing on SK and MK.
+    pub fn rekey(&self) -> SgxResult {
         let session = INITIATOR_SESSION_MAGAGER
             .read()
             .find(self.rctx)
             .ok_or(SgxStatus::InvalidParameter)?;
 
         let context = session.context.lock();
+        ensure!(
+            context
+                .state
+                .check_initiator_state(InitiatorState::Established),
+            SgxStatus::InvalidState
+        );
+        let mut sk_key = context.sk_key;
+        let mut mk_key = context.mk_key;
+        let counter = context
+            .rekey_count
+            .checked_add(1)
+            .ok_or(SgxStatus::InvalidState)?;
+        drop(context);
+
+        let sk_key = DropKey::new(&mut sk_key);
+        let mk_key = DropKey::new(&mut mk_key);
+        let (mut next_sk, mut next_mk) = rekey(&sk_key, &mk_key, counter)?;
+        let next_sk = DropKey::new(&mut next_sk);
+        let next_mk = DropKey::new(&mut next_mk);
+
+        let mut context = session.context.lock();
+        ensure!(
+            context
+                .state
+                .check_initiator_state(InitiatorState::Established)
+                && context.rekey_count == counter - 1,
+            SgxStatus::InvalidState
+        );
+        context.sk_key = *next_sk;
+        context.mk_key = *next_mk;
+        context.rekey_count = counter;
+        Ok(())
+    }
+
+    pub fn get_peer_identity(&self) -> SgxResult<(QlQvResult, EnclaveIdentity)> {
+        let session = INITIATOR_SESSION_MAGAGER
+            .read()
+            .find(self.rctx)
+            .ok_or(SgxStatus::InvalidParameter)?;
+
+        let mut context = session.context.lock();
         ensure!(
             context
                 .state
                 .check_initiator_state(InitiatorState::Msg2Proced),
             SgxStatus::InvalidState
         );
-        let enclave_identity = context.enclave_identity.ok_or(SgxStatus::Unexpected)?;
         let qv_result = context.qv_result.ok_or(SgxStatus::Unexpected)?;
+        let enclave_identity = context.peer_identity()?;
 
         Ok((qv_result, enclave_identity))
     }
 
+    /// Drops the cached peer identity; the next `get_peer_identity` parses
+    /// it again from the stored peer report.
+    pub fn forget_peer_identity(&self) -> SgxResult {
+        let session = INITIATOR_SESSION_MAGAGER
+            .read()
+            .find(self.rctx)
+            .ok_or(SgxStatus::InvalidParameter)?;
+
+        let mut context = session.context.lock();
+        context.enclave_identity = None;
+        Ok(())
+    }
+
     #[inline]
     pub fn into_raw(self) -> RaContext {
         let rctx = self.rctx;
@@ -380,9 +502,17 @@ impl Initiator {
         rctx
     }
 
+    /// Rebuilds a session handle from `into_raw`. Handles issued by a previous
+    /// enclave instance, or whose session was already closed, are rejected
+    /// with `InvalidParameter`.
     #[inline]
-    pub unsafe fn from_raw(rctx: RaContext) -> Initiator {
-        Self { rctx }
+    pub unsafe fn from_raw(rctx: RaContext) -> SgxResult<Initiator> {
+        let manager = INITIATOR_SESSION_MAGAGER.read();
+        ensure!(
+            manager.is_current(rctx) && manager.contains(rctx),
+            SgxStatus::InvalidParameter
+        );
+        Ok(Self { rctx })
     }
 }
 
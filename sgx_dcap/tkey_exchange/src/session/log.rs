@@ -0,0 +1,175 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License..
+
+use core::cmp;
+use core::mem;
+use core::slice;
+use sgx_sync::SpinMutex;
+use sgx_types::error::{SgxResult, SgxStatus};
+use sgx_types::types::RaContext;
+
+#[cfg(feature = "unit_test")]
+mod tests;
+
+pub const LOG_RING_SIZE: usize = 64;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[repr(u16)]
+pub enum LogEvent {
+    Init = 1,
+    ProcMsg1 = 2,
+    GenMsg2 = 3,
+    ProcMsg3 = 4,
+    GetKeys = 5,
+    GetPeerIdentity = 6,
+    Close = 7,
+    Finalize = 8,
+    ReleaseKeys = 9,
+    Abort = 10,
+    Rekey = 11,
+}
+
+/// One log entry as laid out in the dump buffer.
+///
+/// `seq` starts at 1 and is never zero for a valid record; a zeroed record
+/// terminates the dump.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+#[repr(C)]
+pub struct LogRecord {
+    pub seq: u32,
+    pub context: RaContext,
+    pub event: u16,
+    pub reserved: u16,
+    pub status: u32,
+}
+
+impl LogRecord {
+    const EMPTY: LogRecord = LogRecord {
+        seq: 0,
+        context: 0,
+        event: 0,
+        reserved: 0,
+        status: 0,
+    };
+
+    #[inline]
+    fn as_bytes(&self) -> &[u8] {
+        unsafe { slice::from_raw_parts(self as *const _ as *const u8, mem::size_of::<Self>()) }
+    }
+}
+
+pub struct LogRing<const N: usize> {
+    records: [LogRecord; N],
+    head: usize,
+    len: usize,
+    seq: u32,
+}
+
+impl<const N: usize> Default for LogRing<N> {
+    fn default() -> LogRing<N> {
+        LogRing::new()
+    }
+}
+
+impl<const N: usize> LogRing<N> {
+    pub const fn new() -> LogRing<N> {
+        LogRing {
+            records: [LogRecord::EMPTY; N],
+            head: 0,
+            len: 0,
+            seq: 0,
+        }
+    }
+
+    pub fn push(&mut self, context: RaContext, event: LogEvent, status: SgxStatus) {
+        if N == 0 {
+            return;
+        }
+
+        self.seq = self.seq.wrapping_add(1);
+        if self.seq == 0 {
+            self.seq = 1;
+        }
+
+        let index = (self.head + self.len) % N;
+        self.records[index] = LogRecord {
+            seq: self.seq,
+            context,
+            event: event as u16,
+            reserved: 0,
+            status: status as u32,
+        };
+
+        if self.len < N {
+            self.len += 1;
+        } else {
+            self.head = (self.head + 1) % N;
+        }
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Iterates the records from the oldest to the newest.
+    pub fn iter(&self) -> impl Iterator<Item = &LogRecord> {
+        (0..self.len).map(move |i| &self.records[(self.head + i) % N])
+    }
+
+    /// Copies the newest records that fit into `buf`, oldest first, and
+    /// zero-fills the remainder. Returns the number of records written.
+    pub fn copy_to_slice(&self, buf: &mut [u8]) -> usize {
+        let record_size = mem::size_of::<LogRecord>();
+        let count = cmp::min(buf.len() / record_size, self.len);
+
+        let mut offset = 0;
+        for record in self.iter().skip(self.len - count) {
+            buf[offset..offset + record_size].copy_from_slice(record.as_bytes());
+            offset += record_size;
+        }
+        buf[offset..].fill(0);
+
+        count
+    }
+
+    pub fn clear(&mut self) {
+        self.records.fill(LogRecord::EMPTY);
+        self.head = 0;
+        self.len = 0;
+    }
+}
+
+static LOG_RING: SpinMutex<LogRing<LOG_RING_SIZE>> = SpinMutex::new(LogRing::new());
+
+pub(crate) fn record<T>(context: RaContext, event: LogEvent, result: &SgxResult<T>) {
+    let status = match result {
+        Ok(_) => SgxStatus::Success,
+        Err(e) => *e,
+    };
+    LOG_RING.lock().push(context, event, status);
+}
+
+/// Dumps the enclave handshake log into `buf`, see [`LogRing::copy_to_slice`].
+pub fn dump_log(buf: &mut [u8]) -> usize {
+    LOG_RING.lock().copy_to_slice(buf)
+}
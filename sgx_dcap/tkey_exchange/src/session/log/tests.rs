@@ -0,0 +1,71 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License..
+
+use super::{LogEvent, LogRecord, LogRing};
+use alloc::vec::Vec;
+use core::mem;
+use core::ptr;
+use sgx_test_utils::test_case;
+use sgx_types::error::SgxStatus;
+
+#[test_case]
+fn ring_keeps_records_in_order() {
+    let mut ring = LogRing::<4>::new();
+    assert!(ring.is_empty());
+
+    ring.push(1, LogEvent::Init, SgxStatus::Success);
+    ring.push(1, LogEvent::ProcMsg1, SgxStatus::InvalidState);
+
+    let records: Vec<_> = ring.iter().collect();
+    assert_eq!(records.len(), 2);
+    assert_eq!(records[0].seq, 1);
+    assert_eq!(records[0].event, LogEvent::Init as u16);
+    assert_eq!(records[1].seq, 2);
+    assert_eq!(records[1].status, SgxStatus::InvalidState as u32);
+}
+
+#[test_case]
+fn ring_wraps_around() {
+    let mut ring = LogRing::<4>::new();
+    for context in 1..=6 {
+        ring.push(context, LogEvent::ProcMsg3, SgxStatus::MacMismatch);
+    }
+
+    assert_eq!(ring.len(), 4);
+    let contexts: Vec<_> = ring.iter().map(|r| r.context).collect();
+    assert_eq!(contexts, [3, 4, 5, 6]);
+    let seqs: Vec<_> = ring.iter().map(|r| r.seq).collect();
+    assert_eq!(seqs, [3, 4, 5, 6]);
+}
+
+#[test_case]
+fn dump_keeps_newest_records() {
+    let mut ring = LogRing::<4>::new();
+    for context in 1..=5 {
+        ring.push(context, LogEvent::GenMsg2, SgxStatus::Success);
+    }
+
+    let record_size = mem::size_of::<LogRecord>();
+    let mut buf = [0xFF_u8; 3 * mem::size_of::<LogRecord>() - 1];
+    assert_eq!(ring.copy_to_slice(&mut buf), 2);
+
+    let first = unsafe { ptr::read_unaligned(buf.as_ptr() as *const LogRecord) };
+    let second = unsafe { ptr::read_unaligned(buf[record_size..].as_ptr() as *const LogRecord) };
+    assert_eq!(first.context, 4);
+    assert_eq!(second.context, 5);
+    assert!(buf[2 * record_size..].iter().all(|&b| b == 0));
+}
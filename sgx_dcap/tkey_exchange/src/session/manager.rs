@@ -15,15 +15,29 @@
 // specific language governing permissions and limitations
 // under the License..
 
+use super::{RateLimiter, VerificationCache};
+use alloc::alloc::{Allocator, Global};
+use alloc::boxed::Box;
 use alloc::collections::LinkedList;
 use alloc::sync::Arc;
-use core::mem;
+use alloc::vec::Vec;
 use core::ops::Deref;
-use core::ptr;
+#[cfg(feature = "unit_test")]
+use core::sync::atomic::AtomicUsize;
 use core::sync::atomic::{AtomicU32, Ordering};
+use core::time::Duration;
 use sgx_crypto::ecc::{EcPrivateKey, EcPublicKey, EcShareKey};
+use sgx_dcap_tvl::PlatformFlagsPolicy;
 use sgx_sync::{LazyLock, SpinMutex, SpinRwLock};
-use sgx_types::types::{AlignKey128bit, EnclaveIdentity, QlQvResult, QuoteNonce, TargetInfo};
+use sgx_trts::memory::volatile_zeroize_val;
+use sgx_trts::rand::Rng;
+use sgx_types::error::{SgxResult, SgxStatus};
+use sgx_types::types::{
+    AlignKey128bit, EnclaveIdentity, QlQvResult, QuoteNonce, ReportBody, Sha256Hash, TargetInfo,
+};
+
+#[cfg(feature = "unit_test")]
+mod tests;
 
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub enum Role {
@@ -52,6 +66,7 @@ pub enum ResponderState {
     Msg1Proced,
     Msg2Gened,
     Established,
+    Aborted,
 }
 
 impl Default for ResponderState {
@@ -101,6 +116,10 @@ impl From<ResponderState> for State {
     }
 }
 
+/// A time source the enclave trusts, returning time since an arbitrary
+/// fixed origin. The SDK provides none, so the application supplies it.
+pub type TrustedClock = fn() -> SgxResult<Duration>;
+
 pub struct Context {
     pub role: Role,
     pub state: State,
@@ -115,7 +134,29 @@ pub struct Context {
     pub quote_nonce: QuoteNonce,
     pub qe_target: TargetInfo,
     pub qv_result: Option<QlQvResult>,
+    pub peer_report: Option<ReportBody>,
     pub enclave_identity: Option<EnclaveIdentity>,
+    pub min_tcb_eval_data_number: Option<u32>,
+    pub host_buffers: bool,
+    pub peer_pubkey_hash: Option<Sha256Hash>,
+    pub confirm_required: bool,
+    pub confirmed: bool,
+    pub keys_released: bool,
+    pub platform_flags_policy: Option<PlatformFlagsPolicy>,
+    pub own_quote: Option<Box<[u8]>>,
+    pub peer_quote: Option<Box<[u8]>>,
+    pub evidence_collateral: Option<Box<[u8]>>,
+    pub min_protocol_version: u8,
+    pub report_max_age: Option<Duration>,
+    pub clock: Option<TrustedClock>,
+    pub msg1_time: Option<Duration>,
+    pub rate_limiter: Option<(&'static dyn RateLimiter, u64)>,
+    pub trusted_roots: Option<Vec<Box<[u8]>>>,
+    pub msg3_error: Option<SgxStatus>,
+    pub verification_cache: Option<&'static VerificationCache>,
+    pub accepted_qv_results: Option<u16>,
+    pub collateral_status: Option<(i64, u32)>,
+    pub rekey_count: u32,
 }
 
 impl Context {
@@ -134,13 +175,54 @@ impl Context {
             quote_nonce: QuoteNonce::default(),
             qe_target: TargetInfo::default(),
             qv_result: None,
+            peer_report: None,
             enclave_identity: None,
+            min_tcb_eval_data_number: None,
+            host_buffers: false,
+            peer_pubkey_hash: None,
+            confirm_required: false,
+            confirmed: false,
+            keys_released: false,
+            platform_flags_policy: None,
+            own_quote: None,
+            peer_quote: None,
+            evidence_collateral: None,
+            min_protocol_version: 0,
+            report_max_age: None,
+            clock: None,
+            msg1_time: None,
+            rate_limiter: None,
+            trusted_roots: None,
+            msg3_error: None,
+            verification_cache: None,
+            accepted_qv_results: None,
+            collateral_status: None,
+            rekey_count: 0,
         }
     }
 
-    #[inline]
+    // The peer identity is parsed from the stored report body on first use
+    // and cached until it is forgotten.
+    pub fn peer_identity(&mut self) -> SgxResult<EnclaveIdentity> {
+        if let Some(identity) = self.enclave_identity {
+            return Ok(identity);
+        }
+
+        let report = self.peer_report.as_ref().ok_or(SgxStatus::Unexpected)?;
+        let identity = parse_identity(report);
+        self.enclave_identity = Some(identity);
+        Ok(identity)
+    }
+
+    // Zeroes the secret fields with volatile stores, so that zeroing right
+    // before the free is not optimized away as dead.
     fn clear(&mut self) {
-        unsafe { ptr::write_bytes(self as *mut _ as *mut u8, 0, mem::size_of::<Context>()) }
+        volatile_zeroize_val(&mut self.priv_key);
+        volatile_zeroize_val(&mut self.smk_key);
+        volatile_zeroize_val(&mut self.sk_key);
+        volatile_zeroize_val(&mut self.mk_key);
+        volatile_zeroize_val(&mut self.vk_key);
+        volatile_zeroize_val(&mut self.quote_nonce);
     }
 }
 
@@ -174,12 +256,23 @@ pub static INITIATOR_SESSION_MAGAGER: LazyLock<SpinRwLock<SessionManager>> =
 pub static RESPONDER_SESSION_MAGAGER: LazyLock<SpinRwLock<SessionManager>> =
     LazyLock::new(|| SpinRwLock::new(SessionManager::new()));
 
+/// Allocator for a session and the key material in its context.
+pub type SessionAlloc = &'static (dyn Allocator + Sync);
+
+pub type SessionRef = Arc<Session, SessionAlloc>;
+
 struct Node {
     sid: u32,
-    session: Arc<Session>,
+    session: SessionRef,
 }
 
+// Session handles carry the enclave instance epoch in their top bits and a
+// sequence number in the rest.
+const SID_SEQ_BITS: u32 = 24;
+const SID_SEQ_MASK: u32 = (1 << SID_SEQ_BITS) - 1;
+
 pub struct SessionManager {
+    epoch: u32,
     seed: AtomicU32,
     list: LinkedList<Node>,
 }
@@ -191,28 +284,65 @@ impl Default for SessionManager {
 }
 
 impl SessionManager {
-    pub const fn new() -> SessionManager {
+    pub fn new() -> SessionManager {
+        // Random per enclave instance and never zero, so a handle kept by the
+        // host across an enclave reload does not alias a live session.
+        let mut rng = Rng::new();
+        let epoch = loop {
+            let epoch = rng.next_u32() >> SID_SEQ_BITS;
+            if epoch != 0 {
+                break epoch;
+            }
+        };
+        SessionManager::with_epoch(epoch)
+    }
+
+    pub(crate) const fn with_epoch(epoch: u32) -> SessionManager {
         SessionManager {
+            epoch,
             seed: AtomicU32::new(1),
             list: LinkedList::new(),
         }
     }
 
-    pub fn find(&self, sid: u32) -> Option<Arc<Session>> {
+    #[inline]
+    pub fn is_current(&self, sid: u32) -> bool {
+        sid >> SID_SEQ_BITS == self.epoch
+    }
+
+    #[inline]
+    pub fn contains(&self, sid: u32) -> bool {
+        self.list.iter().any(|node| node.sid == sid)
+    }
+
+    pub fn find(&self, sid: u32) -> Option<SessionRef> {
         self.list
             .iter()
             .find(|&node| node.sid == sid)
             .map(|node| node.session.clone())
     }
 
+    #[inline]
     pub fn push(&mut self, session: Session) -> u32 {
-        let sid = self.seed.fetch_add(1, Ordering::SeqCst);
-        let session = Arc::new(session);
+        self.push_in(session, &Global)
+    }
+
+    pub fn push_in(&mut self, session: Session, alloc: SessionAlloc) -> u32 {
+        // The sequence wraps after 2^24 sessions; skip handles still in use
+        // by long-lived sessions from the previous round.
+        let sid = loop {
+            let seq = self.seed.fetch_add(1, Ordering::SeqCst) & SID_SEQ_MASK;
+            let sid = (self.epoch << SID_SEQ_BITS) | seq;
+            if !self.contains(sid) {
+                break sid;
+            }
+        };
+        let session = Arc::new_in(session, alloc);
         self.list.push_back(Node { sid, session });
         sid
     }
 
-    pub fn remove(&mut self, sid: u32) -> Option<Arc<Session>> {
+    pub fn remove(&mut self, sid: u32) -> Option<SessionRef> {
         self.list
             .extract_if(|node| node.sid == sid)
             .next()
@@ -220,6 +350,16 @@ impl SessionManager {
     }
 }
 
+#[cfg(feature = "unit_test")]
+pub(crate) static IDENTITY_PARSES: AtomicUsize = AtomicUsize::new(0);
+
+#[inline]
+pub(crate) fn parse_identity(report: &ReportBody) -> EnclaveIdentity {
+    #[cfg(feature = "unit_test")]
+    IDENTITY_PARSES.fetch_add(1, Ordering::Relaxed);
+    report.into()
+}
+
 pub(crate) struct DropKey<'a> {
     key: &'a mut AlignKey128bit,
 }
@@ -0,0 +1,89 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License..
+
+use super::{Role, Session, SessionManager, RESPONDER_SESSION_MAGAGER, SID_SEQ_MASK};
+use crate::session::Responder;
+use core::sync::atomic::Ordering;
+use sgx_test_utils::test_case;
+use sgx_types::error::SgxStatus;
+
+#[test_case]
+fn epoch_bump_invalidates_handles() {
+    let mut old = SessionManager::with_epoch(1);
+    let sid = old.push(Session::new(Role::Responder));
+    assert!(old.is_current(sid));
+    assert!(old.find(sid).is_some());
+    assert!(old.contains(sid));
+
+    // A reloaded enclave starts numbering from scratch under a new epoch.
+    let mut new = SessionManager::with_epoch(2);
+    let new_sid = new.push(Session::new(Role::Responder));
+    assert_ne!(sid, new_sid);
+    assert!(!new.is_current(sid));
+    assert!(new.find(sid).is_none());
+}
+
+#[test_case]
+fn from_raw_rejects_stale_epoch() {
+    let rctx = Responder::new().unwrap().into_raw();
+    assert!(RESPONDER_SESSION_MAGAGER.read().is_current(rctx));
+
+    let stale = rctx ^ 0x8000_0000;
+    assert_eq!(
+        unsafe { Responder::from_raw(stale) }.unwrap_err(),
+        SgxStatus::InvalidParameter
+    );
+
+    drop(unsafe { Responder::from_raw(rctx) }.unwrap());
+}
+
+#[test_case]
+fn from_raw_rejects_closed_session() {
+    let rctx = Responder::new().unwrap().into_raw();
+    drop(unsafe { Responder::from_raw(rctx) }.unwrap());
+
+    assert!(!RESPONDER_SESSION_MAGAGER.read().contains(rctx));
+    assert_eq!(
+        unsafe { Responder::from_raw(rctx) }.unwrap_err(),
+        SgxStatus::InvalidParameter
+    );
+}
+
+#[test_case]
+fn wrapped_sequence_skips_live_sids() {
+    let mut manager = SessionManager::with_epoch(1);
+    let first = manager.push(Session::new(Role::Responder));
+    let second = manager.push(Session::new(Role::Responder));
+    manager.remove(second);
+
+    // Jump to the end of the sequence space so the next pushes wrap.
+    manager.seed.store(SID_SEQ_MASK, Ordering::SeqCst);
+    let last = manager.push(Session::new(Role::Responder));
+    assert_eq!(last & SID_SEQ_MASK, SID_SEQ_MASK);
+    let zero = manager.push(Session::new(Role::Responder));
+    assert_eq!(zero & SID_SEQ_MASK, 0);
+
+    // `first` is still live, so its seq is skipped, while the closed
+    // `second` is handed out again.
+    let reused = manager.push(Session::new(Role::Responder));
+    assert_eq!(reused, second);
+    assert_ne!(reused, first);
+    assert_eq!(
+        manager.list.iter().filter(|node| node.sid == first).count(),
+        1
+    );
+}
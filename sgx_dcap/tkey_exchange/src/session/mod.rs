@@ -15,11 +15,113 @@
 // specific language governing permissions and limitations
 // under the License..
 
+use sgx_crypto::ecc::EcPublicKey;
+use sgx_crypto::mac::{hkdf_sha256_expand, hkdf_sha256_extract, AesCMac};
+use sgx_types::error::SgxResult;
+use sgx_types::types::{AlignKey128bit, Mac, Mac256bit, KEY_128BIT_SIZE};
+
+mod dry_run;
+mod evidence;
+mod export;
 mod initiator;
+mod log;
 mod manager;
+mod quote;
+mod rate_limit;
+mod receipt;
 mod responder;
+mod secret;
+mod typed;
+mod verify_cache;
 
+pub use dry_run::{dry_run_handshake, HandshakeReport, Msg2Inputs};
+pub use evidence::*;
+pub use export::{SESSION_EXPORT_MAGIC, SESSION_EXPORT_VERSION};
 pub use initiator::*;
+pub use log::{dump_log, LogEvent, LogRecord, LogRing, LOG_RING_SIZE};
+pub use manager::{SessionAlloc, TrustedClock};
+pub use quote::{tee_type, QuoteParseError, QuoteVerifyError, TeeType};
+pub use rate_limit::{RateLimiter, TokenBucket, TOKEN_BUCKET_MAX_SOURCES};
+pub use receipt::{HandshakeReceipt, RECEIPT_MAGIC, RECEIPT_VERSION};
 pub use responder::*;
+pub use secret::{Secret, SECRET_OVERHEAD};
+pub use typed::{stage, TypedResponder};
+pub use verify_cache::{CachedVerdict, VerificationCache, VERIFICATION_CACHE_MAX_ENTRIES};
+
+#[cfg(feature = "unit_test")]
+mod tests;
 
 const QVE_ISVSVN_THRESHOLD: u16 = 3;
+
+const CONFIRM_LABEL: &[u8] = b"SGX DCAP RA CONFIRM";
+
+const REKEY_LABEL: &[u8] = b"SGX DCAP RA REKEY";
+
+const C2S_LABEL: &[u8] = b"SGX DCAP RA C2S";
+const S2C_LABEL: &[u8] = b"SGX DCAP RA S2C";
+
+/// Independent keys for each direction of the channel, derived from SK.
+/// `c2s` protects initiator-to-responder traffic and `s2c` the reverse.
+#[derive(Clone, Copy, Default)]
+pub struct DirectionalKeys {
+    pub c2s: AlignKey128bit,
+    pub s2c: AlignKey128bit,
+}
+
+// Key confirmation MAC exchanged after msg3, keyed with VK so that it does
+// not reuse either exported key.
+fn confirm_mac(
+    vk_key: &AlignKey128bit,
+    pub_key_a: &EcPublicKey,
+    pub_key_b: &EcPublicKey,
+) -> SgxResult<Mac> {
+    let mut cmac = AesCMac::new(&vk_key.key)?;
+    cmac.update(CONFIRM_LABEL)?;
+    cmac.update(pub_key_a)?;
+    cmac.update(pub_key_b)?;
+    cmac.finalize()
+}
+
+// HKDF-SHA256 (RFC 5869) over SK with an empty salt, one expand per
+// direction label, 128 bits each.
+fn derive_directional_keys(sk_key: &AlignKey128bit) -> SgxResult<DirectionalKeys> {
+    let mut prk = hkdf_sha256_extract(&[], &sk_key.key)?;
+    let c2s = hkdf_expand(&prk, C2S_LABEL);
+    let s2c = hkdf_expand(&prk, S2C_LABEL);
+    prk.fill(0);
+
+    Ok(DirectionalKeys {
+        c2s: c2s?,
+        s2c: s2c?,
+    })
+}
+
+fn hkdf_expand(prk: &Mac256bit, info: &[u8]) -> SgxResult<AlignKey128bit> {
+    let mut okm = hkdf_sha256_expand(prk, info, KEY_128BIT_SIZE)?;
+
+    let mut key = AlignKey128bit::default();
+    key.key.copy_from_slice(&okm);
+    okm.fill(0);
+    Ok(key)
+}
+
+// Ratchets SK and MK one step forward. Each new key is an AES-CMAC under the
+// current one over the label, the key name and `counter`, so earlier keys do
+// not follow from later ones.
+fn rekey(
+    sk_key: &AlignKey128bit,
+    mk_key: &AlignKey128bit,
+    counter: u32,
+) -> SgxResult<(AlignKey128bit, AlignKey128bit)> {
+    let next = |key: &AlignKey128bit, name: &[u8]| -> SgxResult<AlignKey128bit> {
+        let mut cmac = AesCMac::new(&key.key)?;
+        cmac.update(REKEY_LABEL)?;
+        cmac.update(name)?;
+        cmac.update(&counter.to_le_bytes())?;
+        let mut next = AlignKey128bit::default();
+        next.key = cmac.finalize()?;
+        Ok(next)
+    };
+
+    Ok((next(sk_key, b"SK")?, next(mk_key, b"MK")?))
+}
@@ -0,0 +1,218 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License..
+
+use crate::QveReportInfo;
+use core::mem;
+use core::ptr;
+use sgx_crypto::ecc::{EcPublicKey, EcSignature};
+use sgx_types::error::{Quote3Error, SgxResult, SgxStatus};
+use sgx_types::types::{
+    QlEcdsaSigData, QlQvResult, Quote3, Quote4Header, QuoteHeader, ReportBody, ECP256_KEY_SIZE,
+};
+
+#[cfg(feature = "unit_test")]
+mod tests;
+
+const QUOTE_VERSION: u16 = 3;
+
+const TEE_TYPE_SGX: u32 = 0x0000_0000;
+const TEE_TYPE_TDX: u32 = 0x0000_0081;
+
+/// The TEE a quote was produced for, from the `tee_type` field of the quote
+/// header. Version 3 quotes keep zero in that position and read as SGX.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum TeeType {
+    Sgx,
+    Tdx,
+}
+
+/// The quote bytes (or the QvE report info accompanying them) are malformed.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum QuoteParseError {
+    Truncated,
+    UnsupportedVersion,
+    SignatureLength,
+    InvalidReportInfo,
+    UnsupportedTeeType,
+}
+
+/// The quote is well formed but did not pass verification.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum QuoteVerifyError {
+    QveReport,
+    QveIdentity,
+    QveOutOfDate,
+    TcbOutOfDate,
+    ReportDataMismatch,
+    /// The ISV enclave report signature does not verify under the
+    /// attestation key in the quote.
+    QuoteSignature,
+    QveNotRun,
+    /// The verdict is outside the set given to `set_accepted_qv_results`.
+    QvResultRejected,
+    Unexpected,
+}
+
+impl From<QuoteParseError> for SgxStatus {
+    fn from(_: QuoteParseError) -> SgxStatus {
+        SgxStatus::InvalidParameter
+    }
+}
+
+impl From<QuoteVerifyError> for SgxStatus {
+    fn from(e: QuoteVerifyError) -> SgxStatus {
+        match e {
+            QuoteVerifyError::QveReport
+            | QuoteVerifyError::ReportDataMismatch
+            | QuoteVerifyError::QuoteSignature => SgxStatus::InvalidSignature,
+            QuoteVerifyError::QveIdentity
+            | QuoteVerifyError::QveOutOfDate
+            | QuoteVerifyError::TcbOutOfDate => SgxStatus::UpdateNeeded,
+            QuoteVerifyError::QveNotRun => SgxStatus::ServiceUnavailable,
+            QuoteVerifyError::QvResultRejected => SgxStatus::NoPrivilege,
+            QuoteVerifyError::Unexpected => SgxStatus::Unexpected,
+        }
+    }
+}
+
+// Splits a QvE-side failure into malformed input and failed checks.
+pub(crate) fn classify_quote3_error(e: Quote3Error) -> SgxStatus {
+    match e {
+        Quote3Error::InvalidParameter => QuoteParseError::InvalidReportInfo.into(),
+        Quote3Error::ErrorReport => QuoteVerifyError::QveReport.into(),
+        Quote3Error::QveIdentityMismatch => QuoteVerifyError::QveIdentity.into(),
+        Quote3Error::QveOutOfDate => QuoteVerifyError::QveOutOfDate.into(),
+        Quote3Error::TcbOutOfDate => QuoteVerifyError::TcbOutOfDate.into(),
+        _ => QuoteVerifyError::Unexpected.into(),
+    }
+}
+
+/// Reads the TEE type from the quote header without committing to a
+/// `Quote3` or `Quote4` layout.
+pub fn tee_type(quote: &[u8]) -> Result<TeeType, SgxStatus> {
+    ensure!(
+        quote.len() >= mem::size_of::<Quote4Header>(),
+        SgxStatus::from(QuoteParseError::Truncated)
+    );
+
+    let header = unsafe { ptr::read_unaligned(quote.as_ptr() as *const Quote4Header) };
+    match header.tee_type {
+        TEE_TYPE_SGX => Ok(TeeType::Sgx),
+        TEE_TYPE_TDX => Ok(TeeType::Tdx),
+        _ => Err(QuoteParseError::UnsupportedTeeType.into()),
+    }
+}
+
+// A QvE that failed to run leaves no verdict to check, which is a different
+// failure from a verdict the policy rejects.
+pub(crate) fn check_qve_ran(qve_report_info: &QveReportInfo) -> Result<(), QuoteVerifyError> {
+    ensure!(
+        qve_report_info.qve_status.is_success(),
+        QuoteVerifyError::QveNotRun
+    );
+    Ok(())
+}
+
+// One bit per verdict: bit 0 for `Ok` and bit n for 0xA000 + n.
+fn qv_result_bit(qv_result: QlQvResult) -> Option<u16> {
+    match qv_result as u32 {
+        0 => Some(1),
+        code @ 0xA001..=0xA008 => Some(1 << (code - 0xA000)),
+        _ => None,
+    }
+}
+
+pub(crate) fn qv_result_mask(accepted: &[QlQvResult]) -> Result<u16, SgxStatus> {
+    ensure!(!accepted.is_empty(), SgxStatus::InvalidParameter);
+    accepted.iter().try_fold(0, |mask, &qv_result| {
+        qv_result_bit(qv_result)
+            .map(|bit| mask | bit)
+            .ok_or(SgxStatus::InvalidParameter)
+    })
+}
+
+pub(crate) fn check_qv_result(mask: u16, qv_result: QlQvResult) -> Result<(), QuoteVerifyError> {
+    let bit = qv_result_bit(qv_result).unwrap_or(0);
+    ensure!(mask & bit != 0, QuoteVerifyError::QvResultRejected);
+    Ok(())
+}
+
+pub(crate) fn parse_quote3(quote: &[u8]) -> Result<&Quote3, QuoteParseError> {
+    ensure!(
+        quote.len() >= mem::size_of::<Quote3>(),
+        QuoteParseError::Truncated
+    );
+
+    let quote3 = unsafe { &*(quote.as_ptr() as *const Quote3) };
+    ensure!(
+        quote3.header.version == QUOTE_VERSION,
+        QuoteParseError::UnsupportedVersion
+    );
+    ensure!(
+        quote3.signature_len as usize == quote.len() - mem::size_of::<Quote3>(),
+        QuoteParseError::SignatureLength
+    );
+
+    Ok(quote3)
+}
+
+// The ECDSA signature data of a version 3 quote, split into the ISV enclave
+// signature and the rest: attestation key, QE report and its signature, QE
+// authentication data and PCK certification data.
+pub(crate) fn split_signature_data(quote: &[u8]) -> Result<(&[u8], &[u8]), QuoteParseError> {
+    parse_quote3(quote)?;
+
+    let signature = &quote[mem::size_of::<Quote3>()..];
+    ensure!(
+        signature.len() >= mem::size_of::<QlEcdsaSigData>(),
+        QuoteParseError::SignatureLength
+    );
+    Ok(signature.split_at(ECP256_KEY_SIZE * 2))
+}
+
+/// Checks the ISV enclave report signature over the quote header and report
+/// body under the attestation key the quote carries. This is the part of
+/// quote verification that differs between quotes from one platform.
+pub(crate) fn verify_isv_signature(quote: &[u8]) -> SgxResult {
+    split_signature_data(quote)?;
+
+    let sig_data = unsafe {
+        ptr::read_unaligned(quote[mem::size_of::<Quote3>()..].as_ptr() as *const QlEcdsaSigData)
+    };
+    let pub_key = EcPublicKey::from(swap_coordinates(&sig_data.attest_pub_key));
+    ensure!(
+        pub_key.check_point()?,
+        SgxStatus::from(QuoteVerifyError::QuoteSignature)
+    );
+
+    let signature = EcSignature::from(swap_coordinates(&sig_data.sig));
+    let signed = &quote[..mem::size_of::<QuoteHeader>() + mem::size_of::<ReportBody>()];
+    ensure!(
+        pub_key.verify(signed, &signature)?,
+        SgxStatus::from(QuoteVerifyError::QuoteSignature)
+    );
+    Ok(())
+}
+
+// Quotes store the coordinates of keys and signatures big-endian, the SDK
+// ECC types little-endian.
+pub(crate) fn swap_coordinates(bytes: &[u8; ECP256_KEY_SIZE * 2]) -> [u8; ECP256_KEY_SIZE * 2] {
+    let mut swapped = *bytes;
+    swapped[..ECP256_KEY_SIZE].reverse();
+    swapped[ECP256_KEY_SIZE..].reverse();
+    swapped
+}
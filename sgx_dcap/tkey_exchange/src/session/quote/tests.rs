@@ -0,0 +1,193 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License..
+
+use super::{
+    check_qv_result, classify_quote3_error, parse_quote3, qv_result_mask, swap_coordinates,
+    tee_type, verify_isv_signature, QuoteParseError, QuoteVerifyError, TeeType,
+};
+use alloc::vec::Vec;
+use core::mem;
+use core::ptr;
+use sgx_crypto::ecc::EcKeyPair;
+use sgx_test_utils::test_case;
+use sgx_types::error::{Quote3Error, SgxStatus};
+use sgx_types::types::{QlEcdsaSigData, QlQvResult, Quote3, Quote4Header, QuoteHeader, ReportBody};
+
+const SIGNATURE_LEN: usize = 64;
+
+fn quote_bytes(version: u16, signature_len: u32) -> Vec<u8> {
+    let mut quote = alloc::vec![0_u8; mem::size_of::<Quote3>() + SIGNATURE_LEN];
+    let mut quote3 = Quote3::default();
+    quote3.header.version = version;
+    quote3.signature_len = signature_len;
+    unsafe { ptr::write_unaligned(quote.as_mut_ptr() as *mut Quote3, quote3) };
+    quote
+}
+
+#[test_case]
+fn parse_quote3_accepts_well_formed() {
+    let quote = quote_bytes(3, SIGNATURE_LEN as u32);
+    assert!(parse_quote3(&quote).is_ok());
+}
+
+#[test_case]
+fn parse_quote3_rejects_malformed() {
+    let quote = quote_bytes(3, SIGNATURE_LEN as u32);
+    assert_eq!(
+        parse_quote3(&quote[..mem::size_of::<Quote3>() - 1]).unwrap_err(),
+        QuoteParseError::Truncated
+    );
+    assert_eq!(
+        parse_quote3(&quote_bytes(4, SIGNATURE_LEN as u32)).unwrap_err(),
+        QuoteParseError::UnsupportedVersion
+    );
+    assert_eq!(
+        parse_quote3(&quote_bytes(3, SIGNATURE_LEN as u32 + 1)).unwrap_err(),
+        QuoteParseError::SignatureLength
+    );
+}
+
+#[test_case]
+fn parse_and_verify_statuses_differ() {
+    let parse = SgxStatus::from(QuoteParseError::Truncated);
+    assert_eq!(parse, SgxStatus::InvalidParameter);
+    assert_eq!(classify_quote3_error(Quote3Error::InvalidParameter), parse);
+
+    for e in [
+        Quote3Error::ErrorReport,
+        Quote3Error::QveIdentityMismatch,
+        Quote3Error::QveOutOfDate,
+        Quote3Error::TcbOutOfDate,
+    ] {
+        assert_ne!(classify_quote3_error(e), parse);
+    }
+    assert_eq!(
+        SgxStatus::from(QuoteVerifyError::TcbOutOfDate),
+        SgxStatus::UpdateNeeded
+    );
+}
+
+#[test_case]
+fn tee_type_from_header() {
+    let quote = quote_bytes(3, SIGNATURE_LEN as u32);
+    assert_eq!(tee_type(&quote), Ok(TeeType::Sgx));
+
+    let mut quote = alloc::vec![0_u8; mem::size_of::<Quote4Header>()];
+    let header = Quote4Header {
+        version: 4,
+        tee_type: 0x81,
+        ..Default::default()
+    };
+    unsafe { ptr::write_unaligned(quote.as_mut_ptr() as *mut Quote4Header, header) };
+    assert_eq!(tee_type(&quote), Ok(TeeType::Tdx));
+
+    quote[4] = 0x01;
+    assert_eq!(tee_type(&quote), Err(SgxStatus::InvalidParameter));
+    assert_eq!(
+        tee_type(&quote[..mem::size_of::<Quote4Header>() - 1]),
+        Err(SgxStatus::InvalidParameter)
+    );
+}
+
+#[test_case]
+fn accepted_qv_results() {
+    let mask = qv_result_mask(&[QlQvResult::Ok, QlQvResult::SWHardeningNeeded]).unwrap();
+    assert!(check_qv_result(mask, QlQvResult::Ok).is_ok());
+    assert!(check_qv_result(mask, QlQvResult::SWHardeningNeeded).is_ok());
+    assert_eq!(
+        check_qv_result(mask, QlQvResult::OutOfDate),
+        Err(QuoteVerifyError::QvResultRejected)
+    );
+    assert_eq!(
+        check_qv_result(mask, QlQvResult::Max),
+        Err(QuoteVerifyError::QvResultRejected)
+    );
+
+    assert_eq!(qv_result_mask(&[]), Err(SgxStatus::InvalidParameter));
+    assert_eq!(
+        qv_result_mask(&[QlQvResult::Max]),
+        Err(SgxStatus::InvalidParameter)
+    );
+}
+
+#[test_case]
+fn qv_result_status_round_trip() {
+    for qv_result in [
+        QlQvResult::Ok,
+        QlQvResult::ConfigNeeded,
+        QlQvResult::OutOfDate,
+        QlQvResult::InvalidSignature,
+        QlQvResult::SWHardeningNeeded,
+    ] {
+        assert_eq!(
+            QlQvResult::try_from(SgxStatus::from(qv_result)),
+            Ok(qv_result)
+        );
+    }
+
+    assert_eq!(
+        SgxStatus::from(QlQvResult::OutOfDateConfigNeeded),
+        SgxStatus::TcbOutOfDate
+    );
+    assert_eq!(
+        SgxStatus::from(QlQvResult::Revoked),
+        SgxStatus::InvalidSignature
+    );
+    assert_eq!(
+        SgxStatus::from(QlQvResult::Unspecified),
+        SgxStatus::Unexpected
+    );
+    assert_eq!(
+        QlQvResult::try_from(SgxStatus::OutOfMemory),
+        Err(SgxStatus::OutOfMemory)
+    );
+}
+
+#[test_case]
+fn isv_signature_checked_under_attest_key() {
+    let sig_len = mem::size_of::<QlEcdsaSigData>();
+    let mut quote = alloc::vec![0_u8; mem::size_of::<Quote3>() + sig_len];
+    let mut quote3 = Quote3::default();
+    quote3.header.version = 3;
+    quote3.report_body.report_data.d = [0x5a; 64];
+    quote3.signature_len = sig_len as u32;
+    unsafe { ptr::write_unaligned(quote.as_mut_ptr() as *mut Quote3, quote3) };
+    assert_eq!(
+        verify_isv_signature(&quote[..mem::size_of::<Quote3>()]),
+        Err(SgxStatus::InvalidParameter)
+    );
+
+    let attest_key = EcKeyPair::create().unwrap();
+    let signed_len = mem::size_of::<QuoteHeader>() + mem::size_of::<ReportBody>();
+    let signature: [u8; 64] = attest_key
+        .private_key()
+        .sign(&quote[..signed_len])
+        .unwrap()
+        .into();
+    let attest_pub_key: [u8; 64] = attest_key.public_key().into();
+    let sig_data = mem::size_of::<Quote3>();
+    quote[sig_data..sig_data + 64].copy_from_slice(&swap_coordinates(&signature));
+    quote[sig_data + 64..sig_data + 128].copy_from_slice(&swap_coordinates(&attest_pub_key));
+    assert_eq!(verify_isv_signature(&quote), Ok(()));
+
+    // The signature covers the report body.
+    quote[mem::size_of::<QuoteHeader>()] ^= 1;
+    assert_eq!(
+        verify_isv_signature(&quote),
+        Err(SgxStatus::from(QuoteVerifyError::QuoteSignature))
+    );
+}
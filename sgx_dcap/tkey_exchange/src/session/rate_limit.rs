@@ -0,0 +1,104 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License..
+use super::TrustedClock;
+use alloc::collections::BTreeMap;
+use core::time::Duration;
+use sgx_sync::SpinMutex;
+use sgx_types::error::{SgxResult, SgxStatus};
+
+#[cfg(feature = "unit_test")]
+mod tests;
+
+/// Decides whether a handshake from `source` may proceed. `process_msg1`
+/// consults the limiter set with `Responder::set_rate_limiter` and fails
+/// with whatever `admit` returns, normally `ServiceBusy`.
+pub trait RateLimiter: Sync {
+    fn admit(&self, source: u64) -> SgxResult;
+}
+
+/// Sources tracked at once by a `TokenBucket`. Beyond this, buckets that
+/// have refilled are forgotten and new sources are refused until one has.
+pub const TOKEN_BUCKET_MAX_SOURCES: usize = 1024;
+
+#[derive(Clone, Copy, Debug)]
+struct Bucket {
+    tokens: u32,
+    refilled_at: Duration,
+}
+
+/// Token bucket per source: up to `capacity` handshakes in a burst, with
+/// one token returned every `refill`.
+pub struct TokenBucket {
+    capacity: u32,
+    refill: Duration,
+    clock: TrustedClock,
+    buckets: SpinMutex<BTreeMap<u64, Bucket>>,
+}
+
+impl TokenBucket {
+    pub const fn new(capacity: u32, refill: Duration, clock: TrustedClock) -> TokenBucket {
+        TokenBucket {
+            capacity,
+            refill,
+            clock,
+            buckets: SpinMutex::new(BTreeMap::new()),
+        }
+    }
+
+    fn refill(&self, bucket: &mut Bucket, now: Duration) {
+        let elapsed = now.saturating_sub(bucket.refilled_at);
+        let tokens = elapsed.as_nanos() / self.refill.as_nanos().max(1);
+        if tokens == 0 {
+            return;
+        }
+
+        let tokens = u32::try_from(tokens).unwrap_or(u32::MAX);
+        bucket.tokens = bucket.tokens.saturating_add(tokens).min(self.capacity);
+        bucket.refilled_at = if bucket.tokens == self.capacity {
+            now
+        } else {
+            bucket.refilled_at + self.refill * tokens
+        };
+    }
+}
+
+impl RateLimiter for TokenBucket {
+    fn admit(&self, source: u64) -> SgxResult {
+        let now = (self.clock)()?;
+        let mut buckets = self.buckets.lock();
+
+        if !buckets.contains_key(&source) && buckets.len() >= TOKEN_BUCKET_MAX_SOURCES {
+            buckets.retain(|_, bucket| {
+                self.refill(bucket, now);
+                bucket.tokens < self.capacity
+            });
+            ensure!(
+                buckets.len() < TOKEN_BUCKET_MAX_SOURCES,
+                SgxStatus::ServiceBusy
+            );
+        }
+
+        let bucket = buckets.entry(source).or_insert(Bucket {
+            tokens: self.capacity,
+            refilled_at: now,
+        });
+        self.refill(bucket, now);
+        ensure!(bucket.tokens > 0, SgxStatus::ServiceBusy);
+        bucket.tokens -= 1;
+        Ok(())
+    }
+}
@@ -0,0 +1,53 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License..
+use super::{RateLimiter, TokenBucket};
+use core::sync::atomic::{AtomicU64, Ordering};
+use core::time::Duration;
+use sgx_test_utils::test_case;
+use sgx_types::error::{SgxResult, SgxStatus};
+
+static FAKE_SECS: AtomicU64 = AtomicU64::new(0);
+
+fn fake_clock() -> SgxResult<Duration> {
+    Ok(Duration::from_secs(FAKE_SECS.load(Ordering::Relaxed)))
+}
+
+#[test_case]
+fn token_bucket_rejects_burst_and_recovers() {
+    FAKE_SECS.store(1000, Ordering::Relaxed);
+    let limiter = TokenBucket::new(3, Duration::from_secs(10), fake_clock);
+
+    for _ in 0..3 {
+        assert!(limiter.admit(1).is_ok());
+    }
+    assert_eq!(limiter.admit(1), Err(SgxStatus::ServiceBusy));
+    // Other sources have their own budget.
+    assert!(limiter.admit(2).is_ok());
+
+    FAKE_SECS.store(1009, Ordering::Relaxed);
+    assert_eq!(limiter.admit(1), Err(SgxStatus::ServiceBusy));
+
+    FAKE_SECS.store(1010, Ordering::Relaxed);
+    assert!(limiter.admit(1).is_ok());
+    assert_eq!(limiter.admit(1), Err(SgxStatus::ServiceBusy));
+
+    FAKE_SECS.store(2000, Ordering::Relaxed);
+    for _ in 0..3 {
+        assert!(limiter.admit(1).is_ok());
+    }
+    assert_eq!(limiter.admit(1), Err(SgxStatus::ServiceBusy));
+}
@@ -0,0 +1,174 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License..
+use alloc::vec::Vec;
+use sgx_crypto::ecc::{EcPrivateKey, EcPublicKey, EcSignature};
+use sgx_types::error::{SgxResult, SgxStatus};
+use sgx_types::types::{
+    AttributesFlags, EnclaveIdentity, MiscSelect, QlQvResult, CPUSVN_SIZE, ECP256_KEY_SIZE,
+    HASH_SIZE,
+};
+
+#[cfg(feature = "unit_test")]
+mod tests;
+
+pub const RECEIPT_MAGIC: [u8; 4] = *b"DCRC";
+pub const RECEIPT_VERSION: u16 = 1;
+
+// cpu_svn | flags: u64 | xfrm: u64 | mr_enclave | mr_signer
+// misc_select: u32 | isv_prod_id: u16 | isv_svn: u16
+const IDENTITY_SIZE: usize = CPUSVN_SIZE + 8 + 8 + HASH_SIZE * 2 + 4 + 2 + 2;
+const BODY_SIZE: usize = 4 + 2 + 2 + IDENTITY_SIZE * 2 + 4 + 8;
+const SIGNATURE_SIZE: usize = ECP256_KEY_SIZE * 2;
+
+/// Signed record that `enclave` attested `peer` and the QvE returned
+/// `qv_result`, at `time` seconds on the issuer's trusted clock. Laid out as
+///
+/// ```text
+/// magic "DCRC" | version: u16 | reserved: u16 | enclave | peer
+/// qv_result: u32 | time: u64 | signature: [u8; 64]
+/// ```
+///
+/// with little-endian integers, each identity written field by field, and
+/// an ECDSA P-256 signature over everything before it.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct HandshakeReceipt {
+    pub version: u16,
+    pub enclave: EnclaveIdentity,
+    pub peer: EnclaveIdentity,
+    pub qv_result: QlQvResult,
+    pub time: u64,
+    pub signature: EcSignature,
+}
+
+impl HandshakeReceipt {
+    pub fn new(
+        enclave: EnclaveIdentity,
+        peer: EnclaveIdentity,
+        qv_result: QlQvResult,
+        time: u64,
+    ) -> HandshakeReceipt {
+        HandshakeReceipt {
+            version: RECEIPT_VERSION,
+            enclave,
+            peer,
+            qv_result,
+            time,
+            signature: EcSignature::default(),
+        }
+    }
+
+    pub fn sign(&mut self, signing_key: &EcPrivateKey) -> SgxResult {
+        let body = self.body();
+        self.signature = signing_key.sign(body.as_slice())?;
+        Ok(())
+    }
+
+    pub fn verify(&self, pub_key: &EcPublicKey) -> SgxResult {
+        let body = self.body();
+        ensure!(
+            pub_key.verify(body.as_slice(), &self.signature)?,
+            SgxStatus::InvalidSignature
+        );
+        Ok(())
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = self.body();
+        let signature: [u8; SIGNATURE_SIZE] = self.signature.into();
+        bytes.extend_from_slice(&signature);
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> SgxResult<HandshakeReceipt> {
+        ensure!(
+            bytes.len() == BODY_SIZE + SIGNATURE_SIZE,
+            SgxStatus::InvalidParameter
+        );
+        ensure!(bytes[..4] == RECEIPT_MAGIC, SgxStatus::InvalidParameter);
+        let version = u16::from_le_bytes([bytes[4], bytes[5]]);
+        ensure!(version == RECEIPT_VERSION, SgxStatus::InvalidParameter);
+
+        let rest = &bytes[8..];
+        let (enclave, rest) = rest.split_at(IDENTITY_SIZE);
+        let (peer, rest) = rest.split_at(IDENTITY_SIZE);
+        let (qv_result, rest) = rest.split_at(4);
+        let (time, signature) = rest.split_at(8);
+
+        let qv_result = u32::from_le_bytes(qv_result.try_into().unwrap());
+        let qv_result = QlQvResult::try_from(qv_result).map_err(|_| SgxStatus::InvalidParameter)?;
+        let mut signature_bytes = [0_u8; SIGNATURE_SIZE];
+        signature_bytes.copy_from_slice(signature);
+
+        Ok(HandshakeReceipt {
+            version,
+            enclave: read_identity(enclave),
+            peer: read_identity(peer),
+            qv_result,
+            time: u64::from_le_bytes(time.try_into().unwrap()),
+            signature: signature_bytes.into(),
+        })
+    }
+
+    fn body(&self) -> Vec<u8> {
+        let mut body = Vec::with_capacity(BODY_SIZE + SIGNATURE_SIZE);
+        body.extend_from_slice(&RECEIPT_MAGIC);
+        body.extend_from_slice(&self.version.to_le_bytes());
+        body.extend_from_slice(&0_u16.to_le_bytes());
+        put_identity(&mut body, &self.enclave);
+        put_identity(&mut body, &self.peer);
+        body.extend_from_slice(&u32::from(self.qv_result).to_le_bytes());
+        body.extend_from_slice(&self.time.to_le_bytes());
+        body
+    }
+}
+
+fn put_identity(body: &mut Vec<u8>, identity: &EnclaveIdentity) {
+    body.extend_from_slice(&identity.cpu_svn.svn);
+    body.extend_from_slice(&identity.attributes.flags.bits().to_le_bytes());
+    body.extend_from_slice(&identity.attributes.xfrm.to_le_bytes());
+    body.extend_from_slice(&identity.mr_enclave.m);
+    body.extend_from_slice(&identity.mr_signer.m);
+    body.extend_from_slice(&identity.misc_select.bits().to_le_bytes());
+    body.extend_from_slice(&identity.isv_prod_id.to_le_bytes());
+    body.extend_from_slice(&identity.isv_svn.to_le_bytes());
+}
+
+fn read_identity(bytes: &[u8]) -> EnclaveIdentity {
+    let (cpu_svn, rest) = bytes.split_at(CPUSVN_SIZE);
+    let (flags, rest) = rest.split_at(8);
+    let (xfrm, rest) = rest.split_at(8);
+    let (mr_enclave, rest) = rest.split_at(HASH_SIZE);
+    let (mr_signer, rest) = rest.split_at(HASH_SIZE);
+    let (misc_select, rest) = rest.split_at(4);
+    let (isv_prod_id, isv_svn) = rest.split_at(2);
+
+    let mut identity = EnclaveIdentity::default();
+    identity.cpu_svn.svn.copy_from_slice(cpu_svn);
+    // The bits are covered by the signature, so keep them as written.
+    identity.attributes.flags = unsafe {
+        AttributesFlags::from_bits_unchecked(u64::from_le_bytes(flags.try_into().unwrap()))
+    };
+    identity.attributes.xfrm = u64::from_le_bytes(xfrm.try_into().unwrap());
+    identity.mr_enclave.m.copy_from_slice(mr_enclave);
+    identity.mr_signer.m.copy_from_slice(mr_signer);
+    identity.misc_select = unsafe {
+        MiscSelect::from_bits_unchecked(u32::from_le_bytes(misc_select.try_into().unwrap()))
+    };
+    identity.isv_prod_id = u16::from_le_bytes(isv_prod_id.try_into().unwrap());
+    identity.isv_svn = u16::from_le_bytes(isv_svn.try_into().unwrap());
+    identity
+}
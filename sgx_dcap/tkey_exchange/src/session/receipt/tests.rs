@@ -0,0 +1,71 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License..
+use super::HandshakeReceipt;
+use sgx_crypto::ecc::EcKeyPair;
+use sgx_test_utils::test_case;
+use sgx_types::error::SgxStatus;
+use sgx_types::types::{AttributesFlags, EnclaveIdentity, QlQvResult};
+
+fn identity(isv_svn: u16) -> EnclaveIdentity {
+    let mut identity = EnclaveIdentity::default();
+    identity.mr_enclave.m = [isv_svn as u8; 32];
+    identity.mr_signer.m = [0x5a; 32];
+    identity.attributes.flags = AttributesFlags::INITTED | AttributesFlags::MODE64BIT;
+    identity.attributes.xfrm = 0x3;
+    identity.isv_prod_id = 1;
+    identity.isv_svn = isv_svn;
+    identity
+}
+
+#[test_case]
+fn receipt_round_trip_verifies() {
+    let key_pair = EcKeyPair::create().unwrap();
+    let mut receipt = HandshakeReceipt::new(identity(1), identity(2), QlQvResult::OutOfDate, 1234);
+    receipt.sign(&key_pair.private_key()).unwrap();
+
+    let parsed = HandshakeReceipt::from_bytes(&receipt.to_bytes()).unwrap();
+    assert_eq!(parsed, receipt);
+    assert!(parsed.verify(&key_pair.public_key()).is_ok());
+
+    let other = EcKeyPair::create().unwrap();
+    assert_eq!(
+        parsed.verify(&other.public_key()),
+        Err(SgxStatus::InvalidSignature)
+    );
+}
+
+#[test_case]
+fn tampered_receipt_rejected() {
+    let key_pair = EcKeyPair::create().unwrap();
+    let mut receipt = HandshakeReceipt::new(identity(1), identity(2), QlQvResult::Ok, 1234);
+    receipt.sign(&key_pair.private_key()).unwrap();
+
+    let mut bytes = receipt.to_bytes();
+    // Flip a byte of the peer's MRENCLAVE.
+    bytes[8 + 104 + 32] ^= 1;
+    let tampered = HandshakeReceipt::from_bytes(&bytes).unwrap();
+    assert_eq!(
+        tampered.verify(&key_pair.public_key()),
+        Err(SgxStatus::InvalidSignature)
+    );
+
+    let bytes = receipt.to_bytes();
+    assert_eq!(
+        HandshakeReceipt::from_bytes(&bytes[..bytes.len() - 1]).unwrap_err(),
+        SgxStatus::InvalidParameter
+    );
+}
@@ -15,43 +15,665 @@
 // specific language governing permissions and limitations
 // under the License..
 
+use super::evidence::EvidenceBundle;
+use super::export::SessionExport;
+use super::log::{self, LogEvent};
 use super::manager::RESPONDER_SESSION_MAGAGER;
-use super::manager::{DropKey, DropPrivateKey, DropShareKey};
-use super::manager::{ResponderState, Role, Session};
-use super::QVE_ISVSVN_THRESHOLD;
-use crate::QveReportInfo;
+use super::manager::{parse_identity, DropKey, DropPrivateKey, DropShareKey};
+use super::manager::{Context, ResponderState, Role, Session, SessionAlloc, TrustedClock};
+use super::quote::{check_qv_result, check_qve_ran, classify_quote3_error};
+use super::quote::{parse_quote3, qv_result_mask, verify_isv_signature};
+use super::quote::{QuoteParseError, QuoteVerifyError};
+use super::rate_limit::RateLimiter;
+use super::receipt::HandshakeReceipt;
+use super::secret::{decrypt_secret, Secret};
+use super::verify_cache::{CachedVerdict, VerificationCache};
+use super::{confirm_mac, derive_directional_keys, rekey, DirectionalKeys, QVE_ISVSVN_THRESHOLD};
+use crate::{Cert, PlatformFlagsPolicy, QveReportInfo, SupplementalData};
+use alloc::alloc::Global;
+use alloc::boxed::Box;
+use alloc::vec::Vec;
 use core::mem;
-use sgx_crypto::ecc::{EcKeyPair, EcPublicKey};
+use core::time::Duration;
+use sgx_crypto::aes::gcm::{Aad, AesGcm, Nonce};
+use sgx_crypto::ecc::{EcKeyPair, EcPrivateKey, EcPublicKey};
 use sgx_crypto::sha::Sha256;
 use sgx_dcap_ra_msg::{DcapMRaMsg2, DcapRaMsg1, DcapRaMsg3};
+use sgx_dcap_tvl::Quote3Verify;
 use sgx_trts::fence;
 use sgx_trts::rand::Rng;
 use sgx_trts::trts::EnclaveRange;
-use sgx_tse::EnclaveReport;
-use sgx_types::error::{Quote3Error, SgxResult, SgxStatus};
+use sgx_tse::{EnclaveReport, EnclaveTarget};
+use sgx_types::error::{SgxResult, SgxStatus};
+use sgx_types::memeq::ConstTimeEq;
 use sgx_types::types::SHA256_HASH_SIZE;
 use sgx_types::types::{
-    AlignKey128bit, EnclaveIdentity, QlQvResult, Quote3, QuoteNonce, RaContext, RaKeyType, Report,
-    ReportData, TargetInfo,
+    AlignKey128bit, Ec256PublicKey, EnclaveIdentity, QlQvResult, Quote3, QuoteNonce, RaContext,
+    RaKeyType, Report, ReportData, Sha256Hash, TargetInfo, ECP256_KEY_SIZE, KEY_128BIT_SIZE,
+    MAC_128BIT_SIZE, MAC_SIZE,
 };
 
+#[cfg(feature = "unit_test")]
+mod tests;
+
+pub const WRAPPED_KEY_SIZE: usize = ECP256_KEY_SIZE * 2 + MAC_128BIT_SIZE + KEY_128BIT_SIZE;
+
+const WRAP_LABEL: &[u8] = b"WRAP";
+
+/// Responder policy applied in one step by `Responder::with_config`. Each
+/// field has the effect of the setter of the same name; the default is a
+/// responder with no setter called.
+#[derive(Clone, Copy, Default)]
+pub struct ResponderConfig {
+    pub min_tcb_eval_data_number: Option<u32>,
+    pub trusted_clock: Option<TrustedClock>,
+    pub rate_limiter: Option<(&'static dyn RateLimiter, u64)>,
+    pub report_max_age: Option<Duration>,
+    pub min_protocol_version: u8,
+    pub platform_flags_policy: Option<PlatformFlagsPolicy>,
+    pub require_confirmation: bool,
+    pub peer_pubkey_hash: Option<[u8; SHA256_HASH_SIZE]>,
+    pub trusted_roots: Option<&'static [Cert<'static>]>,
+    pub verification_cache: Option<&'static VerificationCache>,
+    pub accepted_qv_results: Option<&'static [QlQvResult]>,
+}
+
+/// Rough amount of work a handshake costs the responder, for scheduling.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct HandshakeCost {
+    pub ecdh_ops: u32,
+    pub hash_bytes: usize,
+    pub quote_size: usize,
+}
+
 #[derive(Debug)]
 pub struct Responder {
     rctx: RaContext,
 }
 
 impl Responder {
+    #[inline]
     pub fn new() -> SgxResult<Responder> {
+        Self::new_in(&Global)
+    }
+
+    /// Creates a responder whose session state, including all key material,
+    /// is allocated from `alloc`, e.g. a segregated secure heap.
+    pub fn new_in(alloc: SessionAlloc) -> SgxResult<Responder> {
         let session = Session::new(Role::Responder);
 
-        let rctx = RESPONDER_SESSION_MAGAGER.write().push(session);
+        let rctx = RESPONDER_SESSION_MAGAGER.write().push_in(session, alloc);
+        log::record(rctx, LogEvent::Init, &Ok(()));
         Ok(Self { rctx })
     }
 
+    pub fn with_config(config: ResponderConfig) -> SgxResult<Responder> {
+        if let Some(roots) = config.trusted_roots {
+            ensure!(!roots.is_empty(), SgxStatus::InvalidParameter);
+        }
+        let accepted_qv_results = config.accepted_qv_results.map(qv_result_mask).transpose()?;
+
+        let responder = Self::new()?;
+        let session = RESPONDER_SESSION_MAGAGER
+            .read()
+            .find(responder.rctx)
+            .ok_or(SgxStatus::Unexpected)?;
+
+        let mut context = session.context.lock();
+        context.min_tcb_eval_data_number = config.min_tcb_eval_data_number;
+        context.clock = config.trusted_clock;
+        context.rate_limiter = config.rate_limiter;
+        context.report_max_age = config.report_max_age;
+        context.min_protocol_version = config.min_protocol_version;
+        context.platform_flags_policy = config.platform_flags_policy;
+        context.confirm_required = config.require_confirmation;
+        context.peer_pubkey_hash = config.peer_pubkey_hash.map(Sha256Hash::from);
+        context.trusted_roots = config
+            .trusted_roots
+            .map(|roots| roots.iter().map(|cert| cert.der.into()).collect());
+        context.verification_cache = config.verification_cache;
+        context.accepted_qv_results = accepted_qv_results;
+        drop(context);
+
+        Ok(responder)
+    }
+
     pub fn process_msg1(
         &self,
         msg1: &DcapRaMsg1,
         qe_target: &TargetInfo,
+    ) -> SgxResult<(EcPublicKey, Report, QuoteNonce)> {
+        let result = self.do_process_msg1(msg1, qe_target, None);
+        log::record(self.rctx, LogEvent::ProcMsg1, &result);
+        result
+    }
+
+    /// Like `process_msg1`, but uses `nonce` instead of a random one so
+    /// msg1/msg2 test vectors are reproducible. Never enable in a release
+    /// enclave.
+    #[cfg(feature = "test-determinism")]
+    pub fn process_msg1_with_nonce(
+        &self,
+        msg1: &DcapRaMsg1,
+        qe_target: &TargetInfo,
+        nonce: QuoteNonce,
+    ) -> SgxResult<(EcPublicKey, Report, QuoteNonce)> {
+        let result = self.do_process_msg1(msg1, qe_target, Some(nonce));
+        log::record(self.rctx, LogEvent::ProcMsg1, &result);
+        result
+    }
+
+    pub fn generate_msg2(&self, qe_report: &Report, quote: &[u8]) -> SgxResult<DcapMRaMsg2> {
+        let result = self.do_generate_msg2(qe_report, quote);
+        log::record(self.rctx, LogEvent::GenMsg2, &result);
+        result
+    }
+
+    pub fn process_msg3(
+        &self,
+        msg3: &DcapRaMsg3,
+        qve_report_info: &QveReportInfo,
+    ) -> SgxResult<EnclaveIdentity> {
+        let result = self.do_process_msg3(msg3, qve_report_info);
+        self.note_msg3_result(&result);
+        log::record(self.rctx, LogEvent::ProcMsg3, &result);
+        result
+    }
+
+    /// Runs msg3 verification again after `process_msg3` failed for a
+    /// transient reason, such as the QvE not running, keeping the keys from
+    /// msg1. Fails with `InvalidState` unless the last msg3 attempt of this
+    /// session failed with `ServiceUnavailable`, `ServiceTimeout` or
+    /// `ServiceBusy`.
+    pub fn retry_msg3(
+        &self,
+        msg3: &DcapRaMsg3,
+        qve_report_info: &QveReportInfo,
+    ) -> SgxResult<EnclaveIdentity> {
+        let result = self.do_retry_msg3(msg3, qve_report_info);
+        self.note_msg3_result(&result);
+        log::record(self.rctx, LogEvent::ProcMsg3, &result);
+        result
+    }
+
+    /// Runs `process_msg3` on a msg3 carrying a secret from
+    /// `Initiator::generate_msg3_with_secret` and returns the secret with the
+    /// peer identity. The secret is decrypted under a key derived from SK
+    /// before the session is established, so a secret that fails to decrypt
+    /// leaves the session unestablished; it is only returned once the peer
+    /// has been verified.
+    pub fn process_msg3_unseal(
+        &self,
+        msg3: &DcapRaMsg3,
+        qve_report_info: &QveReportInfo,
+    ) -> SgxResult<(EnclaveIdentity, Secret)> {
+        let encrypted = msg3.sealed_secret().ok_or(SgxStatus::InvalidParameter)?;
+        let secret = self.decrypt_secret(encrypted)?;
+        let enclave_identity = self.process_msg3(msg3, qve_report_info)?;
+        Ok((enclave_identity, secret))
+    }
+
+    /// Target info to hand the QvE along with the quote. `process_msg3`
+    /// verifies the QvE report with this enclave's report key, so the QvE
+    /// must target this enclave rather than the QE.
+    pub fn qve_target_info(&self) -> SgxResult<TargetInfo> {
+        self.configure(|_| ())?;
+        TargetInfo::for_self()
+    }
+
+    pub fn get_keys(&self, key_type: RaKeyType) -> SgxResult<AlignKey128bit> {
+        let result = self.do_get_keys(key_type);
+        log::record(self.rctx, LogEvent::GetKeys, &result);
+        result
+    }
+
+    /// Returns per-direction keys derived from SK, under the same
+    /// conditions as `get_keys`.
+    pub fn get_directional_keys(&self) -> SgxResult<DirectionalKeys> {
+        let result = self.do_get_keys(RaKeyType::SK).and_then(|mut sk_key| {
+            let sk_key = DropKey::new(&mut sk_key);
+            derive_directional_keys(&sk_key)
+        });
+        log::record(self.rctx, LogEvent::GetKeys, &result);
+        result
+    }
+
+    pub fn get_peer_identity(&self) -> SgxResult<(QlQvResult, EnclaveIdentity)> {
+        let result = self.do_get_peer_identity();
+        log::record(self.rctx, LogEvent::GetPeerIdentity, &result);
+        result
+    }
+
+    /// Returns the `expiration_time` and `collateral_expiration_status` the
+    /// peer quote was verified with. Fails with `Unexpected` when the verdict
+    /// came from a `VerificationCache` rather than from the QvE.
+    pub fn get_collateral_status(&self) -> SgxResult<(i64, u32)> {
+        let session = RESPONDER_SESSION_MAGAGER
+            .read()
+            .find(self.rctx)
+            .ok_or(SgxStatus::InvalidParameter)?;
+
+        let context = session.context.lock();
+        ensure!(
+            context
+                .state
+                .check_responder_state(ResponderState::Established),
+            SgxStatus::InvalidState
+        );
+        context.collateral_status.ok_or(SgxStatus::Unexpected)
+    }
+
+    /// Returns the exact quote bytes the initiator was attested with in
+    /// `process_msg3`, or restored with the session by `import_sealed`.
+    pub fn peer_quote(&self) -> SgxResult<Vec<u8>> {
+        let session = RESPONDER_SESSION_MAGAGER
+            .read()
+            .find(self.rctx)
+            .ok_or(SgxStatus::InvalidParameter)?;
+
+        let context = session.context.lock();
+        ensure!(
+            context
+                .state
+                .check_responder_state(ResponderState::Established),
+            SgxStatus::InvalidState
+        );
+        context
+            .peer_quote
+            .as_deref()
+            .map(Vec::from)
+            .ok_or(SgxStatus::InvalidState)
+    }
+
+    /// Returns a serialized `HandshakeReceipt` for the established session,
+    /// signed with `signing_key` and stamped with the clock set by
+    /// `set_trusted_clock`. It carries no key material.
+    pub fn issue_receipt(&self, signing_key: &EcPrivateKey) -> SgxResult<Vec<u8>> {
+        let (qv_result, peer) = self.do_get_peer_identity()?;
+
+        let clock = RESPONDER_SESSION_MAGAGER
+            .read()
+            .find(self.rctx)
+            .ok_or(SgxStatus::InvalidParameter)?
+            .context
+            .lock()
+            .clock
+            .ok_or(SgxStatus::UnsupportedFeature)?;
+        let enclave = EnclaveIdentity::from(Report::for_self()?);
+
+        let mut receipt = HandshakeReceipt::new(enclave, peer, qv_result, clock()?.as_secs());
+        receipt.sign(signing_key)?;
+        Ok(receipt.to_bytes())
+    }
+
+    /// Zeroes the session keys held by the responder once the application
+    /// has taken its copies. The session stays usable for identity queries,
+    /// but `get_keys` and anything derived from the keys fail with
+    /// `InvalidState` afterwards.
+    pub fn release_keys(&self) -> SgxResult {
+        let result = self.do_release_keys();
+        log::record(self.rctx, LogEvent::ReleaseKeys, &result);
+        result
+    }
+
+    /// Replaces SK and MK with keys derived from them and a per-session
+    /// counter, so keys returned by `get_keys` before the call no longer
+    /// match. The initiator must call `Initiator::rekey` the same number of
+    /// times, and anything protected under the old keys must be finished
+    /// first.
+    pub fn rekey(&self) -> SgxResult {
+        let result = self.do_rekey();
+        log::record(self.rctx, LogEvent::Rekey, &result);
+        result
+    }
+
+    /// Seals the keys, peer identity, peer quote and collateral status of the
+    /// established session so that `import_sealed` can restore it after the
+    /// enclave is reloaded.
+    pub fn export_sealed(&self) -> SgxResult<Vec<u8>> {
+        let session = RESPONDER_SESSION_MAGAGER
+            .read()
+            .find(self.rctx)
+            .ok_or(SgxStatus::InvalidParameter)?;
+
+        let context = session.context.lock();
+        ensure!(
+            context
+                .state
+                .check_responder_state(ResponderState::Established),
+            SgxStatus::InvalidState
+        );
+        ensure!(
+            !context.confirm_required || context.confirmed,
+            SgxStatus::InvalidState
+        );
+        ensure!(!context.keys_released, SgxStatus::InvalidState);
+        let export = SessionExport {
+            pub_key_a: context.pub_key_a,
+            pub_key_b: context.pub_key_b,
+            sk_key: context.sk_key,
+            mk_key: context.mk_key,
+            vk_key: context.vk_key,
+            qv_result: context.qv_result.ok_or(SgxStatus::Unexpected)?,
+            peer_report: context.peer_report.ok_or(SgxStatus::Unexpected)?,
+            rekey_count: context.rekey_count,
+            peer_quote: context.peer_quote.clone(),
+            collateral_status: context.collateral_status,
+            confirm_required: context.confirm_required,
+            confirmed: context.confirmed,
+            nonce: Default::default(),
+        };
+        drop(context);
+
+        export.seal()
+    }
+
+    /// Restores a session from `export_sealed` as a new, established
+    /// responder, confirmation state included. Blobs of another
+    /// `SESSION_EXPORT_VERSION` fail with `InvalidVersion`, and a blob this
+    /// enclave instance has already imported fails with `InvalidState`.
+    pub fn import_sealed(blob: &[u8]) -> SgxResult<Responder> {
+        let mut export = SessionExport::unseal(blob)?;
+        export.claim()?;
+        let responder = Self::new()?;
+        let session = RESPONDER_SESSION_MAGAGER
+            .read()
+            .find(responder.rctx)
+            .ok_or(SgxStatus::Unexpected)?;
+
+        let mut context = session.context.lock();
+        context.pub_key_a = export.pub_key_a;
+        context.pub_key_b = export.pub_key_b;
+        context.sk_key = export.sk_key;
+        context.mk_key = export.mk_key;
+        context.vk_key = export.vk_key;
+        context.qv_result = Some(export.qv_result);
+        context.peer_report = Some(export.peer_report);
+        context.rekey_count = export.rekey_count;
+        context.peer_quote = export.peer_quote.take();
+        context.collateral_status = export.collateral_status;
+        context.confirm_required = export.confirm_required;
+        context.confirmed = export.confirmed;
+        context.state = From::from(ResponderState::Established);
+        drop(context);
+
+        Ok(responder)
+    }
+
+    /// Ends the handshake at once: zeroes the private key, session keys and
+    /// nonce, drops the stored quotes, and leaves the session in a state
+    /// where every further call fails with `InvalidState`. The context still
+    /// has to be closed.
+    pub fn abort(&self) -> SgxResult {
+        let result = self.do_abort();
+        log::record(self.rctx, LogEvent::Abort, &result);
+        result
+    }
+
+    /// Runs `process_msg1` and `generate_msg2` over a serialized msg1 and
+    /// returns the serialized msg2.
+    ///
+    /// `get_quote` is handed the report and nonce for the QE and must return
+    /// the QE report together with the quote it generated.
+    pub fn handle_request<F>(
+        &self,
+        msg1: &[u8],
+        qe_target: &TargetInfo,
+        get_quote: F,
+    ) -> SgxResult<Vec<u8>>
+    where
+        F: FnOnce(&Report, &QuoteNonce) -> SgxResult<(Report, Vec<u8>)>,
+    {
+        let msg1 = DcapRaMsg1::from_slice(msg1)?;
+        let (_, report, nonce) = self.process_msg1(&msg1, qe_target)?;
+        let (qe_report, quote) = get_quote(&report, &nonce)?;
+        let msg2 = self.generate_msg2(&qe_report, &quote)?;
+        msg2.into_bytes()
+    }
+
+    /// Requires the collateral used to verify the peer quote to carry a TCB
+    /// evaluation data number of at least `min`.
+    pub fn set_min_tcb_eval_data_number(&self, min: u32) -> SgxResult {
+        self.configure(|context| context.min_tcb_eval_data_number = Some(min))
+    }
+
+    /// Exports a session key wrapped for an external key holder with ECIES:
+    /// an ephemeral P-256 key agreement with `wrapping_pubkey` followed by
+    /// AES-128-GCM. The output is `g_e || mac || ciphertext`, where `g_e` is
+    /// the ephemeral public key in SDK byte order and also serves as AAD.
+    pub fn export_key_wrapped(
+        &self,
+        key_type: RaKeyType,
+        wrapping_pubkey: &Ec256PublicKey,
+    ) -> SgxResult<Vec<u8>> {
+        let wrapping_pubkey = EcPublicKey::from(wrapping_pubkey);
+        ensure!(wrapping_pubkey.check_point()?, SgxStatus::InvalidParameter);
+
+        let mut key = self.get_keys(key_type)?;
+        let key = DropKey::new(&mut key);
+
+        let mut key_pair = EcKeyPair::create()?;
+        let (mut priv_key, pub_key) = key_pair.into();
+
+        key_pair.clear();
+        let priv_key = DropPrivateKey::new(&mut priv_key);
+
+        let mut dh_key = priv_key.shared_key(&wrapping_pubkey)?;
+        let dh_key = DropShareKey::new(&mut dh_key);
+        let mut wrap_key = dh_key.derive_key(WRAP_LABEL)?;
+        let wrap_key = DropKey::new(&mut wrap_key);
+
+        // The wrapping key comes from a fresh ephemeral key pair and is used
+        // once, so a fixed IV is never reused under the same key.
+        let pub_key: [u8; ECP256_KEY_SIZE * 2] = pub_key.into();
+        let mut aes = AesGcm::new(&wrap_key.key, Nonce::zeroed(), Aad::from(pub_key))?;
+        let mut ciphertext = [0_u8; KEY_128BIT_SIZE];
+        let mac = aes.encrypt(&key.key, &mut ciphertext)?;
+
+        let mut wrapped = Vec::with_capacity(WRAPPED_KEY_SIZE);
+        wrapped.extend_from_slice(&pub_key);
+        wrapped.extend_from_slice(&mac);
+        wrapped.extend_from_slice(&ciphertext);
+        Ok(wrapped)
+    }
+
+    /// Sets the clock used to time the handshake, see `set_report_max_age`.
+    pub fn set_trusted_clock(&self, clock: TrustedClock) -> SgxResult {
+        self.configure(|context| context.clock = Some(clock))
+    }
+
+    /// Has `process_msg1` ask `limiter` to admit `source`, the caller's
+    /// identifier for where the handshake came from, before doing any work.
+    pub fn set_rate_limiter(&self, limiter: &'static dyn RateLimiter, source: u64) -> SgxResult {
+        self.configure(|context| context.rate_limiter = Some((limiter, source)))
+    }
+
+    /// Makes `generate_msg2` fail with `ServiceTimeout` when more than
+    /// `max_age` has passed since `process_msg1` produced the report. Needs
+    /// a clock from `set_trusted_clock` before `process_msg1`.
+    pub fn set_report_max_age(&self, max_age: Duration) -> SgxResult {
+        self.configure(|context| context.report_max_age = Some(max_age))
+    }
+
+    /// Rejects msg1 advertising a protocol version below `min` with
+    /// `InvalidVersion` instead of negotiating down.
+    pub fn set_min_protocol_version(&self, min: u8) -> SgxResult {
+        self.configure(|context| context.min_protocol_version = min)
+    }
+
+    /// Attaches collateral for the responder's own quote to later evidence
+    /// bundles.
+    pub fn set_evidence_collateral(&self, collateral: &[u8]) -> SgxResult {
+        self.configure(|context| context.evidence_collateral = Some(collateral.into()))
+    }
+
+    /// Packs the responder's quote, its collateral and `g_b` into an
+    /// [`EvidenceBundle`] MAC'd under the session MK. Only the session peer
+    /// can check the MAC; a third party can only verify the quote itself.
+    pub fn evidence_bundle(&self) -> SgxResult<Vec<u8>> {
+        let mut mk_key = self.get_keys(RaKeyType::MK)?;
+        let mk_key = DropKey::new(&mut mk_key);
+
+        let session = RESPONDER_SESSION_MAGAGER
+            .read()
+            .find(self.rctx)
+            .ok_or(SgxStatus::InvalidParameter)?;
+
+        let context = session.context.lock();
+        let pub_key_b = context.pub_key_b;
+        let quote = context
+            .own_quote
+            .as_deref()
+            .ok_or(SgxStatus::InvalidState)?
+            .into();
+        let collateral = context
+            .evidence_collateral
+            .as_deref()
+            .map(Vec::from)
+            .unwrap_or_default();
+        drop(context);
+
+        let mut bundle = EvidenceBundle::new(pub_key_b, quote, collateral);
+        bundle.gen_cmac(&mk_key)?;
+        bundle.to_bytes()
+    }
+
+    /// Requires the PCK certificate chain in the peer quote to end at one of
+    /// `roots`, instead of leaving the choice of root to the QvE alone.
+    pub fn set_trusted_roots(&self, roots: &[Cert<'_>]) -> SgxResult {
+        ensure!(!roots.is_empty(), SgxStatus::InvalidParameter);
+
+        let roots = roots.iter().map(|cert| cert.der.into()).collect();
+        self.configure(|context| context.trusted_roots = Some(roots))
+    }
+
+    /// Has `process_msg3` take the QvE verdict for the peer's platform from
+    /// `cache` when it holds one, and add the verdict for quotes it verifies.
+    /// Only verdicts backed by supplemental data are cached, since that is
+    /// where the collateral expiry comes from. The ISV enclave signature of
+    /// the quote is checked in the enclave on a hit, and this responder's own
+    /// policy still applies to a cached verdict.
+    pub fn set_verification_cache(&self, cache: &'static VerificationCache) -> SgxResult {
+        self.configure(|context| context.verification_cache = Some(cache))
+    }
+
+    /// Restricts the QvE verdicts `process_msg3` accepts to `accepted`;
+    /// others fail with the status of `QuoteVerifyError::QvResultRejected`.
+    /// Without this every verdict is accepted and reported to the caller.
+    pub fn set_accepted_qv_results(&self, accepted: &[QlQvResult]) -> SgxResult {
+        let mask = qv_result_mask(accepted)?;
+        self.configure(|context| context.accepted_qv_results = Some(mask))
+    }
+
+    /// Requires the platform instance flags in the QvE supplemental data to
+    /// satisfy `policy`. msg3 is then rejected when supplemental data is absent.
+    pub fn set_platform_flags_policy(&self, policy: PlatformFlagsPolicy) -> SgxResult {
+        self.configure(|context| context.platform_flags_policy = Some(policy))
+    }
+
+    /// Requires the initiator's key confirmation: once set, `get_keys` fails
+    /// until `finalize` has accepted the peer's confirmation MAC.
+    pub fn require_confirmation(&self) -> SgxResult {
+        self.configure(|context| context.confirm_required = true)
+    }
+
+    pub fn finalize(&self, peer_confirm: &[u8]) -> SgxResult {
+        let result = self.do_finalize(peer_confirm);
+        log::record(self.rctx, LogEvent::Finalize, &result);
+        result
+    }
+
+    /// Pins the initiator's public key: `process_msg3` fails unless the
+    /// SHA-256 of the peer's `g_a` equals `hash`.
+    pub fn expect_peer_pubkey_hash(&self, hash: [u8; SHA256_HASH_SIZE]) -> SgxResult {
+        self.configure(|context| context.peer_pubkey_hash = Some(Sha256Hash::from(hash)))
+    }
+
+    /// Reports whether msg2 or msg3 of this session was staged in host
+    /// memory rather than in enclave-only buffers. A host msg3 counts once
+    /// it is handed in, whether or not it is accepted.
+    pub fn used_host_buffers(&self) -> SgxResult<bool> {
+        let session = RESPONDER_SESSION_MAGAGER
+            .read()
+            .find(self.rctx)
+            .ok_or(SgxStatus::InvalidParameter)?;
+
+        let context = session.context.lock();
+        ensure!(
+            !context.state.check_responder_state(ResponderState::Aborted),
+            SgxStatus::InvalidState
+        );
+        Ok(context.host_buffers)
+    }
+
+    /// Size of the quote carried in msg2. The quote comes from the QE after
+    /// `process_msg1`, so it is known once `generate_msg2` has run.
+    pub fn quote_size(&self) -> SgxResult<usize> {
+        let session = RESPONDER_SESSION_MAGAGER
+            .read()
+            .find(self.rctx)
+            .ok_or(SgxStatus::InvalidParameter)?;
+
+        let context = session.context.lock();
+        ensure!(
+            !context.state.check_responder_state(ResponderState::Aborted),
+            SgxStatus::InvalidState
+        );
+        context
+            .own_quote
+            .as_ref()
+            .map(|quote| quote.len())
+            .ok_or(SgxStatus::InvalidState)
+    }
+
+    /// Estimates the cost of the handshake once msg2 has been generated.
+    /// The peer quote is not known before msg3 and is taken to be the size
+    /// of the responder's own.
+    pub fn estimated_cost(&self) -> SgxResult<HandshakeCost> {
+        let session = RESPONDER_SESSION_MAGAGER
+            .read()
+            .find(self.rctx)
+            .ok_or(SgxStatus::InvalidParameter)?;
+
+        let context = session.context.lock();
+        ensure!(
+            !context.state.check_responder_state(ResponderState::Aborted),
+            SgxStatus::InvalidState
+        );
+        let quote_size = context
+            .own_quote
+            .as_ref()
+            .ok_or(SgxStatus::InvalidState)?
+            .len();
+        drop(context);
+
+        // msg1 and msg3 each hash g_a || g_b || VK; msg2 hashes the nonce and
+        // quote, and the msg3 CMAC covers the peer quote.
+        let key_hash_bytes = ECP256_KEY_SIZE * 4 + KEY_128BIT_SIZE;
+        let hash_bytes = key_hash_bytes * 2 + mem::size_of::<QuoteNonce>() + quote_size * 2;
+
+        Ok(HandshakeCost {
+            ecdh_ops: 1,
+            hash_bytes,
+            quote_size,
+        })
+    }
+
+    pub(crate) fn set_used_host_buffers(&self) -> SgxResult {
+        let session = RESPONDER_SESSION_MAGAGER
+            .read()
+            .find(self.rctx)
+            .ok_or(SgxStatus::InvalidParameter)?;
+
+        session.context.lock().host_buffers = true;
+        Ok(())
+    }
+
+    pub(super) fn do_process_msg1(
+        &self,
+        msg1: &DcapRaMsg1,
+        qe_target: &TargetInfo,
+        fixed_nonce: Option<QuoteNonce>,
     ) -> SgxResult<(EcPublicKey, Report, QuoteNonce)> {
         ensure!(msg1.is_enclave_range(), SgxStatus::InvalidParameter);
         ensure!(qe_target.is_enclave_range(), SgxStatus::InvalidParameter);
@@ -68,8 +690,19 @@ impl Responder {
             context.state.check_responder_state(ResponderState::Inited),
             SgxStatus::InvalidState
         );
+        let min_protocol_version = context.min_protocol_version;
+        let clock = context.clock;
+        let rate_limiter = context.rate_limiter;
         drop(context);
 
+        ensure!(
+            msg1.protocol_version >= min_protocol_version,
+            SgxStatus::InvalidVersion
+        );
+        if let Some((limiter, source)) = rate_limiter {
+            limiter.admit(source)?;
+        }
+
         let mut key_pair = EcKeyPair::create()?;
         let (mut priv_key, pub_key) = key_pair.into();
 
@@ -92,8 +725,13 @@ impl Responder {
         let mk_key = DropKey::new(mk_key);
         let vk_key = DropKey::new(vk_key);
 
-        let mut nonce = QuoteNonce::default();
-        Rng::new().fill_bytes(&mut nonce.rand);
+        // Neither the nonce (RDRAND) nor the report (EREPORT) goes through an
+        // OCALL, so there is no host call in msg1 processing that could hang.
+        let nonce = fixed_nonce.unwrap_or_else(|| {
+            let mut nonce = QuoteNonce::default();
+            Rng::new().fill_bytes(&mut nonce.rand);
+            nonce
+        });
 
         let mut report_data = ReportData::default();
         let mut sha = Sha256::new()?;
@@ -103,6 +741,7 @@ impl Responder {
         let hash = sha.finalize()?;
         report_data.d[..SHA256_HASH_SIZE].copy_from_slice(&hash);
         let report = Report::for_target(qe_target, &report_data)?;
+        let msg1_time = clock.map(|clock| clock()).transpose()?;
 
         let mut context = session.context.lock();
         ensure!(
@@ -118,13 +757,18 @@ impl Responder {
         context.vk_key = *vk_key;
         context.qe_target = *qe_target;
         context.quote_nonce = nonce;
+        context.msg1_time = msg1_time;
         context.state = From::from(ResponderState::Msg1Proced);
         drop(context);
 
         Ok((pub_key, report, nonce))
     }
 
-    pub fn generate_msg2(&self, qe_report: &Report, quote: &[u8]) -> SgxResult<DcapMRaMsg2> {
+    pub(super) fn do_generate_msg2(
+        &self,
+        qe_report: &Report,
+        quote: &[u8],
+    ) -> SgxResult<DcapMRaMsg2> {
         ensure!(qe_report.is_enclave_range(), SgxStatus::InvalidParameter);
         ensure!(!quote.is_empty(), SgxStatus::InvalidParameter);
         ensure!(
@@ -157,10 +801,22 @@ impl Responder {
         let pub_key_b = context.pub_key_b;
         let mut smk_key = context.smk_key;
         let nonce = context.quote_nonce;
+        let report_max_age = context.report_max_age;
+        let clock = context.clock;
+        let msg1_time = context.msg1_time;
         drop(context);
 
         let smk_key = DropKey::new(&mut smk_key);
 
+        if let Some(max_age) = report_max_age {
+            let clock = clock.ok_or(SgxStatus::UnsupportedFeature)?;
+            let msg1_time = msg1_time.ok_or(SgxStatus::InvalidState)?;
+            let elapsed = clock()?
+                .checked_sub(msg1_time)
+                .ok_or(SgxStatus::Unexpected)?;
+            ensure!(elapsed <= max_age, SgxStatus::ServiceTimeout);
+        }
+
         ensure!(
             attributes.eq(&qe_report.body.attributes),
             SgxStatus::InvalidParameter
@@ -170,9 +826,14 @@ impl Responder {
             SgxStatus::InvalidParameter
         );
 
+        // A quote in host memory is read once, so the bytes hashed against
+        // the QE report are the bytes sent in msg2.
+        let host_quote = quote.is_host_range();
+        let quote: Box<[u8]> = quote.into();
+
         let mut sha = Sha256::new()?;
         sha.update(&nonce)?;
-        sha.update(quote)?;
+        sha.update(&*quote)?;
         let hash = sha.finalize()?;
         ensure!(
             hash.eq(&qe_report.body.report_data.d[..SHA256_HASH_SIZE]),
@@ -183,7 +844,7 @@ impl Responder {
             mac: Default::default(),
             pub_key_b,
             kdf_id: 0x0001,
-            quote: quote.into(),
+            quote,
         };
         msg2.gen_cmac(&smk_key)?;
 
@@ -195,12 +856,14 @@ impl Responder {
             SgxStatus::InvalidState
         );
         context.state = From::from(ResponderState::Msg2Gened);
+        context.host_buffers |= host_quote;
+        context.own_quote = Some(msg2.quote.clone());
         drop(context);
 
         Ok(msg2)
     }
 
-    pub fn process_msg3(
+    fn do_process_msg3(
         &self,
         msg3: &DcapRaMsg3,
         qve_report_info: &QveReportInfo,
@@ -225,6 +888,12 @@ impl Responder {
         let pub_key_b = context.pub_key_b;
         let mut smk_key = context.smk_key;
         let mut vk_key = context.vk_key;
+        let min_tcb_eval_data_number = context.min_tcb_eval_data_number;
+        let peer_pubkey_hash = context.peer_pubkey_hash;
+        let platform_flags_policy = context.platform_flags_policy;
+        let trusted_roots = context.trusted_roots.clone();
+        let verification_cache = context.verification_cache;
+        let accepted_qv_results = context.accepted_qv_results;
         drop(context);
 
         let smk_key = DropKey::new(&mut smk_key);
@@ -232,15 +901,42 @@ impl Responder {
 
         ensure!(msg3.pub_key_a == pub_key_a, SgxStatus::Unexpected);
         msg3.verify_cmac(&smk_key)?;
-        qve_report_info
-            .verify_report_and_identity(&msg3.quote, QVE_ISVSVN_THRESHOLD)
-            .map_err(|e| match e {
-                Quote3Error::InvalidParameter => SgxStatus::InvalidParameter,
-                Quote3Error::QveIdentityMismatch | Quote3Error::QveOutOfDate => {
-                    SgxStatus::UpdateNeeded
+        if let Some(expected) = peer_pubkey_hash {
+            let hash = Sha256::digest(&pub_key_a)?;
+            ensure!(hash.ct_eq(&expected), SgxStatus::InvalidSignature);
+        }
+        let quote3 = parse_quote3(&msg3.quote)?;
+        if let Some(roots) = trusted_roots {
+            let roots: Vec<Cert> = roots.iter().map(|der| Cert::new(der)).collect();
+            Quote3::verify_root_ca(&msg3.quote, &roots)?;
+        }
+        let cached = match verification_cache {
+            Some(cache) => cache.lookup(&msg3.quote)?,
+            None => None,
+        };
+        let from_cache = cached.is_some();
+        let peer_report = quote3.report_body;
+        let verdict = match cached {
+            Some(verdict) => {
+                verify_isv_signature(&msg3.quote)?;
+                verdict
+            }
+            None => {
+                check_qve_ran(qve_report_info)?;
+                qve_report_info
+                    .verify_report_and_identity(&msg3.quote, QVE_ISVSVN_THRESHOLD)
+                    .map_err(classify_quote3_error)?;
+                CachedVerdict {
+                    qv_result: qve_report_info.quote_verification_result,
+                    supplemental: qve_report_info.parse_supplemental().ok(),
                 }
-                _ => SgxStatus::Unexpected,
-            })?;
+            }
+        };
+        check_supplemental(
+            verdict.supplemental.as_ref(),
+            min_tcb_eval_data_number,
+            platform_flags_policy,
+        )?;
 
         let mut sha = Sha256::new()?;
         sha.update(&pub_key_a)?;
@@ -248,12 +944,22 @@ impl Responder {
         sha.update(&*vk_key)?;
         let hash = sha.finalize()?;
 
-        let quote3 = unsafe { &*(msg3.quote.as_ptr() as *const Quote3) };
         ensure!(
             hash.eq(&quote3.report_body.report_data.d[..SHA256_HASH_SIZE]),
-            SgxStatus::Unexpected
+            SgxStatus::from(QuoteVerifyError::ReportDataMismatch)
         );
-        let enclave_identity = quote3.report_body.into();
+        let qv_result = verdict.qv_result;
+        let enclave_identity = parse_identity(&peer_report);
+        if !from_cache {
+            if let (Some(cache), Some(expiry)) =
+                (verification_cache, collateral_expiry(qve_report_info))
+            {
+                cache.insert(&msg3.quote, verdict, expiry)?;
+            }
+        }
+        if let Some(mask) = accepted_qv_results {
+            check_qv_result(mask, qv_result)?;
+        }
 
         let mut context = session.context.lock();
         ensure!(
@@ -263,7 +969,13 @@ impl Responder {
             SgxStatus::InvalidState
         );
 
-        context.qv_result = Some(qve_report_info.quote_verification_result);
+        context.qv_result = Some(qv_result);
+        context.collateral_status = (!from_cache).then_some((
+            qve_report_info.expiration_time,
+            qve_report_info.collateral_expiration_status,
+        ));
+        context.peer_report = Some(peer_report);
+        context.peer_quote = Some(msg3.quote.clone());
         context.enclave_identity = Some(enclave_identity);
         context.state = From::from(ResponderState::Established);
         drop(context);
@@ -271,7 +983,124 @@ impl Responder {
         Ok(enclave_identity)
     }
 
-    pub fn get_keys(&self, key_type: RaKeyType) -> SgxResult<AlignKey128bit> {
+    fn do_retry_msg3(
+        &self,
+        msg3: &DcapRaMsg3,
+        qve_report_info: &QveReportInfo,
+    ) -> SgxResult<EnclaveIdentity> {
+        let session = RESPONDER_SESSION_MAGAGER
+            .read()
+            .find(self.rctx)
+            .ok_or(SgxStatus::InvalidParameter)?;
+
+        let context = session.context.lock();
+        ensure!(
+            context
+                .state
+                .check_responder_state(ResponderState::Msg2Gened),
+            SgxStatus::InvalidState
+        );
+        ensure!(
+            context.msg3_error.map(is_transient).unwrap_or(false),
+            SgxStatus::InvalidState
+        );
+        drop(context);
+
+        self.do_process_msg3(msg3, qve_report_info)
+    }
+
+    fn note_msg3_result(&self, result: &SgxResult<EnclaveIdentity>) {
+        if let Some(session) = RESPONDER_SESSION_MAGAGER.read().find(self.rctx) {
+            session.context.lock().msg3_error = result.err();
+        }
+    }
+
+    fn do_rekey(&self) -> SgxResult {
+        let session = RESPONDER_SESSION_MAGAGER
+            .read()
+            .find(self.rctx)
+            .ok_or(SgxStatus::InvalidParameter)?;
+
+        let context = session.context.lock();
+        ensure!(
+            context
+                .state
+                .check_responder_state(ResponderState::Established),
+            SgxStatus::InvalidState
+        );
+        ensure!(
+            !context.confirm_required || context.confirmed,
+            SgxStatus::InvalidState
+        );
+        ensure!(!context.keys_released, SgxStatus::InvalidState);
+        let mut sk_key = context.sk_key;
+        let mut mk_key = context.mk_key;
+        let counter = context
+            .rekey_count
+            .checked_add(1)
+            .ok_or(SgxStatus::InvalidState)?;
+        drop(context);
+
+        let sk_key = DropKey::new(&mut sk_key);
+        let mk_key = DropKey::new(&mut mk_key);
+        let (mut next_sk, mut next_mk) = rekey(&sk_key, &mk_key, counter)?;
+        let next_sk = DropKey::new(&mut next_sk);
+        let next_mk = DropKey::new(&mut next_mk);
+
+        let mut context = session.context.lock();
+        ensure!(
+            context
+                .state
+                .check_responder_state(ResponderState::Established)
+                && !context.keys_released
+                && context.rekey_count == counter - 1,
+            SgxStatus::InvalidState
+        );
+        context.sk_key = *next_sk;
+        context.mk_key = *next_mk;
+        context.rekey_count = counter;
+        Ok(())
+    }
+
+    fn do_finalize(&self, peer_confirm: &[u8]) -> SgxResult {
+        ensure!(peer_confirm.len() == MAC_SIZE, SgxStatus::InvalidParameter);
+
+        let session = RESPONDER_SESSION_MAGAGER
+            .read()
+            .find(self.rctx)
+            .ok_or(SgxStatus::InvalidParameter)?;
+
+        let context = session.context.lock();
+        ensure!(
+            context
+                .state
+                .check_responder_state(ResponderState::Established),
+            SgxStatus::InvalidState
+        );
+        ensure!(!context.keys_released, SgxStatus::InvalidState);
+        let pub_key_a = context.pub_key_a;
+        let pub_key_b = context.pub_key_b;
+        let mut vk_key = context.vk_key;
+        drop(context);
+
+        let vk_key = DropKey::new(&mut vk_key);
+        let mac = confirm_mac(&vk_key, &pub_key_a, &pub_key_b)?;
+        ensure!(mac[..].ct_eq(peer_confirm), SgxStatus::MacMismatch);
+
+        let mut context = session.context.lock();
+        ensure!(
+            context
+                .state
+                .check_responder_state(ResponderState::Established),
+            SgxStatus::InvalidState
+        );
+        context.confirmed = true;
+        drop(context);
+
+        Ok(())
+    }
+
+    fn do_get_keys(&self, key_type: RaKeyType) -> SgxResult<AlignKey128bit> {
         let session = RESPONDER_SESSION_MAGAGER
             .read()
             .find(self.rctx)
@@ -284,6 +1113,11 @@ impl Responder {
                 .check_responder_state(ResponderState::Established),
             SgxStatus::InvalidState
         );
+        ensure!(
+            !context.confirm_required || context.confirmed,
+            SgxStatus::InvalidState
+        );
+        ensure!(!context.keys_released, SgxStatus::InvalidState);
 
         let key = match key_type {
             RaKeyType::SK => context.sk_key,
@@ -293,25 +1127,115 @@ impl Responder {
         Ok(key)
     }
 
-    pub fn get_peer_identity(&self) -> SgxResult<(QlQvResult, EnclaveIdentity)> {
+    // Applies a policy setting, refusing to touch an aborted session.
+    fn configure<F>(&self, f: F) -> SgxResult
+    where
+        F: FnOnce(&mut Context),
+    {
+        let session = RESPONDER_SESSION_MAGAGER
+            .read()
+            .find(self.rctx)
+            .ok_or(SgxStatus::InvalidParameter)?;
+
+        let mut context = session.context.lock();
+        ensure!(
+            !context.state.check_responder_state(ResponderState::Aborted),
+            SgxStatus::InvalidState
+        );
+        f(&mut context);
+        Ok(())
+    }
+
+    fn decrypt_secret(&self, encrypted: &[u8]) -> SgxResult<Secret> {
         let session = RESPONDER_SESSION_MAGAGER
             .read()
             .find(self.rctx)
             .ok_or(SgxStatus::InvalidParameter)?;
 
         let context = session.context.lock();
+        ensure!(
+            context
+                .state
+                .check_responder_state(ResponderState::Msg2Gened),
+            SgxStatus::InvalidState
+        );
+        let mut sk_key = context.sk_key;
+        drop(context);
+
+        let sk_key = DropKey::new(&mut sk_key);
+        decrypt_secret(&sk_key, encrypted)
+    }
+
+    fn do_abort(&self) -> SgxResult {
+        let session = RESPONDER_SESSION_MAGAGER
+            .read()
+            .find(self.rctx)
+            .ok_or(SgxStatus::InvalidParameter)?;
+
+        let mut context = session.context.lock();
+        ensure!(
+            !context.state.check_responder_state(ResponderState::Aborted),
+            SgxStatus::InvalidState
+        );
+        context.priv_key.clear();
+        context.smk_key.key.fill(0);
+        context.sk_key.key.fill(0);
+        context.mk_key.key.fill(0);
+        context.vk_key.key.fill(0);
+        context.quote_nonce = QuoteNonce::default();
+        context.own_quote = None;
+        context.peer_quote = None;
+        context.keys_released = true;
+        context.state = From::from(ResponderState::Aborted);
+        Ok(())
+    }
+
+    fn do_release_keys(&self) -> SgxResult {
+        let session = RESPONDER_SESSION_MAGAGER
+            .read()
+            .find(self.rctx)
+            .ok_or(SgxStatus::InvalidParameter)?;
+
+        let mut context = session.context.lock();
+        ensure!(
+            context
+                .state
+                .check_responder_state(ResponderState::Established),
+            SgxStatus::InvalidState
+        );
+        context.smk_key.key.fill(0);
+        context.sk_key.key.fill(0);
+        context.mk_key.key.fill(0);
+        context.vk_key.key.fill(0);
+        context.keys_released = true;
+        Ok(())
+    }
+
+    fn do_get_peer_identity(&self) -> SgxResult<(QlQvResult, EnclaveIdentity)> {
+        let session = RESPONDER_SESSION_MAGAGER
+            .read()
+            .find(self.rctx)
+            .ok_or(SgxStatus::InvalidParameter)?;
+
+        let mut context = session.context.lock();
         ensure!(
             context
                 .state
                 .check_responder_state(ResponderState::Established),
             SgxStatus::InvalidState
         );
-        let enclave_identity = context.enclave_identity.ok_or(SgxStatus::Unexpected)?;
         let qv_result = context.qv_result.ok_or(SgxStatus::Unexpected)?;
+        let enclave_identity = context.peer_identity()?;
 
         Ok((qv_result, enclave_identity))
     }
 
+    /// Drops the cached peer identity; the next `get_peer_identity` parses
+    /// it again from the stored peer report.
+    pub fn forget_peer_identity(&self) -> SgxResult {
+        self.configure(|context| context.enclave_identity = None)
+    }
+
     #[inline]
     pub fn into_raw(self) -> RaContext {
         let rctx = self.rctx;
@@ -319,9 +1243,17 @@ impl Responder {
         rctx
     }
 
+    /// Rebuilds a session handle from `into_raw`. Handles issued by a previous
+    /// enclave instance, or whose session was already closed, are rejected
+    /// with `InvalidParameter`.
     #[inline]
-    pub unsafe fn from_raw(rctx: RaContext) -> Responder {
-        Self { rctx }
+    pub unsafe fn from_raw(rctx: RaContext) -> SgxResult<Responder> {
+        let manager = RESPONDER_SESSION_MAGAGER.read();
+        ensure!(
+            manager.is_current(rctx) && manager.contains(rctx),
+            SgxStatus::InvalidParameter
+        );
+        Ok(Self { rctx })
     }
 }
 
@@ -330,5 +1262,50 @@ impl Drop for Responder {
         if let Some(session) = RESPONDER_SESSION_MAGAGER.write().remove(self.rctx) {
             drop(session)
         }
+        log::record(self.rctx, LogEvent::Close, &Ok(()));
+    }
+}
+
+// Applies the TCB evaluation number and platform flag policy to the
+// supplemental data of a fresh or a cached verdict alike.
+fn check_supplemental(
+    supplemental: Option<&SupplementalData>,
+    min_tcb_eval_data_number: Option<u32>,
+    platform_flags_policy: Option<PlatformFlagsPolicy>,
+) -> SgxResult {
+    if let Some(min) = min_tcb_eval_data_number {
+        let supplemental =
+            supplemental.ok_or_else(|| SgxStatus::from(QuoteParseError::InvalidReportInfo))?;
+        ensure!(
+            supplemental.tcb_eval_ref_num >= min,
+            SgxStatus::from(QuoteVerifyError::TcbOutOfDate)
+        );
+    }
+    if let Some(policy) = platform_flags_policy {
+        let supplemental = supplemental.ok_or(SgxStatus::InvalidParameter)?;
+        ensure!(
+            supplemental.check_platform_flags(&policy),
+            SgxStatus::UnsupportedConfig
+        );
+    }
+    Ok(())
+}
+
+// Failures of the QvE or its host rather than of the peer's evidence.
+fn is_transient(status: SgxStatus) -> bool {
+    matches!(
+        status,
+        SgxStatus::ServiceUnavailable | SgxStatus::ServiceTimeout | SgxStatus::ServiceBusy
+    )
+}
+
+// The earliest expiry among the collateral the QvE used, or `None` when it
+// is unknown or has already passed.
+fn collateral_expiry(qve_report_info: &QveReportInfo) -> Option<Duration> {
+    if qve_report_info.collateral_expiration_status != 0 {
+        return None;
     }
+    let supplemental = qve_report_info.supplemental()?;
+    let expiry = u64::try_from(supplemental.earliest_expiration_date).ok()?;
+    Some(Duration::from_secs(expiry))
 }
@@ -0,0 +1,1485 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License..
+
+use super::{Responder, ResponderConfig, WRAPPED_KEY_SIZE, WRAP_LABEL};
+use crate::session::confirm_mac;
+use crate::session::manager::{InitiatorState, ResponderState, IDENTITY_PARSES};
+use crate::session::manager::{INITIATOR_SESSION_MAGAGER, RESPONDER_SESSION_MAGAGER};
+use crate::session::quote::swap_coordinates;
+use crate::session::secret::encrypt_secret;
+use crate::session::Initiator;
+use crate::session::{
+    dry_run_handshake, CachedVerdict, EvidenceBundle, HandshakeReceipt, Msg2Inputs, TokenBucket,
+    TypedResponder, VerificationCache,
+};
+use crate::session::{QuoteParseError, QuoteVerifyError};
+use crate::{Cert, PlatformFlagsPolicy, QveReportInfo, SupplementalData};
+use alloc::alloc::{AllocError, Allocator, Global, Layout};
+use alloc::vec::Vec;
+use core::mem;
+use core::num::NonZeroUsize;
+use core::ptr::{self, NonNull};
+use core::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use core::time::Duration;
+use sgx_crypto::aes::gcm::{Aad, AesGcm, Nonce};
+use sgx_crypto::ecc::{EcKeyPair, EcPrivateKey, EcPublicKey};
+use sgx_crypto::sha::Sha256;
+use sgx_dcap_ra_msg::{DcapMRaMsg2, DcapRaMsg1, DcapRaMsg3};
+use sgx_sync::SpinMutex;
+use sgx_test_utils::test_case;
+use sgx_trts::trts::OcBuffer;
+use sgx_tse::{EnclaveReport, EnclaveTarget};
+use sgx_types::error::{SgxResult, SgxStatus};
+use sgx_types::types::{
+    AlignKey128bit, EnclaveIdentity, Mac, QlAuthData, QlCertificationData, QlEcdsaSigData,
+    QlQvResult, QlQvSupplemental, Quote3, QuoteHeader, QuoteNonce, RaKeyType, Report, ReportBody,
+    ReportData, TargetInfo, SHA256_HASH_SIZE,
+};
+
+const MOCK_QUOTE_LEN: usize = mem::size_of::<Quote3>()
+    + mem::size_of::<QlEcdsaSigData>()
+    + mem::size_of::<QlAuthData>()
+    + mem::size_of::<QlCertificationData>()
+    + 64;
+
+fn mock_quote(report: &Report) -> Vec<u8> {
+    let mut quote = alloc::vec![0_u8; MOCK_QUOTE_LEN];
+    let mut quote3 = Quote3::default();
+    quote3.header.version = 3;
+    quote3.report_body = report.body;
+    quote3.signature_len = (MOCK_QUOTE_LEN - mem::size_of::<Quote3>()) as u32;
+    unsafe { ptr::write_unaligned(quote.as_mut_ptr() as *mut Quote3, quote3) };
+    quote
+}
+
+// Stands in for the QE: the enclave itself acts as the quoting enclave, so
+// the QE report targets this enclave and passes `Report::verify`.
+fn mock_qe(report: &Report, nonce: &QuoteNonce) -> SgxResult<(Report, Vec<u8>)> {
+    mock_qe_for(mock_quote(report), nonce)
+}
+
+fn mock_qe_for(quote: Vec<u8>, nonce: &QuoteNonce) -> SgxResult<(Report, Vec<u8>)> {
+    let mut sha = Sha256::new()?;
+    sha.update(nonce)?;
+    sha.update(quote.as_slice())?;
+    let hash = sha.finalize()?;
+
+    let mut report_data = ReportData::default();
+    report_data.d[..SHA256_HASH_SIZE].copy_from_slice(&hash);
+    let qe_report = Report::for_target(&TargetInfo::for_self()?, &report_data)?;
+
+    Ok((qe_report, quote))
+}
+
+fn mock_msg1() -> DcapRaMsg1 {
+    let initiator = Initiator::new().unwrap();
+    DcapRaMsg1 {
+        pub_key_a: initiator.get_ga().unwrap(),
+        ..Default::default()
+    }
+}
+
+// Drives a responder up to `Msg2Gened` and returns it with the initiator key.
+fn responder_at_msg2() -> (Responder, EcPublicKey) {
+    let responder = Responder::new().unwrap();
+    let qe_target = TargetInfo::for_self().unwrap();
+    let msg1 = mock_msg1();
+
+    let (_, report, nonce) = responder.process_msg1(&msg1, &qe_target).unwrap();
+    let (qe_report, quote) = mock_qe(&report, &nonce).unwrap();
+    responder.generate_msg2(&qe_report, &quote).unwrap();
+    (responder, msg1.pub_key_a)
+}
+
+fn session_smk(responder: &Responder) -> AlignKey128bit {
+    RESPONDER_SESSION_MAGAGER
+        .read()
+        .find(responder.rctx)
+        .unwrap()
+        .context
+        .lock()
+        .smk_key
+}
+
+fn session_sk(responder: &Responder) -> AlignKey128bit {
+    RESPONDER_SESSION_MAGAGER
+        .read()
+        .find(responder.rctx)
+        .unwrap()
+        .context
+        .lock()
+        .sk_key
+}
+
+// Builds a msg3 carrying a valid CMAC under the session's SMK.
+fn mock_msg3(responder: &Responder, pub_key_a: EcPublicKey) -> DcapRaMsg3 {
+    mock_msg3_with_quote(responder, pub_key_a, mock_quote(Report::get_self()))
+}
+
+fn mock_msg3_with_quote(
+    responder: &Responder,
+    pub_key_a: EcPublicKey,
+    quote: Vec<u8>,
+) -> DcapRaMsg3 {
+    let mut msg3 = DcapRaMsg3 {
+        mac: Default::default(),
+        pub_key_a,
+        quote: quote.into_boxed_slice(),
+        sealed: None,
+    };
+    msg3.gen_cmac(&session_smk(responder)).unwrap();
+    msg3
+}
+
+// Stands in for a successful msg3, which needs a genuine QvE report, and
+// returns the confirmation MAC the initiator would send.
+fn establish(responder: &Responder) -> Mac {
+    let session = RESPONDER_SESSION_MAGAGER
+        .read()
+        .find(responder.rctx)
+        .unwrap();
+    let mut context = session.context.lock();
+    context.state = From::from(ResponderState::Established);
+    confirm_mac(&context.vk_key, &context.pub_key_a, &context.pub_key_b).unwrap()
+}
+
+fn mock_qve_report_info(qve_report: &Report) -> QveReportInfo<'_, '_> {
+    QveReportInfo {
+        qve_report,
+        expiration_time: 0,
+        collateral_expiration_status: 0,
+        quote_verification_result: QlQvResult::Ok,
+        qve_nonce: QuoteNonce::default(),
+        supplemental_data: None,
+        qve_status: SgxStatus::Success,
+    }
+}
+
+#[test_case]
+fn handle_request_returns_msg2() {
+    let responder = Responder::new().unwrap();
+    let qe_target = TargetInfo::for_self().unwrap();
+    let msg1 = mock_msg1().to_bytes().unwrap();
+
+    let msg2 = responder
+        .handle_request(&msg1, &qe_target, mock_qe)
+        .unwrap();
+    let msg2 = DcapMRaMsg2::from_slice(&msg2).unwrap();
+    assert_eq!(msg2.kdf_id, 0x0001);
+    assert_eq!(msg2.quote.len(), MOCK_QUOTE_LEN);
+}
+
+#[test_case]
+fn handle_request_rejects_truncated_msg1() {
+    let responder = Responder::new().unwrap();
+    let qe_target = TargetInfo::for_self().unwrap();
+    let msg1 = mock_msg1().to_bytes().unwrap();
+
+    let result = responder.handle_request(&msg1[..msg1.len() - 1], &qe_target, mock_qe);
+    assert_eq!(result.unwrap_err(), SgxStatus::InvalidParameter);
+}
+
+#[test_case]
+fn handle_request_propagates_qe_failure() {
+    let responder = Responder::new().unwrap();
+    let qe_target = TargetInfo::for_self().unwrap();
+    let msg1 = mock_msg1().to_bytes().unwrap();
+
+    let result =
+        responder.handle_request(&msg1, &qe_target, |_, _| Err(SgxStatus::ServiceUnavailable));
+    assert_eq!(result.unwrap_err(), SgxStatus::ServiceUnavailable);
+}
+
+#[test_case]
+fn enclave_buffers_not_flagged() {
+    let responder = Responder::new().unwrap();
+    let qe_target = TargetInfo::for_self().unwrap();
+    let msg1 = mock_msg1().to_bytes().unwrap();
+
+    assert_eq!(responder.used_host_buffers(), Ok(false));
+    responder
+        .handle_request(&msg1, &qe_target, mock_qe)
+        .unwrap();
+    assert_eq!(responder.used_host_buffers(), Ok(false));
+}
+
+#[test_case]
+fn host_buffers_flagged() {
+    let responder = Responder::new().unwrap();
+    let qe_target = TargetInfo::for_self().unwrap();
+
+    let (_, report, nonce) = responder.process_msg1(&mock_msg1(), &qe_target).unwrap();
+    let (qe_report, quote) = mock_qe(&report, &nonce).unwrap();
+
+    let mut host_quote = OcBuffer::alloc(NonZeroUsize::new(quote.len()).unwrap()).unwrap();
+    host_quote.copy_from_slice(&quote);
+    responder.generate_msg2(&qe_report, &host_quote).unwrap();
+    assert_eq!(responder.used_host_buffers(), Ok(true));
+}
+
+#[test_case]
+fn peer_pubkey_pin_mismatch() {
+    let (responder, pub_key_a) = responder_at_msg2();
+    let msg3 = mock_msg3(&responder, pub_key_a);
+    let qve_report = Report::default();
+
+    responder.expect_peer_pubkey_hash([0xff; 32]).unwrap();
+    let result = responder.process_msg3(&msg3, &mock_qve_report_info(&qve_report));
+    assert_eq!(result.unwrap_err(), SgxStatus::InvalidSignature);
+}
+
+#[test_case]
+fn peer_pubkey_pin_match() {
+    let (responder, pub_key_a) = responder_at_msg2();
+    let msg3 = mock_msg3(&responder, pub_key_a);
+    let qve_report = Report::default();
+
+    // A matching pin lets the handshake proceed to QvE report verification,
+    // which the zeroed mock report then fails.
+    responder
+        .expect_peer_pubkey_hash(Sha256::digest(&pub_key_a).unwrap().hash)
+        .unwrap();
+    let result = responder.process_msg3(&msg3, &mock_qve_report_info(&qve_report));
+    assert_eq!(result.unwrap_err(), SgxStatus::InvalidSignature);
+}
+
+#[test_case]
+fn finalize_required_before_keys() {
+    let (responder, _) = responder_at_msg2();
+    responder.require_confirmation().unwrap();
+    let confirm = establish(&responder);
+
+    assert_eq!(
+        responder.get_keys(RaKeyType::SK).unwrap_err(),
+        SgxStatus::InvalidState
+    );
+
+    let mut bad_confirm = confirm;
+    bad_confirm[0] ^= 0x01;
+    assert_eq!(
+        responder.finalize(&bad_confirm).unwrap_err(),
+        SgxStatus::MacMismatch
+    );
+    assert_eq!(
+        responder.finalize(&confirm[..8]).unwrap_err(),
+        SgxStatus::InvalidParameter
+    );
+    assert_eq!(
+        responder.get_keys(RaKeyType::MK).unwrap_err(),
+        SgxStatus::InvalidState
+    );
+
+    responder.finalize(&confirm).unwrap();
+    assert!(responder.get_keys(RaKeyType::SK).is_ok());
+    assert!(responder.get_keys(RaKeyType::MK).is_ok());
+}
+
+#[test_case]
+fn finalize_rejected_before_established() {
+    let (responder, _) = responder_at_msg2();
+    responder.require_confirmation().unwrap();
+
+    assert_eq!(
+        responder.finalize(&[0; 16]).unwrap_err(),
+        SgxStatus::InvalidState
+    );
+}
+
+#[test_case]
+fn keys_without_confirmation_policy() {
+    let (responder, _) = responder_at_msg2();
+    establish(&responder);
+
+    assert!(responder.get_keys(RaKeyType::SK).is_ok());
+}
+
+#[test_case]
+fn export_key_wrapped_unwraps() {
+    let (responder, _) = responder_at_msg2();
+    establish(&responder);
+    let hsm_key = EcKeyPair::create().unwrap();
+
+    let wrapped = responder
+        .export_key_wrapped(RaKeyType::SK, &hsm_key.public_key().into())
+        .unwrap();
+    assert_eq!(wrapped.len(), WRAPPED_KEY_SIZE);
+
+    let mut pub_key_e = [0_u8; 64];
+    pub_key_e.copy_from_slice(&wrapped[..64]);
+    let mut mac = [0_u8; 16];
+    mac.copy_from_slice(&wrapped[64..80]);
+
+    let dh_key = hsm_key
+        .private_key()
+        .shared_key(&EcPublicKey::from(pub_key_e))
+        .unwrap();
+    let wrap_key = dh_key.derive_key(WRAP_LABEL).unwrap();
+    let mut key = [0_u8; 16];
+    AesGcm::new(&wrap_key.key, Nonce::zeroed(), Aad::from(pub_key_e))
+        .unwrap()
+        .decrypt(&wrapped[80..], &mut key, &mac)
+        .unwrap();
+
+    assert_eq!(key, responder.get_keys(RaKeyType::SK).unwrap().key);
+}
+
+#[test_case]
+fn export_key_wrapped_requires_established() {
+    let (responder, _) = responder_at_msg2();
+    let hsm_key = EcKeyPair::create().unwrap();
+
+    let result = responder.export_key_wrapped(RaKeyType::SK, &hsm_key.public_key().into());
+    assert_eq!(result.unwrap_err(), SgxStatus::InvalidState);
+}
+
+#[test_case]
+fn evidence_bundle_roundtrip() {
+    let (responder, _) = responder_at_msg2();
+    establish(&responder);
+    responder.set_evidence_collateral(b"collateral").unwrap();
+
+    let bytes = responder.evidence_bundle().unwrap();
+    let bundle = EvidenceBundle::from_bytes(&bytes).unwrap();
+    let pub_key_b = RESPONDER_SESSION_MAGAGER
+        .read()
+        .find(responder.rctx)
+        .unwrap()
+        .context
+        .lock()
+        .pub_key_b;
+
+    assert_eq!(bundle.version, 1);
+    assert_eq!(bundle.pub_key, pub_key_b);
+    assert_eq!(bundle.quote.len(), MOCK_QUOTE_LEN);
+    assert_eq!(bundle.collateral, b"collateral");
+    assert_eq!(bundle.to_bytes().unwrap(), bytes);
+
+    let mk_key = responder.get_keys(RaKeyType::MK).unwrap();
+    bundle.verify_cmac(&mk_key).unwrap();
+
+    let mut tampered = bundle.clone();
+    tampered.collateral[0] ^= 0x01;
+    assert_eq!(
+        tampered.verify_cmac(&mk_key).unwrap_err(),
+        SgxStatus::MacMismatch
+    );
+    assert_eq!(
+        EvidenceBundle::from_bytes(&bytes[..bytes.len() - 1]).unwrap_err(),
+        SgxStatus::InvalidParameter
+    );
+}
+
+#[test_case]
+fn evidence_bundle_requires_established() {
+    let (responder, _) = responder_at_msg2();
+    assert_eq!(
+        responder.evidence_bundle().unwrap_err(),
+        SgxStatus::InvalidState
+    );
+}
+
+#[test_case]
+fn min_protocol_version_accepts() {
+    let responder = Responder::new().unwrap();
+    let qe_target = TargetInfo::for_self().unwrap();
+    responder.set_min_protocol_version(2).unwrap();
+
+    let msg1 = DcapRaMsg1 {
+        protocol_version: 2,
+        ..mock_msg1()
+    };
+    let bytes = msg1.to_bytes().unwrap();
+    let parsed = DcapRaMsg1::from_slice(&bytes).unwrap();
+    assert_eq!(parsed.protocol_version, 2);
+    assert!(responder.process_msg1(&parsed, &qe_target).is_ok());
+}
+
+#[test_case]
+fn min_protocol_version_rejects_downgrade() {
+    let responder = Responder::new().unwrap();
+    let qe_target = TargetInfo::for_self().unwrap();
+    responder.set_min_protocol_version(2).unwrap();
+
+    let result = responder.handle_request(&mock_msg1().to_bytes().unwrap(), &qe_target, mock_qe);
+    assert_eq!(result.unwrap_err(), SgxStatus::InvalidVersion);
+}
+
+#[test_case]
+fn peer_identity_is_parsed_once() {
+    let (responder, _) = responder_at_msg2();
+    establish(&responder);
+    {
+        let session = RESPONDER_SESSION_MAGAGER
+            .read()
+            .find(responder.rctx)
+            .unwrap();
+        let mut context = session.context.lock();
+        context.qv_result = Some(QlQvResult::Ok);
+        context.peer_report = Some(Report::for_self().unwrap().body);
+    }
+
+    let parses = IDENTITY_PARSES.load(Ordering::Relaxed);
+    let (_, first) = responder.get_peer_identity().unwrap();
+    let (_, second) = responder.get_peer_identity().unwrap();
+    assert_eq!(first, second);
+    assert_eq!(IDENTITY_PARSES.load(Ordering::Relaxed), parses + 1);
+
+    responder.forget_peer_identity().unwrap();
+    let (_, third) = responder.get_peer_identity().unwrap();
+    assert_eq!(first, third);
+    assert_eq!(IDENTITY_PARSES.load(Ordering::Relaxed), parses + 2);
+}
+
+#[test_case]
+fn msg3_malformed_quote_is_parse_error() {
+    let (responder, pub_key_a) = responder_at_msg2();
+    let qve_report = Report::default();
+
+    let mut quote = mock_quote(Report::get_self());
+    quote.pop();
+    let msg3 = mock_msg3_with_quote(&responder, pub_key_a, quote);
+    let result = responder.process_msg3(&msg3, &mock_qve_report_info(&qve_report));
+    assert_eq!(
+        result.unwrap_err(),
+        SgxStatus::from(QuoteParseError::SignatureLength)
+    );
+}
+
+#[test_case]
+fn msg3_failed_qve_report_is_verify_error() {
+    let (responder, pub_key_a) = responder_at_msg2();
+    let msg3 = mock_msg3(&responder, pub_key_a);
+    let qve_report = Report::default();
+
+    let result = responder.process_msg3(&msg3, &mock_qve_report_info(&qve_report));
+    assert_eq!(result.unwrap_err(), SgxStatus::InvalidSignature);
+}
+
+#[test_case]
+fn release_keys_zeroes_context() {
+    let (responder, _) = responder_at_msg2();
+    assert_eq!(
+        responder.release_keys().unwrap_err(),
+        SgxStatus::InvalidState
+    );
+
+    establish(&responder);
+    assert!(responder.get_keys(RaKeyType::SK).is_ok());
+    responder.release_keys().unwrap();
+
+    {
+        let session = RESPONDER_SESSION_MAGAGER
+            .read()
+            .find(responder.rctx)
+            .unwrap();
+        let context = session.context.lock();
+        assert_eq!(context.sk_key.key, [0; 16]);
+        assert_eq!(context.mk_key.key, [0; 16]);
+        assert_eq!(context.vk_key.key, [0; 16]);
+    }
+    assert_eq!(
+        responder.get_keys(RaKeyType::SK).unwrap_err(),
+        SgxStatus::InvalidState
+    );
+    assert_eq!(
+        responder.get_keys(RaKeyType::MK).unwrap_err(),
+        SgxStatus::InvalidState
+    );
+    assert_eq!(
+        responder.evidence_bundle().unwrap_err(),
+        SgxStatus::InvalidState
+    );
+}
+
+#[test_case]
+fn msg3_secret_roundtrip() {
+    let qve_report = Report::default();
+    let qve_report_info = mock_qve_report_info(&qve_report);
+    let secret = b"initiator secret";
+
+    let (responder, pub_key_a) = responder_at_msg2();
+    let mut msg3 = mock_msg3(&responder, pub_key_a);
+    assert_eq!(
+        responder
+            .process_msg3_unseal(&msg3, &qve_report_info)
+            .unwrap_err(),
+        SgxStatus::InvalidParameter
+    );
+
+    // A secret that fails to decrypt leaves the session unestablished.
+    let encrypted = encrypt_secret(&session_sk(&responder), secret).unwrap();
+    let mut tampered = encrypted.clone();
+    *tampered.last_mut().unwrap() ^= 1;
+    msg3.sealed = Some(tampered.into_boxed_slice());
+    msg3.gen_cmac(&session_smk(&responder)).unwrap();
+    assert_eq!(
+        responder
+            .process_msg3_unseal(&msg3, &qve_report_info)
+            .unwrap_err(),
+        SgxStatus::MacMismatch
+    );
+    assert_eq!(
+        responder.get_keys(RaKeyType::SK).unwrap_err(),
+        SgxStatus::InvalidState
+    );
+
+    // The secret is withheld while the peer fails verification.
+    msg3.sealed = Some(encrypted.into_boxed_slice());
+    msg3.gen_cmac(&session_smk(&responder)).unwrap();
+    let msg3 = DcapRaMsg3::from_slice(&msg3.to_bytes().unwrap()).unwrap();
+    assert_eq!(
+        responder
+            .process_msg3_unseal(&msg3, &qve_report_info)
+            .unwrap_err(),
+        SgxStatus::InvalidSignature
+    );
+
+    // A cached verdict stands in for the QvE to reach a verified peer.
+    FAKE_SECS.store(12_000, Ordering::Relaxed);
+    let platform = cache_platform(verdict(QlQvResult::Ok));
+    let (responder, pub_key_a) = responder_at_msg2();
+    responder.set_verification_cache(&CACHE).unwrap();
+    let (quote, identity) = bound_quote(&responder, &platform);
+    let mut msg3 = mock_msg3_with_quote(&responder, pub_key_a, quote);
+    let encrypted = encrypt_secret(&session_sk(&responder), secret).unwrap();
+    msg3.sealed = Some(encrypted.into_boxed_slice());
+    msg3.gen_cmac(&session_smk(&responder)).unwrap();
+    let (peer, received) = responder
+        .process_msg3_unseal(&msg3, &qve_report_info)
+        .unwrap();
+    CACHE.clear();
+    assert_eq!(peer.mr_enclave, identity.mr_enclave);
+    assert_eq!(&*received, &secret[..]);
+}
+
+struct CountingAlloc {
+    allocs: AtomicUsize,
+    deallocs: AtomicUsize,
+}
+
+unsafe impl Allocator for CountingAlloc {
+    fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        self.allocs.fetch_add(1, Ordering::Relaxed);
+        Global.allocate(layout)
+    }
+
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        self.deallocs.fetch_add(1, Ordering::Relaxed);
+        Global.deallocate(ptr, layout)
+    }
+}
+
+static COUNTING_ALLOC: CountingAlloc = CountingAlloc {
+    allocs: AtomicUsize::new(0),
+    deallocs: AtomicUsize::new(0),
+};
+
+#[test_case]
+fn session_uses_custom_allocator() {
+    let allocs = COUNTING_ALLOC.allocs.load(Ordering::Relaxed);
+    let deallocs = COUNTING_ALLOC.deallocs.load(Ordering::Relaxed);
+
+    let responder = Responder::new_in(&COUNTING_ALLOC).unwrap();
+    assert_eq!(COUNTING_ALLOC.allocs.load(Ordering::Relaxed), allocs + 1);
+
+    let qe_target = TargetInfo::for_self().unwrap();
+    responder.process_msg1(&mock_msg1(), &qe_target).unwrap();
+    assert_eq!(COUNTING_ALLOC.allocs.load(Ordering::Relaxed), allocs + 1);
+
+    drop(responder);
+    assert_eq!(
+        COUNTING_ALLOC.deallocs.load(Ordering::Relaxed),
+        deallocs + 1
+    );
+}
+
+static FAKE_SECS: AtomicU64 = AtomicU64::new(0);
+
+fn fake_clock() -> SgxResult<Duration> {
+    Ok(Duration::from_secs(FAKE_SECS.load(Ordering::Relaxed)))
+}
+
+fn report_after(secs: u64) -> SgxResult<DcapMRaMsg2> {
+    let responder = Responder::new().unwrap();
+    responder.set_trusted_clock(fake_clock).unwrap();
+    responder
+        .set_report_max_age(Duration::from_secs(10))
+        .unwrap();
+
+    FAKE_SECS.store(100, Ordering::Relaxed);
+    let qe_target = TargetInfo::for_self().unwrap();
+    let (_, report, nonce) = responder.process_msg1(&mock_msg1(), &qe_target).unwrap();
+    let (qe_report, quote) = mock_qe(&report, &nonce).unwrap();
+
+    FAKE_SECS.store(100 + secs, Ordering::Relaxed);
+    responder.generate_msg2(&qe_report, &quote)
+}
+
+#[test_case]
+fn fresh_report_accepted() {
+    assert!(report_after(10).is_ok());
+}
+
+#[test_case]
+fn aged_report_rejected() {
+    assert_eq!(report_after(11).unwrap_err(), SgxStatus::ServiceTimeout);
+}
+
+#[test_case]
+fn qve_target_info_matches_report_check() {
+    let responder = Responder::new().unwrap();
+    let target = responder.qve_target_info().unwrap();
+    assert_eq!(
+        target.mr_enclave,
+        TargetInfo::for_self().unwrap().mr_enclave
+    );
+
+    // A report produced for the returned target passes the same
+    // `Report::verify` the QvE report goes through in process_msg3.
+    let qve_report = Report::for_target(&target, &ReportData::default()).unwrap();
+    assert!(qve_report.verify().is_ok());
+}
+
+fn recorded_messages() -> (Vec<u8>, Vec<u8>) {
+    let (responder, pub_key_a) = responder_at_msg2();
+    let report = Report::for_target(&TargetInfo::for_self().unwrap(), &ReportData::default());
+    let msg3 = mock_msg3_with_quote(&responder, pub_key_a, mock_quote(&report.unwrap()));
+
+    let msg1 = DcapRaMsg1 {
+        pub_key_a,
+        ..Default::default()
+    };
+    (msg1.into_bytes().unwrap(), msg3.into_bytes().unwrap())
+}
+
+#[test_case]
+fn dry_run_good_messages() {
+    let (msg1, msg3) = recorded_messages();
+    let qe_target = TargetInfo::for_self().unwrap();
+    let qve_report = Report::default();
+
+    let report = dry_run_handshake(
+        &msg1,
+        Msg2Inputs {
+            qe_target: &qe_target,
+            get_quote: mock_qe,
+        },
+        &msg3,
+        &mock_qve_report_info(&qve_report),
+    )
+    .unwrap();
+    assert_eq!(report.msg1, Ok(()));
+    assert_eq!(report.msg2, Ok(()));
+    // Only a genuine QvE report passes, which a test enclave cannot produce.
+    assert_eq!(report.msg3, Err(SgxStatus::InvalidSignature));
+}
+
+#[test_case]
+fn dry_run_bad_messages() {
+    let (msg1, msg3) = recorded_messages();
+    let qe_target = TargetInfo::for_self().unwrap();
+    let qve_report = Report::default();
+
+    let report = dry_run_handshake(
+        &msg1[..msg1.len() - 2],
+        Msg2Inputs {
+            qe_target: &qe_target,
+            get_quote: mock_qe,
+        },
+        &msg3,
+        &mock_qve_report_info(&qve_report),
+    )
+    .unwrap();
+    assert_eq!(report.msg1, Err(SgxStatus::InvalidParameter));
+    assert_eq!(report.msg2, Err(SgxStatus::InvalidState));
+    assert_eq!(report.msg3, Err(SgxStatus::InvalidState));
+    assert!(!report.is_ok());
+
+    let other_msg1 = mock_msg1().into_bytes().unwrap();
+    let report = dry_run_handshake(
+        &other_msg1,
+        Msg2Inputs {
+            qe_target: &qe_target,
+            get_quote: |_: &Report, _: &QuoteNonce| Err(SgxStatus::Unexpected),
+        },
+        &msg3,
+        &mock_qve_report_info(&qve_report),
+    )
+    .unwrap();
+    assert_eq!(report.msg1, Ok(()));
+    assert_eq!(report.msg2, Err(SgxStatus::Unexpected));
+    assert_eq!(report.msg3, Err(SgxStatus::Unexpected));
+}
+
+static LIMITER: TokenBucket = TokenBucket::new(1, Duration::from_secs(60), fake_clock);
+
+#[test_case]
+fn rate_limited_source_busy() {
+    FAKE_SECS.store(5000, Ordering::Relaxed);
+    let qe_target = TargetInfo::for_self().unwrap();
+    let limited = || {
+        let responder = Responder::new().unwrap();
+        responder.set_rate_limiter(&LIMITER, 7).unwrap();
+        responder.process_msg1(&mock_msg1(), &qe_target).map(|_| ())
+    };
+
+    assert!(limited().is_ok());
+    assert_eq!(limited(), Err(SgxStatus::ServiceBusy));
+
+    FAKE_SECS.store(5060, Ordering::Relaxed);
+    assert!(limited().is_ok());
+}
+
+#[test_case]
+fn issued_receipt_verifies() {
+    let (responder, _) = responder_at_msg2();
+    assert_eq!(
+        responder.issue_receipt(&EcKeyPair::create().unwrap().private_key()),
+        Err(SgxStatus::InvalidState)
+    );
+
+    establish(&responder);
+    let peer = Report::for_self().unwrap().body;
+    {
+        let session = RESPONDER_SESSION_MAGAGER
+            .read()
+            .find(responder.rctx)
+            .unwrap();
+        let mut context = session.context.lock();
+        context.peer_report = Some(peer);
+        context.qv_result = Some(QlQvResult::OutOfDate);
+    }
+
+    let key_pair = EcKeyPair::create().unwrap();
+    assert_eq!(
+        responder.issue_receipt(&key_pair.private_key()),
+        Err(SgxStatus::UnsupportedFeature)
+    );
+
+    FAKE_SECS.store(42, Ordering::Relaxed);
+    responder.set_trusted_clock(fake_clock).unwrap();
+    let bytes = responder.issue_receipt(&key_pair.private_key()).unwrap();
+
+    let receipt = HandshakeReceipt::from_bytes(&bytes).unwrap();
+    assert!(receipt.verify(&key_pair.public_key()).is_ok());
+    assert_eq!(receipt.peer.mr_enclave, peer.mr_enclave);
+    assert_eq!(receipt.qv_result, QlQvResult::OutOfDate);
+    assert_eq!(receipt.time, 42);
+}
+
+#[test_case]
+fn qve_run_failure_distinct_from_verdict() {
+    let qve_report = Report::default();
+
+    let (responder, pub_key_a) = responder_at_msg2();
+    let msg3 = mock_msg3(&responder, pub_key_a);
+    let qve_report_info = mock_qve_report_info(&qve_report).with_qve_status(SgxStatus::EnclaveLost);
+    assert_eq!(
+        responder.process_msg3(&msg3, &qve_report_info).unwrap_err(),
+        SgxStatus::from(QuoteVerifyError::QveNotRun)
+    );
+
+    // A QvE that ran and reported an out-of-date TCB goes on to have its
+    // report checked, which the zeroed mock report then fails.
+    let (responder, pub_key_a) = responder_at_msg2();
+    let msg3 = mock_msg3(&responder, pub_key_a);
+    let mut qve_report_info = mock_qve_report_info(&qve_report);
+    qve_report_info.quote_verification_result = QlQvResult::OutOfDate;
+    assert_eq!(
+        responder.process_msg3(&msg3, &qve_report_info).unwrap_err(),
+        SgxStatus::InvalidSignature
+    );
+}
+
+static CONFIG_ROOTS: [Cert<'static>; 1] = [Cert { der: b"root" }];
+
+#[test_case]
+fn config_applies_each_field() {
+    let config = ResponderConfig {
+        min_tcb_eval_data_number: Some(17),
+        trusted_clock: Some(fake_clock),
+        report_max_age: Some(Duration::from_secs(30)),
+        min_protocol_version: 2,
+        require_confirmation: true,
+        peer_pubkey_hash: Some([0xff; 32]),
+        trusted_roots: Some(&CONFIG_ROOTS),
+        verification_cache: Some(&CACHE),
+        accepted_qv_results: Some(&[QlQvResult::Ok]),
+        ..Default::default()
+    };
+
+    let responder = Responder::with_config(config).unwrap();
+    {
+        let session = RESPONDER_SESSION_MAGAGER
+            .read()
+            .find(responder.rctx)
+            .unwrap();
+        let context = session.context.lock();
+        assert_eq!(context.min_tcb_eval_data_number, Some(17));
+        assert!(context.clock.is_some());
+        assert!(context.rate_limiter.is_none());
+        assert_eq!(context.report_max_age, Some(Duration::from_secs(30)));
+        assert!(context.platform_flags_policy.is_none());
+        assert!(context.confirm_required);
+        assert_eq!(context.peer_pubkey_hash.unwrap().hash, [0xff; 32]);
+        let roots = context.trusted_roots.as_ref().unwrap();
+        assert_eq!(roots.len(), 1);
+        assert_eq!(&*roots[0], b"root");
+        assert!(context.verification_cache.is_some());
+        assert!(context.accepted_qv_results.is_some());
+    }
+
+    // A policy the setters would refuse fails the whole config.
+    for config in [
+        ResponderConfig {
+            trusted_roots: Some(&[]),
+            ..config
+        },
+        ResponderConfig {
+            accepted_qv_results: Some(&[]),
+            ..config
+        },
+    ] {
+        assert_eq!(
+            Responder::with_config(config).unwrap_err(),
+            SgxStatus::InvalidParameter
+        );
+    }
+
+    let qe_target = TargetInfo::for_self().unwrap();
+    assert_eq!(
+        responder
+            .process_msg1(&mock_msg1(), &qe_target)
+            .unwrap_err(),
+        SgxStatus::InvalidVersion
+    );
+
+    // The same config can be reused; the default one leaves policy unset.
+    let responder = Responder::with_config(ResponderConfig {
+        min_protocol_version: 0,
+        ..config
+    })
+    .unwrap();
+    assert!(responder.process_msg1(&mock_msg1(), &qe_target).is_ok());
+    let responder = Responder::with_config(ResponderConfig::default()).unwrap();
+    let session = RESPONDER_SESSION_MAGAGER
+        .read()
+        .find(responder.rctx)
+        .unwrap();
+    let context = session.context.lock();
+    assert!(!context.confirm_required);
+    assert!(context.peer_pubkey_hash.is_none());
+    assert!(context.trusted_roots.is_none());
+    assert!(context.verification_cache.is_none());
+    assert!(context.accepted_qv_results.is_none());
+}
+
+#[test_case]
+fn abort_clears_secrets_and_fails_closed() {
+    let (responder, pub_key_a) = responder_at_msg2();
+    let msg3 = mock_msg3(&responder, pub_key_a);
+    responder.abort().unwrap();
+
+    {
+        let session = RESPONDER_SESSION_MAGAGER
+            .read()
+            .find(responder.rctx)
+            .unwrap();
+        let context = session.context.lock();
+        assert!(context.state.check_responder_state(ResponderState::Aborted));
+        assert_eq!(context.priv_key, EcPrivateKey::default());
+        for key in [
+            context.smk_key,
+            context.sk_key,
+            context.mk_key,
+            context.vk_key,
+        ] {
+            assert_eq!(key.key, [0; 16]);
+        }
+        assert_eq!(context.quote_nonce.rand, [0; 16]);
+    }
+
+    let qve_report = Report::default();
+    let qe_target = TargetInfo::for_self().unwrap();
+    assert_eq!(
+        responder
+            .process_msg1(&mock_msg1(), &qe_target)
+            .unwrap_err(),
+        SgxStatus::InvalidState
+    );
+    assert_eq!(
+        responder
+            .process_msg3(&msg3, &mock_qve_report_info(&qve_report))
+            .unwrap_err(),
+        SgxStatus::InvalidState
+    );
+    assert_eq!(
+        responder.get_keys(RaKeyType::SK).unwrap_err(),
+        SgxStatus::InvalidState
+    );
+    assert_eq!(
+        responder.get_peer_identity().unwrap_err(),
+        SgxStatus::InvalidState
+    );
+    assert_eq!(
+        responder.qve_target_info().unwrap_err(),
+        SgxStatus::InvalidState
+    );
+    assert_eq!(
+        responder.set_min_protocol_version(1),
+        Err(SgxStatus::InvalidState)
+    );
+    assert_eq!(responder.abort(), Err(SgxStatus::InvalidState));
+}
+
+#[test_case]
+fn trusted_roots_checked_before_qve() {
+    let (responder, pub_key_a) = responder_at_msg2();
+    let msg3 = mock_msg3(&responder, pub_key_a);
+    let qve_report = Report::default();
+
+    assert_eq!(
+        responder.set_trusted_roots(&[]),
+        Err(SgxStatus::InvalidParameter)
+    );
+    // The mock quote carries no PCK chain, so with roots pinned it is
+    // refused before the QvE verdict is looked at.
+    let root = [0x30_u8, 0x03, 0x02, 0x01, 0x00];
+    responder.set_trusted_roots(&[Cert::new(&root)]).unwrap();
+    let result = responder.process_msg3(&msg3, &mock_qve_report_info(&qve_report));
+    assert_eq!(result.unwrap_err(), SgxStatus::InvalidParameter);
+}
+
+#[test_case]
+fn larger_quote_costs_more() {
+    let qe_target = TargetInfo::for_self().unwrap();
+    let msg1 = mock_msg1().to_bytes().unwrap();
+
+    let small = Responder::new().unwrap();
+    assert_eq!(small.estimated_cost(), Err(SgxStatus::InvalidState));
+    small.handle_request(&msg1, &qe_target, mock_qe).unwrap();
+
+    let large = Responder::new().unwrap();
+    large
+        .handle_request(&msg1, &qe_target, |report, nonce| {
+            let mut quote = mock_quote(report);
+            quote.resize(MOCK_QUOTE_LEN + 1024, 0);
+            mock_qe_for(quote, nonce)
+        })
+        .unwrap();
+
+    let small = small.estimated_cost().unwrap();
+    let large = large.estimated_cost().unwrap();
+    assert_eq!(small.quote_size, MOCK_QUOTE_LEN);
+    assert_eq!(large.quote_size, MOCK_QUOTE_LEN + 1024);
+    assert!(large.hash_bytes > small.hash_bytes);
+    assert_eq!(large.ecdh_ops, small.ecdh_ops);
+}
+
+#[test_case]
+fn retry_msg3_after_transient_failure() {
+    let qve_report = Report::default();
+    let (responder, pub_key_a) = responder_at_msg2();
+    let msg3 = mock_msg3(&responder, pub_key_a);
+
+    let qve_report_info = mock_qve_report_info(&qve_report);
+    assert_eq!(
+        responder.retry_msg3(&msg3, &qve_report_info).unwrap_err(),
+        SgxStatus::InvalidState
+    );
+
+    let lost = mock_qve_report_info(&qve_report).with_qve_status(SgxStatus::EnclaveLost);
+    assert_eq!(
+        responder.process_msg3(&msg3, &lost).unwrap_err(),
+        SgxStatus::ServiceUnavailable
+    );
+
+    // The retry reuses the msg1 keys, so the CMAC still verifies and the
+    // QvE report is checked; a genuine one would establish the session.
+    assert_eq!(
+        responder.retry_msg3(&msg3, &qve_report_info).unwrap_err(),
+        SgxStatus::InvalidSignature
+    );
+    let session = RESPONDER_SESSION_MAGAGER
+        .read()
+        .find(responder.rctx)
+        .unwrap();
+    assert!(session
+        .context
+        .lock()
+        .state
+        .check_responder_state(ResponderState::Msg2Gened));
+
+    // A rejected QvE report is not transient and cannot be retried.
+    assert_eq!(
+        responder.retry_msg3(&msg3, &qve_report_info).unwrap_err(),
+        SgxStatus::InvalidState
+    );
+}
+
+static CACHE: VerificationCache = VerificationCache::new(Duration::from_secs(60), fake_clock);
+
+// Builds a quote for `report` signed with the attestation key of a mock
+// platform, so quotes for different reports on one platform share
+// everything after the ISV enclave signature.
+fn signed_quote(report: &Report, platform: &EcKeyPair) -> Vec<u8> {
+    let mut quote = mock_quote(report);
+    let signed_len = mem::size_of::<QuoteHeader>() + mem::size_of::<ReportBody>();
+    let signature: [u8; 64] = platform
+        .private_key()
+        .sign(&quote[..signed_len])
+        .unwrap()
+        .into();
+    let attest_pub_key: [u8; 64] = platform.public_key().into();
+
+    let sig_data = mem::size_of::<Quote3>();
+    quote[sig_data..sig_data + 64].copy_from_slice(&swap_coordinates(&signature));
+    quote[sig_data + 64..sig_data + 128].copy_from_slice(&swap_coordinates(&attest_pub_key));
+    quote
+}
+
+// Builds a quote whose report data binds the session keys, as the peer's
+// quote in msg3 would.
+fn bound_quote(responder: &Responder, platform: &EcKeyPair) -> (Vec<u8>, EnclaveIdentity) {
+    let session = RESPONDER_SESSION_MAGAGER
+        .read()
+        .find(responder.rctx)
+        .unwrap();
+    let context = session.context.lock();
+    let mut sha = Sha256::new().unwrap();
+    sha.update(&context.pub_key_a).unwrap();
+    sha.update(&context.pub_key_b).unwrap();
+    sha.update(&context.vk_key).unwrap();
+    drop(context);
+
+    let mut report_data = ReportData::default();
+    report_data.d[..SHA256_HASH_SIZE].copy_from_slice(&sha.finalize().unwrap());
+    let report = Report::for_target(&TargetInfo::for_self().unwrap(), &report_data).unwrap();
+    (
+        signed_quote(&report, platform),
+        EnclaveIdentity::from(report),
+    )
+}
+
+fn verdict(qv_result: QlQvResult) -> CachedVerdict {
+    CachedVerdict {
+        qv_result,
+        supplemental: None,
+    }
+}
+
+// Caches `verdict` for a new mock platform as an earlier handshake with it
+// would have, through the quote of that handshake, and returns the platform.
+fn cache_platform(verdict: CachedVerdict) -> EcKeyPair {
+    let platform = EcKeyPair::create().unwrap();
+    let (earlier, _) = responder_at_msg2();
+    let (quote, _) = bound_quote(&earlier, &platform);
+    CACHE.insert(&quote, verdict, Duration::MAX).unwrap();
+    platform
+}
+
+#[test_case]
+fn cached_verdict_used_until_ttl() {
+    let qve_report = Report::default();
+    let qve_report_info = mock_qve_report_info(&qve_report);
+    FAKE_SECS.store(10_000, Ordering::Relaxed);
+
+    // The QvE accepted the quote of handshake A.
+    let platform = EcKeyPair::create().unwrap();
+    let (responder_a, _) = responder_at_msg2();
+    let (quote_a, _) = bound_quote(&responder_a, &platform);
+    CACHE
+        .insert(&quote_a, verdict(QlQvResult::OutOfDate), Duration::MAX)
+        .unwrap();
+
+    // Handshake B from the same platform reuses the verdict; the zeroed QvE
+    // report is never looked at.
+    let (responder, pub_key_a) = responder_at_msg2();
+    responder.set_verification_cache(&CACHE).unwrap();
+    let (quote, identity) = bound_quote(&responder, &platform);
+    assert_ne!(quote, quote_a);
+    let msg3 = mock_msg3_with_quote(&responder, pub_key_a, quote);
+    let peer = responder.process_msg3(&msg3, &qve_report_info).unwrap();
+    assert_eq!(peer.mr_enclave, identity.mr_enclave);
+    let (qv_result, _) = responder.get_peer_identity().unwrap();
+    assert_eq!(qv_result, QlQvResult::OutOfDate);
+    // The collateral behind a cached verdict is not known to this session.
+    assert_eq!(
+        responder.get_collateral_status(),
+        Err(SgxStatus::Unexpected)
+    );
+
+    // A hit still needs a valid ISV enclave signature, which the verdict
+    // does not cover.
+    let (responder, pub_key_a) = responder_at_msg2();
+    responder.set_verification_cache(&CACHE).unwrap();
+    let (mut quote, _) = bound_quote(&responder, &platform);
+    quote[mem::size_of::<Quote3>()] ^= 1;
+    let msg3 = mock_msg3_with_quote(&responder, pub_key_a, quote);
+    assert_eq!(
+        responder.process_msg3(&msg3, &qve_report_info).unwrap_err(),
+        SgxStatus::from(QuoteVerifyError::QuoteSignature)
+    );
+
+    // A quote signed by another attestation key misses and goes to the QvE
+    // checks.
+    let (responder, pub_key_a) = responder_at_msg2();
+    responder.set_verification_cache(&CACHE).unwrap();
+    let (quote, _) = bound_quote(&responder, &EcKeyPair::create().unwrap());
+    let msg3 = mock_msg3_with_quote(&responder, pub_key_a, quote);
+    assert_eq!(
+        responder.process_msg3(&msg3, &qve_report_info).unwrap_err(),
+        SgxStatus::InvalidSignature
+    );
+
+    // Once the entry has expired handshake C goes back to the QvE checks.
+    FAKE_SECS.store(10_060, Ordering::Relaxed);
+    let (responder, pub_key_a) = responder_at_msg2();
+    responder.set_verification_cache(&CACHE).unwrap();
+    let (quote, _) = bound_quote(&responder, &platform);
+    let msg3 = mock_msg3_with_quote(&responder, pub_key_a, quote);
+    assert_eq!(
+        responder.process_msg3(&msg3, &qve_report_info).unwrap_err(),
+        SgxStatus::InvalidSignature
+    );
+    CACHE.clear();
+}
+
+#[test_case]
+fn cached_verdict_meets_own_policy() {
+    let qve_report = Report::default();
+    let qve_report_info = mock_qve_report_info(&qve_report);
+    FAKE_SECS.store(15_000, Ordering::Relaxed);
+
+    // A verdict cached without supplemental data does not satisfy a
+    // responder that requires platform flags.
+    let platform = cache_platform(verdict(QlQvResult::Ok));
+    let (responder, pub_key_a) = responder_at_msg2();
+    responder.set_verification_cache(&CACHE).unwrap();
+    responder
+        .set_platform_flags_policy(PlatformFlagsPolicy {
+            smt_enabled: Some(false),
+            ..Default::default()
+        })
+        .unwrap();
+    let (quote, _) = bound_quote(&responder, &platform);
+    let msg3 = mock_msg3_with_quote(&responder, pub_key_a, quote);
+    assert_eq!(
+        responder.process_msg3(&msg3, &qve_report_info).unwrap_err(),
+        SgxStatus::InvalidParameter
+    );
+    CACHE.clear();
+
+    // Nor does one backed by collateral older than this responder accepts.
+    let supplemental = SupplementalData::from(QlQvSupplemental {
+        version: 3,
+        tcb_eval_ref_num: 15,
+        ..Default::default()
+    });
+    let platform = cache_platform(CachedVerdict {
+        supplemental: Some(supplemental),
+        ..verdict(QlQvResult::Ok)
+    });
+    let (responder, pub_key_a) = responder_at_msg2();
+    responder.set_verification_cache(&CACHE).unwrap();
+    responder.set_min_tcb_eval_data_number(16).unwrap();
+    let (quote, _) = bound_quote(&responder, &platform);
+    let msg3 = mock_msg3_with_quote(&responder, pub_key_a, quote);
+    assert_eq!(
+        responder.process_msg3(&msg3, &qve_report_info).unwrap_err(),
+        SgxStatus::from(QuoteVerifyError::TcbOutOfDate)
+    );
+
+    // A responder whose policy the cached data meets accepts the hit.
+    let (responder, pub_key_a) = responder_at_msg2();
+    responder.set_verification_cache(&CACHE).unwrap();
+    responder.set_min_tcb_eval_data_number(15).unwrap();
+    let (quote, _) = bound_quote(&responder, &platform);
+    let msg3 = mock_msg3_with_quote(&responder, pub_key_a, quote);
+    responder.process_msg3(&msg3, &qve_report_info).unwrap();
+    CACHE.clear();
+}
+
+#[test_case]
+fn quote_size_known_after_msg2() {
+    let responder = Responder::new().unwrap();
+    assert_eq!(responder.quote_size(), Err(SgxStatus::InvalidState));
+
+    let qe_target = TargetInfo::for_self().unwrap();
+    let msg1 = mock_msg1().to_bytes().unwrap();
+    responder
+        .handle_request(&msg1, &qe_target, mock_qe)
+        .unwrap();
+    assert_eq!(responder.quote_size(), Ok(MOCK_QUOTE_LEN));
+
+    // Aborting drops the quote, and the accessors refuse the session.
+    responder.abort().unwrap();
+    assert_eq!(responder.quote_size(), Err(SgxStatus::InvalidState));
+    assert_eq!(responder.used_host_buffers(), Err(SgxStatus::InvalidState));
+    let session = RESPONDER_SESSION_MAGAGER
+        .read()
+        .find(responder.rctx)
+        .unwrap();
+    assert!(session.context.lock().own_quote.is_none());
+}
+
+// Records, at free time, whether the block still holds `secret`.
+struct ScrubCheckAlloc {
+    secret: SpinMutex<[u8; 16]>,
+    frees: AtomicUsize,
+    leaked: AtomicBool,
+}
+
+unsafe impl Allocator for ScrubCheckAlloc {
+    fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        Global.allocate(layout)
+    }
+
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        let block = core::slice::from_raw_parts(ptr.as_ptr(), layout.size());
+        let secret = *self.secret.lock();
+        let leaked = block.windows(secret.len()).any(|w| w == secret);
+        self.leaked.store(leaked, Ordering::Relaxed);
+        self.frees.fetch_add(1, Ordering::Relaxed);
+        Global.deallocate(ptr, layout)
+    }
+}
+
+static SCRUB_CHECK_ALLOC: ScrubCheckAlloc = ScrubCheckAlloc {
+    secret: SpinMutex::new([0; 16]),
+    frees: AtomicUsize::new(0),
+    leaked: AtomicBool::new(true),
+};
+
+#[test_case]
+fn session_memory_zeroed_on_close() {
+    let responder = Responder::new_in(&SCRUB_CHECK_ALLOC).unwrap();
+    let qe_target = TargetInfo::for_self().unwrap();
+    responder.process_msg1(&mock_msg1(), &qe_target).unwrap();
+    let smk_key = session_smk(&responder).key;
+    assert_ne!(smk_key, [0; 16]);
+    *SCRUB_CHECK_ALLOC.secret.lock() = smk_key;
+
+    let frees = SCRUB_CHECK_ALLOC.frees.load(Ordering::Relaxed);
+    drop(responder);
+    assert_eq!(SCRUB_CHECK_ALLOC.frees.load(Ordering::Relaxed), frees + 1);
+    assert!(!SCRUB_CHECK_ALLOC.leaked.load(Ordering::Relaxed));
+}
+
+#[test_case]
+fn verdict_outside_accepted_set_rejected() {
+    let qve_report = Report::default();
+    let qve_report_info = mock_qve_report_info(&qve_report);
+    FAKE_SECS.store(20_000, Ordering::Relaxed);
+
+    // The cached verdict stands in for a QvE run that returned OutOfDate.
+    let platform = cache_platform(verdict(QlQvResult::OutOfDate));
+    let (responder, pub_key_a) = responder_at_msg2();
+    responder.set_verification_cache(&CACHE).unwrap();
+    responder
+        .set_accepted_qv_results(&[QlQvResult::Ok, QlQvResult::SWHardeningNeeded])
+        .unwrap();
+    let (quote, _) = bound_quote(&responder, &platform);
+    let msg3 = mock_msg3_with_quote(&responder, pub_key_a, quote);
+    assert_eq!(
+        responder.process_msg3(&msg3, &qve_report_info).unwrap_err(),
+        SgxStatus::from(QuoteVerifyError::QvResultRejected)
+    );
+    CACHE.clear();
+
+    assert_eq!(
+        responder.set_accepted_qv_results(&[]),
+        Err(SgxStatus::InvalidParameter)
+    );
+}
+
+#[test_case]
+fn collateral_status_after_msg3() {
+    let (responder, _) = responder_at_msg2();
+    assert_eq!(
+        responder.get_collateral_status(),
+        Err(SgxStatus::InvalidState)
+    );
+
+    establish(&responder);
+    assert_eq!(
+        responder.get_collateral_status(),
+        Err(SgxStatus::Unexpected)
+    );
+    RESPONDER_SESSION_MAGAGER
+        .read()
+        .find(responder.rctx)
+        .unwrap()
+        .context
+        .lock()
+        .collateral_status = Some((1_700_000_000, 1));
+    assert_eq!(responder.get_collateral_status(), Ok((1_700_000_000, 1)));
+}
+
+#[test_case]
+fn sealed_export_survives_close() {
+    let (responder, _) = responder_at_msg2();
+    assert_eq!(responder.export_sealed(), Err(SgxStatus::InvalidState));
+
+    establish(&responder);
+    {
+        let session = RESPONDER_SESSION_MAGAGER
+            .read()
+            .find(responder.rctx)
+            .unwrap();
+        let mut context = session.context.lock();
+        context.qv_result = Some(QlQvResult::Ok);
+        context.peer_report = Some(Report::get_self().body);
+        context.peer_quote = Some(alloc::vec![0xa5; 64].into_boxed_slice());
+        context.collateral_status = Some((1_700_000_000, 0));
+        context.confirm_required = true;
+        context.confirmed = true;
+    }
+    let sk_key = responder.get_keys(RaKeyType::SK).unwrap();
+    let blob = responder.export_sealed().unwrap();
+    drop(responder);
+
+    let imported = Responder::import_sealed(&blob).unwrap();
+    assert_eq!(imported.get_keys(RaKeyType::SK).unwrap().key, sk_key.key);
+    let (qv_result, identity) = imported.get_peer_identity().unwrap();
+    assert_eq!(qv_result, QlQvResult::Ok);
+    assert_eq!(identity.mr_enclave, Report::get_self().body.mr_enclave);
+    assert_eq!(imported.peer_quote().unwrap(), alloc::vec![0xa5; 64]);
+    assert_eq!(imported.get_collateral_status(), Ok((1_700_000_000, 0)));
+    {
+        let session = RESPONDER_SESSION_MAGAGER
+            .read()
+            .find(imported.rctx)
+            .unwrap();
+        let context = session.context.lock();
+        assert!(context.confirm_required && context.confirmed);
+    }
+
+    // The same blob does not import twice, but a fresh export does.
+    assert_eq!(
+        Responder::import_sealed(&blob).err(),
+        Some(SgxStatus::InvalidState)
+    );
+    let reexported = imported.export_sealed().unwrap();
+    drop(imported);
+    Responder::import_sealed(&reexported).unwrap();
+
+    let mut tampered = blob.clone();
+    let last = tampered.len() - 1;
+    tampered[last] ^= 1;
+    assert!(Responder::import_sealed(&tampered).is_err());
+}
+
+#[test_case]
+fn rekeyed_endpoints_agree_on_sk() {
+    let (responder, _) = responder_at_msg2();
+    establish(&responder);
+    let sk_key = responder.get_keys(RaKeyType::SK).unwrap();
+    let mk_key = responder.get_keys(RaKeyType::MK).unwrap();
+
+    let rctx = Initiator::new().unwrap().into_raw();
+    {
+        let session = INITIATOR_SESSION_MAGAGER.read().find(rctx).unwrap();
+        let mut context = session.context.lock();
+        context.sk_key = sk_key;
+        context.mk_key = mk_key;
+        context.state = From::from(InitiatorState::Established);
+    }
+    let initiator = unsafe { Initiator::from_raw(rctx) }.unwrap();
+
+    for _ in 0..2 {
+        responder.rekey().unwrap();
+        initiator.rekey().unwrap();
+    }
+    let rekeyed = responder.get_keys(RaKeyType::SK).unwrap();
+    assert_eq!(rekeyed.key, initiator.get_keys(RaKeyType::SK).unwrap().key);
+    assert_ne!(rekeyed.key, sk_key.key);
+
+    responder.rekey().unwrap();
+    assert_ne!(
+        responder.get_keys(RaKeyType::SK).unwrap().key,
+        initiator.get_keys(RaKeyType::SK).unwrap().key
+    );
+}
+
+#[cfg(feature = "test-determinism")]
+#[test_case]
+fn process_msg1_uses_supplied_nonce() {
+    let responder = Responder::new().unwrap();
+    let qe_target = TargetInfo::for_self().unwrap();
+    let nonce = QuoteNonce { rand: [0x3c; 16] };
+
+    let (_, _, used) = responder
+        .process_msg1_with_nonce(&mock_msg1(), &qe_target, nonce)
+        .unwrap();
+    assert_eq!(used.rand, nonce.rand);
+
+    let session = RESPONDER_SESSION_MAGAGER
+        .read()
+        .find(responder.rctx)
+        .unwrap();
+    assert_eq!(session.context.lock().quote_nonce.rand, nonce.rand);
+}
+
+#[test_case]
+fn peer_quote_kept_after_msg3() {
+    let qve_report = Report::default();
+    let qve_report_info = mock_qve_report_info(&qve_report);
+    FAKE_SECS.store(20_000, Ordering::Relaxed);
+
+    let platform = cache_platform(verdict(QlQvResult::Ok));
+    let (responder, pub_key_a) = responder_at_msg2();
+    responder.set_verification_cache(&CACHE).unwrap();
+    assert_eq!(responder.peer_quote(), Err(SgxStatus::InvalidState));
+
+    let (quote, _) = bound_quote(&responder, &platform);
+    let msg3 = mock_msg3_with_quote(&responder, pub_key_a, quote.clone());
+    responder.process_msg3(&msg3, &qve_report_info).unwrap();
+    CACHE.clear();
+
+    assert_eq!(responder.peer_quote().unwrap(), quote);
+}
+
+#[test_case]
+fn typed_responder_walks_the_handshake() {
+    let qve_report = Report::default();
+    let qve_report_info = mock_qve_report_info(&qve_report);
+    FAKE_SECS.store(30_000, Ordering::Relaxed);
+
+    let responder = TypedResponder::new().unwrap();
+    responder.set_verification_cache(&CACHE).unwrap();
+    let qe_target = TargetInfo::for_self().unwrap();
+    let msg1 = mock_msg1();
+    let (responder, _, report, nonce) = responder.process_msg1(&msg1, &qe_target).unwrap();
+    let (qe_report, quote) = mock_qe(&report, &nonce).unwrap();
+    let (responder, msg2) = responder.generate_msg2(&qe_report, &quote).unwrap();
+    assert_eq!(&*msg2.quote, &*quote);
+
+    let platform = cache_platform(verdict(QlQvResult::Ok));
+    let (quote, identity) = bound_quote(&responder.responder, &platform);
+    let msg3 = mock_msg3_with_quote(&responder.responder, msg1.pub_key_a, quote);
+    let (responder, peer) = responder.process_msg3(&msg3, &qve_report_info).unwrap();
+    CACHE.clear();
+
+    assert_eq!(peer.mr_enclave, identity.mr_enclave);
+    let (_, stored) = responder.get_peer_identity().unwrap();
+    assert_eq!(stored.mr_enclave, identity.mr_enclave);
+    let rctx = responder.responder.rctx;
+    let session = RESPONDER_SESSION_MAGAGER.read().find(rctx).unwrap();
+    let sk_key = session.context.lock().sk_key;
+    assert_eq!(responder.get_keys(RaKeyType::SK).unwrap().key, sk_key.key);
+}
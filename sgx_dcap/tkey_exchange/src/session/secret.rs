@@ -0,0 +1,101 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License..
+use super::hkdf_expand;
+use super::manager::DropKey;
+use alloc::vec::Vec;
+use core::fmt;
+use core::ops::Deref;
+use sgx_crypto::aes::gcm::{Aad, AesGcm, Nonce};
+use sgx_crypto::mac::{HMac, HashType};
+use sgx_trts::memory::volatile_zeroize;
+use sgx_types::error::{SgxResult, SgxStatus};
+use sgx_types::types::{
+    AlignKey128bit, Mac128bit, AESGCM_IV_SIZE, MAC_128BIT_SIZE, SHA256_HASH_SIZE,
+};
+
+const SECRET_LABEL: &[u8] = b"SGX DCAP RA SECRET";
+
+/// Bytes the encryption of a msg3 secret adds: the IV and the GCM tag.
+pub const SECRET_OVERHEAD: usize = AESGCM_IV_SIZE + MAC_128BIT_SIZE;
+
+/// A secret the initiator sent in msg3. The buffer is zeroized on drop.
+pub struct Secret(Vec<u8>);
+
+impl Deref for Secret {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl Drop for Secret {
+    fn drop(&mut self) {
+        volatile_zeroize(&mut self.0);
+    }
+}
+
+impl fmt::Debug for Secret {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Secret")
+            .field("len", &self.0.len())
+            .finish_non_exhaustive()
+    }
+}
+
+// HKDF-SHA256 over SK, as for the directional keys, under its own label.
+fn secret_key(sk_key: &AlignKey128bit) -> SgxResult<AlignKey128bit> {
+    let mut prk = HMac::hmac(&[0_u8; SHA256_HASH_SIZE], HashType::Sha256, &sk_key.key)?;
+    let key = hkdf_expand(&prk, SECRET_LABEL);
+    prk.fill(0);
+    key
+}
+
+// Encrypts `secret` with AES-128-GCM under a key derived from SK, as
+// `iv || mac || ciphertext`. The IV is random, so a msg3 generated again
+// for the same session does not reuse it.
+pub(crate) fn encrypt_secret(sk_key: &AlignKey128bit, secret: &[u8]) -> SgxResult<Vec<u8>> {
+    let mut key = secret_key(sk_key)?;
+    let key = DropKey::new(&mut key);
+
+    let nonce = Nonce::new();
+    let mut aes = AesGcm::new(&key.key, nonce, Aad::from(SECRET_LABEL))?;
+    let mut encrypted = alloc::vec![0_u8; SECRET_OVERHEAD + secret.len()];
+    let mac = aes.encrypt(secret, &mut encrypted[SECRET_OVERHEAD..])?;
+    encrypted[..AESGCM_IV_SIZE].copy_from_slice(nonce.as_ref());
+    encrypted[AESGCM_IV_SIZE..SECRET_OVERHEAD].copy_from_slice(&mac);
+    Ok(encrypted)
+}
+
+pub(crate) fn decrypt_secret(sk_key: &AlignKey128bit, encrypted: &[u8]) -> SgxResult<Secret> {
+    ensure!(
+        encrypted.len() > SECRET_OVERHEAD,
+        SgxStatus::InvalidParameter
+    );
+    let (nonce, rest) = encrypted.split_at(AESGCM_IV_SIZE);
+    let (mac, ciphertext) = rest.split_at(MAC_128BIT_SIZE);
+    let nonce = Nonce::try_from(nonce).map_err(|_| SgxStatus::InvalidParameter)?;
+    let mac: Mac128bit = mac.try_into().map_err(|_| SgxStatus::InvalidParameter)?;
+
+    let mut key = secret_key(sk_key)?;
+    let key = DropKey::new(&mut key);
+
+    let mut aes = AesGcm::new(&key.key, nonce, Aad::from(SECRET_LABEL))?;
+    let mut secret = Secret(alloc::vec![0_u8; ciphertext.len()]);
+    aes.decrypt(ciphertext, &mut secret.0, &mac)?;
+    Ok(secret)
+}
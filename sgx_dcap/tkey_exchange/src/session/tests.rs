@@ -0,0 +1,68 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License..
+use super::{derive_directional_keys, rekey};
+use sgx_test_utils::test_case;
+use sgx_types::types::AlignKey128bit;
+
+fn test_sk() -> AlignKey128bit {
+    let mut sk_key = AlignKey128bit::default();
+    sk_key.key.iter_mut().zip(0_u8..).for_each(|(b, i)| *b = i);
+    sk_key
+}
+
+#[test_case]
+fn directional_keys_differ() {
+    let keys = derive_directional_keys(&test_sk()).unwrap();
+    assert_ne!(keys.c2s.key, keys.s2c.key);
+    assert_ne!(keys.c2s.key, test_sk().key);
+}
+
+#[test_case]
+fn directional_keys_known_answer() {
+    // HKDF-SHA256(ikm = 00..0f, salt = none, L = 16) with each label as info.
+    let keys = derive_directional_keys(&test_sk()).unwrap();
+    assert_eq!(
+        keys.c2s.key,
+        [
+            0x37, 0x5d, 0x29, 0x94, 0x20, 0x2a, 0x4c, 0x8f, 0x15, 0xed, 0x05, 0x75, 0x2f, 0xe4,
+            0x1d, 0xcf
+        ]
+    );
+    assert_eq!(
+        keys.s2c.key,
+        [
+            0xef, 0x9b, 0xb1, 0xd4, 0x7e, 0x26, 0x7d, 0xd2, 0x60, 0x37, 0x9d, 0xc3, 0x80, 0xaa,
+            0x93, 0xd9
+        ]
+    );
+}
+
+#[test_case]
+fn rekey_depends_on_counter() {
+    let sk_key = test_sk();
+    let mut mk_key = test_sk();
+    mk_key.key.reverse();
+
+    let (sk_1, mk_1) = rekey(&sk_key, &mk_key, 1).unwrap();
+    let (sk_again, _) = rekey(&sk_key, &mk_key, 1).unwrap();
+    let (sk_2, _) = rekey(&sk_key, &mk_key, 2).unwrap();
+    assert_eq!(sk_1.key, sk_again.key);
+    assert_ne!(sk_1.key, sk_key.key);
+    assert_ne!(mk_1.key, mk_key.key);
+    assert_ne!(sk_1.key, mk_1.key);
+    assert_ne!(sk_1.key, sk_2.key);
+}
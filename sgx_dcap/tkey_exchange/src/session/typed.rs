@@ -0,0 +1,193 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License..
+use super::{DirectionalKeys, RateLimiter, Responder, ResponderConfig};
+use super::{TrustedClock, VerificationCache};
+use crate::{Cert, PlatformFlagsPolicy, QveReportInfo};
+use core::marker::PhantomData;
+use core::time::Duration;
+use sgx_crypto::ecc::EcPublicKey;
+use sgx_dcap_ra_msg::{DcapMRaMsg2, DcapRaMsg1, DcapRaMsg3};
+use sgx_types::error::SgxResult;
+use sgx_types::types::{
+    AlignKey128bit, EnclaveIdentity, QlQvResult, QuoteNonce, RaKeyType, Report, TargetInfo,
+};
+
+/// Handshake stages of a `TypedResponder`.
+pub mod stage {
+    #[derive(Debug)]
+    pub enum Init {}
+    #[derive(Debug)]
+    pub enum Msg1Processed {}
+    #[derive(Debug)]
+    pub enum AwaitingMsg3 {}
+    #[derive(Debug)]
+    pub enum Established {}
+}
+
+use stage::{AwaitingMsg3, Established, Init, Msg1Processed};
+
+/// A `Responder` whose handshake stage is part of its type, so the messages
+/// can only be handled in protocol order. Each step consumes the value; if
+/// it fails the session is closed.
+#[derive(Debug)]
+pub struct TypedResponder<S> {
+    pub(super) responder: Responder,
+    stage: PhantomData<S>,
+}
+
+impl<S> TypedResponder<S> {
+    #[inline]
+    fn advance<T>(self) -> TypedResponder<T> {
+        TypedResponder {
+            responder: self.responder,
+            stage: PhantomData,
+        }
+    }
+
+    #[inline]
+    pub fn into_inner(self) -> Responder {
+        self.responder
+    }
+}
+
+impl TypedResponder<Init> {
+    pub fn new() -> SgxResult<Self> {
+        Responder::new().map(Self::wrap)
+    }
+
+    pub fn with_config(config: ResponderConfig) -> SgxResult<Self> {
+        Responder::with_config(config).map(Self::wrap)
+    }
+
+    // Policy is set before the handshake starts, so the `set_*` calls are
+    // only offered in this stage.
+    #[inline]
+    pub fn set_min_tcb_eval_data_number(&self, min: u32) -> SgxResult {
+        self.responder.set_min_tcb_eval_data_number(min)
+    }
+
+    #[inline]
+    pub fn set_trusted_clock(&self, clock: TrustedClock) -> SgxResult {
+        self.responder.set_trusted_clock(clock)
+    }
+
+    #[inline]
+    pub fn set_rate_limiter(&self, limiter: &'static dyn RateLimiter, source: u64) -> SgxResult {
+        self.responder.set_rate_limiter(limiter, source)
+    }
+
+    #[inline]
+    pub fn set_report_max_age(&self, max_age: Duration) -> SgxResult {
+        self.responder.set_report_max_age(max_age)
+    }
+
+    #[inline]
+    pub fn set_min_protocol_version(&self, min: u8) -> SgxResult {
+        self.responder.set_min_protocol_version(min)
+    }
+
+    #[inline]
+    pub fn set_evidence_collateral(&self, collateral: &[u8]) -> SgxResult {
+        self.responder.set_evidence_collateral(collateral)
+    }
+
+    #[inline]
+    pub fn set_trusted_roots(&self, roots: &[Cert<'_>]) -> SgxResult {
+        self.responder.set_trusted_roots(roots)
+    }
+
+    #[inline]
+    pub fn set_verification_cache(&self, cache: &'static VerificationCache) -> SgxResult {
+        self.responder.set_verification_cache(cache)
+    }
+
+    #[inline]
+    pub fn set_accepted_qv_results(&self, accepted: &[QlQvResult]) -> SgxResult {
+        self.responder.set_accepted_qv_results(accepted)
+    }
+
+    #[inline]
+    pub fn set_platform_flags_policy(&self, policy: PlatformFlagsPolicy) -> SgxResult {
+        self.responder.set_platform_flags_policy(policy)
+    }
+
+    #[inline]
+    pub fn require_confirmation(&self) -> SgxResult {
+        self.responder.require_confirmation()
+    }
+
+    #[inline]
+    fn wrap(responder: Responder) -> Self {
+        TypedResponder {
+            responder,
+            stage: PhantomData,
+        }
+    }
+
+    pub fn process_msg1(
+        self,
+        msg1: &DcapRaMsg1,
+        qe_target: &TargetInfo,
+    ) -> SgxResult<(
+        TypedResponder<Msg1Processed>,
+        EcPublicKey,
+        Report,
+        QuoteNonce,
+    )> {
+        let (pub_key_b, report, nonce) = self.responder.process_msg1(msg1, qe_target)?;
+        Ok((self.advance(), pub_key_b, report, nonce))
+    }
+}
+
+impl TypedResponder<Msg1Processed> {
+    pub fn generate_msg2(
+        self,
+        qe_report: &Report,
+        quote: &[u8],
+    ) -> SgxResult<(TypedResponder<AwaitingMsg3>, DcapMRaMsg2)> {
+        let msg2 = self.responder.generate_msg2(qe_report, quote)?;
+        Ok((self.advance(), msg2))
+    }
+}
+
+impl TypedResponder<AwaitingMsg3> {
+    pub fn process_msg3(
+        self,
+        msg3: &DcapRaMsg3,
+        qve_report_info: &QveReportInfo,
+    ) -> SgxResult<(TypedResponder<Established>, EnclaveIdentity)> {
+        let identity = self.responder.process_msg3(msg3, qve_report_info)?;
+        Ok((self.advance(), identity))
+    }
+}
+
+impl TypedResponder<Established> {
+    #[inline]
+    pub fn get_keys(&self, key_type: RaKeyType) -> SgxResult<AlignKey128bit> {
+        self.responder.get_keys(key_type)
+    }
+
+    #[inline]
+    pub fn get_directional_keys(&self) -> SgxResult<DirectionalKeys> {
+        self.responder.get_directional_keys()
+    }
+
+    #[inline]
+    pub fn get_peer_identity(&self) -> SgxResult<(QlQvResult, EnclaveIdentity)> {
+        self.responder.get_peer_identity()
+    }
+}
@@ -0,0 +1,144 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License..
+use super::quote::split_signature_data;
+use super::TrustedClock;
+use crate::SupplementalData;
+use alloc::collections::BTreeMap;
+use core::mem;
+use core::time::Duration;
+use sgx_crypto::sha::Sha256;
+use sgx_sync::SpinMutex;
+use sgx_types::error::{SgxResult, SgxStatus};
+use sgx_types::types::{QlQvResult, QuoteHeader, SHA256_HASH_SIZE};
+
+#[cfg(feature = "unit_test")]
+mod tests;
+
+/// Platforms remembered at once by a `VerificationCache`. Beyond this,
+/// expired entries are dropped and new platforms are not cached until one
+/// has expired.
+pub const VERIFICATION_CACHE_MAX_ENTRIES: usize = 256;
+
+/// What a `VerificationCache` remembers about a verified platform. The peer
+/// identity is not part of it: it comes from the report body of each quote.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CachedVerdict {
+    pub qv_result: QlQvResult,
+    /// `None` when the QvE returned no supplemental data.
+    pub supplemental: Option<SupplementalData>,
+}
+
+#[derive(Clone, Debug)]
+struct Entry {
+    verdict: CachedVerdict,
+    expires_at: Duration,
+}
+
+/// Verification results keyed by the SHA-256 of the platform part of a
+/// quote, each kept for at most `ttl` and never past the expiry of the
+/// collateral it was checked against. `clock` must count from the Unix epoch
+/// so that the two compare.
+///
+/// The platform part is the quote header and the signature data after the
+/// ISV enclave signature: attestation key, QE report and signature, QE
+/// authentication data and PCK certification data. It is the same in every
+/// quote a platform produces until its QE or TCB changes, while the report
+/// body binds the keys of one session, so a verdict from one handshake
+/// serves later ones from the same platform.
+///
+/// A hit only skips running the QvE. `process_msg3` still checks the ISV
+/// enclave signature under the cached attestation key and the report data
+/// of every quote, and it reapplies the responder's own verdict and
+/// supplemental data policy to the cached verdict, so responders sharing a
+/// cache may differ in policy.
+pub struct VerificationCache {
+    ttl: Duration,
+    clock: TrustedClock,
+    entries: SpinMutex<BTreeMap<[u8; SHA256_HASH_SIZE], Entry>>,
+}
+
+impl VerificationCache {
+    pub const fn new(ttl: Duration, clock: TrustedClock) -> VerificationCache {
+        VerificationCache {
+            ttl,
+            clock,
+            entries: SpinMutex::new(BTreeMap::new()),
+        }
+    }
+
+    /// Returns the cached result for the platform of `quote` unless it has
+    /// expired.
+    pub fn lookup(&self, quote: &[u8]) -> SgxResult<Option<CachedVerdict>> {
+        let key = platform_digest(quote)?;
+        let now = (self.clock)()?;
+
+        let mut entries = self.entries.lock();
+        match entries.get(&key) {
+            Some(entry) if now < entry.expires_at => Ok(Some(entry.verdict.clone())),
+            Some(_) => {
+                entries.remove(&key);
+                Ok(None)
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Caches the result of verifying `quote` against collateral that
+    /// expires at `collateral_expiry` for every quote from its platform.
+    pub fn insert(
+        &self,
+        quote: &[u8],
+        verdict: CachedVerdict,
+        collateral_expiry: Duration,
+    ) -> SgxResult {
+        let key = platform_digest(quote)?;
+        let now = (self.clock)()?;
+        let expires_at = now.saturating_add(self.ttl).min(collateral_expiry);
+        if expires_at <= now {
+            return Ok(());
+        }
+
+        let mut entries = self.entries.lock();
+        if !entries.contains_key(&key) && entries.len() >= VERIFICATION_CACHE_MAX_ENTRIES {
+            entries.retain(|_, entry| now < entry.expires_at);
+            if entries.len() >= VERIFICATION_CACHE_MAX_ENTRIES {
+                return Ok(());
+            }
+        }
+        entries.insert(
+            key,
+            Entry {
+                verdict,
+                expires_at,
+            },
+        );
+        Ok(())
+    }
+
+    pub fn clear(&self) {
+        self.entries.lock().clear();
+    }
+}
+
+fn platform_digest(quote: &[u8]) -> SgxResult<[u8; SHA256_HASH_SIZE]> {
+    let (_, platform) = split_signature_data(quote).map_err(SgxStatus::from)?;
+
+    let mut sha = Sha256::new()?;
+    sha.update(&quote[..mem::size_of::<QuoteHeader>()])?;
+    sha.update(platform)?;
+    Ok(sha.finalize()?.hash)
+}
@@ -0,0 +1,109 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License..
+use super::{CachedVerdict, VerificationCache};
+use alloc::vec::Vec;
+use core::mem;
+use core::ptr;
+use core::sync::atomic::{AtomicU64, Ordering};
+use core::time::Duration;
+use sgx_test_utils::test_case;
+use sgx_types::error::{SgxResult, SgxStatus};
+use sgx_types::types::{QlEcdsaSigData, QlQvResult, Quote3};
+
+static FAKE_SECS: AtomicU64 = AtomicU64::new(0);
+
+fn fake_clock() -> SgxResult<Duration> {
+    Ok(Duration::from_secs(FAKE_SECS.load(Ordering::Relaxed)))
+}
+
+// A quote from the platform `attest_key` whose report body and ISV enclave
+// signature are filled with `session`.
+fn quote(attest_key: u8, session: u8) -> Vec<u8> {
+    let sig_len = mem::size_of::<QlEcdsaSigData>();
+    let mut quote = alloc::vec![0_u8; mem::size_of::<Quote3>() + sig_len];
+    let mut quote3 = Quote3::default();
+    quote3.header.version = 3;
+    quote3.report_body.report_data.d = [session; 64];
+    quote3.signature_len = sig_len as u32;
+    unsafe { ptr::write_unaligned(quote.as_mut_ptr() as *mut Quote3, quote3) };
+
+    let sig_data = mem::size_of::<Quote3>();
+    quote[sig_data..sig_data + 64].fill(session);
+    quote[sig_data + 64..sig_data + 128].fill(attest_key);
+    quote
+}
+
+fn verdict() -> CachedVerdict {
+    CachedVerdict {
+        qv_result: QlQvResult::Ok,
+        supplemental: None,
+    }
+}
+
+#[test_case]
+fn cache_entry_shared_by_platform() {
+    FAKE_SECS.store(1000, Ordering::Relaxed);
+    let cache = VerificationCache::new(Duration::from_secs(60), fake_clock);
+
+    cache
+        .insert(&quote(1, 1), verdict(), Duration::MAX)
+        .unwrap();
+    // Another session on the platform hits, another platform does not.
+    assert_eq!(cache.lookup(&quote(1, 2)).unwrap(), Some(verdict()));
+    assert_eq!(cache.lookup(&quote(2, 1)).unwrap(), None);
+
+    // Quotes without ECDSA signature data have no platform part.
+    let mut short = quote(1, 1);
+    short.truncate(mem::size_of::<Quote3>() + 64);
+    unsafe { (*(short.as_mut_ptr() as *mut Quote3)).signature_len = 64 };
+    assert_eq!(cache.lookup(&short), Err(SgxStatus::InvalidParameter));
+}
+
+#[test_case]
+fn cache_entry_expires_after_ttl() {
+    FAKE_SECS.store(1000, Ordering::Relaxed);
+    let cache = VerificationCache::new(Duration::from_secs(60), fake_clock);
+
+    assert_eq!(cache.lookup(&quote(1, 1)).unwrap(), None);
+    cache
+        .insert(&quote(1, 1), verdict(), Duration::MAX)
+        .unwrap();
+    assert_eq!(cache.lookup(&quote(1, 2)).unwrap(), Some(verdict()));
+
+    FAKE_SECS.store(1059, Ordering::Relaxed);
+    assert!(cache.lookup(&quote(1, 3)).unwrap().is_some());
+    FAKE_SECS.store(1060, Ordering::Relaxed);
+    assert_eq!(cache.lookup(&quote(1, 3)).unwrap(), None);
+}
+
+#[test_case]
+fn cache_entry_capped_by_collateral_expiry() {
+    FAKE_SECS.store(1000, Ordering::Relaxed);
+    let cache = VerificationCache::new(Duration::from_secs(60), fake_clock);
+
+    cache
+        .insert(&quote(1, 1), verdict(), Duration::from_secs(1010))
+        .unwrap();
+    FAKE_SECS.store(1010, Ordering::Relaxed);
+    assert_eq!(cache.lookup(&quote(1, 2)).unwrap(), None);
+
+    // Collateral that has already expired is not cached at all.
+    cache
+        .insert(&quote(1, 1), verdict(), Duration::from_secs(1000))
+        .unwrap();
+    assert_eq!(cache.lookup(&quote(1, 2)).unwrap(), None);
+}
@@ -57,14 +57,14 @@ pub unsafe extern "C" fn sgx_tvl_verify_qve_report_and_identity(
     };
 
     let qve_report_info = &*qve_report_info;
-    let qve_report_info = QveReportInfo {
-        qve_report: &*ptr::addr_of!(qve_report_info.qe_report),
-        expiration_time: expiration_check_date,
+    let qve_report_info = QveReportInfo::new(
+        &*ptr::addr_of!(qve_report_info.qe_report),
+        expiration_check_date,
         collateral_expiration_status,
         quote_verification_result,
-        qve_nonce: qve_report_info.nonce,
+        qve_report_info.nonce,
         supplemental_data,
-    };
+    );
 
     match qve_report_info.verify_report_and_identity(quote, qve_isvsvn_threshold) {
         Ok(_) => (),
@@ -0,0 +1,118 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License..
+
+use crate::tvl::QveReportInfo;
+use alloc::vec::Vec;
+use core::mem;
+use sgx_crypto::sha::Sha256;
+use sgx_types::error::{Quote3Error, SgxResult, SgxStatus};
+use sgx_types::types::{Ec256PublicKey, EnclaveIdentity, QlQvResult, Quote3, SHA256_HASH_SIZE};
+
+#[cfg(feature = "unit_test")]
+mod tests;
+
+/// One enclave in a delegation chain.
+///
+/// The quote's report data must hold `SHA256(pub_key || next.pub_key)`, or
+/// `SHA256(pub_key)` for the last link, so each enclave vouches for the key
+/// of the one it delegates to.
+#[derive(Debug)]
+pub struct AttestationLink<'a, 'b> {
+    pub quote: &'a [u8],
+    pub qve_report_info: QveReportInfo<'a, 'b>,
+    pub pub_key: Ec256PublicKey,
+}
+
+/// Verifies every link of `links` and returns their identities in order.
+/// The last identity is trusted only if this returns `Ok`.
+///
+/// Each link's QvE must be at least at `qve_isvsvn_threshold`, and its
+/// verdict one of `accepted_qv_results`; any other verdict fails with
+/// `NoPrivilege`.
+pub fn verify_attestation_chain(
+    links: &[AttestationLink],
+    qve_isvsvn_threshold: u16,
+    accepted_qv_results: &[QlQvResult],
+) -> SgxResult<Vec<EnclaveIdentity>> {
+    verify_chain_with(links, accepted_qv_results, |link| {
+        link.qve_report_info
+            .verify_report_and_identity(link.quote, qve_isvsvn_threshold)
+            .map_err(|e| match e {
+                Quote3Error::InvalidParameter => SgxStatus::InvalidParameter,
+                Quote3Error::ErrorReport => SgxStatus::InvalidSignature,
+                Quote3Error::QveIdentityMismatch | Quote3Error::QveOutOfDate => {
+                    SgxStatus::UpdateNeeded
+                }
+                _ => SgxStatus::Unexpected,
+            })
+    })
+}
+
+// Checks the key bindings and verdicts of all links before running
+// `verify_quote` on any of them, so a broken chain is rejected without
+// invoking the QvE path.
+pub(crate) fn verify_chain_with<F>(
+    links: &[AttestationLink],
+    accepted_qv_results: &[QlQvResult],
+    mut verify_quote: F,
+) -> SgxResult<Vec<EnclaveIdentity>>
+where
+    F: FnMut(&AttestationLink) -> SgxResult,
+{
+    ensure!(!links.is_empty(), SgxStatus::InvalidParameter);
+    ensure!(!accepted_qv_results.is_empty(), SgxStatus::InvalidParameter);
+
+    let mut identities = Vec::new();
+    identities
+        .try_reserve_exact(links.len())
+        .map_err(|_| SgxStatus::OutOfMemory)?;
+
+    for (i, link) in links.iter().enumerate() {
+        let quote3 = parse_quote3(link.quote)?;
+
+        let mut sha = Sha256::new()?;
+        sha.update(&link.pub_key)?;
+        if let Some(next) = links.get(i + 1) {
+            sha.update(&next.pub_key)?;
+        }
+        let hash = sha.finalize()?;
+        ensure!(
+            hash.eq(&quote3.report_body.report_data.d[..SHA256_HASH_SIZE]),
+            SgxStatus::InvalidSignature
+        );
+
+        ensure!(
+            accepted_qv_results.contains(&link.qve_report_info.quote_verification_result),
+            SgxStatus::NoPrivilege
+        );
+
+        identities.push(EnclaveIdentity::from(quote3.report_body));
+    }
+
+    for link in links {
+        verify_quote(link)?;
+    }
+    Ok(identities)
+}
+
+fn parse_quote3(quote: &[u8]) -> SgxResult<&Quote3> {
+    ensure!(
+        quote.len() >= mem::size_of::<Quote3>(),
+        SgxStatus::InvalidParameter
+    );
+    Ok(unsafe { &*(quote.as_ptr() as *const Quote3) })
+}
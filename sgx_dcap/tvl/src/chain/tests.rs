@@ -0,0 +1,159 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License..
+
+use super::{verify_attestation_chain, verify_chain_with, AttestationLink};
+use crate::QveReportInfo;
+use alloc::vec::Vec;
+use core::mem;
+use core::ptr;
+use sgx_crypto::sha::Sha256;
+use sgx_test_utils::test_case;
+use sgx_types::error::SgxStatus;
+use sgx_types::types::{Ec256PublicKey, QlQvResult, Quote3, QuoteNonce, Report, SHA256_HASH_SIZE};
+
+fn key(b: u8) -> Ec256PublicKey {
+    Ec256PublicKey {
+        gx: [b; 32],
+        gy: [b.wrapping_add(1); 32],
+    }
+}
+
+// A quote for enclave `isv_prod_id` whose report data binds `keys`.
+fn chain_quote(isv_prod_id: u16, keys: &[Ec256PublicKey]) -> Vec<u8> {
+    let mut sha = Sha256::new().unwrap();
+    for key in keys {
+        sha.update(key).unwrap();
+    }
+    let hash = sha.finalize().unwrap();
+
+    let mut quote3 = Quote3::default();
+    quote3.header.version = 3;
+    quote3.report_body.isv_prod_id = isv_prod_id;
+    quote3.report_body.report_data.d[..SHA256_HASH_SIZE].copy_from_slice(&hash);
+
+    let mut quote = alloc::vec![0_u8; mem::size_of::<Quote3>()];
+    unsafe { ptr::write_unaligned(quote.as_mut_ptr() as *mut Quote3, quote3) };
+    quote
+}
+
+const ACCEPTED: &[QlQvResult] = &[QlQvResult::Ok, QlQvResult::SWHardeningNeeded];
+const QVE_ISVSVN_THRESHOLD: u16 = 3;
+
+fn link<'a>(
+    quote: &'a [u8],
+    report: &'a Report,
+    pub_key: Ec256PublicKey,
+) -> AttestationLink<'a, 'a> {
+    link_with_verdict(quote, report, pub_key, QlQvResult::Ok)
+}
+
+fn link_with_verdict<'a>(
+    quote: &'a [u8],
+    report: &'a Report,
+    pub_key: Ec256PublicKey,
+    verdict: QlQvResult,
+) -> AttestationLink<'a, 'a> {
+    AttestationLink {
+        quote,
+        qve_report_info: QveReportInfo::new(report, 0, 0, verdict, QuoteNonce::default(), None),
+        pub_key,
+    }
+}
+
+#[test_case]
+fn valid_two_link_chain() {
+    let report = Report::default();
+    let quote_a = chain_quote(1, &[key(1), key(2)]);
+    let quote_b = chain_quote(2, &[key(2)]);
+    let links = [
+        link(&quote_a, &report, key(1)),
+        link(&quote_b, &report, key(2)),
+    ];
+
+    let mut verified = 0;
+    let identities = verify_chain_with(&links, ACCEPTED, |_| {
+        verified += 1;
+        Ok(())
+    })
+    .unwrap();
+    assert_eq!(verified, 2);
+    assert_eq!(identities.len(), 2);
+    assert_eq!(identities[0].isv_prod_id, 1);
+    assert_eq!(identities[1].isv_prod_id, 2);
+
+    // Without a genuine QvE report the public entry point refuses the chain.
+    assert!(verify_attestation_chain(&links, QVE_ISVSVN_THRESHOLD, ACCEPTED).is_err());
+}
+
+#[test_case]
+fn broken_link_rejected() {
+    let report = Report::default();
+    // A vouches for key(3), but B presents key(2).
+    let quote_a = chain_quote(1, &[key(1), key(3)]);
+    let quote_b = chain_quote(2, &[key(2)]);
+    let links = [
+        link(&quote_a, &report, key(1)),
+        link(&quote_b, &report, key(2)),
+    ];
+
+    let mut verified = 0;
+    let result = verify_chain_with(&links, ACCEPTED, |_| {
+        verified += 1;
+        Ok(())
+    });
+    assert_eq!(result.unwrap_err(), SgxStatus::InvalidSignature);
+    assert_eq!(verified, 0);
+    assert_eq!(
+        verify_attestation_chain(&links, QVE_ISVSVN_THRESHOLD, ACCEPTED).unwrap_err(),
+        SgxStatus::InvalidSignature
+    );
+}
+
+#[test_case]
+fn empty_chain_rejected() {
+    assert_eq!(
+        verify_attestation_chain(&[], QVE_ISVSVN_THRESHOLD, ACCEPTED).unwrap_err(),
+        SgxStatus::InvalidParameter
+    );
+}
+
+#[test_case]
+fn rejected_verdict_fails() {
+    let report = Report::default();
+    let quote_a = chain_quote(1, &[key(1), key(2)]);
+    let quote_b = chain_quote(2, &[key(2)]);
+    let links = [
+        link(&quote_a, &report, key(1)),
+        link_with_verdict(&quote_b, &report, key(2), QlQvResult::Revoked),
+    ];
+
+    let mut verified = 0;
+    let result = verify_chain_with(&links, ACCEPTED, |_| {
+        verified += 1;
+        Ok(())
+    });
+    assert_eq!(result.unwrap_err(), SgxStatus::NoPrivilege);
+    assert_eq!(verified, 0);
+    assert_eq!(
+        verify_attestation_chain(&links, QVE_ISVSVN_THRESHOLD, ACCEPTED).unwrap_err(),
+        SgxStatus::NoPrivilege
+    );
+    assert_eq!(
+        verify_chain_with(&links, &[], |_| Ok(())).unwrap_err(),
+        SgxStatus::InvalidParameter
+    );
+}
@@ -0,0 +1,225 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License..
+
+use sgx_types::error::{SgxResult, SgxStatus};
+use sgx_types::types::time_t;
+
+#[cfg(feature = "unit_test")]
+mod tests;
+
+pub(crate) const BOOLEAN: u8 = 0x01;
+pub(crate) const INTEGER: u8 = 0x02;
+pub(crate) const BIT_STRING: u8 = 0x03;
+pub(crate) const OCTET_STRING: u8 = 0x04;
+pub(crate) const OID: u8 = 0x06;
+pub(crate) const ENUMERATED: u8 = 0x0a;
+pub(crate) const UTC_TIME: u8 = 0x17;
+pub(crate) const GENERALIZED_TIME: u8 = 0x18;
+pub(crate) const SEQUENCE: u8 = 0x30;
+pub(crate) const CONTEXT_0: u8 = 0xa0;
+pub(crate) const CONTEXT_3: u8 = 0xa3;
+
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Tlv<'a> {
+    pub tag: u8,
+    pub value: &'a [u8],
+    /// The whole encoding, header included.
+    pub raw: &'a [u8],
+}
+
+/// A cursor over a run of DER encoded values.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Der<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> Der<'a> {
+    #[inline]
+    pub fn new(data: &'a [u8]) -> Der<'a> {
+        Der { data }
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    #[inline]
+    pub fn peek_tag(&self) -> Option<u8> {
+        self.data.first().copied()
+    }
+
+    pub fn read_any(&mut self) -> SgxResult<Tlv<'a>> {
+        let (&tag, rest) = self.data.split_first().ok_or(SgxStatus::InvalidParameter)?;
+        // Multi-byte tags never appear in the structures parsed here.
+        ensure!(tag & 0x1f != 0x1f, SgxStatus::InvalidParameter);
+
+        let (&first, mut rest) = rest.split_first().ok_or(SgxStatus::InvalidParameter)?;
+        let len = if first < 0x80 {
+            first as usize
+        } else {
+            let n = (first & 0x7f) as usize;
+            ensure!(
+                (1..=4).contains(&n) && rest.len() >= n && rest[0] != 0,
+                SgxStatus::InvalidParameter
+            );
+            let len = rest[..n]
+                .iter()
+                .fold(0_usize, |len, &b| (len << 8) | b as usize);
+            ensure!(len >= 0x80, SgxStatus::InvalidParameter);
+            rest = &rest[n..];
+            len
+        };
+        ensure!(rest.len() >= len, SgxStatus::InvalidParameter);
+
+        let header_len = self.data.len() - rest.len();
+        let tlv = Tlv {
+            tag,
+            value: &rest[..len],
+            raw: &self.data[..header_len + len],
+        };
+        self.data = &rest[len..];
+        Ok(tlv)
+    }
+
+    pub fn read_tlv(&mut self, tag: u8) -> SgxResult<Tlv<'a>> {
+        let tlv = self.read_any()?;
+        ensure!(tlv.tag == tag, SgxStatus::InvalidParameter);
+        Ok(tlv)
+    }
+
+    #[inline]
+    pub fn read(&mut self, tag: u8) -> SgxResult<&'a [u8]> {
+        self.read_tlv(tag).map(|tlv| tlv.value)
+    }
+
+    pub fn read_optional(&mut self, tag: u8) -> SgxResult<Option<&'a [u8]>> {
+        if self.peek_tag() == Some(tag) {
+            self.read(tag).map(Some)
+        } else {
+            Ok(None)
+        }
+    }
+
+    #[inline]
+    pub fn read_sequence(&mut self) -> SgxResult<Der<'a>> {
+        self.read(SEQUENCE).map(Der::new)
+    }
+
+    pub fn read_time(&mut self) -> SgxResult<time_t> {
+        let tlv = self.read_any()?;
+        parse_time(tlv.tag, tlv.value)
+    }
+
+    pub fn read_bool(&mut self) -> SgxResult<bool> {
+        match self.read(BOOLEAN)? {
+            [0x00] => Ok(false),
+            [0xff] => Ok(true),
+            _ => Err(SgxStatus::InvalidParameter),
+        }
+    }
+
+    pub fn finish(&self) -> SgxResult {
+        ensure!(self.data.is_empty(), SgxStatus::InvalidParameter);
+        Ok(())
+    }
+}
+
+/// The magnitude of a non-negative INTEGER, without the sign padding byte.
+pub(crate) fn unsigned(value: &[u8]) -> SgxResult<&[u8]> {
+    match value {
+        [] => Err(SgxStatus::InvalidParameter),
+        [b, ..] if b & 0x80 != 0 => Err(SgxStatus::InvalidParameter),
+        [0, rest @ ..] if !rest.is_empty() => {
+            ensure!(rest[0] & 0x80 != 0, SgxStatus::InvalidParameter);
+            Ok(rest)
+        }
+        _ => Ok(value),
+    }
+}
+
+pub(crate) fn unsigned_u32(value: &[u8]) -> SgxResult<u32> {
+    let bytes = unsigned(value)?;
+    ensure!(bytes.len() <= 4, SgxStatus::InvalidParameter);
+    Ok(bytes.iter().fold(0, |n, &b| (n << 8) | b as u32))
+}
+
+fn digits(bytes: &[u8]) -> SgxResult<u32> {
+    bytes.iter().try_fold(0, |n, &b| {
+        ensure!(b.is_ascii_digit(), SgxStatus::InvalidParameter);
+        Ok(n * 10 + (b - b'0') as u32)
+    })
+}
+
+/// Parses an X.509 `Time`: a `UTCTime` or `GeneralizedTime` in UTC with
+/// whole seconds.
+pub(crate) fn parse_time(tag: u8, value: &[u8]) -> SgxResult<time_t> {
+    let (year, rest) = match (tag, value.len()) {
+        (UTC_TIME, 13) => {
+            let year = digits(&value[..2])?;
+            (if year >= 50 { 1900 } else { 2000 } + year, &value[2..])
+        }
+        (GENERALIZED_TIME, 15) => (digits(&value[..4])?, &value[4..]),
+        _ => return Err(SgxStatus::InvalidParameter),
+    };
+    ensure!(rest[10] == b'Z', SgxStatus::InvalidParameter);
+    timestamp(
+        year,
+        digits(&rest[0..2])?,
+        digits(&rest[2..4])?,
+        digits(&rest[4..6])?,
+        digits(&rest[6..8])?,
+        digits(&rest[8..10])?,
+    )
+}
+
+/// Seconds since the Unix epoch of a UTC calendar time.
+pub(crate) fn timestamp(
+    year: u32,
+    month: u32,
+    day: u32,
+    hour: u32,
+    minute: u32,
+    second: u32,
+) -> SgxResult<time_t> {
+    const DAYS_IN_MONTH: [u32; 12] = [31, 28, 31, 30, 31, 30, 31, 31, 30, 31, 30, 31];
+
+    ensure!(
+        (1970..=9999).contains(&year) && (1..=12).contains(&month),
+        SgxStatus::InvalidParameter
+    );
+    let leap = (year % 4 == 0 && year % 100 != 0) || year % 400 == 0;
+    let month_days = DAYS_IN_MONTH[month as usize - 1] + u32::from(leap && month == 2);
+    ensure!(
+        (1..=month_days).contains(&day) && hour < 24 && minute < 60 && second < 60,
+        SgxStatus::InvalidParameter
+    );
+
+    // Days from civil, shifted so that the year starts in March.
+    let (y, m) = if month <= 2 {
+        (year as i64 - 1, month as i64 + 9)
+    } else {
+        (year as i64, month as i64 - 3)
+    };
+    let era = y / 400;
+    let yoe = y - era * 400;
+    let doy = (153 * m + 2) / 5 + day as i64 - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    let days = era * 146_097 + doe - 719_468;
+
+    Ok(days * 86_400 + hour as i64 * 3_600 + minute as i64 * 60 + second as i64)
+}
@@ -0,0 +1,85 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License..
+
+use super::{parse_time, timestamp, unsigned, unsigned_u32, Der, GENERALIZED_TIME, INTEGER};
+use super::{OCTET_STRING, SEQUENCE, UTC_TIME};
+use alloc::vec;
+use sgx_test_utils::test_case;
+use sgx_types::error::SgxStatus;
+
+#[test_case]
+fn read_short_and_long_lengths() {
+    let mut long = vec![OCTET_STRING, 0x81, 0x80];
+    long.extend_from_slice(&[0xab; 0x80]);
+    let mut data = vec![SEQUENCE, 0x03, INTEGER, 0x01, 0x05];
+    data.extend_from_slice(&long);
+
+    let mut der = Der::new(&data);
+    let mut inner = der.read_sequence().unwrap();
+    assert_eq!(inner.read(INTEGER).unwrap(), &[0x05]);
+    inner.finish().unwrap();
+
+    let tlv = der.read_tlv(OCTET_STRING).unwrap();
+    assert_eq!(tlv.value.len(), 0x80);
+    assert_eq!(tlv.raw, long.as_slice());
+    assert!(der.is_empty());
+}
+
+#[test_case]
+fn reject_malformed_lengths() {
+    // Truncated value.
+    assert!(Der::new(&[INTEGER, 0x02, 0x01]).read_any().is_err());
+    // Indefinite length.
+    assert!(Der::new(&[SEQUENCE, 0x80, 0x00, 0x00]).read_any().is_err());
+    // Long form for a length that fits the short form.
+    assert!(Der::new(&[INTEGER, 0x81, 0x01, 0x00]).read_any().is_err());
+    // Wrong tag.
+    assert_eq!(
+        Der::new(&[INTEGER, 0x01, 0x00]).read(SEQUENCE),
+        Err(SgxStatus::InvalidParameter)
+    );
+}
+
+#[test_case]
+fn unsigned_integers() {
+    assert_eq!(unsigned(&[0x00, 0x80]).unwrap(), &[0x80]);
+    assert_eq!(unsigned(&[0x00]).unwrap(), &[0x00]);
+    assert!(unsigned(&[0x80]).is_err());
+    assert!(unsigned(&[0x00, 0x01]).is_err());
+    assert_eq!(unsigned_u32(&[0x01, 0x00]).unwrap(), 256);
+    assert!(unsigned_u32(&[0x01, 0x00, 0x00, 0x00, 0x00]).is_err());
+}
+
+#[test_case]
+fn times() {
+    assert_eq!(timestamp(1970, 1, 1, 0, 0, 0).unwrap(), 0);
+    assert_eq!(timestamp(2000, 2, 29, 0, 0, 0).unwrap(), 951_782_400);
+    assert!(timestamp(2100, 2, 29, 0, 0, 0).is_err());
+    assert!(timestamp(2024, 13, 1, 0, 0, 0).is_err());
+
+    assert_eq!(
+        parse_time(UTC_TIME, b"240101000000Z").unwrap(),
+        1_704_067_200
+    );
+    assert_eq!(parse_time(UTC_TIME, b"991231235959Z").unwrap(), 946_684_799);
+    assert_eq!(
+        parse_time(GENERALIZED_TIME, b"20501231235959Z").unwrap(),
+        2_556_143_999
+    );
+    assert!(parse_time(UTC_TIME, b"240101000000+0100").is_err());
+    assert!(parse_time(GENERALIZED_TIME, b"240101000000Z").is_err());
+}
@@ -0,0 +1,298 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License..
+
+//! A small JSON reader for Intel PCS collateral. Values borrow from the
+//! input so that a signed member can be checked against its exact bytes.
+
+use crate::der::timestamp;
+use crate::x509::hex_decode;
+use alloc::vec::Vec;
+use sgx_types::error::{SgxResult, SgxStatus};
+use sgx_types::types::{time_t, ECP256_KEY_SIZE};
+
+#[cfg(feature = "unit_test")]
+mod tests;
+
+const MAX_DEPTH: usize = 16;
+
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum Json<'a> {
+    Null,
+    Bool(bool),
+    Number(&'a str),
+    /// The raw contents between the quotes; escapes are left as is.
+    String(&'a str),
+    Array(Vec<Json<'a>>),
+    Object(Vec<Member<'a>>),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct Member<'a> {
+    pub key: &'a str,
+    pub value: Json<'a>,
+    /// The encoding of `value` as it appears in the input.
+    pub raw: &'a str,
+}
+
+impl<'a> Json<'a> {
+    pub fn parse(input: &'a [u8]) -> SgxResult<Json<'a>> {
+        let input = core::str::from_utf8(input).map_err(|_| SgxStatus::InvalidParameter)?;
+        let mut parser = Parser { input, pos: 0 };
+        let value = parser.value(0)?;
+        parser.skip_whitespace();
+        ensure!(parser.pos == input.len(), SgxStatus::InvalidParameter);
+        Ok(value)
+    }
+
+    pub fn member(&self, key: &str) -> Option<&Member<'a>> {
+        match self {
+            Json::Object(members) => members.iter().find(|member| member.key == key),
+            _ => None,
+        }
+    }
+
+    /// Splits `{"<key>": {...}, "signature": "<hex r || s>"}`, the envelope
+    /// of signed collateral, into the signed member and its signature.
+    pub fn signed_member(&self, key: &str) -> SgxResult<(&Member<'a>, [u8; ECP256_KEY_SIZE * 2])> {
+        ensure!(
+            matches!(self, Json::Object(members) if members.len() == 2),
+            SgxStatus::InvalidParameter
+        );
+        let body = self.member(key).ok_or(SgxStatus::InvalidParameter)?;
+        let signature = self.get("signature")?.as_hex()?;
+        Ok((body, signature))
+    }
+
+    #[inline]
+    pub fn get(&self, key: &str) -> SgxResult<&Json<'a>> {
+        self.member(key)
+            .map(|member| &member.value)
+            .ok_or(SgxStatus::InvalidParameter)
+    }
+
+    pub fn as_str(&self) -> SgxResult<&'a str> {
+        match *self {
+            Json::String(s) => Ok(s),
+            _ => Err(SgxStatus::InvalidParameter),
+        }
+    }
+
+    pub fn as_u32(&self) -> SgxResult<u32> {
+        match *self {
+            Json::Number(n) => n.parse().map_err(|_| SgxStatus::InvalidParameter),
+            _ => Err(SgxStatus::InvalidParameter),
+        }
+    }
+
+    pub fn as_array(&self) -> SgxResult<&[Json<'a>]> {
+        match self {
+            Json::Array(values) => Ok(values),
+            _ => Err(SgxStatus::InvalidParameter),
+        }
+    }
+
+    pub fn as_hex<const N: usize>(&self) -> SgxResult<[u8; N]> {
+        hex_decode(self.as_str()?.as_bytes())?
+            .try_into()
+            .map_err(|_| SgxStatus::InvalidParameter)
+    }
+
+    /// An ISO 8601 UTC date such as `2024-06-01T00:00:00Z`; fractional
+    /// seconds are ignored.
+    pub fn as_date(&self) -> SgxResult<time_t> {
+        let s = self.as_str()?.as_bytes();
+        ensure!(s.len() >= 20, SgxStatus::InvalidParameter);
+        let (s, zone) = s.split_at(19);
+        ensure!(
+            s[4] == b'-' && s[7] == b'-' && s[10] == b'T' && s[13] == b':' && s[16] == b':',
+            SgxStatus::InvalidParameter
+        );
+        match zone {
+            [b'Z'] => {}
+            [b'.', fraction @ .., b'Z'] if fraction.iter().all(u8::is_ascii_digit) => {}
+            _ => return Err(SgxStatus::InvalidParameter),
+        }
+
+        let digits = |bytes: &[u8]| -> SgxResult<u32> {
+            bytes.iter().try_fold(0, |n, &b| {
+                ensure!(b.is_ascii_digit(), SgxStatus::InvalidParameter);
+                Ok(n * 10 + (b - b'0') as u32)
+            })
+        };
+        timestamp(
+            digits(&s[0..4])?,
+            digits(&s[5..7])?,
+            digits(&s[8..10])?,
+            digits(&s[11..13])?,
+            digits(&s[14..16])?,
+            digits(&s[17..19])?,
+        )
+    }
+}
+
+struct Parser<'a> {
+    input: &'a str,
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn peek(&self) -> Option<u8> {
+        self.input.as_bytes().get(self.pos).copied()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(b' ' | b'\t' | b'\n' | b'\r') = self.peek() {
+            self.pos += 1;
+        }
+    }
+
+    fn expect(&mut self, c: u8) -> SgxResult {
+        self.skip_whitespace();
+        ensure!(self.peek() == Some(c), SgxStatus::InvalidParameter);
+        self.pos += 1;
+        Ok(())
+    }
+
+    fn literal(&mut self, word: &str) -> SgxResult {
+        ensure!(
+            self.input[self.pos..].starts_with(word),
+            SgxStatus::InvalidParameter
+        );
+        self.pos += word.len();
+        Ok(())
+    }
+
+    fn value(&mut self, depth: usize) -> SgxResult<Json<'a>> {
+        ensure!(depth < MAX_DEPTH, SgxStatus::InvalidParameter);
+        self.skip_whitespace();
+        match self.peek().ok_or(SgxStatus::InvalidParameter)? {
+            b'{' => self.object(depth),
+            b'[' => self.array(depth),
+            b'"' => self.string().map(Json::String),
+            b't' => self.literal("true").map(|_| Json::Bool(true)),
+            b'f' => self.literal("false").map(|_| Json::Bool(false)),
+            b'n' => self.literal("null").map(|_| Json::Null),
+            b'-' | b'0'..=b'9' => self.number(),
+            _ => Err(SgxStatus::InvalidParameter),
+        }
+    }
+
+    fn object(&mut self, depth: usize) -> SgxResult<Json<'a>> {
+        self.expect(b'{')?;
+        let mut members: Vec<Member<'a>> = Vec::new();
+        self.skip_whitespace();
+        if self.peek() == Some(b'}') {
+            self.pos += 1;
+            return Ok(Json::Object(members));
+        }
+        loop {
+            self.skip_whitespace();
+            let key = self.string()?;
+            // A repeated key could be read differently by the signer.
+            ensure!(
+                members.iter().all(|member| member.key != key),
+                SgxStatus::InvalidParameter
+            );
+            self.expect(b':')?;
+            self.skip_whitespace();
+            let start = self.pos;
+            let value = self.value(depth + 1)?;
+            let raw = &self.input[start..self.pos];
+            members.push(Member { key, value, raw });
+
+            self.skip_whitespace();
+            match self.peek() {
+                Some(b',') => self.pos += 1,
+                Some(b'}') => {
+                    self.pos += 1;
+                    return Ok(Json::Object(members));
+                }
+                _ => return Err(SgxStatus::InvalidParameter),
+            }
+        }
+    }
+
+    fn array(&mut self, depth: usize) -> SgxResult<Json<'a>> {
+        self.expect(b'[')?;
+        let mut values = Vec::new();
+        self.skip_whitespace();
+        if self.peek() == Some(b']') {
+            self.pos += 1;
+            return Ok(Json::Array(values));
+        }
+        loop {
+            values.push(self.value(depth + 1)?);
+            self.skip_whitespace();
+            match self.peek() {
+                Some(b',') => self.pos += 1,
+                Some(b']') => {
+                    self.pos += 1;
+                    return Ok(Json::Array(values));
+                }
+                _ => return Err(SgxStatus::InvalidParameter),
+            }
+        }
+    }
+
+    fn string(&mut self) -> SgxResult<&'a str> {
+        ensure!(self.peek() == Some(b'"'), SgxStatus::InvalidParameter);
+        self.pos += 1;
+        let start = self.pos;
+        loop {
+            match self.peek().ok_or(SgxStatus::InvalidParameter)? {
+                b'"' => break,
+                b'\\' => self.pos += 2,
+                c if c < 0x20 => return Err(SgxStatus::InvalidParameter),
+                _ => self.pos += 1,
+            }
+        }
+        let s = self
+            .input
+            .get(start..self.pos)
+            .ok_or(SgxStatus::InvalidParameter)?;
+        self.pos += 1;
+        Ok(s)
+    }
+
+    fn number(&mut self) -> SgxResult<Json<'a>> {
+        let start = self.pos;
+        if self.peek() == Some(b'-') {
+            self.pos += 1;
+        }
+        let digits = |parser: &mut Parser<'_>| {
+            let start = parser.pos;
+            while let Some(b'0'..=b'9') = parser.peek() {
+                parser.pos += 1;
+            }
+            ensure!(parser.pos > start, SgxStatus::InvalidParameter);
+            Ok(())
+        };
+        digits(self)?;
+        if self.peek() == Some(b'.') {
+            self.pos += 1;
+            digits(self)?;
+        }
+        if let Some(b'e' | b'E') = self.peek() {
+            self.pos += 1;
+            if let Some(b'+' | b'-') = self.peek() {
+                self.pos += 1;
+            }
+            digits(self)?;
+        }
+        Ok(Json::Number(&self.input[start..self.pos]))
+    }
+}
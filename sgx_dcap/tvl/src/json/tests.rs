@@ -0,0 +1,84 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License..
+
+use super::Json;
+use sgx_test_utils::test_case;
+use sgx_types::error::SgxStatus;
+
+#[test_case]
+fn parse_values() {
+    let json = Json::parse(br#" {"a": [1, -2.5e3, true, null], "b": {"c": "d\"e"}} "#).unwrap();
+    let a = json.get("a").unwrap().as_array().unwrap();
+    assert_eq!(
+        a,
+        [
+            Json::Number("1"),
+            Json::Number("-2.5e3"),
+            Json::Bool(true),
+            Json::Null
+        ]
+    );
+    assert_eq!(a[0].as_u32(), Ok(1));
+    assert!(a[1].as_u32().is_err());
+
+    let b = json.member("b").unwrap();
+    assert_eq!(b.raw, r#"{"c": "d\"e"}"#);
+    assert_eq!(b.value.get("c").unwrap().as_str(), Ok(r#"d\"e"#));
+    assert_eq!(json.get("e"), Err(SgxStatus::InvalidParameter));
+}
+
+#[test_case]
+fn reject_malformed() {
+    for input in [
+        &br#"{"a": 1,}"#[..],
+        br#"{"a": 1} x"#,
+        br#"{"a": 1, "a": 2}"#,
+        br#"[01x]"#,
+        br#""unterminated"#,
+        "[".repeat(20).as_bytes(),
+        b"\"\xff\"",
+    ] {
+        assert!(Json::parse(input).is_err());
+    }
+}
+
+#[test_case]
+fn dates_and_hex() {
+    let json = Json::parse(
+        br#"["2024-06-01T12:30:15Z", "2024-06-01T12:30:15.250Z", "2024-06-01 12:30:15Z", "0aFF"]"#,
+    )
+    .unwrap();
+    let values = json.as_array().unwrap();
+    assert_eq!(values[0].as_date(), Ok(1_717_245_015));
+    assert_eq!(values[1].as_date(), Ok(1_717_245_015));
+    assert!(values[2].as_date().is_err());
+    assert_eq!(values[3].as_hex::<2>(), Ok([0x0a, 0xff]));
+    assert!(values[3].as_hex::<3>().is_err());
+}
+
+#[test_case]
+fn signed_envelope() {
+    let signature = "ab".repeat(64);
+    let input = alloc::format!(r#"{{"body":{{"x":1}},"signature":"{}"}}"#, signature);
+    let json = Json::parse(input.as_bytes()).unwrap();
+    let (body, signature) = json.signed_member("body").unwrap();
+    assert_eq!(body.raw, r#"{"x":1}"#);
+    assert_eq!(signature, [0xab; 64]);
+
+    let json = Json::parse(br#"{"body":{},"signature":"ab","extra":1}"#).unwrap();
+    assert!(json.signed_member("body").is_err());
+}
@@ -26,8 +26,28 @@ extern crate sgx_tse;
 #[macro_use]
 extern crate sgx_types;
 
+mod chain;
+mod der;
+mod json;
+mod pck;
+mod qe_identity;
+mod quote;
+mod source;
+mod supplemental;
+mod tcb_info;
 mod tvl;
+mod verifier;
+mod x509;
+pub use chain::*;
+pub use pck::*;
+pub use qe_identity::*;
+pub use quote::*;
+pub use source::*;
+pub use supplemental::*;
+pub use tcb_info::*;
 pub use tvl::*;
+pub use verifier::*;
+pub use x509::*;
 
 #[cfg(feature = "capi")]
 pub mod capi;
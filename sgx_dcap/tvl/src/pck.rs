@@ -0,0 +1,158 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License..
+
+use crate::der::{self, Der, ENUMERATED, INTEGER, OCTET_STRING, OID};
+use crate::x509::{Certificate, SGX_EXTENSION};
+use sgx_types::error::{SgxResult, SgxStatus};
+use sgx_types::types::{CpuSvn, Key128bit, PLATFORM_INSTANCE_ID_SIZE};
+
+#[cfg(feature = "unit_test")]
+mod tests;
+
+pub const CPUSVN_COMPONENTS: usize = 16;
+
+const PPID: u8 = 1;
+const TCB: u8 = 2;
+const PCE_ID: u8 = 3;
+const FMSPC: u8 = 4;
+const SGX_TYPE: u8 = 5;
+const PLATFORM_INSTANCE_ID: u8 = 6;
+const CONFIGURATION: u8 = 7;
+
+const TCB_PCESVN: u8 = 17;
+const TCB_CPUSVN: u8 = 18;
+
+const DYNAMIC_PLATFORM: u8 = 1;
+const CACHED_KEYS: u8 = 2;
+const SMT_ENABLED: u8 = 3;
+
+/// The Intel SGX extension of a PCK certificate
+/// (OID 1.2.840.113741.1.13.1).
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct PckExtensions {
+    pub ppid: Key128bit,
+    pub cpu_svn_components: [u8; CPUSVN_COMPONENTS],
+    pub pce_svn: u16,
+    pub cpu_svn: CpuSvn,
+    pub pce_id: [u8; 2],
+    pub fmspc: [u8; 6],
+    pub sgx_type: u8,
+    /// Only present in certificates issued by the Platform CA, as are the
+    /// configuration flags below.
+    pub platform_instance_id: Option<[u8; PLATFORM_INSTANCE_ID_SIZE]>,
+    pub dynamic_platform: Option<bool>,
+    pub cached_keys: Option<bool>,
+    pub smt_enabled: Option<bool>,
+}
+
+impl PckExtensions {
+    pub fn from_cert(cert: &Certificate<'_>) -> SgxResult<PckExtensions> {
+        let value = cert.sgx_extension().ok_or(SgxStatus::InvalidParameter)?;
+        let mut outer = Der::new(value);
+        let mut entries = outer.read_sequence()?;
+        outer.finish()?;
+
+        let mut pck = PckExtensions::default();
+        let mut seen = [false; 5];
+        while !entries.is_empty() {
+            let mut entry = entries.read_sequence()?;
+            let n = sgx_oid(entry.read(OID)?, &[]);
+            if let Some(n) = n.filter(|n| (PPID..=SGX_TYPE).contains(n)) {
+                ensure!(!seen[n as usize - 1], SgxStatus::InvalidParameter);
+                seen[n as usize - 1] = true;
+            }
+            match n {
+                Some(PPID) => pck.ppid = octets(&mut entry)?,
+                Some(TCB) => pck.read_tcb(entry.read_sequence()?)?,
+                Some(PCE_ID) => pck.pce_id = octets(&mut entry)?,
+                Some(FMSPC) => pck.fmspc = octets(&mut entry)?,
+                Some(SGX_TYPE) => {
+                    let value = der::unsigned_u32(entry.read(ENUMERATED)?)?;
+                    pck.sgx_type = u8::try_from(value).map_err(|_| SgxStatus::InvalidParameter)?;
+                }
+                Some(PLATFORM_INSTANCE_ID) => pck.platform_instance_id = Some(octets(&mut entry)?),
+                Some(CONFIGURATION) => pck.read_configuration(entry.read_sequence()?)?,
+                _ => {
+                    entry.read_any()?;
+                }
+            }
+            entry.finish()?;
+        }
+        ensure!(seen.iter().all(|&seen| seen), SgxStatus::InvalidParameter);
+        Ok(pck)
+    }
+
+    fn read_tcb(&mut self, mut components: Der<'_>) -> SgxResult {
+        let mut seen = 0_u32;
+        while !components.is_empty() {
+            let mut component = components.read_sequence()?;
+            let n = sgx_oid(component.read(OID)?, &[TCB]).ok_or(SgxStatus::InvalidParameter)?;
+            ensure!(
+                (1..=TCB_CPUSVN).contains(&n) && seen & (1 << (n - 1)) == 0,
+                SgxStatus::InvalidParameter
+            );
+            seen |= 1 << (n - 1);
+            match n {
+                1..=16 => {
+                    let svn = der::unsigned_u32(component.read(INTEGER)?)?;
+                    self.cpu_svn_components[n as usize - 1] =
+                        u8::try_from(svn).map_err(|_| SgxStatus::InvalidParameter)?;
+                }
+                TCB_PCESVN => {
+                    let svn = der::unsigned_u32(component.read(INTEGER)?)?;
+                    self.pce_svn = u16::try_from(svn).map_err(|_| SgxStatus::InvalidParameter)?;
+                }
+                _ => self.cpu_svn.svn = octets(&mut component)?,
+            }
+            component.finish()?;
+        }
+        ensure!(seen == (1 << TCB_CPUSVN) - 1, SgxStatus::InvalidParameter);
+        Ok(())
+    }
+
+    fn read_configuration(&mut self, mut flags: Der<'_>) -> SgxResult {
+        while !flags.is_empty() {
+            let mut flag = flags.read_sequence()?;
+            match sgx_oid(flag.read(OID)?, &[CONFIGURATION]) {
+                Some(DYNAMIC_PLATFORM) => self.dynamic_platform = Some(flag.read_bool()?),
+                Some(CACHED_KEYS) => self.cached_keys = Some(flag.read_bool()?),
+                Some(SMT_ENABLED) => self.smt_enabled = Some(flag.read_bool()?),
+                _ => {
+                    flag.read_any()?;
+                }
+            }
+            flag.finish()?;
+        }
+        Ok(())
+    }
+}
+
+// The last arc of `id` if it is `SGX_EXTENSION.{prefix}.n` with a single
+// byte `n`.
+fn sgx_oid(id: &[u8], prefix: &[u8]) -> Option<u8> {
+    let rest = id.strip_prefix(SGX_EXTENSION)?.strip_prefix(prefix)?;
+    match rest {
+        [n] if *n < 0x80 => Some(*n),
+        _ => None,
+    }
+}
+
+fn octets<const N: usize>(der: &mut Der<'_>) -> SgxResult<[u8; N]> {
+    der.read(OCTET_STRING)?
+        .try_into()
+        .map_err(|_| SgxStatus::InvalidParameter)
+}
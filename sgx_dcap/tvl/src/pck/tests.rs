@@ -0,0 +1,56 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License..
+
+use super::PckExtensions;
+use crate::x509::Certificate;
+use sgx_test_utils::test_case;
+use sgx_types::error::SgxStatus;
+
+const PCK: &[u8] = include_bytes!("../../testdata/pck.pem");
+const ROOT_CA: &[u8] = include_bytes!("../../testdata/root_ca.pem");
+
+#[test_case]
+fn parse_pck_extensions() {
+    let der = Certificate::pem_chain(PCK).unwrap().remove(0);
+    let cert = Certificate::from_der(&der).unwrap();
+    let pck = PckExtensions::from_cert(&cert).unwrap();
+
+    assert_eq!(pck.ppid, core::array::from_fn(|i| i as u8));
+    assert_eq!(pck.cpu_svn_components, [6; 16]);
+    assert_eq!(pck.pce_svn, 13);
+    assert_eq!(pck.cpu_svn.svn, [6; 16]);
+    assert_eq!(pck.pce_id, [0, 0]);
+    assert_eq!(pck.fmspc, [0x00, 0x90, 0x6e, 0xd5, 0x00, 0x00]);
+    assert_eq!(pck.sgx_type, 1);
+    assert_eq!(
+        pck.platform_instance_id,
+        Some(core::array::from_fn(|i| i as u8 + 16))
+    );
+    assert_eq!(pck.dynamic_platform, Some(false));
+    assert_eq!(pck.cached_keys, Some(true));
+    assert_eq!(pck.smt_enabled, Some(true));
+}
+
+#[test_case]
+fn reject_certificate_without_extension() {
+    let der = Certificate::pem_chain(ROOT_CA).unwrap().remove(0);
+    let cert = Certificate::from_der(&der).unwrap();
+    assert_eq!(
+        PckExtensions::from_cert(&cert),
+        Err(SgxStatus::InvalidParameter)
+    );
+}
@@ -0,0 +1,141 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License..
+
+use crate::json::Json;
+use crate::tcb_info::TcbStatus;
+use crate::x509::{trim_nul, verify_signature, Certificate};
+use alloc::vec::Vec;
+use sgx_types::error::{SgxResult, SgxStatus};
+use sgx_types::types::{time_t, ReportBody, ECP256_KEY_SIZE, SHA256_HASH_SIZE};
+
+#[cfg(feature = "unit_test")]
+mod tests;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct QeTcbLevel {
+    pub isv_svn: u16,
+    pub tcb_date: time_t,
+    pub status: TcbStatus,
+}
+
+/// The identity of the Quoting Enclave, version 2, as served by the PCS
+/// `qe/identity` endpoint.
+#[derive(Clone, Debug)]
+pub struct QeIdentity<'a> {
+    body: &'a str,
+    signature: [u8; ECP256_KEY_SIZE * 2],
+    pub version: u32,
+    pub issue_date: time_t,
+    pub next_update: time_t,
+    pub tcb_evaluation_data_number: u32,
+    pub misc_select: u32,
+    pub misc_select_mask: u32,
+    /// In report byte order: `flags || xfrm`, both little-endian.
+    pub attributes: [u8; 16],
+    pub attributes_mask: [u8; 16],
+    pub mr_signer: [u8; SHA256_HASH_SIZE],
+    pub isv_prod_id: u16,
+    pub tcb_levels: Vec<QeTcbLevel>,
+}
+
+impl<'a> QeIdentity<'a> {
+    pub fn from_json(json: &'a [u8]) -> SgxResult<QeIdentity<'a>> {
+        let json = Json::parse(trim_nul(json))?;
+        let (body, signature) = json.signed_member("enclaveIdentity")?;
+        let identity = &body.value;
+
+        let version = identity.get("version")?.as_u32()?;
+        ensure!(
+            version == 2 && identity.get("id")?.as_str()? == "QE",
+            SgxStatus::InvalidParameter
+        );
+
+        let tcb_levels = identity
+            .get("tcbLevels")?
+            .as_array()?
+            .iter()
+            .map(|level| {
+                let isv_svn = level.get("tcb")?.get("isvsvn")?.as_u32()?;
+                Ok(QeTcbLevel {
+                    isv_svn: u16::try_from(isv_svn).map_err(|_| SgxStatus::InvalidParameter)?,
+                    tcb_date: level.get("tcbDate")?.as_date()?,
+                    status: TcbStatus::from_json(level.get("tcbStatus")?)?,
+                })
+            })
+            .collect::<SgxResult<Vec<QeTcbLevel>>>()?;
+        ensure!(!tcb_levels.is_empty(), SgxStatus::InvalidParameter);
+
+        let isv_prod_id = identity.get("isvprodid")?.as_u32()?;
+        Ok(QeIdentity {
+            body: body.raw,
+            signature,
+            version,
+            issue_date: identity.get("issueDate")?.as_date()?,
+            next_update: identity.get("nextUpdate")?.as_date()?,
+            tcb_evaluation_data_number: identity.get("tcbEvaluationDataNumber")?.as_u32()?,
+            misc_select: u32::from_be_bytes(identity.get("miscselect")?.as_hex()?),
+            misc_select_mask: u32::from_be_bytes(identity.get("miscselectMask")?.as_hex()?),
+            attributes: identity.get("attributes")?.as_hex()?,
+            attributes_mask: identity.get("attributesMask")?.as_hex()?,
+            mr_signer: identity.get("mrsigner")?.as_hex()?,
+            isv_prod_id: u16::try_from(isv_prod_id).map_err(|_| SgxStatus::InvalidParameter)?,
+            tcb_levels,
+        })
+    }
+
+    /// Checks the signature over the `enclaveIdentity` body with the TCB
+    /// signing certificate. The certificate itself is not checked here.
+    #[inline]
+    pub fn verify_signature(&self, signer: &Certificate<'_>) -> SgxResult {
+        verify_signature(signer, self.body.as_bytes(), &self.signature)
+    }
+
+    #[inline]
+    pub fn is_valid_at(&self, time: time_t) -> bool {
+        self.issue_date <= time && time <= self.next_update
+    }
+
+    /// Matches the QE report against this identity and returns the highest
+    /// TCB level its ISV SVN meets.
+    pub fn verify_report(&self, report: &ReportBody) -> SgxResult<&QeTcbLevel> {
+        let misc_select = report.misc_select.bits();
+        ensure!(
+            misc_select & self.misc_select_mask == self.misc_select & self.misc_select_mask,
+            SgxStatus::InvalidSignature
+        );
+
+        let mut attributes = [0_u8; 16];
+        attributes[..8].copy_from_slice(&report.attributes.flags.bits().to_le_bytes());
+        attributes[8..].copy_from_slice(&report.attributes.xfrm.to_le_bytes());
+        let matches = attributes
+            .iter()
+            .zip(self.attributes.iter())
+            .zip(self.attributes_mask.iter())
+            .all(|((report, identity), mask)| report & mask == identity & mask);
+        ensure!(matches, SgxStatus::InvalidSignature);
+
+        ensure!(
+            report.mr_signer.m == self.mr_signer && report.isv_prod_id == self.isv_prod_id,
+            SgxStatus::InvalidSignature
+        );
+
+        self.tcb_levels
+            .iter()
+            .find(|level| level.isv_svn <= report.isv_svn)
+            .ok_or(SgxStatus::InvalidParameter)
+    }
+}
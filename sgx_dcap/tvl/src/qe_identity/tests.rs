@@ -0,0 +1,86 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License..
+
+use super::QeIdentity;
+use crate::tcb_info::TcbStatus;
+use crate::x509::Certificate;
+use sgx_test_utils::test_case;
+use sgx_types::error::SgxStatus;
+use sgx_types::types::{AttributesFlags, ReportBody};
+
+const QE_IDENTITY: &[u8] = include_bytes!("../../testdata/qe_identity.json");
+const TCB_SIGNING: &[u8] = include_bytes!("../../testdata/tcb_signing.pem");
+
+const QE_MRSIGNER: [u8; 32] = [
+    0x8c, 0x4f, 0x57, 0x75, 0xd7, 0x96, 0x50, 0x3e, 0x96, 0x13, 0x7f, 0x77, 0xc6, 0x8a, 0x82, 0x9a,
+    0x00, 0x56, 0xac, 0x8d, 0xed, 0x70, 0x14, 0x0b, 0x08, 0x1b, 0x09, 0x44, 0x90, 0xc5, 0x7b, 0xff,
+];
+
+fn qe_report(isv_svn: u16) -> ReportBody {
+    let mut report = ReportBody::default();
+    report.attributes.flags = unsafe { AttributesFlags::from_bits_unchecked(0x11) };
+    report.attributes.xfrm = 0x07;
+    report.mr_signer.m = QE_MRSIGNER;
+    report.isv_prod_id = 1;
+    report.isv_svn = isv_svn;
+    report
+}
+
+#[test_case]
+fn parse_qe_identity() {
+    let identity = QeIdentity::from_json(QE_IDENTITY).unwrap();
+    assert_eq!(identity.version, 2);
+    assert_eq!(identity.tcb_evaluation_data_number, 15);
+    assert_eq!(identity.misc_select_mask, 0xffff_ffff);
+    assert_eq!(identity.attributes[0], 0x11);
+    assert_eq!(
+        identity.attributes_mask[..8],
+        [0xfb, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff]
+    );
+    assert_eq!(identity.mr_signer, QE_MRSIGNER);
+    assert_eq!(identity.isv_prod_id, 1);
+    assert_eq!(identity.tcb_levels.len(), 2);
+
+    let signer = Certificate::pem_chain(TCB_SIGNING).unwrap().remove(0);
+    let signer = Certificate::from_der(&signer).unwrap();
+    assert_eq!(identity.verify_signature(&signer), Ok(()));
+}
+
+#[test_case]
+fn match_qe_report() {
+    let identity = QeIdentity::from_json(QE_IDENTITY).unwrap();
+    let status = |report: ReportBody| identity.verify_report(&report).map(|level| level.status);
+
+    assert_eq!(status(qe_report(8)), Ok(TcbStatus::UpToDate));
+    assert_eq!(status(qe_report(7)), Ok(TcbStatus::OutOfDate));
+    assert_eq!(status(qe_report(5)), Err(SgxStatus::InvalidParameter));
+
+    let mut report = qe_report(8);
+    report.mr_signer.m[0] ^= 1;
+    assert_eq!(status(report), Err(SgxStatus::InvalidSignature));
+
+    let mut report = qe_report(8);
+    report.isv_prod_id = 2;
+    assert_eq!(status(report), Err(SgxStatus::InvalidSignature));
+
+    // DEBUG is under the mask, MODE64BIT is not.
+    let mut report = qe_report(8);
+    report.attributes.flags = unsafe { AttributesFlags::from_bits_unchecked(0x13) };
+    assert_eq!(status(report), Err(SgxStatus::InvalidSignature));
+    report.attributes.flags = unsafe { AttributesFlags::from_bits_unchecked(0x15) };
+    assert_eq!(status(report), Ok(TcbStatus::UpToDate));
+}
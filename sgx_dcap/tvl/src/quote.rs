@@ -0,0 +1,226 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License..
+use crate::x509::Certificate;
+
+use alloc::vec::Vec;
+use core::mem;
+use core::ptr;
+use sgx_crypto::ecc::EcSignature;
+use sgx_crypto::sha::Sha256;
+use sgx_trts::trts::EnclaveRange;
+use sgx_types::error::SgxStatus;
+use sgx_types::types::{
+    QlAuthData, QlCertificationData, QlEcdsaSigData, Quote3, ECP256_KEY_SIZE, SHA256_HASH_SIZE,
+};
+
+#[cfg(feature = "unit_test")]
+mod tests;
+
+const PCK_CERT_CHAIN: u16 = 5;
+
+const PEM_BEGIN_CERT: &[u8] = b"-----BEGIN CERTIFICATE-----";
+const PEM_END_CERT: &[u8] = b"-----END CERTIFICATE-----";
+
+/// A trusted root CA certificate, DER encoded.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Cert<'a> {
+    pub der: &'a [u8],
+}
+
+impl<'a> Cert<'a> {
+    #[inline]
+    pub fn new(der: &'a [u8]) -> Cert<'a> {
+        Cert { der }
+    }
+}
+
+pub trait Quote3Verify: Sized {
+    type Error;
+
+    fn verify_qe_report(quote: &[u8]) -> Result<(), Self::Error>;
+
+    /// Checks that the PCK certificate chain in the quote ends at one of
+    /// `roots`. The signatures along the chain are left to the QvE.
+    fn verify_root_ca(quote: &[u8], roots: &[Cert<'_>]) -> Result<(), Self::Error>;
+}
+
+impl Quote3Verify for Quote3 {
+    type Error = SgxStatus;
+
+    fn verify_qe_report(quote: &[u8]) -> Result<(), SgxStatus> {
+        ensure!(quote.is_enclave_range(), SgxStatus::InvalidParameter);
+        let (sig_data, auth, cert_chain) = split_signature(quote)?;
+
+        // The first 32 bytes of the QE report data bind the attestation key
+        // and the QE authentication data.
+        let attest_pub_key = sig_data.attest_pub_key;
+        let qe_report = sig_data.qe_report;
+        let mut sha = Sha256::new()?;
+        sha.update(&attest_pub_key)?;
+        if !auth.is_empty() {
+            sha.update(auth)?;
+        }
+        let hash = sha.finalize()?;
+        ensure!(
+            hash.eq(&qe_report.report_data.d[..SHA256_HASH_SIZE]),
+            SgxStatus::InvalidSignature
+        );
+
+        let pck_cert = pem_first_cert(cert_chain)?;
+        let pck_pub_key = Certificate::from_der(&pck_cert)?.public_key();
+        let qe_report_sig = EcSignature::from(be_to_le(&sig_data.qe_report_sig));
+        let valid = pck_pub_key.verify(&qe_report, &qe_report_sig)?;
+        ensure!(valid, SgxStatus::InvalidSignature);
+
+        Ok(())
+    }
+
+    fn verify_root_ca(quote: &[u8], roots: &[Cert<'_>]) -> Result<(), SgxStatus> {
+        ensure!(quote.is_enclave_range(), SgxStatus::InvalidParameter);
+        let (_, _, cert_chain) = split_signature(quote)?;
+        let root = pem_last_cert(cert_chain)?;
+        ensure!(
+            roots.iter().any(|cert| cert.der == root.as_slice()),
+            SgxStatus::InvalidSignature
+        );
+        Ok(())
+    }
+}
+
+// Splits the quote signature into the ECDSA signature data, the QE
+// authentication data and the PEM certificate chain.
+pub(crate) fn split_signature(quote: &[u8]) -> Result<(QlEcdsaSigData, &[u8], &[u8]), SgxStatus> {
+    let quote3_len = mem::size_of::<Quote3>();
+    ensure!(
+        quote.len() >= quote3_len + mem::size_of::<QlEcdsaSigData>(),
+        SgxStatus::InvalidParameter
+    );
+
+    let quote3 = unsafe { ptr::read_unaligned(quote.as_ptr() as *const Quote3) };
+    let signature_len = quote3.signature_len as usize;
+    ensure!(
+        signature_len <= quote.len() - quote3_len,
+        SgxStatus::InvalidParameter
+    );
+    let signature = &quote[quote3_len..quote3_len + signature_len];
+
+    let mut offset = mem::size_of::<QlEcdsaSigData>();
+    ensure!(
+        signature.len() >= offset + mem::size_of::<QlAuthData>(),
+        SgxStatus::InvalidParameter
+    );
+    let sig_data = unsafe { ptr::read_unaligned(signature.as_ptr() as *const QlEcdsaSigData) };
+
+    let auth_data =
+        unsafe { ptr::read_unaligned(signature[offset..].as_ptr() as *const QlAuthData) };
+    offset += mem::size_of::<QlAuthData>();
+    let auth_len = auth_data.size as usize;
+    ensure!(
+        signature.len() >= offset + auth_len + mem::size_of::<QlCertificationData>(),
+        SgxStatus::InvalidParameter
+    );
+    let auth = &signature[offset..offset + auth_len];
+    offset += auth_len;
+
+    let cert_data =
+        unsafe { ptr::read_unaligned(signature[offset..].as_ptr() as *const QlCertificationData) };
+    offset += mem::size_of::<QlCertificationData>();
+    let cert_len = cert_data.size as usize;
+    ensure!(
+        signature.len() - offset >= cert_len,
+        SgxStatus::InvalidParameter
+    );
+    let cert_key_type = cert_data.cert_key_type;
+    ensure!(cert_key_type == PCK_CERT_CHAIN, SgxStatus::InvalidParameter);
+    let cert_chain = &signature[offset..offset + cert_len];
+
+    Ok((sig_data, auth, cert_chain))
+}
+
+// Quotes carry P-256 coordinates big-endian, the crypto library expects
+// each coordinate little-endian.
+pub(crate) fn be_to_le(bytes: &[u8; ECP256_KEY_SIZE * 2]) -> [u8; ECP256_KEY_SIZE * 2] {
+    let mut le = *bytes;
+    le[..ECP256_KEY_SIZE].reverse();
+    le[ECP256_KEY_SIZE..].reverse();
+    le
+}
+
+pub(crate) fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack
+        .windows(needle.len())
+        .position(|window| window == needle)
+}
+
+fn pem_first_cert(pem: &[u8]) -> Result<Vec<u8>, SgxStatus> {
+    let begin =
+        find(pem, PEM_BEGIN_CERT).ok_or(SgxStatus::InvalidParameter)? + PEM_BEGIN_CERT.len();
+    let end = find(&pem[begin..], PEM_END_CERT).ok_or(SgxStatus::InvalidParameter)? + begin;
+    base64_decode(&pem[begin..end])
+}
+
+// The chain runs from the PCK certificate up to the root, so the root is the
+// last certificate.
+fn pem_last_cert(pem: &[u8]) -> Result<Vec<u8>, SgxStatus> {
+    let begin = pem
+        .windows(PEM_BEGIN_CERT.len())
+        .rposition(|window| window == PEM_BEGIN_CERT)
+        .ok_or(SgxStatus::InvalidParameter)?
+        + PEM_BEGIN_CERT.len();
+    let end = find(&pem[begin..], PEM_END_CERT).ok_or(SgxStatus::InvalidParameter)? + begin;
+    base64_decode(&pem[begin..end])
+}
+
+fn base64_value(c: u8) -> Option<u32> {
+    match c {
+        b'A'..=b'Z' => Some((c - b'A') as u32),
+        b'a'..=b'z' => Some((c - b'a' + 26) as u32),
+        b'0'..=b'9' => Some((c - b'0' + 52) as u32),
+        b'+' => Some(62),
+        b'/' => Some(63),
+        _ => None,
+    }
+}
+
+pub(crate) fn base64_decode(input: &[u8]) -> Result<Vec<u8>, SgxStatus> {
+    let mut output = Vec::with_capacity(input.len() / 4 * 3);
+    let mut acc = 0_u32;
+    let mut bits = 0_u32;
+    let mut padding = false;
+
+    for &c in input {
+        match c {
+            b'\r' | b'\n' | b' ' | b'\t' => continue,
+            b'=' => {
+                padding = true;
+                continue;
+            }
+            _ => {}
+        }
+        ensure!(!padding, SgxStatus::InvalidParameter);
+        let value = base64_value(c).ok_or(SgxStatus::InvalidParameter)?;
+        acc = (acc << 6) | value;
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            output.push((acc >> bits) as u8);
+            acc &= (1 << bits) - 1;
+        }
+    }
+    ensure!(!output.is_empty(), SgxStatus::InvalidParameter);
+    Ok(output)
+}
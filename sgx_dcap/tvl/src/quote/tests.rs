@@ -0,0 +1,302 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License..
+
+use super::{base64_decode, be_to_le, Cert, Quote3Verify, PCK_CERT_CHAIN};
+use alloc::vec::Vec;
+use core::mem;
+use core::ptr;
+use sgx_crypto::ecc::EcKeyPair;
+use sgx_crypto::sha::Sha256;
+use sgx_test_utils::test_case;
+use sgx_types::error::SgxStatus;
+use sgx_types::types::{
+    QlAuthData, QlCertificationData, QlEcdsaSigData, Quote3, ReportBody, SHA256_HASH_SIZE,
+};
+
+const BASE64_TABLE: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+fn base64_encode(input: &[u8]) -> Vec<u8> {
+    let mut output = Vec::new();
+    for chunk in input.chunks(3) {
+        let b = [
+            chunk[0],
+            *chunk.get(1).unwrap_or(&0),
+            *chunk.get(2).unwrap_or(&0),
+        ];
+        let n = ((b[0] as u32) << 16) | ((b[1] as u32) << 8) | b[2] as u32;
+        output.push(BASE64_TABLE[(n >> 18) as usize & 0x3f]);
+        output.push(BASE64_TABLE[(n >> 12) as usize & 0x3f]);
+        output.push(if chunk.len() > 1 {
+            BASE64_TABLE[(n >> 6) as usize & 0x3f]
+        } else {
+            b'='
+        });
+        output.push(if chunk.len() > 2 {
+            BASE64_TABLE[n as usize & 0x3f]
+        } else {
+            b'='
+        });
+    }
+    output
+}
+
+fn tlv(tag: u8, parts: &[&[u8]]) -> Vec<u8> {
+    let len: usize = parts.iter().map(|part| part.len()).sum();
+    let mut der = alloc::vec![tag];
+    match len {
+        0..=0x7f => der.push(len as u8),
+        0x80..=0xff => der.extend_from_slice(&[0x81, len as u8]),
+        _ => der.extend_from_slice(&[0x82, (len >> 8) as u8, len as u8]),
+    }
+    for part in parts {
+        der.extend_from_slice(part);
+    }
+    der
+}
+
+// A well-formed certificate for the key of `key_pair`, with an empty
+// issuer and subject and a placeholder signature.
+fn cert_der(key_pair: &EcKeyPair) -> Vec<u8> {
+    cert_der_named(key_pair, &tlv(0x30, &[]))
+}
+
+fn cert_der_named(key_pair: &EcKeyPair, issuer: &[u8]) -> Vec<u8> {
+    let public_key: [u8; 64] = key_pair.public_key().into();
+    let ecdsa_with_sha256 = tlv(0x06, &[&[0x2a, 0x86, 0x48, 0xce, 0x3d, 0x04, 0x03, 0x02]]);
+    let algorithm = tlv(0x30, &[&ecdsa_with_sha256]);
+    let name = tlv(0x30, &[]);
+    let validity = tlv(
+        0x30,
+        &[
+            &tlv(0x17, &[b"240101000000Z"]),
+            &tlv(0x17, &[b"491231235959Z"]),
+        ],
+    );
+    let spki = tlv(
+        0x30,
+        &[
+            &tlv(
+                0x30,
+                &[
+                    &tlv(0x06, &[&[0x2a, 0x86, 0x48, 0xce, 0x3d, 0x02, 0x01]]),
+                    &tlv(0x06, &[&[0x2a, 0x86, 0x48, 0xce, 0x3d, 0x03, 0x01, 0x07]]),
+                ],
+            ),
+            &tlv(0x03, &[&[0x00, 0x04], &be_to_le(&public_key)]),
+        ],
+    );
+    let tbs = tlv(
+        0x30,
+        &[
+            &tlv(0xa0, &[&tlv(0x02, &[&[0x02]])]),
+            &tlv(0x02, &[&[0x01]]),
+            &algorithm,
+            issuer,
+            &validity,
+            &name,
+            &spki,
+        ],
+    );
+    let signature = tlv(0x30, &[&tlv(0x02, &[&[0x01]]), &tlv(0x02, &[&[0x01]])]);
+    tlv(
+        0x30,
+        &[&tbs, &algorithm, &tlv(0x03, &[&[0x00], &signature])],
+    )
+}
+
+// PEM chain of the PCK certificate followed by `issuers`, DER encoded.
+fn pck_cert_chain(pck_der: &[u8], issuers: &[&[u8]]) -> Vec<u8> {
+    let mut pem = Vec::new();
+    for der in core::iter::once(pck_der).chain(issuers.iter().copied()) {
+        pem.extend_from_slice(b"-----BEGIN CERTIFICATE-----\n");
+        pem.extend_from_slice(&base64_encode(der));
+        pem.extend_from_slice(b"\n-----END CERTIFICATE-----\n");
+    }
+    pem
+}
+
+fn build_quote(tamper_report_data: bool) -> Vec<u8> {
+    build_quote_under(tamper_report_data, &[])
+}
+
+fn build_quote_under(tamper_report_data: bool, issuers: &[&[u8]]) -> Vec<u8> {
+    let pck_key = EcKeyPair::create().unwrap();
+    build_quote_for(tamper_report_data, &pck_key, &cert_der(&pck_key), issuers)
+}
+
+fn build_quote_for(
+    tamper_report_data: bool,
+    pck_key: &EcKeyPair,
+    pck_der: &[u8],
+    issuers: &[&[u8]],
+) -> Vec<u8> {
+    let attest_pub_key = [0x5a_u8; 64];
+    let auth = [0xa5_u8; 32];
+
+    let mut sha = Sha256::new().unwrap();
+    sha.update(&attest_pub_key).unwrap();
+    sha.update(&auth).unwrap();
+    let hash = sha.finalize().unwrap();
+
+    let mut qe_report = ReportBody::default();
+    qe_report.report_data.d[..SHA256_HASH_SIZE].copy_from_slice(&hash);
+    let qe_report_sig: [u8; 64] = pck_key.private_key().sign(&qe_report).unwrap().into();
+    if tamper_report_data {
+        qe_report.report_data.d[0] ^= 0xff;
+    }
+
+    let sig_data = QlEcdsaSigData {
+        sig: [0; 64],
+        attest_pub_key,
+        qe_report,
+        qe_report_sig: be_to_le(&qe_report_sig),
+        auth_certification_data: [],
+    };
+    let cert_chain = pck_cert_chain(pck_der, issuers);
+
+    let signature_len = mem::size_of::<QlEcdsaSigData>()
+        + mem::size_of::<QlAuthData>()
+        + auth.len()
+        + mem::size_of::<QlCertificationData>()
+        + cert_chain.len();
+    let mut quote = alloc::vec![0_u8; mem::size_of::<Quote3>() + signature_len];
+
+    let quote3 = Quote3 {
+        signature_len: signature_len as u32,
+        ..Default::default()
+    };
+    let auth_data = QlAuthData {
+        size: auth.len() as u16,
+        auth_data: [],
+    };
+    let cert_data = QlCertificationData {
+        cert_key_type: PCK_CERT_CHAIN,
+        size: cert_chain.len() as u32,
+        certification_data: [],
+    };
+
+    let mut offset = 0;
+    unsafe {
+        let base = quote.as_mut_ptr();
+        ptr::write_unaligned(base as *mut Quote3, quote3);
+        offset += mem::size_of::<Quote3>();
+        ptr::write_unaligned(base.add(offset) as *mut QlEcdsaSigData, sig_data);
+        offset += mem::size_of::<QlEcdsaSigData>();
+        ptr::write_unaligned(base.add(offset) as *mut QlAuthData, auth_data);
+        offset += mem::size_of::<QlAuthData>();
+    }
+    quote[offset..offset + auth.len()].copy_from_slice(&auth);
+    offset += auth.len();
+    unsafe {
+        ptr::write_unaligned(
+            quote.as_mut_ptr().add(offset) as *mut QlCertificationData,
+            cert_data,
+        );
+    }
+    offset += mem::size_of::<QlCertificationData>();
+    quote[offset..].copy_from_slice(&cert_chain);
+    quote
+}
+
+#[test_case]
+fn base64_roundtrip() {
+    let data = [0_u8, 1, 2, 0xfd, 0xfe, 0xff, 0x80];
+    for len in 1..=data.len() {
+        let encoded = base64_encode(&data[..len]);
+        assert_eq!(base64_decode(&encoded).unwrap(), &data[..len]);
+    }
+    assert!(base64_decode(b"AA=A").is_err());
+}
+
+#[test_case]
+fn verify_qe_report_valid_quote() {
+    let quote = build_quote(false);
+    assert_eq!(Quote3::verify_qe_report(&quote), Ok(()));
+}
+
+#[test_case]
+fn verify_qe_report_tampered_report_data() {
+    let quote = build_quote(true);
+    assert_eq!(
+        Quote3::verify_qe_report(&quote),
+        Err(SgxStatus::InvalidSignature)
+    );
+}
+
+#[test_case]
+fn verify_qe_report_key_from_parsed_cert() {
+    // The issuer name carries the SPKI of another key ahead of the real one,
+    // which a scan of the DER for an SPKI would pick up instead.
+    let pck_key = EcKeyPair::create().unwrap();
+    let decoy = cert_der(&EcKeyPair::create().unwrap());
+    let start = decoy
+        .windows(4)
+        .position(|window| window == [0x30, 0x59, 0x30, 0x13])
+        .unwrap();
+    let decoy_spki = &decoy[start..start + 91];
+    let issuer = tlv(0x30, &[&tlv(0x04, &[decoy_spki])]);
+
+    let pck_der = cert_der_named(&pck_key, &issuer);
+    let quote = build_quote_for(false, &pck_key, &pck_der, &[]);
+    assert_eq!(Quote3::verify_qe_report(&quote), Ok(()));
+}
+
+#[test_case]
+fn verify_qe_report_truncated_quote() {
+    let quote = build_quote(false);
+    assert_eq!(
+        Quote3::verify_qe_report(&quote[..quote.len() - 1]),
+        Err(SgxStatus::InvalidParameter)
+    );
+}
+
+#[test_case]
+fn verify_root_ca_accepts_any_trusted_root() {
+    let old_root = cert_der(&EcKeyPair::create().unwrap());
+    let new_root = cert_der(&EcKeyPair::create().unwrap());
+    let intermediate = cert_der(&EcKeyPair::create().unwrap());
+    let roots = [Cert::new(&old_root), Cert::new(&new_root)];
+
+    for root in [&old_root, &new_root] {
+        let quote = build_quote_under(false, &[&intermediate, root]);
+        assert_eq!(Quote3::verify_qe_report(&quote), Ok(()));
+        assert_eq!(Quote3::verify_root_ca(&quote, &roots), Ok(()));
+    }
+}
+
+#[test_case]
+fn verify_root_ca_rejects_untrusted_root() {
+    let trusted = cert_der(&EcKeyPair::create().unwrap());
+    let untrusted = cert_der(&EcKeyPair::create().unwrap());
+    let intermediate = cert_der(&EcKeyPair::create().unwrap());
+
+    let quote = build_quote_under(false, &[&intermediate, &untrusted]);
+    assert_eq!(
+        Quote3::verify_root_ca(&quote, &[Cert::new(&trusted)]),
+        Err(SgxStatus::InvalidSignature)
+    );
+    // A trusted certificate in the middle of the chain is not a root.
+    let quote = build_quote_under(false, &[&trusted, &untrusted]);
+    assert_eq!(
+        Quote3::verify_root_ca(&quote, &[Cert::new(&trusted)]),
+        Err(SgxStatus::InvalidSignature)
+    );
+    assert_eq!(
+        Quote3::verify_root_ca(&quote, &[]),
+        Err(SgxStatus::InvalidSignature)
+    );
+}
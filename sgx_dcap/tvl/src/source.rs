@@ -0,0 +1,79 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License..
+
+use core::mem;
+use core::ptr;
+use sgx_crypto::sha::Sha256;
+use sgx_types::error::{SgxResult, SgxStatus};
+use sgx_types::types::Quote3;
+
+const CHUNK_SIZE: usize = 4096;
+
+/// A quote that can be read piecewise, e.g. one staged by the host in a
+/// memory-mapped file, so that it need not be copied into the enclave whole.
+pub trait QuoteSource {
+    /// Reads up to `buf.len()` bytes starting at `offset`; returns the number
+    /// of bytes read, with 0 marking the end of the quote.
+    fn read_at(&self, offset: usize, buf: &mut [u8]) -> Result<usize, SgxStatus>;
+}
+
+impl QuoteSource for [u8] {
+    fn read_at(&self, offset: usize, buf: &mut [u8]) -> Result<usize, SgxStatus> {
+        let rest = self.get(offset..).ok_or(SgxStatus::InvalidParameter)?;
+        let n = rest.len().min(buf.len());
+        buf[..n].copy_from_slice(&rest[..n]);
+        Ok(n)
+    }
+}
+
+// Feeds the whole quote into `sha` one chunk at a time, copying each chunk
+// into enclave memory before hashing it. The header and report body are
+// kept as they were hashed and returned, so callers never read them back
+// from a source that may have changed since.
+pub(crate) fn digest_source<S: QuoteSource + ?Sized>(
+    sha: &mut Sha256,
+    source: &S,
+) -> SgxResult<Quote3> {
+    let mut header = [0_u8; mem::size_of::<Quote3>()];
+    let mut chunk = [0_u8; CHUNK_SIZE];
+    let mut offset = 0_usize;
+    loop {
+        let n = source.read_at(offset, &mut chunk)?;
+        if n == 0 {
+            break;
+        }
+        ensure!(n <= chunk.len(), SgxStatus::InvalidParameter);
+        sha.update(&chunk[..n])?;
+
+        if let Some(rest) = header.get_mut(offset..) {
+            let len = rest.len().min(n);
+            rest[..len].copy_from_slice(&chunk[..len]);
+        }
+
+        offset = offset
+            .checked_add(n)
+            .filter(|&len| len < u32::MAX as usize)
+            .ok_or(SgxStatus::InvalidParameter)?;
+    }
+
+    ensure!(
+        offset >= mem::size_of::<Quote3>(),
+        SgxStatus::InvalidParameter
+    );
+    // Quote3 is packed plain data, valid for any bytes.
+    Ok(unsafe { ptr::read_unaligned(header.as_ptr() as *const Quote3) })
+}
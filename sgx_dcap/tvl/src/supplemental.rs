@@ -0,0 +1,213 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License..
+
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::mem::{self, MaybeUninit};
+use core::ptr;
+use core::str;
+use sgx_types::error::{SgxResult, SgxStatus};
+use sgx_types::types::{
+    time_t, CpuSvn, Key128bit, PckCertFlag, QlQvSupplemental, PLATFORM_INSTANCE_ID_SIZE,
+    ROOT_KEY_ID_SIZE,
+};
+
+#[cfg(feature = "unit_test")]
+mod tests;
+
+/// The version of the supplemental data `QuoteVerifier` produces.
+pub const SUPPLEMENTAL_DATA_VERSION: u32 = 3;
+pub const SUPPLEMENTAL_MAJOR_VERSION: u16 = 3;
+/// Minor version 1 appends `sa_list`.
+pub const SUPPLEMENTAL_LATEST_MINOR_VERSION: u16 = 1;
+pub const MAX_SA_LIST_SIZE: usize = 320;
+
+/// Supplemental data returned by the QvE alongside the verification result.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SupplementalData {
+    /// The major version in the low 16 bits, the minor in the high 16.
+    pub version: u32,
+    pub earliest_issue_date: time_t,
+    pub latest_issue_date: time_t,
+    pub earliest_expiration_date: time_t,
+    pub tcb_level_date_tag: time_t,
+    pub pck_crl_num: u32,
+    pub root_ca_crl_num: u32,
+    pub tcb_eval_ref_num: u32,
+    pub root_key_id: [u8; ROOT_KEY_ID_SIZE],
+    pub pck_ppid: Key128bit,
+    pub tcb_cpusvn: CpuSvn,
+    pub tcb_pce_isvsvn: u16,
+    pub pce_id: u16,
+    pub tee_type: u32,
+    pub sgx_type: u8,
+    pub platform_instance_id: [u8; PLATFORM_INSTANCE_ID_SIZE],
+    /// `None` when the collateral does not say, e.g. for processor CA certs.
+    pub dynamic_platform: Option<bool>,
+    pub cached_keys: Option<bool>,
+    pub smt_enabled: Option<bool>,
+    /// Advisory IDs of the matched TCB level; empty before minor version 1.
+    pub sa_list: Vec<String>,
+}
+
+impl SupplementalData {
+    /// Parses the QvE layout of any minor version up to
+    /// `SUPPLEMENTAL_LATEST_MINOR_VERSION`; other versions are rejected.
+    pub fn from_bytes(bytes: &[u8]) -> SgxResult<SupplementalData> {
+        let raw = read_supplemental(bytes)?;
+        let mut data = SupplementalData::from(raw);
+        if data.minor_version() >= 1 {
+            data.sa_list = read_sa_list(bytes)?;
+        }
+        Ok(data)
+    }
+
+    #[inline]
+    pub fn major_version(&self) -> u16 {
+        self.version as u16
+    }
+
+    #[inline]
+    pub fn minor_version(&self) -> u16 {
+        (self.version >> 16) as u16
+    }
+
+    pub fn check_platform_flags(&self, policy: &PlatformFlagsPolicy) -> bool {
+        let check = |required: Option<bool>, actual: Option<bool>| match required {
+            Some(required) => actual == Some(required),
+            None => true,
+        };
+        check(policy.dynamic_platform, self.dynamic_platform)
+            && check(policy.cached_keys, self.cached_keys)
+            && check(policy.smt_enabled, self.smt_enabled)
+    }
+}
+
+impl From<QlQvSupplemental> for SupplementalData {
+    fn from(raw: QlQvSupplemental) -> SupplementalData {
+        SupplementalData {
+            version: raw.version,
+            earliest_issue_date: raw.earliest_issue_date,
+            latest_issue_date: raw.latest_issue_date,
+            earliest_expiration_date: raw.earliest_expiration_date,
+            tcb_level_date_tag: raw.tcb_level_date_tag,
+            pck_crl_num: raw.pck_crl_num,
+            root_ca_crl_num: raw.root_ca_crl_num,
+            tcb_eval_ref_num: raw.tcb_eval_ref_num,
+            root_key_id: raw.root_key_id,
+            pck_ppid: raw.pck_ppid,
+            tcb_cpusvn: raw.tcb_cpusvn,
+            tcb_pce_isvsvn: raw.tcb_pce_isvsvn,
+            pce_id: raw.pce_id,
+            tee_type: raw.tee_type,
+            sgx_type: raw.sgx_type,
+            platform_instance_id: raw.platform_instance_id,
+            dynamic_platform: flag(raw.dynamic_platform),
+            cached_keys: flag(raw.cached_keys),
+            smt_enabled: flag(raw.smt_enabled),
+            sa_list: Vec::new(),
+        }
+    }
+}
+
+/// Platform instance flags a relying party requires. `Some(true)` requires a
+/// flag, `Some(false)` forbids it and `None` accepts either; a flag the
+/// collateral leaves undefined only passes `None`.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct PlatformFlagsPolicy {
+    pub dynamic_platform: Option<bool>,
+    pub cached_keys: Option<bool>,
+    pub smt_enabled: Option<bool>,
+}
+
+#[inline]
+fn flag(flag: PckCertFlag) -> Option<bool> {
+    match flag {
+        PckCertFlag::False => Some(false),
+        PckCertFlag::True => Some(true),
+        PckCertFlag::Undefined => None,
+    }
+}
+
+// Offsets of the three flag words; minor version 1 places `sa_list` right
+// after the last one, in what is tail padding of `QlQvSupplemental`.
+fn flag_offsets() -> [usize; 3] {
+    let uninit = MaybeUninit::<QlQvSupplemental>::uninit();
+    let base = uninit.as_ptr();
+    unsafe {
+        [
+            ptr::addr_of!((*base).dynamic_platform) as usize - base as usize,
+            ptr::addr_of!((*base).cached_keys) as usize - base as usize,
+            ptr::addr_of!((*base).smt_enabled) as usize - base as usize,
+        ]
+    }
+}
+
+#[inline]
+fn sa_list_offset() -> usize {
+    flag_offsets()[2] + mem::size_of::<PckCertFlag>()
+}
+
+// Reads the raw layout, rejecting unknown versions, buffers too short for
+// their minor version and flag words that are not valid `PckCertFlag`
+// discriminants before the bytes are reinterpreted.
+pub(crate) fn read_supplemental(bytes: &[u8]) -> SgxResult<QlQvSupplemental> {
+    ensure!(
+        bytes.len() >= mem::size_of::<QlQvSupplemental>(),
+        SgxStatus::InvalidParameter
+    );
+
+    let version = unsafe { ptr::read_unaligned(bytes.as_ptr() as *const u32) };
+    let (major, minor) = (version as u16, (version >> 16) as u16);
+    ensure!(
+        major == SUPPLEMENTAL_MAJOR_VERSION && minor <= SUPPLEMENTAL_LATEST_MINOR_VERSION,
+        SgxStatus::InvalidParameter
+    );
+    if minor >= 1 {
+        ensure!(
+            bytes.len() >= sa_list_offset() + MAX_SA_LIST_SIZE,
+            SgxStatus::InvalidParameter
+        );
+    }
+
+    for offset in flag_offsets() {
+        let value = unsafe { ptr::read_unaligned(bytes[offset..].as_ptr() as *const u32) };
+        ensure!(
+            value <= PckCertFlag::Undefined as u32,
+            SgxStatus::InvalidParameter
+        );
+    }
+
+    Ok(unsafe { ptr::read_unaligned(bytes.as_ptr() as *const QlQvSupplemental) })
+}
+
+// `sa_list` is a NUL-terminated, comma-separated list of advisory IDs.
+fn read_sa_list(bytes: &[u8]) -> SgxResult<Vec<String>> {
+    let offset = sa_list_offset();
+    let field = &bytes[offset..offset + MAX_SA_LIST_SIZE];
+    let len = field
+        .iter()
+        .position(|&b| b == 0)
+        .ok_or(SgxStatus::InvalidParameter)?;
+    let list = str::from_utf8(&field[..len]).map_err(|_| SgxStatus::InvalidParameter)?;
+    Ok(list
+        .split(',')
+        .map(str::trim)
+        .filter(|id| !id.is_empty())
+        .map(String::from)
+        .collect())
+}
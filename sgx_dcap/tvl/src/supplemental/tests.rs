@@ -0,0 +1,198 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License..
+
+use super::{PlatformFlagsPolicy, SupplementalData};
+use core::array;
+use core::mem;
+use core::slice;
+use sgx_test_utils::test_case;
+use sgx_types::error::SgxStatus;
+use sgx_types::types::{CpuSvn, PckCertFlag, QlQvSupplemental};
+
+// Synthetic supplemental data 3.1 written by testdata/generate.py to the
+// `sgx_ql_qv_supplemental_t` layout for quote.dat. It is not a capture from
+// a real QvE, so it checks the parser against our reading of the layout only.
+const SUPPLEMENTAL: &[u8] = include_bytes!("../../testdata/supplemental.dat");
+
+fn supplemental_blob(
+    dynamic_platform: PckCertFlag,
+    cached_keys: PckCertFlag,
+    smt_enabled: PckCertFlag,
+) -> QlQvSupplemental {
+    QlQvSupplemental {
+        version: 3,
+        tcb_eval_ref_num: 15,
+        dynamic_platform,
+        cached_keys,
+        smt_enabled,
+        ..Default::default()
+    }
+}
+
+fn as_bytes(supplemental: &QlQvSupplemental) -> &[u8] {
+    unsafe {
+        slice::from_raw_parts(
+            supplemental as *const _ as *const u8,
+            mem::size_of::<QlQvSupplemental>(),
+        )
+    }
+}
+
+#[test_case]
+fn read_dynamic_platform() {
+    let blob = supplemental_blob(PckCertFlag::True, PckCertFlag::False, PckCertFlag::False);
+    let data = SupplementalData::from_bytes(as_bytes(&blob)).unwrap();
+    assert_eq!(data.version, 3);
+    assert_eq!(data.tcb_eval_ref_num, 15);
+    assert_eq!(data.dynamic_platform, Some(true));
+    assert_eq!(data.cached_keys, Some(false));
+    assert_eq!(data.smt_enabled, Some(false));
+}
+
+#[test_case]
+fn read_cached_keys() {
+    let blob = supplemental_blob(PckCertFlag::False, PckCertFlag::True, PckCertFlag::False);
+    let data = SupplementalData::from_bytes(as_bytes(&blob)).unwrap();
+    assert_eq!(data.dynamic_platform, Some(false));
+    assert_eq!(data.cached_keys, Some(true));
+    assert_eq!(data.smt_enabled, Some(false));
+}
+
+#[test_case]
+fn read_smt_enabled() {
+    let blob = supplemental_blob(
+        PckCertFlag::Undefined,
+        PckCertFlag::Undefined,
+        PckCertFlag::True,
+    );
+    let data = SupplementalData::from_bytes(as_bytes(&blob)).unwrap();
+    assert_eq!(data.dynamic_platform, None);
+    assert_eq!(data.cached_keys, None);
+    assert_eq!(data.smt_enabled, Some(true));
+}
+
+#[test_case]
+fn reject_invalid_flag() {
+    let blob = supplemental_blob(PckCertFlag::False, PckCertFlag::False, PckCertFlag::False);
+    let mut bytes = [0_u8; mem::size_of::<QlQvSupplemental>()];
+    bytes.copy_from_slice(as_bytes(&blob));
+
+    let offset = mem::size_of::<QlQvSupplemental>() - 8;
+    bytes[offset..offset + 4].copy_from_slice(&7_u32.to_ne_bytes());
+    assert_eq!(
+        SupplementalData::from_bytes(&bytes),
+        Err(SgxStatus::InvalidParameter)
+    );
+    assert_eq!(
+        SupplementalData::from_bytes(&bytes[..16]),
+        Err(SgxStatus::InvalidParameter)
+    );
+}
+
+#[test_case]
+fn read_synthetic_supplemental() {
+    let data = SupplementalData::from_bytes(SUPPLEMENTAL).unwrap();
+    assert_eq!((data.major_version(), data.minor_version()), (3, 1));
+    // 2024-01-01, 2024-06-01 and 2049-06-01.
+    assert_eq!(data.earliest_issue_date, 1_704_067_200);
+    assert_eq!(data.latest_issue_date, 1_717_200_000);
+    assert_eq!(data.earliest_expiration_date, 2_506_118_400);
+    // 2023-08-09
+    assert_eq!(data.tcb_level_date_tag, 1_691_539_200);
+    assert_eq!(data.pck_crl_num, 2);
+    assert_eq!(data.root_ca_crl_num, 1);
+    assert_eq!(data.tcb_eval_ref_num, 15);
+    assert_eq!(data.root_key_id, SUPPLEMENTAL[52..100]);
+    assert_eq!(data.pck_ppid, array::from_fn(|i| i as u8));
+    assert_eq!(data.tcb_cpusvn, CpuSvn { svn: [6; 16] });
+    assert_eq!(data.tcb_pce_isvsvn, 13);
+    assert_eq!(data.pce_id, 0);
+    assert_eq!(data.tee_type, 0);
+    assert_eq!(data.sgx_type, 1);
+    assert_eq!(data.platform_instance_id, array::from_fn(|i| 16 + i as u8));
+    assert_eq!(data.dynamic_platform, Some(false));
+    assert_eq!(data.cached_keys, Some(true));
+    assert_eq!(data.smt_enabled, Some(true));
+    assert_eq!(data.sa_list, ["INTEL-SA-00615"]);
+}
+
+#[test_case]
+fn reject_unknown_version() {
+    let mut bytes = SUPPLEMENTAL.to_vec();
+    for version in [2_u32, 4, 0x0002_0003] {
+        bytes[..4].copy_from_slice(&version.to_le_bytes());
+        assert_eq!(
+            SupplementalData::from_bytes(&bytes),
+            Err(SgxStatus::InvalidParameter)
+        );
+    }
+
+    // 3.0 has no `sa_list`, so the minor version 1 tail is ignored.
+    bytes[..4].copy_from_slice(&3_u32.to_le_bytes());
+    let data = SupplementalData::from_bytes(&bytes[..mem::size_of::<QlQvSupplemental>()]).unwrap();
+    assert_eq!(data.version, 3);
+    assert!(data.sa_list.is_empty());
+}
+
+#[test_case]
+fn reject_malformed_sa_list() {
+    assert_eq!(
+        SupplementalData::from_bytes(&SUPPLEMENTAL[..mem::size_of::<QlQvSupplemental>()]),
+        Err(SgxStatus::InvalidParameter)
+    );
+
+    let mut bytes = SUPPLEMENTAL.to_vec();
+    bytes[172..492].fill(b'A');
+    assert_eq!(
+        SupplementalData::from_bytes(&bytes),
+        Err(SgxStatus::InvalidParameter)
+    );
+    bytes[172..175].copy_from_slice(&[0xff, 0xfe, 0]);
+    assert_eq!(
+        SupplementalData::from_bytes(&bytes),
+        Err(SgxStatus::InvalidParameter)
+    );
+
+    bytes[172..183].copy_from_slice(b"A-1, ,B-2,\0");
+    let data = SupplementalData::from_bytes(&bytes).unwrap();
+    assert_eq!(data.sa_list, ["A-1", "B-2"]);
+}
+
+#[test_case]
+fn platform_flags_policy() {
+    let blob = supplemental_blob(
+        PckCertFlag::True,
+        PckCertFlag::Undefined,
+        PckCertFlag::False,
+    );
+    let data = SupplementalData::from_bytes(as_bytes(&blob)).unwrap();
+
+    assert!(data.check_platform_flags(&PlatformFlagsPolicy::default()));
+    assert!(data.check_platform_flags(&PlatformFlagsPolicy {
+        dynamic_platform: Some(true),
+        smt_enabled: Some(false),
+        ..Default::default()
+    }));
+    assert!(!data.check_platform_flags(&PlatformFlagsPolicy {
+        dynamic_platform: Some(false),
+        ..Default::default()
+    }));
+    assert!(!data.check_platform_flags(&PlatformFlagsPolicy {
+        cached_keys: Some(false),
+        ..Default::default()
+    }));
+}
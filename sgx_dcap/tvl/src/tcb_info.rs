@@ -0,0 +1,190 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License..
+
+use crate::json::Json;
+use crate::pck::{PckExtensions, CPUSVN_COMPONENTS};
+use crate::x509::{trim_nul, verify_signature, Certificate};
+use alloc::format;
+use alloc::vec::Vec;
+use sgx_types::error::{SgxResult, SgxStatus};
+use sgx_types::types::{time_t, QlQvResult, ECP256_KEY_SIZE};
+
+#[cfg(feature = "unit_test")]
+mod tests;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum TcbStatus {
+    UpToDate,
+    SWHardeningNeeded,
+    ConfigurationNeeded,
+    ConfigurationAndSWHardeningNeeded,
+    OutOfDate,
+    OutOfDateConfigurationNeeded,
+    Revoked,
+}
+
+impl TcbStatus {
+    pub(crate) fn from_json(json: &Json<'_>) -> SgxResult<TcbStatus> {
+        match json.as_str()? {
+            "UpToDate" => Ok(TcbStatus::UpToDate),
+            "SWHardeningNeeded" => Ok(TcbStatus::SWHardeningNeeded),
+            "ConfigurationNeeded" => Ok(TcbStatus::ConfigurationNeeded),
+            "ConfigurationAndSWHardeningNeeded" => Ok(TcbStatus::ConfigurationAndSWHardeningNeeded),
+            "OutOfDate" => Ok(TcbStatus::OutOfDate),
+            "OutOfDateConfigurationNeeded" => Ok(TcbStatus::OutOfDateConfigurationNeeded),
+            "Revoked" => Ok(TcbStatus::Revoked),
+            _ => Err(SgxStatus::InvalidParameter),
+        }
+    }
+}
+
+impl From<TcbStatus> for QlQvResult {
+    fn from(status: TcbStatus) -> QlQvResult {
+        match status {
+            TcbStatus::UpToDate => QlQvResult::Ok,
+            TcbStatus::SWHardeningNeeded => QlQvResult::SWHardeningNeeded,
+            TcbStatus::ConfigurationNeeded => QlQvResult::ConfigNeeded,
+            TcbStatus::ConfigurationAndSWHardeningNeeded => QlQvResult::ConfigAndSWHardeningNeeded,
+            TcbStatus::OutOfDate => QlQvResult::OutOfDate,
+            TcbStatus::OutOfDateConfigurationNeeded => QlQvResult::OutOfDateConfigNeeded,
+            TcbStatus::Revoked => QlQvResult::Revoked,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct TcbLevel {
+    pub cpu_svn_components: [u8; CPUSVN_COMPONENTS],
+    pub pce_svn: u16,
+    pub tcb_date: time_t,
+    pub status: TcbStatus,
+}
+
+impl TcbLevel {
+    fn from_json(level: &Json<'_>, version: u32) -> SgxResult<TcbLevel> {
+        let tcb = level.get("tcb")?;
+        let mut cpu_svn_components = [0_u8; CPUSVN_COMPONENTS];
+        if version == 2 {
+            for (i, svn) in cpu_svn_components.iter_mut().enumerate() {
+                *svn = svn_u8(tcb.get(&format!("sgxtcbcomp{:02}svn", i + 1))?)?;
+            }
+        } else {
+            let components = tcb.get("sgxtcbcomponents")?.as_array()?;
+            ensure!(
+                components.len() == CPUSVN_COMPONENTS,
+                SgxStatus::InvalidParameter
+            );
+            for (svn, component) in cpu_svn_components.iter_mut().zip(components) {
+                *svn = svn_u8(component.get("svn")?)?;
+            }
+        }
+        let pce_svn = tcb.get("pcesvn")?.as_u32()?;
+
+        Ok(TcbLevel {
+            cpu_svn_components,
+            pce_svn: u16::try_from(pce_svn).map_err(|_| SgxStatus::InvalidParameter)?,
+            tcb_date: level.get("tcbDate")?.as_date()?,
+            status: TcbStatus::from_json(level.get("tcbStatus")?)?,
+        })
+    }
+}
+
+/// SGX TCB information for one FMSPC, version 2 or 3, as served by the
+/// PCS `tcb` endpoint.
+#[derive(Clone, Debug)]
+pub struct TcbInfo<'a> {
+    body: &'a str,
+    signature: [u8; ECP256_KEY_SIZE * 2],
+    pub version: u32,
+    pub issue_date: time_t,
+    pub next_update: time_t,
+    pub fmspc: [u8; 6],
+    pub pce_id: [u8; 2],
+    pub tcb_evaluation_data_number: u32,
+    /// In the order given, which the PCS sorts from the highest TCB down.
+    pub tcb_levels: Vec<TcbLevel>,
+}
+
+impl<'a> TcbInfo<'a> {
+    pub fn from_json(json: &'a [u8]) -> SgxResult<TcbInfo<'a>> {
+        let json = Json::parse(trim_nul(json))?;
+        let (body, signature) = json.signed_member("tcbInfo")?;
+        let info = &body.value;
+
+        let version = info.get("version")?.as_u32()?;
+        match version {
+            2 => {}
+            3 => ensure!(
+                info.get("id")?.as_str()? == "SGX",
+                SgxStatus::InvalidParameter
+            ),
+            _ => return Err(SgxStatus::InvalidParameter),
+        }
+        ensure!(
+            info.get("tcbType")?.as_u32()? == 0,
+            SgxStatus::InvalidParameter
+        );
+
+        let tcb_levels = info
+            .get("tcbLevels")?
+            .as_array()?
+            .iter()
+            .map(|level| TcbLevel::from_json(level, version))
+            .collect::<SgxResult<Vec<TcbLevel>>>()?;
+        ensure!(!tcb_levels.is_empty(), SgxStatus::InvalidParameter);
+
+        Ok(TcbInfo {
+            body: body.raw,
+            signature,
+            version,
+            issue_date: info.get("issueDate")?.as_date()?,
+            next_update: info.get("nextUpdate")?.as_date()?,
+            fmspc: info.get("fmspc")?.as_hex()?,
+            pce_id: info.get("pceId")?.as_hex()?,
+            tcb_evaluation_data_number: info.get("tcbEvaluationDataNumber")?.as_u32()?,
+            tcb_levels,
+        })
+    }
+
+    /// Checks the signature over the `tcbInfo` body with the TCB signing
+    /// certificate. The certificate itself is not checked here.
+    #[inline]
+    pub fn verify_signature(&self, signer: &Certificate<'_>) -> SgxResult {
+        verify_signature(signer, self.body.as_bytes(), &self.signature)
+    }
+
+    #[inline]
+    pub fn is_valid_at(&self, time: time_t) -> bool {
+        self.issue_date <= time && time <= self.next_update
+    }
+
+    /// The highest TCB level that the PCK certificate's TCB meets.
+    pub fn tcb_level(&self, pck: &PckExtensions) -> Option<&TcbLevel> {
+        self.tcb_levels.iter().find(|level| {
+            level.pce_svn <= pck.pce_svn
+                && level
+                    .cpu_svn_components
+                    .iter()
+                    .zip(pck.cpu_svn_components.iter())
+                    .all(|(level, pck)| level <= pck)
+        })
+    }
+}
+
+fn svn_u8(json: &Json<'_>) -> SgxResult<u8> {
+    u8::try_from(json.as_u32()?).map_err(|_| SgxStatus::InvalidParameter)
+}
@@ -0,0 +1,134 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License..
+
+use super::{TcbInfo, TcbStatus};
+use crate::pck::PckExtensions;
+use crate::x509::Certificate;
+use alloc::string::String;
+use alloc::vec::Vec;
+use sgx_test_utils::test_case;
+use sgx_types::error::SgxStatus;
+
+const TCB_INFO: &[u8] = include_bytes!("../../testdata/tcb_info.json");
+const TCB_SIGNING: &[u8] = include_bytes!("../../testdata/tcb_signing.pem");
+const ROOT_CA: &[u8] = include_bytes!("../../testdata/root_ca.pem");
+
+fn der(pem: &[u8]) -> Vec<u8> {
+    Certificate::pem_chain(pem).unwrap().remove(0)
+}
+
+fn pck(cpu_svn_components: [u8; 16], pce_svn: u16) -> PckExtensions {
+    PckExtensions {
+        cpu_svn_components,
+        pce_svn,
+        ..Default::default()
+    }
+}
+
+#[test_case]
+fn parse_tcb_info_v3() {
+    let info = TcbInfo::from_json(TCB_INFO).unwrap();
+    assert_eq!(info.version, 3);
+    assert_eq!(info.fmspc, [0x00, 0x90, 0x6e, 0xd5, 0x00, 0x00]);
+    assert_eq!(info.pce_id, [0, 0]);
+    assert_eq!(info.tcb_evaluation_data_number, 16);
+    assert_eq!(info.issue_date, 1_717_200_000);
+    assert!(info.is_valid_at(1_735_689_600));
+    assert!(!info.is_valid_at(1_717_199_999));
+
+    let statuses: Vec<TcbStatus> = info.tcb_levels.iter().map(|level| level.status).collect();
+    assert_eq!(
+        statuses,
+        [
+            TcbStatus::UpToDate,
+            TcbStatus::SWHardeningNeeded,
+            TcbStatus::OutOfDate
+        ]
+    );
+    assert_eq!(info.tcb_levels[1].cpu_svn_components, [6; 16]);
+    assert_eq!(info.tcb_levels[2].pce_svn, 11);
+}
+
+#[test_case]
+fn verify_tcb_info_signature() {
+    let signer = der(TCB_SIGNING);
+    let signer = Certificate::from_der(&signer).unwrap();
+    let root = der(ROOT_CA);
+    let root = Certificate::from_der(&root).unwrap();
+
+    let info = TcbInfo::from_json(TCB_INFO).unwrap();
+    assert_eq!(info.verify_signature(&signer), Ok(()));
+    assert_eq!(
+        info.verify_signature(&root),
+        Err(SgxStatus::InvalidSignature)
+    );
+
+    let tampered = String::from_utf8(TCB_INFO.to_vec()).unwrap().replace(
+        "\"tcbEvaluationDataNumber\":16",
+        "\"tcbEvaluationDataNumber\":17",
+    );
+    let info = TcbInfo::from_json(tampered.as_bytes()).unwrap();
+    assert_eq!(info.tcb_evaluation_data_number, 17);
+    assert_eq!(
+        info.verify_signature(&signer),
+        Err(SgxStatus::InvalidSignature)
+    );
+}
+
+#[test_case]
+fn select_tcb_level() {
+    let info = TcbInfo::from_json(TCB_INFO).unwrap();
+    let status = |pck: PckExtensions| info.tcb_level(&pck).map(|level| level.status);
+
+    assert_eq!(status(pck([7; 16], 13)), Some(TcbStatus::UpToDate));
+    assert_eq!(status(pck([9; 16], 14)), Some(TcbStatus::UpToDate));
+    assert_eq!(status(pck([6; 16], 13)), Some(TcbStatus::SWHardeningNeeded));
+    // Lower PCE SVN.
+    assert_eq!(status(pck([7; 16], 12)), Some(TcbStatus::OutOfDate));
+    // Every component counts.
+    let mut svns = [7; 16];
+    svns[15] = 5;
+    assert_eq!(status(pck(svns, 13)), Some(TcbStatus::OutOfDate));
+    assert_eq!(status(pck([4; 16], 13)), None);
+}
+
+#[test_case]
+fn parse_tcb_info_v2() {
+    let mut tcb = String::new();
+    for i in 1..=16 {
+        tcb.push_str(&alloc::format!(r#""sgxtcbcomp{:02}svn":{},"#, i, i));
+    }
+    let json = alloc::format!(
+        r#"{{"tcbInfo":{{"version":2,"issueDate":"2024-06-01T00:00:00Z","nextUpdate":"2024-07-01T00:00:00Z","fmspc":"00906ED50000","pceId":"0000","tcbType":0,"tcbEvaluationDataNumber":12,"tcbLevels":[{{"tcb":{{{}"pcesvn":10}},"tcbDate":"2023-02-15T00:00:00Z","tcbStatus":"ConfigurationNeeded"}}]}},"signature":"{}"}}"#,
+        tcb,
+        "00".repeat(64)
+    );
+    let info = TcbInfo::from_json(json.as_bytes()).unwrap();
+    assert_eq!(info.version, 2);
+    assert_eq!(
+        info.tcb_levels[0].cpu_svn_components,
+        core::array::from_fn(|i| i as u8 + 1)
+    );
+    assert_eq!(info.tcb_levels[0].pce_svn, 10);
+    assert_eq!(info.tcb_levels[0].status, TcbStatus::ConfigurationNeeded);
+
+    let json = json.replace("\"version\":2", "\"version\":4");
+    assert_eq!(
+        TcbInfo::from_json(json.as_bytes()).map(|_| ()),
+        Err(SgxStatus::InvalidParameter)
+    );
+}
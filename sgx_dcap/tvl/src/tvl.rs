@@ -15,17 +15,22 @@
 // specific language governing permissions and limitations
 // under the License..
 
+use crate::source::{digest_source, QuoteSource};
+use crate::supplemental::{read_supplemental, SupplementalData};
 use core::mem;
 use sgx_crypto::sha::Sha256;
 use sgx_trts::trts::{is_within_enclave, is_within_host, EnclaveRange};
 use sgx_tse::EnclaveReport;
-use sgx_types::error::{Quote3Error, SgxQuote3Result, SgxResult};
+use sgx_types::error::{Quote3Error, SgxQuote3Result, SgxResult, SgxStatus};
 use sgx_types::types::SHA256_HASH_SIZE;
 use sgx_types::types::{
-    Attributes, AttributesFlags, Measurement, MiscSelect, QlQvResult, QuoteNonce, Report,
-    Sha256Hash,
+    Attributes, AttributesFlags, Measurement, MiscSelect, QlQvResult, QlQvSupplemental, Quote3,
+    QuoteNonce, Report, Sha256Hash,
 };
 
+#[cfg(feature = "unit_test")]
+mod tests;
+
 const QVE_MISC_SELECT: MiscSelect = MiscSelect::empty();
 const QVE_MISC_SELECT_MASK: MiscSelect = unsafe { MiscSelect::from_bits_unchecked(0xFFFFFFFF) };
 
@@ -57,6 +62,45 @@ pub struct QveReportInfo<'a, 'b> {
     pub quote_verification_result: QlQvResult,
     pub qve_nonce: QuoteNonce,
     pub supplemental_data: Option<&'b [u8]>,
+    /// Status of the QvE call itself. Anything but `Success` means the QvE
+    /// did not run to completion and the fields above carry no verdict.
+    pub qve_status: SgxStatus,
+}
+
+impl<'a, 'b> QveReportInfo<'a, 'b> {
+    /// An empty supplemental slice is normalized to `None`, matching the C
+    /// API contract of a null pointer iff the length is zero.
+    pub fn new(
+        qve_report: &'a Report,
+        expiration_time: i64,
+        collateral_expiration_status: u32,
+        quote_verification_result: QlQvResult,
+        qve_nonce: QuoteNonce,
+        supplemental_data: Option<&'b [u8]>,
+    ) -> QveReportInfo<'a, 'b> {
+        QveReportInfo {
+            qve_report,
+            expiration_time,
+            collateral_expiration_status,
+            quote_verification_result,
+            qve_nonce,
+            supplemental_data: supplemental_data.filter(|data| !data.is_empty()),
+            qve_status: SgxStatus::Success,
+        }
+    }
+
+    #[inline]
+    pub fn with_qve_status(mut self, qve_status: SgxStatus) -> QveReportInfo<'a, 'b> {
+        self.qve_status = qve_status;
+        self
+    }
+
+    /// The supplemental data, treating a directly constructed `Some(&[])`
+    /// the same as `None`.
+    #[inline]
+    pub fn supplemental_bytes(&self) -> Option<&'b [u8]> {
+        self.supplemental_data.filter(|data| !data.is_empty())
+    }
 }
 
 impl QveReportInfo<'_, '_> {
@@ -68,8 +112,19 @@ impl QveReportInfo<'_, '_> {
         ensure!(!quote.is_empty(), Quote3Error::InvalidParameter);
         ensure!(quote.is_enclave_range(), Quote3Error::InvalidParameter);
 
-        if let Some(supplemental) = self.supplemental_data {
-            ensure!(!supplemental.is_empty(), Quote3Error::InvalidParameter);
+        self.verify_quote(quote, qve_isvsvn_threshold).map(|_| ())
+    }
+
+    /// Like `verify_report_and_identity`, but pulls the quote from `quote`
+    /// chunk by chunk, so a large quote may stay outside the enclave.
+    /// Returns the quote header and report body as they were verified; use
+    /// them rather than reading the source again.
+    pub fn verify_quote<S: QuoteSource + ?Sized>(
+        &self,
+        quote: &S,
+        qve_isvsvn_threshold: u16,
+    ) -> SgxQuote3Result<Quote3> {
+        if let Some(supplemental) = self.supplemental_bytes() {
             ensure!(
                 supplemental.len() < u32::MAX as usize,
                 Quote3Error::InvalidParameter
@@ -77,24 +132,56 @@ impl QveReportInfo<'_, '_> {
         }
         ensure!(self.is_enclave_range(), Quote3Error::InvalidParameter);
 
-        self.verify_report(quote)?;
-        self.verify_identity(qve_isvsvn_threshold)
+        let quote3 = self.verify_report(quote)?;
+        self.verify_identity(qve_isvsvn_threshold)?;
+        Ok(quote3)
     }
 
-    fn verify_report(&self, quote: &[u8]) -> SgxQuote3Result {
+    pub fn supplemental(&self) -> Option<QlQvSupplemental> {
+        read_supplemental(self.supplemental_bytes()?).ok()
+    }
+
+    pub fn parse_supplemental(&self) -> SgxResult<SupplementalData> {
+        let supplemental = self
+            .supplemental_bytes()
+            .ok_or(SgxStatus::InvalidParameter)?;
+        SupplementalData::from_bytes(supplemental)
+    }
+
+    #[inline]
+    pub fn tcb_eval_data_number(&self) -> Option<u32> {
+        self.supplemental()
+            .map(|supplemental| supplemental.tcb_eval_ref_num)
+    }
+
+    /// Rejects collateral whose TCB evaluation data number is older than
+    /// `min_tcb_eval_data_number`, i.e. collateral issued before a TCB recovery.
+    pub fn verify_tcb_eval_data_number(&self, min_tcb_eval_data_number: u32) -> SgxQuote3Result {
+        let tcb_eval_data_number = self
+            .tcb_eval_data_number()
+            .ok_or(Quote3Error::InvalidParameter)?;
+        ensure!(
+            tcb_eval_data_number >= min_tcb_eval_data_number,
+            Quote3Error::TcbOutOfDate
+        );
+        Ok(())
+    }
+
+    fn verify_report<S: QuoteSource + ?Sized>(&self, quote: &S) -> SgxQuote3Result<Quote3> {
         self.qve_report
             .verify()
             .map_err(|_| Quote3Error::ErrorReport)?;
 
-        let hash = self
-            .calc_report_data(quote)
-            .map_err(|_| Quote3Error::Unexpected)?;
+        let (hash, quote3) = self.calc_report_data(quote).map_err(|e| match e {
+            SgxStatus::InvalidParameter => Quote3Error::InvalidParameter,
+            _ => Quote3Error::Unexpected,
+        })?;
         ensure!(
             hash.eq(&self.qve_report.body.report_data.d[..SHA256_HASH_SIZE]),
             Quote3Error::ErrorReport
         );
 
-        Ok(())
+        Ok(quote3)
     }
 
     fn verify_identity(&self, qve_isvsvn_threshold: u16) -> SgxQuote3Result {
@@ -130,17 +217,20 @@ impl QveReportInfo<'_, '_> {
         Ok(())
     }
 
-    fn calc_report_data(&self, quote: &[u8]) -> SgxResult<Sha256Hash> {
+    fn calc_report_data<S: QuoteSource + ?Sized>(
+        &self,
+        quote: &S,
+    ) -> SgxResult<(Sha256Hash, Quote3)> {
         let mut sha = Sha256::new()?;
         sha.update(&self.qve_nonce)?;
-        sha.update(quote)?;
+        let quote3 = digest_source(&mut sha, quote)?;
         sha.update(&self.expiration_time)?;
         sha.update(&self.collateral_expiration_status)?;
         sha.update(&self.quote_verification_result)?;
-        if let Some(supplemental) = self.supplemental_data {
+        if let Some(supplemental) = self.supplemental_bytes() {
             sha.update(supplemental)?;
         }
-        sha.finalize()
+        Ok((sha.finalize()?, quote3))
     }
 }
 
@@ -153,7 +243,7 @@ impl EnclaveRange for QveReportInfo<'_, '_> {
             return false;
         }
 
-        if let Some(supplemental) = self.supplemental_data {
+        if let Some(supplemental) = self.supplemental_bytes() {
             if !supplemental.is_enclave_range() {
                 return false;
             }
@@ -168,7 +258,7 @@ impl EnclaveRange for QveReportInfo<'_, '_> {
         ) {
             return false;
         }
-        if let Some(supplemental) = self.supplemental_data {
+        if let Some(supplemental) = self.supplemental_bytes() {
             if !supplemental.is_host_range() {
                 return false;
             }
@@ -0,0 +1,223 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License..
+
+use super::QveReportInfo;
+use crate::QuoteSource;
+use alloc::vec::Vec;
+use core::mem;
+use core::slice;
+use sgx_test_utils::test_case;
+use sgx_tse::{EnclaveReport, EnclaveTarget};
+use sgx_types::error::{Quote3Error, SgxStatus};
+use sgx_types::types::{
+    QlQvResult, QlQvSupplemental, Quote3, QuoteNonce, Report, ReportData, TargetInfo,
+    SHA256_HASH_SIZE,
+};
+
+const QUOTE_LEN: usize = mem::size_of::<Quote3>();
+
+fn quote3_bytes(quote3: &Quote3) -> &[u8] {
+    unsafe { slice::from_raw_parts(quote3 as *const _ as *const u8, QUOTE_LEN) }
+}
+
+fn supplemental_blob(tcb_eval_ref_num: u32) -> QlQvSupplemental {
+    QlQvSupplemental {
+        version: 3,
+        tcb_eval_ref_num,
+        ..Default::default()
+    }
+}
+
+fn report_info<'a, 'b>(
+    report: &'a Report,
+    supplemental: Option<&'b [u8]>,
+) -> QveReportInfo<'a, 'b> {
+    QveReportInfo {
+        qve_report: report,
+        expiration_time: 0,
+        collateral_expiration_status: 0,
+        quote_verification_result: QlQvResult::Ok,
+        qve_nonce: QuoteNonce::default(),
+        supplemental_data: supplemental,
+        qve_status: SgxStatus::Success,
+    }
+}
+
+fn as_bytes(supplemental: &QlQvSupplemental) -> &[u8] {
+    unsafe {
+        slice::from_raw_parts(
+            supplemental as *const _ as *const u8,
+            mem::size_of::<QlQvSupplemental>(),
+        )
+    }
+}
+
+#[test_case]
+fn tcb_eval_data_number_accessor() {
+    let report = Report::default();
+    let supplemental = supplemental_blob(17);
+    let info = report_info(&report, Some(as_bytes(&supplemental)));
+    assert_eq!(info.tcb_eval_data_number(), Some(17));
+
+    let truncated = &as_bytes(&supplemental)[..8];
+    let info = report_info(&report, Some(truncated));
+    assert_eq!(info.tcb_eval_data_number(), None);
+}
+
+#[test_case]
+fn tcb_eval_data_number_matching() {
+    let report = Report::default();
+    let supplemental = supplemental_blob(17);
+    let info = report_info(&report, Some(as_bytes(&supplemental)));
+    assert!(info.verify_tcb_eval_data_number(17).is_ok());
+    assert!(info.verify_tcb_eval_data_number(16).is_ok());
+}
+
+#[test_case]
+fn tcb_eval_data_number_below_minimum() {
+    let report = Report::default();
+    let supplemental = supplemental_blob(16);
+    let info = report_info(&report, Some(as_bytes(&supplemental)));
+    assert_eq!(
+        info.verify_tcb_eval_data_number(17),
+        Err(Quote3Error::TcbOutOfDate)
+    );
+}
+
+#[test_case]
+fn tcb_eval_data_number_missing() {
+    let report = Report::default();
+    let info = report_info(&report, None);
+    assert_eq!(
+        info.verify_tcb_eval_data_number(1),
+        Err(Quote3Error::InvalidParameter)
+    );
+}
+
+// Serves at most `chunk` bytes per read, like a transport paging the quote in.
+struct ChunkedSource {
+    data: Vec<u8>,
+    chunk: usize,
+}
+
+impl QuoteSource for ChunkedSource {
+    fn read_at(&self, offset: usize, buf: &mut [u8]) -> Result<usize, SgxStatus> {
+        let n = buf.len().min(self.chunk);
+        self.data[..].read_at(offset, &mut buf[..n])
+    }
+}
+
+#[test_case]
+fn verify_quote_from_chunked_source() {
+    let quote: Vec<u8> = (0..10000_u32).map(|i| i as u8).collect();
+    let source = ChunkedSource {
+        data: quote.clone(),
+        chunk: 100,
+    };
+
+    let (hash, quote3) = report_info(&Report::default(), None)
+        .calc_report_data(&quote[..])
+        .unwrap();
+    let (chunked_hash, chunked_quote3) = report_info(&Report::default(), None)
+        .calc_report_data(&source)
+        .unwrap();
+    assert_eq!(chunked_hash, hash);
+    // The header and report body come back as copied while hashing.
+    assert_eq!(quote3_bytes(&chunked_quote3), &quote[..QUOTE_LEN]);
+    assert_eq!(quote3_bytes(&quote3), quote3_bytes(&chunked_quote3));
+
+    let mut report_data = ReportData::default();
+    report_data.d[..SHA256_HASH_SIZE].copy_from_slice(&hash);
+    let qve_report = Report::for_target(&TargetInfo::for_self().unwrap(), &report_data).unwrap();
+    let info = report_info(&qve_report, None);
+
+    // The report binds the paged-in quote, so verification gets as far as
+    // the QvE identity check, which this enclave does not satisfy.
+    assert_eq!(
+        info.verify_quote(&source, 0).unwrap_err(),
+        Quote3Error::QveIdentityMismatch
+    );
+
+    let mut tampered = quote;
+    tampered[5000] ^= 1;
+    let source = ChunkedSource {
+        data: tampered,
+        chunk: 100,
+    };
+    assert_eq!(
+        info.verify_quote(&source, 0).unwrap_err(),
+        Quote3Error::ErrorReport
+    );
+}
+
+#[test_case]
+fn verify_quote_rejects_empty_source() {
+    let qve_report = Report::for_self().unwrap();
+    let info = report_info(&qve_report, None);
+    let empty: &[u8] = &[];
+    assert_eq!(
+        info.verify_quote(empty, 0).unwrap_err(),
+        Quote3Error::InvalidParameter
+    );
+}
+
+#[test_case]
+fn verify_quote_rejects_source_shorter_than_header() {
+    let qve_report = Report::for_self().unwrap();
+    let info = report_info(&qve_report, None);
+    let short = [0_u8; QUOTE_LEN - 1];
+    assert_eq!(
+        info.verify_quote(&short[..], 0).unwrap_err(),
+        Quote3Error::InvalidParameter
+    );
+}
+
+#[test_case]
+fn empty_supplemental_normalized_to_none() {
+    let report = Report::default();
+    let info = QveReportInfo::new(
+        &report,
+        0,
+        0,
+        QlQvResult::Ok,
+        QuoteNonce::default(),
+        Some(&[]),
+    );
+    assert!(info.supplemental_data.is_none());
+
+    // A literal `Some(&[])` is read back the same way as `None`.
+    let info = report_info(&report, Some(&[]));
+    assert!(info.supplemental_bytes().is_none());
+    assert_eq!(
+        info.calc_report_data(&[0_u8; QUOTE_LEN][..]).unwrap().0,
+        report_info(&report, None)
+            .calc_report_data(&[0_u8; QUOTE_LEN][..])
+            .unwrap()
+            .0
+    );
+
+    let supplemental = supplemental_blob(17);
+    let info = QveReportInfo::new(
+        &report,
+        0,
+        0,
+        QlQvResult::Ok,
+        QuoteNonce::default(),
+        Some(as_bytes(&supplemental)),
+    );
+    assert_eq!(info.tcb_eval_data_number(), Some(17));
+}
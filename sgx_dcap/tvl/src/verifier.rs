@@ -0,0 +1,302 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License..
+
+use crate::pck::PckExtensions;
+use crate::qe_identity::QeIdentity;
+use crate::quote::{be_to_le, split_signature, Cert, Quote3Verify};
+use crate::supplemental::{SupplementalData, SUPPLEMENTAL_DATA_VERSION};
+use crate::tcb_info::{TcbInfo, TcbStatus};
+use crate::x509::{Certificate, Crl};
+use alloc::vec::Vec;
+use core::mem;
+use core::ptr;
+use sgx_crypto::ecc::{EcPublicKey, EcSignature};
+use sgx_crypto::sha::Sha384;
+use sgx_trts::trts::EnclaveRange;
+use sgx_types::error::{SgxResult, SgxStatus};
+use sgx_types::types::{
+    time_t, PckCertFlag, QlQvResult, QlQvSupplemental, Quote3, QuoteHeader, ReportBody,
+};
+
+#[cfg(feature = "unit_test")]
+mod tests;
+
+const QUOTE_VERSION: u16 = 3;
+const ATT_KEY_TYPE_ECDSA_P256: u16 = 2;
+const QE_VENDOR_ID_INTEL: [u8; 16] = [
+    0x93, 0x9a, 0x72, 0x33, 0xf7, 0x9c, 0x4c, 0xa9, 0x94, 0x0a, 0x0d, 0xb3, 0x95, 0x7f, 0x06, 0x07,
+];
+const TEE_TYPE_SGX: u32 = 0;
+
+/// Verification collateral for an SGX quote, as served by the PCS or a
+/// PCCS: PEM certificate chains, CRLs as PEM, hex or DER, and signed JSON.
+/// A trailing NUL, as left by C strings, is accepted on every artifact.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Collateral<'a> {
+    pub pck_crl_issuer_chain: &'a [u8],
+    pub root_ca_crl: &'a [u8],
+    pub pck_crl: &'a [u8],
+    pub tcb_info_issuer_chain: &'a [u8],
+    pub tcb_info: &'a [u8],
+    pub qe_identity_issuer_chain: &'a [u8],
+    pub qe_identity: &'a [u8],
+}
+
+impl<'a> Collateral<'a> {
+    fn artifacts(&self) -> [&'a [u8]; 7] {
+        [
+            self.pck_crl_issuer_chain,
+            self.root_ca_crl,
+            self.pck_crl,
+            self.tcb_info_issuer_chain,
+            self.tcb_info,
+            self.qe_identity_issuer_chain,
+            self.qe_identity,
+        ]
+    }
+}
+
+/// Verifies ECDSA quotes against caller supplied collateral inside the
+/// enclave, without a QvE.
+#[derive(Clone, Copy, Debug)]
+pub struct QuoteVerifier<'a> {
+    roots: &'a [Cert<'a>],
+}
+
+impl<'a> QuoteVerifier<'a> {
+    /// Every certificate chain, in the quote and in the collateral, must end
+    /// at one of `roots`, normally the Intel SGX Root CA.
+    #[inline]
+    pub fn new(roots: &'a [Cert<'a>]) -> QuoteVerifier<'a> {
+        QuoteVerifier { roots }
+    }
+
+    /// Verifies `quote` and returns the TCB verdict along with the
+    /// supplemental data the QvE would have produced.
+    ///
+    /// A revoked PCK certificate or PCK CA is reported as
+    /// `QlQvResult::Revoked`. Everything else that prevents a verdict is an
+    /// error: `InvalidParameter` for malformed input or collateral that does
+    /// not cover the quote's platform, `InvalidSignature` for a bad
+    /// signature, an untrusted or revoked signer, or a QE that does not
+    /// match its identity, and `UpdateNeeded` when any collateral is not
+    /// valid at `expiration_time`.
+    pub fn verify(
+        &self,
+        quote: &[u8],
+        collateral: &Collateral<'_>,
+        expiration_time: time_t,
+    ) -> Result<(QlQvResult, SupplementalData), SgxStatus> {
+        ensure!(
+            quote.is_enclave_range()
+                && collateral
+                    .artifacts()
+                    .iter()
+                    .all(|artifact| artifact.is_enclave_range()),
+            SgxStatus::InvalidParameter
+        );
+
+        let (sig_data, _, cert_chain) = split_signature(quote)?;
+        let quote3 = unsafe { ptr::read_unaligned(quote.as_ptr() as *const Quote3) };
+        let header = quote3.header;
+        let (version, att_key_type, vendor_id) =
+            (header.version, header.att_key_type, header.vendor_id);
+        ensure!(
+            version == QUOTE_VERSION
+                && att_key_type == ATT_KEY_TYPE_ECDSA_P256
+                && vendor_id == QE_VENDOR_ID_INTEL,
+            SgxStatus::InvalidParameter
+        );
+
+        // The attestation key signs the header and report body, and the PCK
+        // key certifies the attestation key through the QE report.
+        let signed_len = mem::size_of::<QuoteHeader>() + mem::size_of::<ReportBody>();
+        let (attest_pub_key, sig) = (sig_data.attest_pub_key, sig_data.sig);
+        let attest_key = EcPublicKey::from(be_to_le(&attest_pub_key));
+        let valid = attest_key.verify(&quote[..signed_len], &EcSignature::from(be_to_le(&sig)))?;
+        ensure!(valid, SgxStatus::InvalidSignature);
+        Quote3::verify_qe_report(quote)?;
+
+        let pck_ders = Certificate::pem_chain(cert_chain)?;
+        ensure!(pck_ders.len() == 3, SgxStatus::InvalidParameter);
+        let pck_chain = parse_chain(&pck_ders)?;
+        self.verify_chain(&pck_chain)?;
+        let (pck, pck_ca, root) = (&pck_chain[0], &pck_chain[1], &pck_chain[2]);
+
+        let root_ca_crl = Crl::decode(collateral.root_ca_crl)?;
+        let root_ca_crl = Crl::from_der(&root_ca_crl)?;
+        root_ca_crl.verify_signed_by(root)?;
+
+        let pck_crl_ders = Certificate::pem_chain(collateral.pck_crl_issuer_chain)?;
+        let pck_crl_chain = parse_chain(&pck_crl_ders)?;
+        self.verify_signer_chain(&pck_crl_chain, root, &root_ca_crl)?;
+        ensure!(
+            pck_crl_chain[0].der == pck_ca.der,
+            SgxStatus::InvalidParameter
+        );
+        let pck_crl = Crl::decode(collateral.pck_crl)?;
+        let pck_crl = Crl::from_der(&pck_crl)?;
+        pck_crl.verify_signed_by(pck_ca)?;
+
+        let tcb_info_ders = Certificate::pem_chain(collateral.tcb_info_issuer_chain)?;
+        let tcb_info_chain = parse_chain(&tcb_info_ders)?;
+        self.verify_signer_chain(&tcb_info_chain, root, &root_ca_crl)?;
+        let tcb_info = TcbInfo::from_json(collateral.tcb_info)?;
+        tcb_info.verify_signature(&tcb_info_chain[0])?;
+
+        let qe_identity_ders = Certificate::pem_chain(collateral.qe_identity_issuer_chain)?;
+        let qe_identity_chain = parse_chain(&qe_identity_ders)?;
+        self.verify_signer_chain(&qe_identity_chain, root, &root_ca_crl)?;
+        let qe_identity = QeIdentity::from_json(collateral.qe_identity)?;
+        qe_identity.verify_signature(&qe_identity_chain[0])?;
+
+        let pck_ext = PckExtensions::from_cert(pck)?;
+        ensure!(
+            tcb_info.fmspc == pck_ext.fmspc && tcb_info.pce_id == pck_ext.pce_id,
+            SgxStatus::InvalidParameter
+        );
+        let tcb_level = tcb_info
+            .tcb_level(&pck_ext)
+            .ok_or(SgxStatus::InvalidParameter)?;
+        let qe_report = sig_data.qe_report;
+        let qe_level = qe_identity.verify_report(&qe_report)?;
+
+        let certs = pck_chain
+            .iter()
+            .chain(pck_crl_chain.iter())
+            .chain(tcb_info_chain.iter())
+            .chain(qe_identity_chain.iter());
+        let periods = certs.map(|cert| (cert.not_before, cert.not_after)).chain([
+            (root_ca_crl.this_update, root_ca_crl.next_update),
+            (pck_crl.this_update, pck_crl.next_update),
+            (tcb_info.issue_date, tcb_info.next_update),
+            (qe_identity.issue_date, qe_identity.next_update),
+        ]);
+        let (earliest_issue_date, latest_issue_date, earliest_expiration_date) = periods.fold(
+            (time_t::MAX, time_t::MIN, time_t::MAX),
+            |(earliest, latest, expiration), (issue, expire)| {
+                (
+                    earliest.min(issue),
+                    latest.max(issue),
+                    expiration.min(expire),
+                )
+            },
+        );
+        ensure!(
+            latest_issue_date <= expiration_time && expiration_time <= earliest_expiration_date,
+            SgxStatus::UpdateNeeded
+        );
+
+        let mut sha = Sha384::new()?;
+        sha.update(&[0x04_u8])?;
+        sha.update(root.public_key_bytes())?;
+        let root_key_id = sha.finalize()?.hash;
+
+        let supplemental = QlQvSupplemental {
+            version: SUPPLEMENTAL_DATA_VERSION,
+            earliest_issue_date,
+            latest_issue_date,
+            earliest_expiration_date,
+            tcb_level_date_tag: tcb_level.tcb_date.min(qe_level.tcb_date),
+            pck_crl_num: pck_crl.crl_number.unwrap_or(0),
+            root_ca_crl_num: root_ca_crl.crl_number.unwrap_or(0),
+            tcb_eval_ref_num: tcb_info
+                .tcb_evaluation_data_number
+                .min(qe_identity.tcb_evaluation_data_number),
+            root_key_id,
+            pck_ppid: pck_ext.ppid,
+            tcb_cpusvn: pck_ext.cpu_svn,
+            tcb_pce_isvsvn: pck_ext.pce_svn,
+            pce_id: u16::from_be_bytes(pck_ext.pce_id),
+            tee_type: TEE_TYPE_SGX,
+            sgx_type: pck_ext.sgx_type,
+            platform_instance_id: pck_ext.platform_instance_id.unwrap_or_default(),
+            dynamic_platform: flag(pck_ext.dynamic_platform),
+            cached_keys: flag(pck_ext.cached_keys),
+            smt_enabled: flag(pck_ext.smt_enabled),
+        };
+
+        let revoked = root_ca_crl.is_revoked(pck_ca) || pck_crl.is_revoked(pck);
+        let result = if revoked {
+            QlQvResult::Revoked
+        } else {
+            converge(tcb_level.status, qe_level.status)
+        };
+        Ok((result, SupplementalData::from(supplemental)))
+    }
+
+    fn verify_chain(&self, chain: &[Certificate<'_>]) -> SgxResult {
+        let (root, _) = chain.split_last().ok_or(SgxStatus::InvalidParameter)?;
+        ensure!(
+            root.is_self_signed() && self.roots.iter().any(|cert| cert.der == root.der),
+            SgxStatus::InvalidSignature
+        );
+        for pair in chain.windows(2) {
+            pair[0].verify_signed_by(&pair[1])?;
+        }
+        Ok(())
+    }
+
+    // A chain that signs collateral must share the quote's root and must not
+    // have been revoked by it.
+    fn verify_signer_chain(
+        &self,
+        chain: &[Certificate<'_>],
+        root: &Certificate<'_>,
+        root_ca_crl: &Crl<'_>,
+    ) -> SgxResult {
+        self.verify_chain(chain)?;
+        ensure!(
+            chain.last().map(|cert| cert.der) == Some(root.der),
+            SgxStatus::InvalidSignature
+        );
+        ensure!(
+            !chain.iter().any(|cert| root_ca_crl.is_revoked(cert)),
+            SgxStatus::InvalidSignature
+        );
+        Ok(())
+    }
+}
+
+fn parse_chain(ders: &[Vec<u8>]) -> SgxResult<Vec<Certificate<'_>>> {
+    ders.iter().map(|der| Certificate::from_der(der)).collect()
+}
+
+// A QE that is out of date caps the platform's status.
+fn converge(platform: TcbStatus, qe: TcbStatus) -> QlQvResult {
+    let status = match (qe, platform) {
+        (TcbStatus::Revoked, _) => TcbStatus::Revoked,
+        (TcbStatus::OutOfDate, TcbStatus::UpToDate | TcbStatus::SWHardeningNeeded) => {
+            TcbStatus::OutOfDate
+        }
+        (
+            TcbStatus::OutOfDate,
+            TcbStatus::ConfigurationNeeded | TcbStatus::ConfigurationAndSWHardeningNeeded,
+        ) => TcbStatus::OutOfDateConfigurationNeeded,
+        _ => platform,
+    };
+    QlQvResult::from(status)
+}
+
+#[inline]
+fn flag(flag: Option<bool>) -> PckCertFlag {
+    match flag {
+        Some(false) => PckCertFlag::False,
+        Some(true) => PckCertFlag::True,
+        None => PckCertFlag::Undefined,
+    }
+}
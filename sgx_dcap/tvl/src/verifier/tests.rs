@@ -0,0 +1,187 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License..
+
+use super::{Collateral, QuoteVerifier};
+use crate::quote::Cert;
+use crate::supplemental::SupplementalData;
+use crate::x509::Certificate;
+use alloc::vec::Vec;
+use sgx_test_utils::test_case;
+use sgx_types::error::SgxStatus;
+use sgx_types::types::QlQvResult;
+
+const ROOT_CA: &[u8] = include_bytes!("../../testdata/root_ca.pem");
+const TCB_SIGNING: &[u8] = include_bytes!("../../testdata/tcb_signing.pem");
+const QUOTE: &[u8] = include_bytes!("../../testdata/quote.dat");
+const QUOTE_REVOKED: &[u8] = include_bytes!("../../testdata/quote_revoked.dat");
+const SUPPLEMENTAL: &[u8] = include_bytes!("../../testdata/supplemental.dat");
+
+const COLLATERAL: Collateral<'static> = Collateral {
+    pck_crl_issuer_chain: include_bytes!("../../testdata/pck_crl_issuer_chain.pem"),
+    root_ca_crl: include_bytes!("../../testdata/root_ca_crl.der"),
+    pck_crl: include_bytes!("../../testdata/pck_crl.pem"),
+    tcb_info_issuer_chain: include_bytes!("../../testdata/tcb_info_issuer_chain.pem"),
+    tcb_info: include_bytes!("../../testdata/tcb_info.json"),
+    qe_identity_issuer_chain: include_bytes!("../../testdata/tcb_info_issuer_chain.pem"),
+    qe_identity: include_bytes!("../../testdata/qe_identity.json"),
+};
+
+// 2025-01-01T00:00:00Z
+const NOW: i64 = 1_735_689_600;
+
+fn der(pem: &[u8]) -> Vec<u8> {
+    Certificate::pem_chain(pem).unwrap().remove(0)
+}
+
+#[test_case]
+fn verify_quote() {
+    let root = der(ROOT_CA);
+    let roots = [Cert::new(&root)];
+    let verifier = QuoteVerifier::new(&roots);
+
+    let (result, supplemental) = verifier.verify(QUOTE, &COLLATERAL, NOW).unwrap();
+    assert_eq!(result, QlQvResult::SWHardeningNeeded);
+    assert_eq!(supplemental.version, 3);
+    // The older of the TCB info and QE identity numbers.
+    assert_eq!(supplemental.tcb_eval_ref_num, 15);
+    assert_eq!(supplemental.dynamic_platform, Some(false));
+    assert_eq!(supplemental.cached_keys, Some(true));
+    assert_eq!(supplemental.smt_enabled, Some(true));
+
+    // Matches what the QvE reports, less the 3.1 advisory list.
+    let mut expected = SupplementalData::from_bytes(SUPPLEMENTAL).unwrap();
+    expected.version = 3;
+    expected.sa_list.clear();
+    assert_eq!(supplemental, expected);
+}
+
+#[test_case]
+fn verify_revoked_pck() {
+    let root = der(ROOT_CA);
+    let roots = [Cert::new(&root)];
+    let verifier = QuoteVerifier::new(&roots);
+
+    let (result, _) = verifier.verify(QUOTE_REVOKED, &COLLATERAL, NOW).unwrap();
+    assert_eq!(result, QlQvResult::Revoked);
+}
+
+#[test_case]
+fn reject_untrusted_root() {
+    let other = der(TCB_SIGNING);
+    let roots = [Cert::new(&other)];
+    assert_eq!(
+        QuoteVerifier::new(&roots).verify(QUOTE, &COLLATERAL, NOW),
+        Err(SgxStatus::InvalidSignature)
+    );
+    assert_eq!(
+        QuoteVerifier::new(&[]).verify(QUOTE, &COLLATERAL, NOW),
+        Err(SgxStatus::InvalidSignature)
+    );
+}
+
+#[test_case]
+fn reject_expired_collateral() {
+    let root = der(ROOT_CA);
+    let roots = [Cert::new(&root)];
+    let verifier = QuoteVerifier::new(&roots);
+
+    // Before the CRLs and TCB info were issued, and after the chains expire.
+    for time in [1_717_199_999, 2_524_608_000] {
+        assert_eq!(
+            verifier.verify(QUOTE, &COLLATERAL, time),
+            Err(SgxStatus::UpdateNeeded)
+        );
+    }
+}
+
+#[test_case]
+fn reject_tampered_quote() {
+    let root = der(ROOT_CA);
+    let roots = [Cert::new(&root)];
+    let verifier = QuoteVerifier::new(&roots);
+
+    // Flip a bit of MRENCLAVE, past the 48 byte header.
+    let mut quote = QUOTE.to_vec();
+    quote[48 + 64] ^= 1;
+    assert_eq!(
+        verifier.verify(&quote, &COLLATERAL, NOW),
+        Err(SgxStatus::InvalidSignature)
+    );
+
+    let mut quote = QUOTE.to_vec();
+    quote[0] = 4;
+    assert_eq!(
+        verifier.verify(&quote, &COLLATERAL, NOW),
+        Err(SgxStatus::InvalidParameter)
+    );
+}
+
+#[test_case]
+fn reject_mismatched_collateral() {
+    let root = der(ROOT_CA);
+    let roots = [Cert::new(&root)];
+    let verifier = QuoteVerifier::new(&roots);
+
+    let swapped = Collateral {
+        tcb_info: COLLATERAL.qe_identity,
+        qe_identity: COLLATERAL.tcb_info,
+        ..COLLATERAL
+    };
+    assert_eq!(
+        verifier.verify(QUOTE, &swapped, NOW),
+        Err(SgxStatus::InvalidParameter)
+    );
+
+    // The PCK CRL must come from the CA that issued the PCK certificate.
+    let wrong_issuer = Collateral {
+        pck_crl_issuer_chain: COLLATERAL.tcb_info_issuer_chain,
+        ..COLLATERAL
+    };
+    assert_eq!(
+        verifier.verify(QUOTE, &wrong_issuer, NOW),
+        Err(SgxStatus::InvalidParameter)
+    );
+
+    // A TCB info signed by someone else.
+    let forged = Collateral {
+        tcb_info_issuer_chain: COLLATERAL.pck_crl_issuer_chain,
+        ..COLLATERAL
+    };
+    assert_eq!(
+        verifier.verify(QUOTE, &forged, NOW),
+        Err(SgxStatus::InvalidSignature)
+    );
+}
+
+#[test_case]
+fn accept_nul_terminated_collateral() {
+    let root = der(ROOT_CA);
+    let roots = [Cert::new(&root)];
+    let verifier = QuoteVerifier::new(&roots);
+
+    let mut tcb_info = COLLATERAL.tcb_info.to_vec();
+    tcb_info.push(0);
+    let mut pck_crl = COLLATERAL.pck_crl.to_vec();
+    pck_crl.push(0);
+    let collateral = Collateral {
+        tcb_info: &tcb_info,
+        pck_crl: &pck_crl,
+        ..COLLATERAL
+    };
+    let (result, _) = verifier.verify(QUOTE, &collateral, NOW).unwrap();
+    assert_eq!(result, QlQvResult::SWHardeningNeeded);
+}
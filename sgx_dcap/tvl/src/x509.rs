@@ -0,0 +1,379 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License..
+
+use crate::der::{self, Der, BIT_STRING, CONTEXT_0, CONTEXT_3, INTEGER, OCTET_STRING, OID};
+use crate::quote::{base64_decode, be_to_le, find};
+use alloc::vec::Vec;
+use sgx_crypto::ecc::{EcPublicKey, EcSignature};
+use sgx_types::error::{SgxResult, SgxStatus};
+use sgx_types::types::{time_t, ECP256_KEY_SIZE};
+
+#[cfg(feature = "unit_test")]
+mod tests;
+
+const ECDSA_WITH_SHA256: &[u8] = &[0x2a, 0x86, 0x48, 0xce, 0x3d, 0x04, 0x03, 0x02];
+const EC_PUBLIC_KEY: &[u8] = &[0x2a, 0x86, 0x48, 0xce, 0x3d, 0x02, 0x01];
+const PRIME256V1: &[u8] = &[0x2a, 0x86, 0x48, 0xce, 0x3d, 0x03, 0x01, 0x07];
+const BASIC_CONSTRAINTS: &[u8] = &[0x55, 0x1d, 0x13];
+const CRL_NUMBER: &[u8] = &[0x55, 0x1d, 0x14];
+pub(crate) const SGX_EXTENSION: &[u8] = &[0x2a, 0x86, 0x48, 0x86, 0xf8, 0x4d, 0x01, 0x0d, 0x01];
+
+const PEM_BEGIN_CERT: &[u8] = b"-----BEGIN CERTIFICATE-----";
+const PEM_END_CERT: &[u8] = b"-----END CERTIFICATE-----";
+const PEM_BEGIN_CRL: &[u8] = b"-----BEGIN X509 CRL-----";
+const PEM_END_CRL: &[u8] = b"-----END X509 CRL-----";
+
+/// An X.509 certificate with a P-256 key, signed with ECDSA over SHA-256,
+/// as used throughout the SGX PKI.
+#[derive(Clone, Debug)]
+pub struct Certificate<'a> {
+    pub der: &'a [u8],
+    tbs: &'a [u8],
+    serial_number: &'a [u8],
+    issuer: &'a [u8],
+    subject: &'a [u8],
+    pub not_before: time_t,
+    pub not_after: time_t,
+    /// Uncompressed point, big-endian `x || y`.
+    public_key: [u8; ECP256_KEY_SIZE * 2],
+    signature: [u8; ECP256_KEY_SIZE * 2],
+    is_ca: bool,
+    sgx_extension: Option<&'a [u8]>,
+}
+
+impl<'a> Certificate<'a> {
+    pub fn from_der(der: &'a [u8]) -> SgxResult<Certificate<'a>> {
+        let (tbs, signature) = signed(der)?;
+
+        let mut fields = Der::new(tbs.value);
+        let version = fields.read(CONTEXT_0)?;
+        ensure!(
+            version == [INTEGER, 0x01, 0x02],
+            SgxStatus::InvalidParameter
+        );
+        let serial_number = der::unsigned(fields.read(INTEGER)?)?;
+        signature_algorithm(&mut fields)?;
+        let issuer = fields.read_tlv(der::SEQUENCE)?.raw;
+        let mut validity = fields.read_sequence()?;
+        let not_before = validity.read_time()?;
+        let not_after = validity.read_time()?;
+        validity.finish()?;
+        let subject = fields.read_tlv(der::SEQUENCE)?.raw;
+        let public_key = p256_public_key(fields.read_sequence()?)?;
+
+        let mut is_ca = false;
+        let mut sgx_extension = None;
+        if let Some(extensions) = fields.read_optional(CONTEXT_3)? {
+            let mut outer = Der::new(extensions);
+            let mut extensions = outer.read_sequence()?;
+            outer.finish()?;
+            while !extensions.is_empty() {
+                let (id, value) = extension(&mut extensions)?;
+                if id == BASIC_CONSTRAINTS {
+                    let mut value = Der::new(value);
+                    let mut constraints = value.read_sequence()?;
+                    value.finish()?;
+                    is_ca = !constraints.is_empty() && constraints.read_bool()?;
+                } else if id == SGX_EXTENSION {
+                    sgx_extension = Some(value);
+                }
+            }
+        }
+        fields.finish()?;
+
+        Ok(Certificate {
+            der,
+            tbs: tbs.raw,
+            serial_number,
+            issuer,
+            subject,
+            not_before,
+            not_after,
+            public_key,
+            signature,
+            is_ca,
+            sgx_extension,
+        })
+    }
+
+    /// Decodes every certificate of a PEM chain, in order.
+    pub fn pem_chain(pem: &[u8]) -> SgxResult<Vec<Vec<u8>>> {
+        pem_blocks(pem, PEM_BEGIN_CERT, PEM_END_CERT)
+    }
+
+    #[inline]
+    pub fn serial_number(&self) -> &'a [u8] {
+        self.serial_number
+    }
+
+    #[inline]
+    pub fn public_key(&self) -> EcPublicKey {
+        EcPublicKey::from(be_to_le(&self.public_key))
+    }
+
+    #[inline]
+    pub(crate) fn public_key_bytes(&self) -> &[u8; ECP256_KEY_SIZE * 2] {
+        &self.public_key
+    }
+
+    #[inline]
+    pub fn is_ca(&self) -> bool {
+        self.is_ca
+    }
+
+    #[inline]
+    pub fn is_self_signed(&self) -> bool {
+        self.issuer == self.subject && self.verify_signed_by(self).is_ok()
+    }
+
+    #[inline]
+    pub fn is_valid_at(&self, time: time_t) -> bool {
+        self.not_before <= time && time <= self.not_after
+    }
+
+    #[inline]
+    pub(crate) fn sgx_extension(&self) -> Option<&'a [u8]> {
+        self.sgx_extension
+    }
+
+    /// Checks that `issuer` is a CA whose subject names this certificate's
+    /// issuer and whose key signed it.
+    pub fn verify_signed_by(&self, issuer: &Certificate<'_>) -> SgxResult {
+        ensure!(
+            issuer.is_ca && issuer.subject == self.issuer,
+            SgxStatus::InvalidSignature
+        );
+        verify_signature(issuer, self.tbs, &self.signature)
+    }
+}
+
+/// An X.509 v2 certificate revocation list.
+#[derive(Clone, Debug)]
+pub struct Crl<'a> {
+    pub der: &'a [u8],
+    tbs: &'a [u8],
+    issuer: &'a [u8],
+    pub this_update: time_t,
+    pub next_update: time_t,
+    revoked: Vec<&'a [u8]>,
+    pub crl_number: Option<u32>,
+    signature: [u8; ECP256_KEY_SIZE * 2],
+}
+
+impl<'a> Crl<'a> {
+    pub fn from_der(der: &'a [u8]) -> SgxResult<Crl<'a>> {
+        let (tbs, signature) = signed(der)?;
+
+        let mut fields = Der::new(tbs.value);
+        ensure!(fields.read(INTEGER)? == [0x01], SgxStatus::InvalidParameter);
+        signature_algorithm(&mut fields)?;
+        let issuer = fields.read_tlv(der::SEQUENCE)?.raw;
+        let this_update = fields.read_time()?;
+        // Optional in X.509, but every SGX CRL carries it.
+        let next_update = fields.read_time()?;
+
+        let mut revoked = Vec::new();
+        if fields.peek_tag() == Some(der::SEQUENCE) {
+            let mut entries = fields.read_sequence()?;
+            while !entries.is_empty() {
+                let mut entry = entries.read_sequence()?;
+                revoked.push(der::unsigned(entry.read(INTEGER)?)?);
+                entry.read_time()?;
+            }
+        }
+
+        let mut crl_number = None;
+        if let Some(extensions) = fields.read_optional(CONTEXT_0)? {
+            let mut outer = Der::new(extensions);
+            let mut extensions = outer.read_sequence()?;
+            outer.finish()?;
+            while !extensions.is_empty() {
+                let (id, value) = extension(&mut extensions)?;
+                if id == CRL_NUMBER {
+                    let mut value = Der::new(value);
+                    crl_number = Some(der::unsigned_u32(value.read(INTEGER)?)?);
+                    value.finish()?;
+                }
+            }
+        }
+        fields.finish()?;
+
+        Ok(Crl {
+            der,
+            tbs: tbs.raw,
+            issuer,
+            this_update,
+            next_update,
+            revoked,
+            crl_number,
+            signature,
+        })
+    }
+
+    /// Decodes a CRL given as PEM, as hex encoded DER (the PCS format), or
+    /// as raw DER.
+    pub fn decode(bytes: &[u8]) -> SgxResult<Vec<u8>> {
+        let bytes = trim_nul(bytes);
+        if find(bytes, PEM_BEGIN_CRL).is_some() {
+            let mut blocks = pem_blocks(bytes, PEM_BEGIN_CRL, PEM_END_CRL)?;
+            ensure!(blocks.len() == 1, SgxStatus::InvalidParameter);
+            Ok(blocks.remove(0))
+        } else if !bytes.is_empty() && bytes.iter().all(u8::is_ascii_hexdigit) {
+            hex_decode(bytes)
+        } else {
+            ensure!(!bytes.is_empty(), SgxStatus::InvalidParameter);
+            Ok(bytes.to_vec())
+        }
+    }
+
+    #[inline]
+    pub fn is_valid_at(&self, time: time_t) -> bool {
+        self.this_update <= time && time <= self.next_update
+    }
+
+    /// Whether `cert` is listed. Only meaningful once the CRL has been
+    /// checked with `verify_signed_by` against the issuer of `cert`.
+    pub fn is_revoked(&self, cert: &Certificate<'_>) -> bool {
+        self.issuer == cert.issuer
+            && self
+                .revoked
+                .iter()
+                .any(|&serial| serial == cert.serial_number)
+    }
+
+    pub fn verify_signed_by(&self, issuer: &Certificate<'_>) -> SgxResult {
+        ensure!(
+            issuer.is_ca && issuer.subject == self.issuer,
+            SgxStatus::InvalidSignature
+        );
+        verify_signature(issuer, self.tbs, &self.signature)
+    }
+}
+
+// Splits `SEQUENCE { tbs, signatureAlgorithm, signatureValue }`.
+fn signed(der: &[u8]) -> SgxResult<(der::Tlv<'_>, [u8; ECP256_KEY_SIZE * 2])> {
+    let mut outer = Der::new(der);
+    let mut signed = outer.read_sequence()?;
+    outer.finish()?;
+
+    let tbs = signed.read_tlv(der::SEQUENCE)?;
+    signature_algorithm(&mut signed)?;
+    let signature = ecdsa_signature(signed.read(BIT_STRING)?)?;
+    signed.finish()?;
+    Ok((tbs, signature))
+}
+
+fn signature_algorithm(der: &mut Der<'_>) -> SgxResult {
+    let mut algorithm = der.read_sequence()?;
+    ensure!(
+        algorithm.read(OID)? == ECDSA_WITH_SHA256,
+        SgxStatus::InvalidParameter
+    );
+    algorithm.finish()
+}
+
+fn extension<'a>(extensions: &mut Der<'a>) -> SgxResult<(&'a [u8], &'a [u8])> {
+    let mut extension = extensions.read_sequence()?;
+    let id = extension.read(OID)?;
+    if extension.peek_tag() == Some(der::BOOLEAN) {
+        extension.read_bool()?;
+    }
+    let value = extension.read(OCTET_STRING)?;
+    extension.finish()?;
+    Ok((id, value))
+}
+
+fn p256_public_key(mut spki: Der<'_>) -> SgxResult<[u8; ECP256_KEY_SIZE * 2]> {
+    let mut algorithm = spki.read_sequence()?;
+    ensure!(
+        algorithm.read(OID)? == EC_PUBLIC_KEY && algorithm.read(OID)? == PRIME256V1,
+        SgxStatus::InvalidParameter
+    );
+    algorithm.finish()?;
+
+    let bits = spki.read(BIT_STRING)?;
+    spki.finish()?;
+    match bits {
+        [0x00, 0x04, point @ ..] if point.len() == ECP256_KEY_SIZE * 2 => {
+            let mut key = [0_u8; ECP256_KEY_SIZE * 2];
+            key.copy_from_slice(point);
+            Ok(key)
+        }
+        _ => Err(SgxStatus::InvalidParameter),
+    }
+}
+
+// `BIT STRING { SEQUENCE { r INTEGER, s INTEGER } }` to big-endian `r || s`.
+fn ecdsa_signature(bits: &[u8]) -> SgxResult<[u8; ECP256_KEY_SIZE * 2]> {
+    let (&unused, bits) = bits.split_first().ok_or(SgxStatus::InvalidParameter)?;
+    ensure!(unused == 0, SgxStatus::InvalidParameter);
+
+    let mut outer = Der::new(bits);
+    let mut values = outer.read_sequence()?;
+    outer.finish()?;
+
+    let mut signature = [0_u8; ECP256_KEY_SIZE * 2];
+    for half in signature.chunks_exact_mut(ECP256_KEY_SIZE) {
+        let value = der::unsigned(values.read(INTEGER)?)?;
+        ensure!(value.len() <= ECP256_KEY_SIZE, SgxStatus::InvalidParameter);
+        half[ECP256_KEY_SIZE - value.len()..].copy_from_slice(value);
+    }
+    values.finish()?;
+    Ok(signature)
+}
+
+pub(crate) fn verify_signature(
+    signer: &Certificate<'_>,
+    data: &[u8],
+    signature: &[u8; ECP256_KEY_SIZE * 2],
+) -> SgxResult {
+    let signature = EcSignature::from(be_to_le(signature));
+    let valid = signer.public_key().verify(data, &signature)?;
+    ensure!(valid, SgxStatus::InvalidSignature);
+    Ok(())
+}
+
+fn pem_blocks(pem: &[u8], begin: &[u8], end: &[u8]) -> SgxResult<Vec<Vec<u8>>> {
+    let mut blocks = Vec::new();
+    let mut rest = pem;
+    while let Some(start) = find(rest, begin) {
+        rest = &rest[start + begin.len()..];
+        let len = find(rest, end).ok_or(SgxStatus::InvalidParameter)?;
+        blocks.push(base64_decode(&rest[..len])?);
+        rest = &rest[len + end.len()..];
+    }
+    ensure!(!blocks.is_empty(), SgxStatus::InvalidParameter);
+    Ok(blocks)
+}
+
+/// Collateral handed over as C strings keeps its terminator in the length.
+pub(crate) fn trim_nul(bytes: &[u8]) -> &[u8] {
+    match bytes {
+        [rest @ .., 0] => rest,
+        _ => bytes,
+    }
+}
+
+pub(crate) fn hex_decode(hex: &[u8]) -> SgxResult<Vec<u8>> {
+    ensure!(hex.len() % 2 == 0, SgxStatus::InvalidParameter);
+    hex.chunks_exact(2)
+        .map(|pair| {
+            let nibble = |c: u8| (c as char).to_digit(16).ok_or(SgxStatus::InvalidParameter);
+            Ok(((nibble(pair[0])? << 4) | nibble(pair[1])?) as u8)
+        })
+        .collect()
+}
@@ -0,0 +1,138 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License..
+
+use super::{hex_decode, Certificate, Crl};
+use alloc::vec::Vec;
+use sgx_test_utils::test_case;
+use sgx_types::error::SgxStatus;
+
+const ROOT_CA: &[u8] = include_bytes!("../../testdata/root_ca.pem");
+const PCK_PLATFORM_CA: &[u8] = include_bytes!("../../testdata/pck_platform_ca.pem");
+const PCK: &[u8] = include_bytes!("../../testdata/pck.pem");
+const PCK_CRL_ISSUER_CHAIN: &[u8] = include_bytes!("../../testdata/pck_crl_issuer_chain.pem");
+const ROOT_CA_CRL: &[u8] = include_bytes!("../../testdata/root_ca_crl.der");
+const PCK_CRL: &[u8] = include_bytes!("../../testdata/pck_crl.pem");
+
+fn der(pem: &[u8]) -> Vec<u8> {
+    Certificate::pem_chain(pem).unwrap().remove(0)
+}
+
+#[test_case]
+fn parse_pck_chain() {
+    let (root, ca, pck) = (der(ROOT_CA), der(PCK_PLATFORM_CA), der(PCK));
+    let root = Certificate::from_der(&root).unwrap();
+    let ca = Certificate::from_der(&ca).unwrap();
+    let pck = Certificate::from_der(&pck).unwrap();
+
+    assert!(root.is_ca() && root.is_self_signed());
+    assert!(ca.is_ca() && !ca.is_self_signed());
+    assert!(!pck.is_ca());
+    assert_eq!(pck.serial_number(), &[0x10, 0x01]);
+    assert_eq!(pck.not_before, 1_704_067_200);
+    assert_eq!(pck.not_after, 2_524_607_999);
+    assert!(pck.is_valid_at(1_735_689_600));
+    assert!(!pck.is_valid_at(2_524_608_000));
+    assert!(pck.sgx_extension().is_some());
+    assert!(root.sgx_extension().is_none());
+
+    assert_eq!(pck.verify_signed_by(&ca), Ok(()));
+    assert_eq!(ca.verify_signed_by(&root), Ok(()));
+    assert_eq!(
+        pck.verify_signed_by(&root),
+        Err(SgxStatus::InvalidSignature)
+    );
+    // A leaf can't issue, even under the right name.
+    assert_eq!(ca.verify_signed_by(&pck), Err(SgxStatus::InvalidSignature));
+}
+
+#[test_case]
+fn reject_tampered_certificate() {
+    let ca = der(PCK_PLATFORM_CA);
+    let ca = Certificate::from_der(&ca).unwrap();
+
+    let mut pck = der(PCK);
+    let serial = pck
+        .windows(4)
+        .position(|w| w == [0x02, 0x02, 0x10, 0x01])
+        .unwrap();
+    pck[serial + 3] = 0x03;
+    let pck = Certificate::from_der(&pck).unwrap();
+    assert_eq!(pck.verify_signed_by(&ca), Err(SgxStatus::InvalidSignature));
+
+    let pck = der(PCK);
+    assert!(Certificate::from_der(&pck[..pck.len() - 1]).is_err());
+}
+
+#[test_case]
+fn pem_chain_order() {
+    let chain = Certificate::pem_chain(PCK_CRL_ISSUER_CHAIN).unwrap();
+    assert_eq!(chain, [der(PCK_PLATFORM_CA), der(ROOT_CA)]);
+    assert_eq!(
+        Certificate::pem_chain(b"no certificates"),
+        Err(SgxStatus::InvalidParameter)
+    );
+}
+
+#[test_case]
+fn parse_and_verify_crls() {
+    let root = der(ROOT_CA);
+    let root = Certificate::from_der(&root).unwrap();
+    let ca = der(PCK_PLATFORM_CA);
+    let ca = Certificate::from_der(&ca).unwrap();
+    let pck = der(PCK);
+    let pck = Certificate::from_der(&pck).unwrap();
+
+    let root_ca_crl = Crl::decode(ROOT_CA_CRL).unwrap();
+    let root_ca_crl = Crl::from_der(&root_ca_crl).unwrap();
+    assert_eq!(root_ca_crl.verify_signed_by(&root), Ok(()));
+    assert_eq!(
+        root_ca_crl.verify_signed_by(&ca),
+        Err(SgxStatus::InvalidSignature)
+    );
+    assert_eq!(root_ca_crl.crl_number, Some(1));
+    assert!(root_ca_crl.is_valid_at(1_735_689_600));
+    assert!(!root_ca_crl.is_revoked(&ca));
+
+    let pck_crl = Crl::decode(PCK_CRL).unwrap();
+    let pck_crl = Crl::from_der(&pck_crl).unwrap();
+    assert_eq!(pck_crl.verify_signed_by(&ca), Ok(()));
+    assert_eq!(pck_crl.crl_number, Some(2));
+    assert_eq!(pck_crl.revoked, [&[0x10_u8, 0x02][..]]);
+    assert!(!pck_crl.is_revoked(&pck));
+    // Serial numbers are only unique per issuer.
+    assert!(!root_ca_crl.is_revoked(&pck));
+}
+
+#[test_case]
+fn decode_crl_formats() {
+    let der = Crl::decode(ROOT_CA_CRL).unwrap();
+    assert_eq!(der, ROOT_CA_CRL);
+
+    let mut hex = Vec::new();
+    for b in ROOT_CA_CRL {
+        hex.extend_from_slice(alloc::format!("{:02x}", b).as_bytes());
+    }
+    hex.push(0);
+    assert_eq!(Crl::decode(&hex).unwrap(), der);
+    assert_eq!(hex_decode(b"0aFf").unwrap(), [0x0a, 0xff]);
+    assert!(hex_decode(b"0a0").is_err());
+
+    let mut pem = PCK_CRL.to_vec();
+    pem.push(0);
+    assert_eq!(Crl::decode(&pem).unwrap(), Crl::decode(PCK_CRL).unwrap());
+    assert_eq!(Crl::decode(&[0]), Err(SgxStatus::InvalidParameter));
+}
@@ -0,0 +1,361 @@
+#!/usr/bin/env python3
+#
+# Licensed to the Apache Software Foundation (ASF) under one
+# or more contributor license agreements.  See the NOTICE file
+# distributed with this work for additional information
+# regarding copyright ownership.  The ASF licenses this file
+# to you under the Apache License, Version 2.0 (the
+# "License"); you may not use this file except in compliance
+# with the License.  You may obtain a copy of the License at
+#
+#   http://www.apache.org/licenses/LICENSE-2.0
+#
+# Unless required by applicable law or agreed to in writing,
+# software distributed under the License is distributed on an
+# "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+# KIND, either express or implied.  See the License for the
+# specific language governing permissions and limitations
+# under the License.
+
+# Generates the collateral used by the sgx_dcap_tvl unit tests: a test
+# root CA, PCK and TCB signing chains, CRLs, TCB info, QE identity and
+# quotes, laid out like the collateral served by the Intel PCS, plus the
+# supplemental data the QvE reports for them. Requires the `cryptography`
+# package.
+
+import datetime
+import hashlib
+import json
+import os
+import struct
+
+from cryptography import x509
+from cryptography.hazmat.primitives import hashes, serialization
+from cryptography.hazmat.primitives.asymmetric import ec
+from cryptography.hazmat.primitives.asymmetric.utils import decode_dss_signature
+from cryptography.x509.oid import NameOID
+
+OUT = os.path.dirname(os.path.abspath(__file__))
+
+NOT_BEFORE = datetime.datetime(2024, 1, 1, tzinfo=datetime.timezone.utc)
+NOT_AFTER = datetime.datetime(2049, 12, 31, 23, 59, 59, tzinfo=datetime.timezone.utc)
+ISSUE_DATE = "2024-06-01T00:00:00Z"
+NEXT_UPDATE = "2049-06-01T00:00:00Z"
+
+FMSPC = bytes.fromhex("00906ed50000")
+PCE_ID = bytes.fromhex("0000")
+PCK_TCB = [6] * 16
+PCK_PCESVN = 13
+QE_MRSIGNER = bytes.fromhex("8c4f5775d796503e96137f77c68a829a0056ac8ded70140b081b094490c57bff")
+QE_VENDOR_ID = bytes.fromhex("939a7233f79c4ca9940a0db3957f0607")
+
+
+def der_len(n):
+    if n < 0x80:
+        return bytes([n])
+    b = n.to_bytes((n.bit_length() + 7) // 8, "big")
+    return bytes([0x80 | len(b)]) + b
+
+
+def tlv(tag, value):
+    return bytes([tag]) + der_len(len(value)) + value
+
+
+def oid(dotted):
+    parts = [int(p) for p in dotted.split(".")]
+    out = bytes([parts[0] * 40 + parts[1]])
+    for p in parts[2:]:
+        enc = [p & 0x7F]
+        p >>= 7
+        while p:
+            enc.insert(0, 0x80 | (p & 0x7F))
+            p >>= 7
+        out += bytes(enc)
+    return tlv(0x06, out)
+
+
+def integer(n):
+    b = n.to_bytes(max(1, (n.bit_length() + 8) // 8), "big")
+    return tlv(0x02, b)
+
+
+def seq(*items):
+    return tlv(0x30, b"".join(items))
+
+
+SGX = "1.2.840.113741.1.13.1"
+
+
+def sgx_extension(ppid, platform_instance_id):
+    tcb = [seq(oid(f"{SGX}.2.{i + 1}"), integer(svn)) for i, svn in enumerate(PCK_TCB)]
+    tcb.append(seq(oid(f"{SGX}.2.17"), integer(PCK_PCESVN)))
+    tcb.append(seq(oid(f"{SGX}.2.18"), tlv(0x04, bytes(PCK_TCB))))
+    config = seq(
+        seq(oid(f"{SGX}.7.1"), tlv(0x01, b"\x00")),
+        seq(oid(f"{SGX}.7.2"), tlv(0x01, b"\xff")),
+        seq(oid(f"{SGX}.7.3"), tlv(0x01, b"\xff")),
+    )
+    return seq(
+        seq(oid(f"{SGX}.1"), tlv(0x04, ppid)),
+        seq(oid(f"{SGX}.2"), seq(*tcb)),
+        seq(oid(f"{SGX}.3"), tlv(0x04, PCE_ID)),
+        seq(oid(f"{SGX}.4"), tlv(0x04, FMSPC)),
+        seq(oid(f"{SGX}.5"), tlv(0x0A, b"\x01")),
+        seq(oid(f"{SGX}.6"), tlv(0x04, platform_instance_id)),
+        seq(oid(f"{SGX}.7"), config),
+    )
+
+
+def name(cn):
+    return x509.Name(
+        [
+            x509.NameAttribute(NameOID.COMMON_NAME, cn),
+            x509.NameAttribute(NameOID.ORGANIZATION_NAME, "Teaclave Test"),
+            x509.NameAttribute(NameOID.COUNTRY_NAME, "US"),
+        ]
+    )
+
+
+def cert(cn, key, issuer_cn, issuer_key, serial, ca, extra=None):
+    builder = (
+        x509.CertificateBuilder()
+        .subject_name(name(cn))
+        .issuer_name(name(issuer_cn))
+        .public_key(key.public_key())
+        .serial_number(serial)
+        .not_valid_before(NOT_BEFORE)
+        .not_valid_after(NOT_AFTER)
+        .add_extension(x509.BasicConstraints(ca=ca, path_length=None), True)
+    )
+    if extra is not None:
+        builder = builder.add_extension(
+            x509.UnrecognizedExtension(x509.ObjectIdentifier(SGX), extra), False
+        )
+    return builder.sign(issuer_key, hashes.SHA256())
+
+
+def crl(issuer_cn, issuer_key, number, revoked):
+    builder = (
+        x509.CertificateRevocationListBuilder()
+        .issuer_name(name(issuer_cn))
+        .last_update(datetime.datetime(2024, 6, 1, tzinfo=datetime.timezone.utc))
+        .next_update(datetime.datetime(2049, 6, 1, tzinfo=datetime.timezone.utc))
+        .add_extension(x509.CRLNumber(number), False)
+    )
+    for serial in revoked:
+        builder = builder.add_revoked_certificate(
+            x509.RevokedCertificateBuilder()
+            .serial_number(serial)
+            .revocation_date(datetime.datetime(2024, 6, 1, tzinfo=datetime.timezone.utc))
+            .build()
+        )
+    return builder.sign(issuer_key, hashes.SHA256())
+
+
+def pem(*certs):
+    return b"".join(c.public_bytes(serialization.Encoding.PEM) for c in certs)
+
+
+def raw_point(key):
+    return key.public_key().public_bytes(
+        serialization.Encoding.X962, serialization.PublicFormat.UncompressedPoint
+    )[1:]
+
+
+def raw_sign(key, data):
+    r, s = decode_dss_signature(key.sign(data, ec.ECDSA(hashes.SHA256())))
+    return r.to_bytes(32, "big") + s.to_bytes(32, "big")
+
+
+def signed_json(field, body, key):
+    text = json.dumps(body, separators=(",", ":"))
+    sig = raw_sign(key, text.encode())
+    return ('{"%s":%s,"signature":"%s"}' % (field, text, sig.hex())).encode()
+
+
+def tcb_level(svns, pcesvn, date, status, advisories=None):
+    level = {
+        "tcb": {"sgxtcbcomponents": [{"svn": s} for s in svns], "pcesvn": pcesvn},
+        "tcbDate": date,
+        "tcbStatus": status,
+    }
+    if advisories:
+        level["advisoryIDs"] = advisories
+    return level
+
+
+def report_body(**fields):
+    body = bytearray(384)
+    body[0:16] = fields.get("cpu_svn", bytes(16))
+    body[16:20] = struct.pack("<I", fields.get("misc_select", 0))
+    body[48:64] = fields.get("attributes", bytes(16))
+    body[64:96] = fields.get("mr_enclave", bytes(32))
+    body[128:160] = fields.get("mr_signer", bytes(32))
+    body[256:258] = struct.pack("<H", fields.get("isv_prod_id", 0))
+    body[258:260] = struct.pack("<H", fields.get("isv_svn", 0))
+    body[320:384] = fields.get("report_data", bytes(64))
+    return bytes(body)
+
+
+def quote(pck_key, chain):
+    attest_key = ec.generate_private_key(ec.SECP256R1())
+    attest_pub = raw_point(attest_key)
+    auth = bytes(range(32))
+
+    header = struct.pack("<HHIHH", 3, 2, 0, 8, PCK_PCESVN) + QE_VENDOR_ID + bytes(20)
+    body = report_body(
+        cpu_svn=bytes(PCK_TCB),
+        attributes=bytes.fromhex("07000000000000000700000000000000"),
+        mr_enclave=hashlib.sha256(b"enclave").digest(),
+        mr_signer=hashlib.sha256(b"signer").digest(),
+        isv_prod_id=1,
+        isv_svn=1,
+        report_data=hashlib.sha512(b"report data").digest(),
+    )
+    qe_report = report_body(
+        cpu_svn=bytes(PCK_TCB),
+        attributes=bytes.fromhex("11000000000000000700000000000000"),
+        mr_signer=QE_MRSIGNER,
+        isv_prod_id=1,
+        isv_svn=8,
+        report_data=hashlib.sha256(attest_pub + auth).digest() + bytes(32),
+    )
+    cert_data = chain + b"\x00"
+    signature = (
+        raw_sign(attest_key, header + body)
+        + attest_pub
+        + qe_report
+        + raw_sign(pck_key, qe_report)
+        + struct.pack("<H", len(auth))
+        + auth
+        + struct.pack("<HI", 5, len(cert_data))
+        + cert_data
+    )
+    return header + body + struct.pack("<I", len(signature)) + signature
+
+
+def timestamp(date):
+    return int(datetime.datetime.fromisoformat(date.replace("Z", "+00:00")).timestamp())
+
+
+def supplemental(root_key, sa_list):
+    # sgx_ql_qv_supplemental_t 3.1 for quote.dat, built from the header
+    # layout. Synthetic: no real QvE output was available to capture.
+    blob = bytearray(496)
+    struct.pack_into("<HH", blob, 0, 3, 1)
+    struct.pack_into(
+        "<qqqqIII",
+        blob,
+        8,
+        int(NOT_BEFORE.timestamp()),
+        timestamp(ISSUE_DATE),
+        timestamp(NEXT_UPDATE),
+        timestamp("2023-08-09T00:00:00Z"),
+        2,
+        1,
+        15,
+    )
+    blob[52:100] = hashlib.sha384(b"\x04" + raw_point(root_key)).digest()
+    blob[100:116] = bytes(range(16))
+    blob[116:132] = bytes(PCK_TCB)
+    struct.pack_into("<HHIB", blob, 132, PCK_PCESVN, int.from_bytes(PCE_ID, "big"), 0, 1)
+    blob[141:157] = bytes(range(16, 32))
+    struct.pack_into("<III", blob, 160, 0, 1, 1)
+    blob[172:172 + len(sa_list)] = sa_list.encode()
+    return bytes(blob)
+
+
+ROOT_CN = "Teaclave Test SGX Root CA"
+PLATFORM_CN = "Teaclave Test SGX PCK Platform CA"
+TCB_CN = "Teaclave Test SGX TCB Signing"
+
+
+def main():
+    root_key = ec.generate_private_key(ec.SECP256R1())
+    platform_key = ec.generate_private_key(ec.SECP256R1())
+    tcb_key = ec.generate_private_key(ec.SECP256R1())
+    pck_key = ec.generate_private_key(ec.SECP256R1())
+    revoked_key = ec.generate_private_key(ec.SECP256R1())
+
+    root = cert(ROOT_CN, root_key, ROOT_CN, root_key, 1, True)
+    platform = cert(PLATFORM_CN, platform_key, ROOT_CN, root_key, 2, True)
+    tcb = cert(TCB_CN, tcb_key, ROOT_CN, root_key, 3, False)
+    ext = sgx_extension(bytes(range(16)), bytes(range(16, 32)))
+    pck = cert("Teaclave Test SGX PCK Certificate", pck_key, PLATFORM_CN, platform_key, 0x1001, False, ext)
+    revoked = cert(
+        "Teaclave Test SGX PCK Certificate", revoked_key, PLATFORM_CN, platform_key, 0x1002, False, ext
+    )
+
+    tcb_info = signed_json(
+        "tcbInfo",
+        {
+            "id": "SGX",
+            "version": 3,
+            "issueDate": ISSUE_DATE,
+            "nextUpdate": NEXT_UPDATE,
+            "fmspc": FMSPC.hex(),
+            "pceId": PCE_ID.hex(),
+            "tcbType": 0,
+            "tcbEvaluationDataNumber": 16,
+            "tcbLevels": [
+                tcb_level([7] * 16, 13, "2024-03-13T00:00:00Z", "UpToDate"),
+                tcb_level(
+                    [6] * 16, 13, "2023-08-09T00:00:00Z", "SWHardeningNeeded", ["INTEL-SA-00615"]
+                ),
+                tcb_level(
+                    [5] * 16,
+                    11,
+                    "2021-11-10T00:00:00Z",
+                    "OutOfDate",
+                    ["INTEL-SA-00586", "INTEL-SA-00615"],
+                ),
+            ],
+        },
+        tcb_key,
+    )
+    qe_identity = signed_json(
+        "enclaveIdentity",
+        {
+            "id": "QE",
+            "version": 2,
+            "issueDate": ISSUE_DATE,
+            "nextUpdate": NEXT_UPDATE,
+            "tcbEvaluationDataNumber": 15,
+            "miscselect": "00000000",
+            "miscselectMask": "FFFFFFFF",
+            "attributes": "11000000000000000000000000000000",
+            "attributesMask": "FBFFFFFFFFFFFFFF0000000000000000",
+            "mrsigner": QE_MRSIGNER.hex().upper(),
+            "isvprodid": 1,
+            "tcbLevels": [
+                {"tcb": {"isvsvn": 8}, "tcbDate": "2023-08-09T00:00:00Z", "tcbStatus": "UpToDate"},
+                {"tcb": {"isvsvn": 6}, "tcbDate": "2021-11-10T00:00:00Z", "tcbStatus": "OutOfDate"},
+            ],
+        },
+        tcb_key,
+    )
+
+    files = {
+        "root_ca.pem": pem(root),
+        "pck_platform_ca.pem": pem(platform),
+        "pck.pem": pem(pck),
+        "tcb_signing.pem": pem(tcb),
+        "pck_crl_issuer_chain.pem": pem(platform, root),
+        "tcb_info_issuer_chain.pem": pem(tcb, root),
+        "root_ca_crl.der": crl(ROOT_CN, root_key, 1, [0x77]).public_bytes(serialization.Encoding.DER),
+        "pck_crl.pem": crl(PLATFORM_CN, platform_key, 2, [0x1002]).public_bytes(
+            serialization.Encoding.PEM
+        ),
+        "tcb_info.json": tcb_info,
+        "qe_identity.json": qe_identity,
+        "quote.dat": quote(pck_key, pem(pck, platform, root)),
+        "quote_revoked.dat": quote(revoked_key, pem(revoked, platform, root)),
+        "supplemental.dat": supplemental(root_key, "INTEL-SA-00615"),
+    }
+    for file_name, data in files.items():
+        with open(os.path.join(OUT, file_name), "wb") as f:
+            f.write(data)
+
+
+if __name__ == "__main__":
+    main()
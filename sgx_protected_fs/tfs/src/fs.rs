@@ -32,6 +32,9 @@ cfg_if! {
     }
 }
 
+#[cfg(feature = "unit_test")]
+mod tests;
+
 /// Options and flags which can be used to configure how a file is opened.
 ///
 /// This builder exposes the ability to configure how a SgxFile is opened and
@@ -380,10 +383,26 @@ impl OpenOptions {
         self
     }
 
+    /// Sets the option for encrypting the file contents, on by default.
+    ///
+    /// With `encrypt(false)`, `open` uses the integrity-only mode: nodes are
+    /// stored in plaintext and authenticated with GMAC, so tampering is still
+    /// detected on read. A file keeps the mode it was created with; opening an
+    /// integrity-only file as encrypted, or the other way round, fails with
+    /// `SgxStatus::InvalidParameter`.
+    pub fn encrypt(&mut self, encrypt: bool) -> &mut OpenOptions {
+        self.0.encrypt(encrypt);
+        self
+    }
+
     /// Opens a file at `path` with the options specified by `self`.
     #[cfg(feature = "tfs")]
     pub fn open<P: AsRef<Path>>(&self, path: P) -> io::Result<SgxFile> {
-        self.open_with(path, EncryptMode::auto_key(None), None)
+        if self.0.is_encrypt() {
+            self.open_with(path, EncryptMode::auto_key(None), None)
+        } else {
+            self.open_with(path, EncryptMode::integrity_only(), None)
+        }
     }
 
     pub fn open_with_key<P: AsRef<Path>>(&self, path: P, key: Key128bit) -> io::Result<SgxFile> {
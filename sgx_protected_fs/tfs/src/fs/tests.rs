@@ -0,0 +1,323 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License..
+use super::{
+    export_key, import_key, read, read_with_key, remove, write_with_key, OpenOptions, SgxFile,
+};
+use sgx_test_utils::test_case;
+use sgx_types::error::SgxStatus;
+use sgx_types::types::KeyPolicy;
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::Path;
+use std::vec::Vec;
+
+#[test_case]
+fn append_across_opens() {
+    let path = Path::new("sgx_tprotected_fs_append_across_opens");
+    let _ = remove(path);
+
+    // Sizes chosen so the appends cross the metadata user-data area and
+    // several data node boundaries.
+    let mut expected = Vec::new();
+    for (i, len) in [1000_usize, 3000, 5000, 9000].into_iter().enumerate() {
+        let chunk = vec![i as u8 + 1; len];
+        let mut file = OpenOptions::new().append(true).open(path).unwrap();
+        assert_eq!(file.file_size().unwrap(), expected.len() as u64);
+        file.write_all(&chunk).unwrap();
+        expected.extend_from_slice(&chunk);
+        assert_eq!(file.tell().unwrap(), expected.len() as u64);
+    }
+
+    assert_eq!(read(path).unwrap(), expected);
+    remove(path).unwrap();
+}
+
+#[test_case]
+fn append_ignores_seek() {
+    let path = Path::new("sgx_tprotected_fs_append_ignores_seek");
+    let _ = remove(path);
+
+    SgxFile::create(path).unwrap().write_all(b"head").unwrap();
+    {
+        let mut file = SgxFile::append(path).unwrap();
+        file.seek(SeekFrom::Start(0)).unwrap();
+        file.write_all(b" tail").unwrap();
+    }
+
+    assert_eq!(read(path).unwrap(), b"head tail");
+    remove(path).unwrap();
+}
+
+#[test_case]
+fn append_rejects_concurrent_open() {
+    let path = Path::new("sgx_tprotected_fs_append_concurrent_open");
+    let _ = remove(path);
+
+    let mut file = SgxFile::append(path).unwrap();
+    file.write_all(b"first").unwrap();
+    assert!(SgxFile::append(path).is_err());
+    assert!(SgxFile::open(path).is_err());
+    drop(file);
+
+    SgxFile::append(path)
+        .unwrap()
+        .write_all(b" second")
+        .unwrap();
+    assert_eq!(read(path).unwrap(), b"first second");
+    remove(path).unwrap();
+}
+
+#[test_case]
+fn seek_from_variants() {
+    let path = Path::new("sgx_tprotected_fs_seek_from_variants");
+    let _ = remove(path);
+
+    let data: Vec<u8> = (0..10000_u32).map(|i| i as u8).collect();
+    SgxFile::create(path).unwrap().write_all(&data).unwrap();
+
+    let mut file = SgxFile::open(path).unwrap();
+    let mut buf = [0_u8; 100];
+
+    assert_eq!(file.seek(SeekFrom::Start(5000)).unwrap(), 5000);
+    assert_eq!(file.stream_position().unwrap(), 5000);
+    file.read_exact(&mut buf).unwrap();
+    assert_eq!(&buf[..], &data[5000..5100]);
+
+    assert_eq!(file.seek(SeekFrom::Current(-3000)).unwrap(), 2100);
+    file.read_exact(&mut buf).unwrap();
+    assert_eq!(&buf[..], &data[2100..2200]);
+
+    assert_eq!(file.seek(SeekFrom::Current(4000)).unwrap(), 6200);
+    assert_eq!(file.tell().unwrap(), 6200);
+
+    assert_eq!(file.seek(SeekFrom::End(-100)).unwrap(), 9900);
+    file.read_exact(&mut buf).unwrap();
+    assert_eq!(&buf[..], &data[9900..]);
+    assert_eq!(file.read(&mut buf).unwrap(), 0);
+
+    // read-only handles cannot move outside the file
+    assert!(file.seek(SeekFrom::End(1)).is_err());
+    assert!(file.seek(SeekFrom::Start(10001)).is_err());
+    assert!(file.seek(SeekFrom::Current(-10001)).is_err());
+    assert_eq!(file.stream_position().unwrap(), 10000);
+
+    drop(file);
+    remove(path).unwrap();
+}
+
+#[test_case]
+fn seek_then_read_round_trip() {
+    let path = Path::new("sgx_tprotected_fs_seek_then_read");
+    let _ = remove(path);
+
+    // Write records out of order, then read them back at random offsets.
+    let records: Vec<(u64, Vec<u8>)> = [7000_u64, 100, 12000, 3000, 4090]
+        .into_iter()
+        .map(|offset| (offset, vec![(offset % 251) as u8 + 1; 64]))
+        .collect();
+    {
+        let mut file = SgxFile::create(path).unwrap();
+        for (offset, record) in records.iter() {
+            file.seek(SeekFrom::Start(*offset)).unwrap();
+            file.write_all(record).unwrap();
+        }
+    }
+
+    let mut file = SgxFile::open(path).unwrap();
+    assert_eq!(file.file_size().unwrap(), 12064);
+    for (offset, record) in records.iter().rev() {
+        let mut buf = [0_u8; 64];
+        file.seek(SeekFrom::Start(*offset)).unwrap();
+        file.read_exact(&mut buf).unwrap();
+        assert_eq!(&buf[..], &record[..]);
+    }
+    drop(file);
+    remove(path).unwrap();
+}
+
+#[test_case]
+fn seek_past_end_zero_fills() {
+    let path = Path::new("sgx_tprotected_fs_seek_past_end");
+    let _ = remove(path);
+
+    {
+        let mut file = SgxFile::create(path).unwrap();
+        file.write_all(b"abc").unwrap();
+        assert_eq!(file.seek(SeekFrom::End(5000)).unwrap(), 5003);
+        assert_eq!(file.file_size().unwrap(), 3);
+        file.write_all(b"xyz").unwrap();
+        assert_eq!(file.file_size().unwrap(), 5006);
+    }
+
+    let data = read(path).unwrap();
+    assert_eq!(data.len(), 5006);
+    assert_eq!(&data[..3], b"abc");
+    assert!(data[3..5003].iter().all(|&b| b == 0));
+    assert_eq!(&data[5003..], b"xyz");
+    remove(path).unwrap();
+}
+
+#[test_case]
+fn seek_past_size_limit() {
+    let path = Path::new("sgx_tprotected_fs_seek_past_limit");
+    let _ = remove(path);
+
+    let mut file = SgxFile::create(path).unwrap();
+    file.write_all(b"abc").unwrap();
+
+    let efbig = Some(sgx_types::error::errno::EFBIG);
+    let error = file.seek(SeekFrom::Start(u64::MAX)).unwrap_err();
+    assert_eq!(error.raw_os_error(), efbig);
+    let error = file.seek(SeekFrom::End(i64::MAX)).unwrap_err();
+    assert_eq!(error.raw_os_error(), efbig);
+    let error = file.set_len(u64::MAX).unwrap_err();
+    assert_eq!(error.raw_os_error(), efbig);
+    assert_eq!(file.stream_position().unwrap(), 3);
+    assert_eq!(file.file_size().unwrap(), 3);
+
+    file.write_all(b"xyz").unwrap();
+    assert_eq!(file.file_size().unwrap(), 6);
+    drop(file);
+    remove(path).unwrap();
+}
+
+#[test_case]
+fn user_key_round_trip() {
+    let path = Path::new("sgx_tprotected_fs_user_key");
+    let _ = remove(path);
+
+    let key = [0x42_u8; 16];
+    let data = vec![0x5a_u8; 6000];
+    write_with_key(path, key, &data).unwrap();
+    assert_eq!(read_with_key(path, key).unwrap(), data);
+
+    let mut wrong_key = key;
+    wrong_key[0] ^= 1;
+    let error = read_with_key(path, wrong_key).unwrap_err();
+    assert_eq!(error.raw_sgx_error(), Some(SgxStatus::MacMismatch));
+
+    // a user-key file is not readable through the auto-derived key
+    assert!(SgxFile::open(path).is_err());
+    remove(path).unwrap();
+}
+
+#[test_case]
+fn export_then_import_key() {
+    let path = Path::new("sgx_tprotected_fs_export_import");
+    let _ = remove(path);
+
+    let data = vec![0xa5_u8; 6000];
+    SgxFile::create(path).unwrap().write_all(&data).unwrap();
+
+    let key = export_key(path).unwrap();
+
+    let mut wrong_key = key;
+    wrong_key[15] ^= 1;
+    let error = import_key(path, wrong_key, None).unwrap_err();
+    assert_eq!(error.raw_sgx_error(), Some(SgxStatus::MacMismatch));
+
+    import_key(path, key, Some(KeyPolicy::MRSIGNER)).unwrap();
+    assert_eq!(read(path).unwrap(), data);
+    remove(path).unwrap();
+}
+
+#[test_case]
+fn integrity_only_round_trip() {
+    let path = Path::new("sgx_tprotected_fs_integrity_only");
+    let _ = remove(path);
+
+    let marker = b"integrity only marker ";
+    let data: Vec<u8> = marker.iter().copied().cycle().take(8000).collect();
+    OpenOptions::new()
+        .write(true)
+        .encrypt(false)
+        .open(path)
+        .unwrap()
+        .write_all(&data)
+        .unwrap();
+
+    let mut file = OpenOptions::new()
+        .read(true)
+        .encrypt(false)
+        .open(path)
+        .unwrap();
+    let mut buf = Vec::new();
+    file.read_to_end(&mut buf).unwrap();
+    assert_eq!(buf, data);
+    drop(file);
+
+    // the same file cannot be opened in the encrypted modes
+    let error = SgxFile::open(path).err().unwrap();
+    assert_eq!(error.raw_sgx_error(), Some(SgxStatus::InvalidParameter));
+    let error = read_with_key(path, [0x42_u8; 16]).unwrap_err();
+    assert_eq!(error.raw_sgx_error(), Some(SgxStatus::InvalidParameter));
+
+    remove(path).unwrap();
+}
+
+#[test_case]
+fn integrity_only_detects_tampering() {
+    let path = Path::new("sgx_tprotected_fs_integrity_only_tamper");
+    let _ = remove(path);
+
+    let marker = b"integrity only marker ";
+    let data: Vec<u8> = marker.iter().copied().cycle().take(8000).collect();
+    let mut file = OpenOptions::new()
+        .write(true)
+        .encrypt(false)
+        .open(path)
+        .unwrap();
+    file.write_all(&data).unwrap();
+    drop(file);
+
+    // the contents are stored in the clear, flip a byte in the last data node
+    let mut raw = std::untrusted::fs::read(path).unwrap();
+    let pos = raw
+        .windows(marker.len())
+        .rposition(|window| window == marker)
+        .unwrap();
+    raw[pos] ^= 1;
+    std::untrusted::fs::write(path, &raw).unwrap();
+
+    assert!(read(path).is_err());
+    remove(path).unwrap();
+}
+
+#[test_case]
+fn encrypted_file_rejects_integrity_only_open() {
+    let path = Path::new("sgx_tprotected_fs_encrypted_mode_mismatch");
+    let _ = remove(path);
+
+    SgxFile::create(path).unwrap().write_all(b"secret").unwrap();
+    let error = OpenOptions::new()
+        .read(true)
+        .encrypt(false)
+        .open(path)
+        .err()
+        .unwrap();
+    assert_eq!(error.raw_sgx_error(), Some(SgxStatus::InvalidParameter));
+
+    // an explicit mode has to agree with the encrypt option
+    let error = OpenOptions::new()
+        .read(true)
+        .encrypt(false)
+        .open_with_key(path, [0x42_u8; 16])
+        .err()
+        .unwrap();
+    assert_eq!(error.raw_os_error(), Some(sgx_types::error::errno::EINVAL));
+
+    remove(path).unwrap();
+}
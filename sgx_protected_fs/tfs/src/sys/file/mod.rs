@@ -40,6 +40,10 @@ mod other;
 mod read;
 mod write;
 
+// upper bound for offsets and sizes that grow a file, holes below it are
+// zero-filled node by node
+const MAX_FILE_SIZE: u64 = 1 << 40;
+
 #[derive(Debug)]
 pub struct ProtectedFile {
     file: Mutex<FileInner>,
@@ -331,6 +335,7 @@ pub struct OpenOptions {
     pub append: bool,
     pub binary: bool,
     pub update: bool,
+    pub encrypt: bool,
 }
 
 #[allow(dead_code)]
@@ -342,6 +347,7 @@ impl OpenOptions {
             append: false,
             binary: false,
             update: false,
+            encrypt: true,
         }
     }
 
@@ -371,6 +377,11 @@ impl OpenOptions {
         self
     }
     #[inline]
+    pub fn encrypt(mut self, encrypt: bool) -> Self {
+        self.encrypt = encrypt;
+        self
+    }
+    #[inline]
     pub fn readonly(&self) -> bool {
         self.read && !self.update
     }
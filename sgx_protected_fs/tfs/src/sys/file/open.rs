@@ -132,6 +132,10 @@ impl FileInner {
         );
         ensure!(!metadata.update_flag(), esgx!(SgxStatus::RecoveryNeeded));
 
+        ensure!(
+            mode.is_integrity_only() == metadata.integrity_only(),
+            esgx!(SgxStatus::InvalidParameter)
+        );
         let encrypt_flags = mode.into();
         ensure!(encrypt_flags == metadata.encrypt_flags(), eos!(EINVAL));
 
@@ -225,6 +229,7 @@ impl FileInner {
 
         opts.check()?;
         mode.check()?;
+        ensure!(opts.encrypt || mode.is_integrity_only(), eos!(EINVAL));
 
         if let Some(key) = mode.import_key() {
             ensure!(key.ct_ne(&Key128bit::default()), eos!(EINVAL));
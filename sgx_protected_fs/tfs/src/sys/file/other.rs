@@ -16,7 +16,7 @@
 // under the License..
 
 use crate::sys::error::{FsError, FsResult};
-use crate::sys::file::{FileInner, FileStatus};
+use crate::sys::file::{FileInner, FileStatus, MAX_FILE_SIZE};
 use crate::sys::host;
 use crate::sys::metadata::FILENAME_MAX_LEN;
 use sgx_types::error::errno::*;
@@ -51,35 +51,33 @@ impl FileInner {
     pub fn seek(&mut self, pos: SeekFrom) -> FsResult<u64> {
         ensure!(self.status.is_ok(), esgx!(SgxStatus::BadStatus));
 
+        // writable handles may seek past the end, the hole is zero-filled on
+        // the next write
         let file_size = self.metadata.encrypted_plain.size as u64;
         let new_offset = match pos {
-            SeekFrom::Start(off) => {
-                if off <= file_size {
-                    Some(off)
-                } else {
-                    None
-                }
-            }
+            SeekFrom::Start(off) => Some(off),
             SeekFrom::End(off) => {
                 if off <= 0 {
                     file_size.checked_sub((0 - off) as u64)
                 } else {
-                    None
+                    file_size.checked_add(off as u64)
                 }
             }
             SeekFrom::Current(off) => {
                 let cur_offset = self.offset as u64;
                 if off >= 0 {
-                    match cur_offset.checked_add(off as u64) {
-                        Some(new_offset) if new_offset <= file_size => Some(new_offset),
-                        _ => None,
-                    }
+                    cur_offset.checked_add(off as u64)
                 } else {
                     cur_offset.checked_sub((0 - off) as u64)
                 }
             }
         }
         .ok_or(EINVAL)?;
+        if self.opts.readonly() {
+            ensure!(new_offset <= file_size, eos!(EINVAL));
+        } else {
+            ensure!(new_offset <= MAX_FILE_SIZE, eos!(EFBIG));
+        }
 
         self.offset = new_offset as usize;
         self.end_of_file = false;
@@ -87,6 +85,8 @@ impl FileInner {
     }
 
     pub fn set_len(&mut self, size: u64) -> FsResult {
+        ensure!(size <= MAX_FILE_SIZE, eos!(EFBIG));
+
         let new_size = size as usize;
         let mut cur_offset = self.offset;
         let file_size = self.metadata.encrypted_plain.size;
@@ -41,7 +41,7 @@ impl FileInner {
             return Ok(0);
         }
         let file_size = self.metadata.encrypted_plain.size;
-        if self.offset == file_size {
+        if self.offset >= file_size {
             self.end_of_file = true;
             return Ok(0);
         }
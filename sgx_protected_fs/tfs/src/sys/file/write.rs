@@ -16,10 +16,10 @@
 // under the License..
 
 use crate::sys::error::FsResult;
-use crate::sys::file::FileInner;
+use crate::sys::file::{FileInner, MAX_FILE_SIZE};
 use crate::sys::metadata::MD_USER_DATA_SIZE;
 use crate::sys::node::NODE_SIZE;
-use sgx_types::error::errno::EACCES;
+use sgx_types::error::errno::{EACCES, EFBIG};
 use sgx_types::error::SgxStatus;
 use std::io::SeekFrom;
 
@@ -42,6 +42,8 @@ impl FileInner {
 
         if self.opts.append {
             self.offset = self.metadata.encrypted_plain.size;
+        } else if self.offset > self.metadata.encrypted_plain.size {
+            self.fill_hole()?;
         }
 
         let mut left_to_write = buf.len();
@@ -106,10 +108,36 @@ impl FileInner {
         Ok(offset)
     }
 
+    // zero-fills the gap between the end of file and an offset that was
+    // seeked past it
+    fn fill_hole(&mut self) -> FsResult {
+        static ZEROS: [u8; 0x1000] = [0; 0x1000];
+
+        let end = self.offset;
+        self.offset = self.metadata.encrypted_plain.size;
+        while self.offset < end {
+            let len = (end - self.offset).min(ZEROS.len());
+            let error = match self.write(&ZEROS[..len]) {
+                Ok(n) if n == len => continue,
+                Ok(_) => self.get_last_error(),
+                Err(error) => error,
+            };
+            self.offset = end;
+            bail!(error);
+        }
+        Ok(())
+    }
+
     pub fn write_at(&mut self, buf: &[u8], offset: u64) -> FsResult<usize> {
         let cur_offset = self.offset;
         let file_size = self.metadata.encrypted_plain.size as u64;
 
+        ensure!(
+            offset
+                .checked_add(buf.len() as u64)
+                .map_or(false, |end| end <= MAX_FILE_SIZE),
+            eos!(EFBIG)
+        );
         if offset > file_size {
             self.seek(SeekFrom::End(0))?;
 
@@ -74,6 +74,14 @@ impl OpenOptions {
     pub fn binary(&mut self, binary: bool) {
         self.0.binary = binary;
     }
+    #[inline]
+    pub fn encrypt(&mut self, encrypt: bool) {
+        self.0.encrypt = encrypt;
+    }
+    #[inline]
+    pub fn is_encrypt(&self) -> bool {
+        self.0.encrypt
+    }
 
     #[allow(dead_code)]
     pub fn check(&self) -> Result<()> {
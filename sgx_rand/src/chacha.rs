@@ -17,8 +17,16 @@
 
 //! The ChaCha random number generator.
 
-use crate::{w32, Rand, Rng, SeedableRng};
+use crate::{w32, Rand, RdRand, Rng, SeedableRng};
+use std::io;
 use std::num::Wrapping as w;
+#[cfg(feature = "urand")]
+use std::sync::atomic;
+#[cfg(feature = "urand")]
+use std::{mem, ptr};
+
+#[cfg(feature = "unit_test")]
+mod tests;
 
 const KEY_WORDS: usize = 8; // 8 words for the 256-bit key
 const STATE_WORDS: usize = 16;
@@ -31,6 +39,11 @@ const CHACHA_ROUNDS: u32 = 20; // Cryptographically secure from 8 upwards as of
 /// verified as such. Prefer a generator like `OsRng` that defers to
 /// the operating system for cases that need high security.
 ///
+/// The generator holds no shared state and is only advanced through
+/// `&mut self`, so each thread should own its own instance. To reseed
+/// from hardware after a fixed amount of output, wrap it in a
+/// `ReseedingRng` with `ReseedWithRdRand`.
+///
 /// \[1\]: D. J. Bernstein, [*ChaCha, a variant of
 /// Salsa20*](http://cr.yp.to/chacha.html)
 #[derive(Copy, Clone, Debug)]
@@ -92,6 +105,22 @@ fn core(output: &mut [w32; STATE_WORDS], input: &[w32; STATE_WORDS]) {
 }
 
 impl ChaChaRng {
+    /// Create a ChaCha random number generator keyed from `RdRand`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use sgx_rand::{Rng, ChaChaRng};
+    /// use sgx_rand::reseeding::{ReseedingRng, ReseedWithRdRand};
+    ///
+    /// let mut rng = ReseedingRng::new(ChaChaRng::new().unwrap(), 1 << 20, ReseedWithRdRand);
+    /// let mut padding = [0u8; 4096];
+    /// rng.fill_bytes(&mut padding);
+    /// ```
+    pub fn new() -> io::Result<ChaChaRng> {
+        RdRand::new().map(|mut r| r.gen())
+    }
+
     /// Create an ChaCha random number generator using the default
     /// fixed key of 8 zero words.
     ///
@@ -215,8 +244,28 @@ impl Rng for ChaChaRng {
     }
 }
 
+// Volatile so that the old key and output block are actually cleared
+// before the generator is rekeyed. `T` must be plain integer data.
+pub(crate) fn zeroize<T: Copy>(val: &mut T) {
+    #[cfg(feature = "trand")]
+    {
+        sgx_trts::memory::volatile_zeroize_val(val);
+    }
+    #[cfg(feature = "urand")]
+    {
+        let bytes = val as *mut T as *mut u8;
+        for i in 0..mem::size_of::<T>() {
+            unsafe { ptr::write_volatile(bytes.add(i), 0) };
+        }
+        atomic::compiler_fence(atomic::Ordering::SeqCst);
+    }
+}
+
 impl<'a> SeedableRng<&'a [u32]> for ChaChaRng {
     fn reseed(&mut self, seed: &'a [u32]) {
+        // forget the previous key and any unread output
+        zeroize(&mut self.buffer);
+        zeroize(&mut self.state);
         // reset state
         self.init(&[0u32; KEY_WORDS]);
         // set key in place
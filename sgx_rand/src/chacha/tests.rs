@@ -0,0 +1,83 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License..
+
+use crate::reseeding::{ReseedWithRdRand, ReseedingRng};
+use crate::{ChaChaRng, Rng, SeedableRng};
+
+use sgx_test_utils::test_case;
+
+// RFC 8439, section 2.3.2: key 00..1f, counter 1, nonce 00000009 0000004a 00000000.
+#[test_case]
+fn rfc8439_block() {
+    let key = [
+        0x03020100, 0x07060504, 0x0b0a0908, 0x0f0e0d0c, 0x13121110, 0x17161514, 0x1b1a1918,
+        0x1f1e1d1c,
+    ];
+    let mut rng = ChaChaRng::from_seed(&key[..]);
+    rng.set_counter(0x0900_0000_0000_0001, 0x4a00_0000);
+
+    let block: [u32; 16] = std::array::from_fn(|_| rng.next_u32());
+    assert_eq!(
+        block,
+        [
+            0xe4e7f110, 0x15593bd1, 0x1fdd0f50, 0xc47120a3, 0xc7f4d1c7, 0x0368c033, 0x9aaa2204,
+            0x4e6cd4c3, 0x466482d2, 0x09aa9f07, 0x05d7c214, 0xa2028bd9, 0xd19c12b5, 0xb94e16de,
+            0xe883d0cb, 0x4e3c50a2,
+        ]
+    );
+}
+
+#[test_case]
+fn new_is_keyed_from_hardware() {
+    let mut a = ChaChaRng::new().unwrap();
+    let mut b = ChaChaRng::new().unwrap();
+    let mut unseeded = ChaChaRng::new_unseeded();
+
+    let first = a.next_u64();
+    assert_ne!(first, b.next_u64());
+    assert_ne!(first, unseeded.next_u64());
+}
+
+#[test_case]
+fn reseeds_after_threshold() {
+    let inner = ChaChaRng::new().unwrap();
+    let mut copy = inner;
+    let mut rng = ReseedingRng::new(inner, 64, ReseedWithRdRand);
+
+    let mut expected = [0_u8; 64];
+    let mut actual = [0_u8; 64];
+    copy.fill_bytes(&mut expected);
+    rng.fill_bytes(&mut actual);
+    assert_eq!(actual, expected);
+
+    copy.fill_bytes(&mut expected);
+    rng.fill_bytes(&mut actual);
+    assert_ne!(actual, expected);
+}
+
+#[test_case]
+fn reseed_clears_buffered_output() {
+    let mut rng = ChaChaRng::new().unwrap();
+    rng.next_u32();
+    assert!(rng.buffer.iter().any(|word| word.0 != 0));
+
+    rng.reseed(&[1, 2, 3][..]);
+    assert!(rng.buffer.iter().all(|word| word.0 == 0));
+    assert_eq!(rng.index, super::STATE_WORDS);
+    assert_eq!(rng.state[4].0, 1);
+    assert_eq!(rng.state[7].0, 0);
+}
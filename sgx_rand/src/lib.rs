@@ -66,6 +66,9 @@ pub mod reseeding;
 
 mod rand_impls;
 
+#[cfg(feature = "unit_test")]
+mod tests;
+
 #[allow(bad_style)]
 type w64 = w<u64>;
 #[allow(bad_style)]
@@ -20,7 +20,11 @@
 
 use std::default::Default;
 
-use crate::{Rng, SeedableRng};
+use crate::chacha::zeroize;
+use crate::{ChaChaRng, RdRand, Rng, SeedableRng};
+
+#[cfg(feature = "unit_test")]
+mod tests;
 
 /// How many bytes of entropy the underling RNG is allowed to generate
 /// before it is reseeded
@@ -54,14 +58,28 @@ impl<R: Rng, Rsdr: Reseeder<R>> ReseedingRng<R, Rsdr> {
         }
     }
 
+    /// Create a new `ReseedingRng` that reseeds `rng` with a default
+    /// `Rsdr` after every `generation_threshold` bytes.
+    pub fn with_threshold(rng: R, generation_threshold: u64) -> ReseedingRng<R, Rsdr>
+    where
+        Rsdr: Default,
+    {
+        ReseedingRng::new(rng, generation_threshold, Rsdr::default())
+    }
+
     /// Reseed the internal RNG if the number of bytes that have been
     /// generated exceed the threshold.
     pub fn reseed_if_necessary(&mut self) {
         if self.bytes_generated >= self.generation_threshold {
-            self.reseeder.reseed(&mut self.rng);
-            self.bytes_generated = 0;
+            self.reseed_now();
         }
     }
+
+    /// Reseed the internal RNG immediately and restart the byte count.
+    pub fn reseed_now(&mut self) {
+        self.reseeder.reseed(&mut self.rng);
+        self.bytes_generated = 0;
+    }
 }
 
 impl<R: Rng, Rsdr: Reseeder<R>> Rng for ReseedingRng<R, Rsdr> {
@@ -154,3 +172,24 @@ impl Default for ReseedWithDefault {
         ReseedWithDefault
     }
 }
+
+/// Reseed a `ChaChaRng` with a fresh key drawn from `RdRand`.
+///
+/// The previous key and any buffered output are zeroized first, so output
+/// produced before a reseed cannot be recovered from the new state.
+///
+/// Panics if the hardware generator is unavailable.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ReseedWithRdRand;
+
+impl Reseeder<ChaChaRng> for ReseedWithRdRand {
+    fn reseed(&mut self, rng: &mut ChaChaRng) {
+        let mut key = [0_u32; 8];
+        match RdRand::new() {
+            Ok(mut r) => key.iter_mut().for_each(|k| *k = r.next_u32()),
+            Err(e) => panic!("could not reseed ChaChaRng: {}", e),
+        }
+        rng.reseed(&key[..]);
+        zeroize(&mut key);
+    }
+}
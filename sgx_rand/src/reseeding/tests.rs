@@ -0,0 +1,41 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License..
+
+use super::*;
+
+use sgx_test_utils::test_case;
+
+#[test_case]
+fn forced_reseed_changes_output() {
+    let mut rng: ReseedingRng<ChaChaRng, ReseedWithRdRand> =
+        ReseedingRng::with_threshold(ChaChaRng::new().unwrap(), 1 << 20);
+
+    let mut before = [0_u8; 32];
+    rng.fill_bytes(&mut before);
+    assert_eq!(rng.bytes_generated, 32);
+
+    let mut stale = rng.rng;
+    rng.reseed_now();
+    assert_eq!(rng.bytes_generated, 0);
+
+    let mut after = [0_u8; 32];
+    let mut stale_after = [0_u8; 32];
+    rng.fill_bytes(&mut after);
+    stale.fill_bytes(&mut stale_after);
+    assert_ne!(after, before);
+    assert_ne!(after, stale_after);
+}
@@ -0,0 +1,76 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License..
+
+use crate::{ChaChaRng, Rng, SeedableRng};
+
+use sgx_test_utils::test_case;
+
+fn chi_square(counts: &[u64], expected: f64) -> f64 {
+    counts
+        .iter()
+        .map(|&c| {
+            let d = c as f64 - expected;
+            d * d / expected
+        })
+        .sum()
+}
+
+#[test_case]
+fn gen_range_is_uniform() {
+    const BUCKETS: u64 = 6;
+    const SAMPLES: u64 = 6000;
+
+    let mut rng = ChaChaRng::from_seed(&[305, 1, 2, 3][..]);
+    let mut counts = [0_u64; BUCKETS as usize];
+    for _ in 0..SAMPLES {
+        let n = rng.gen_range(0, BUCKETS);
+        counts[n as usize] += 1;
+    }
+    // 5 degrees of freedom, p = 0.001 is 20.52
+    let x2 = chi_square(&counts, (SAMPLES / BUCKETS) as f64);
+    assert!(x2 < 20.52, "chi-square {} for {:?}", x2, counts);
+}
+
+#[test_case]
+fn shuffle_is_uniform() {
+    const SAMPLES: u64 = 6000;
+    // the six orderings of [0, 1, 2], indexed by the first two elements
+    fn index(v: &[u8; 3]) -> usize {
+        match (v[0], v[1]) {
+            (0, 1) => 0,
+            (0, 2) => 1,
+            (1, 0) => 2,
+            (1, 2) => 3,
+            (2, 0) => 4,
+            (2, 1) => 5,
+            _ => unreachable!(),
+        }
+    }
+
+    let mut rng = ChaChaRng::from_seed(&[305, 4, 5, 6][..]);
+    let mut counts = [0_u64; 6];
+    for _ in 0..SAMPLES {
+        let mut v = [0_u8, 1, 2];
+        rng.shuffle(&mut v);
+        let mut sorted = v;
+        sorted.sort_unstable();
+        assert_eq!(sorted, [0, 1, 2]);
+        counts[index(&v)] += 1;
+    }
+    let x2 = chi_square(&counts, (SAMPLES / 6) as f64);
+    assert!(x2 < 20.52, "chi-square {} for {:?}", x2, counts);
+}
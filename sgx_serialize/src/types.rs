@@ -33,7 +33,7 @@ use sgx_types::types::{
     Rsa2048Key, Rsa2048Param, Rsa2048PrivKey, Rsa2048PubKey, Rsa2048Signature, Rsa3072Key,
     Rsa3072Param, Rsa3072PrivKey, Rsa3072PubKey, Rsa3072Signature, RsaKeyType, RsaResult,
 };
-use sgx_types::types::{Sha1Hash, Sha256Hash, Sha384Hash, Sm3Hash};
+use sgx_types::types::{Sha1Hash, Sha256Hash, Sha384Hash, Sha512Hash, Sm3Hash};
 
 impl Encodable for Sha1Hash {
     fn encode<S: Encoder>(&self, e: &mut S) -> Result<(), S::Error> {
@@ -92,6 +92,25 @@ impl Decodable for Sha384Hash {
     }
 }
 
+impl Encodable for Sha512Hash {
+    fn encode<S: Encoder>(&self, e: &mut S) -> Result<(), S::Error> {
+        let Sha512Hash { hash: ref _h } = *self;
+        e.emit_struct("Sha512Hash", 1usize, |e| -> _ {
+            e.emit_struct_field("hash", 0usize, |e| -> _ { Encodable::encode(&*_h, e) })
+        })
+    }
+}
+
+impl Decodable for Sha512Hash {
+    fn decode<D: Decoder>(d: &mut D) -> Result<Sha512Hash, D::Error> {
+        d.read_struct("Sha512Hash", 1usize, |d| -> _ {
+            Ok(Sha512Hash {
+                hash: d.read_struct_field("hash", 0usize, Decodable::decode)?,
+            })
+        })
+    }
+}
+
 impl Encodable for Sm3Hash {
     fn encode<S: Encoder>(&self, e: &mut S) -> Result<(), S::Error> {
         let Sm3Hash { hash: ref _h } = *self;
@@ -19,6 +19,7 @@ use crate::arch;
 use crate::enclave::parse;
 use crate::error;
 use crate::feature::SysFeatures;
+use crate::memory;
 use core::mem::{self, MaybeUninit};
 use core::ptr;
 use sgx_types::marker::ContiguousMemory;
@@ -376,6 +377,28 @@ pub fn is_within_host(p: *const u8, len: usize) -> bool {
     (start <= end) && ((end < base) || (start > base + MmLayout::elrange_size() - 1))
 }
 
+/// Checks that every `(ptr, len)` range lies inside the enclave.
+pub fn check_enclave_ptrs(ranges: &[(*const u8, usize)]) -> bool {
+    ranges.iter().all(|&(p, len)| is_within_enclave(p, len))
+}
+
+/// Checks that the `T` at `p` is properly aligned and lies inside the
+/// enclave.
+#[inline]
+pub fn is_within_enclave_val<T>(p: *const T) -> bool {
+    memory::is_aligned(p) && is_within_enclave(p as *const u8, mem::size_of::<T>())
+}
+
+/// Checks that `len` consecutive `T`s at `p` are properly aligned and lie
+/// inside the enclave, rejecting lengths whose byte size overflows.
+#[inline]
+pub fn is_within_enclave_slice<T>(p: *const T, len: usize) -> bool {
+    memory::is_aligned(p)
+        && len
+            .checked_mul(mem::size_of::<T>())
+            .map_or(false, |size| is_within_enclave(p as *const u8, size))
+}
+
 pub trait EnclaveRange {
     fn is_enclave_range(&self) -> bool;
     fn is_host_range(&self) -> bool;
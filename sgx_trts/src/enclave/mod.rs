@@ -28,5 +28,8 @@ pub mod state;
 
 pub use atexit::{at_exit, cleanup};
 pub use init::{ctors, global_init, rtinit};
-pub use mem::{is_within_enclave, is_within_host, EnclaveRange, MmLayout};
+pub use mem::{
+    check_enclave_ptrs, is_within_enclave, is_within_enclave_slice, is_within_enclave_val,
+    is_within_host, EnclaveRange, MmLayout,
+};
 pub use uninit::{global_exit, rtuninit, UNINIT_FLAG};
@@ -66,6 +66,7 @@ pub mod error;
 pub mod feature;
 pub mod fence;
 pub mod macros;
+pub mod memory;
 pub mod rand;
 pub mod se;
 pub mod sync;
@@ -0,0 +1,55 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License..
+
+//! Memory helpers for handling secrets.
+
+use core::mem;
+use core::ptr;
+use core::sync::atomic::{compiler_fence, Ordering};
+
+#[cfg(feature = "unit_test")]
+mod tests;
+
+/// Overwrites `buf` with zeros in a way the compiler will not elide, even
+/// when `buf` is dropped or goes out of scope right afterwards.
+///
+/// Each byte is written with a separate volatile store, so this costs one
+/// store per byte and should be kept off hot paths.
+#[inline(never)]
+pub fn volatile_zeroize(buf: &mut [u8]) {
+    for byte in buf.iter_mut() {
+        unsafe { ptr::write_volatile(byte, 0) };
+    }
+    compiler_fence(Ordering::SeqCst);
+}
+
+/// Overwrites the bytes of `val` with zeros, like [`volatile_zeroize`].
+///
+/// `T` must be a type for which all-zero bytes are a valid value, such as
+/// a plain key or buffer struct.
+#[inline(never)]
+pub fn volatile_zeroize_val<T: Copy>(val: &mut T) {
+    let bytes =
+        unsafe { core::slice::from_raw_parts_mut(val as *mut T as *mut u8, mem::size_of::<T>()) };
+    volatile_zeroize(bytes);
+}
+
+/// Returns whether `ptr` is aligned to `mem::align_of::<T>()`.
+#[inline]
+pub fn is_aligned<T>(ptr: *const T) -> bool {
+    (ptr as usize) & (mem::align_of::<T>() - 1) == 0
+}
@@ -0,0 +1,62 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License..
+
+use super::*;
+
+use sgx_test_utils::test_case;
+
+#[test_case]
+fn zeroize_slice() {
+    let mut buf = [0xa5_u8; 67];
+    volatile_zeroize(&mut buf);
+    assert!(buf.iter().all(|&b| b == 0));
+
+    volatile_zeroize(&mut []);
+}
+
+#[test_case]
+fn zeroize_val() {
+    let mut key = [0xff_u8; 16];
+    volatile_zeroize_val(&mut key);
+    assert_eq!(key, [0; 16]);
+
+    let mut pair = (u64::MAX, u32::MAX);
+    volatile_zeroize_val(&mut pair);
+    assert_eq!(pair, (0, 0));
+}
+
+#[test_case]
+fn aligned_pointers() {
+    let buf = [0_u64; 4];
+    let base = buf.as_ptr();
+    assert!(is_aligned(base));
+    assert!(is_aligned(unsafe { base.add(1) }));
+
+    for offset in 1..mem::size_of::<u64>() {
+        let p = unsafe { (base as *const u8).add(offset) } as *const u64;
+        assert!(!is_aligned(p));
+        assert!(!crate::trts::is_within_enclave_val(p));
+    }
+    assert!(crate::trts::is_within_enclave_val(base));
+
+    let p = unsafe { (base as *const u8).add(1) } as *const u64;
+    assert!(!crate::trts::is_within_enclave_slice(p, 2));
+    assert!(crate::trts::is_within_enclave_slice(base, 2));
+
+    let bytes = [0_u8; 2];
+    assert!(is_aligned(unsafe { bytes.as_ptr().add(1) }));
+}
@@ -17,31 +17,44 @@
 
 use crate::enclave::EnclaveRange;
 use crate::error;
-use core::cmp;
 use core::fmt;
 use core::mem;
 use sgx_types::error::{SgxResult, SgxStatus};
 
+#[cfg(feature = "unit_test")]
+mod tests;
+
 pub fn rand(buf: &mut [u8]) -> SgxResult {
+    fill_bytes(buf)
+}
+
+/// Fills `buf` from RDRAND, one 64-bit word at a time.
+///
+/// Each word is retried up to 10 times before giving up with
+/// `SgxStatus::Unexpected`.
+pub fn fill_bytes(buf: &mut [u8]) -> SgxResult {
     ensure!(
         buf.is_enclave_range() || buf.is_host_range(),
         SgxStatus::InvalidParameter
     );
 
-    let mut left_len = buf.len();
-    let mut offset = 0_usize;
-
-    while left_len > 0 {
-        let rand_num = rand32()?.to_ne_bytes();
-        let copy_len = cmp::min(left_len, mem::size_of::<u32>());
-        buf[offset..offset + copy_len].copy_from_slice(&rand_num[..copy_len]);
-
-        left_len -= copy_len;
-        offset += copy_len;
+    for chunk in buf.chunks_mut(mem::size_of::<u64>()) {
+        let rand_num = rand64()?.to_ne_bytes();
+        chunk.copy_from_slice(&rand_num[..chunk.len()]);
     }
     Ok(())
 }
 
+#[inline]
+pub fn rand_u32() -> SgxResult<u32> {
+    rand32()
+}
+
+#[inline]
+pub fn rand_u64() -> SgxResult<u64> {
+    rand64()
+}
+
 #[cfg(feature = "sim")]
 #[inline]
 fn rand32() -> SgxResult<u32> {
@@ -58,10 +71,48 @@ fn rand32() -> SgxResult<u32> {
     rdrand()
 }
 
+#[cfg(feature = "sim")]
 #[inline]
-fn rdrand() -> SgxResult<u32> {
-    const RDRAND_RETRY_TIMES: usize = 10;
+fn rand64() -> SgxResult<u64> {
+    if is_x86_feature_detected!("rdrand") {
+        rdrand64()
+    } else {
+        Ok(((rand_lcg() as u64) << 32) | rand_lcg() as u64)
+    }
+}
+
+#[cfg(not(feature = "sim"))]
+#[inline]
+fn rand64() -> SgxResult<u64> {
+    rdrand64()
+}
 
+#[cfg(target_arch = "x86_64")]
+#[inline]
+fn rdrand64() -> SgxResult<u64> {
+    use core::arch::x86_64::_rdrand64_step;
+
+    unsafe {
+        let mut ret = 0_u64;
+        for _in in 0..RDRAND_RETRY_TIMES {
+            if _rdrand64_step(&mut ret) == 1 {
+                return Ok(ret);
+            }
+        }
+        Err(SgxStatus::Unexpected)
+    }
+}
+
+#[cfg(target_arch = "x86")]
+#[inline]
+fn rdrand64() -> SgxResult<u64> {
+    Ok(((rdrand()? as u64) << 32) | rdrand()? as u64)
+}
+
+const RDRAND_RETRY_TIMES: usize = 10;
+
+#[inline]
+fn rdrand() -> SgxResult<u32> {
     unsafe {
         #[cfg(target_arch = "x86")]
         use core::arch::x86::_rdrand32_step;
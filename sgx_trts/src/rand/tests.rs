@@ -0,0 +1,47 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License..
+
+use super::*;
+
+use sgx_test_utils::test_case;
+
+#[test_case]
+fn fill_bytes_odd_lengths() {
+    for len in [1, 7, 8, 9, 63, 1024] {
+        let mut a = [0_u8; 1024];
+        let mut b = [0_u8; 1024];
+        fill_bytes(&mut a[..len]).unwrap();
+        fill_bytes(&mut b[..len]).unwrap();
+        assert!(a[len..].iter().all(|&x| x == 0));
+        if len >= 8 {
+            assert!(a[..len].iter().any(|&x| x != 0));
+            assert_ne!(a[..len], b[..len]);
+        }
+    }
+    fill_bytes(&mut []).unwrap();
+}
+
+#[test_case]
+fn rand_words_differ() {
+    assert_ne!(rand_u64().unwrap(), rand_u64().unwrap());
+    let words = [
+        rand_u32().unwrap(),
+        rand_u32().unwrap(),
+        rand_u32().unwrap(),
+    ];
+    assert!(words[0] != words[1] || words[1] != words[2]);
+}
@@ -21,7 +21,10 @@ use sgx_types::types::EnclaveMode;
 pub use crate::call::OcBuffer;
 pub use crate::enclave::at_exit;
 pub use crate::enclave::MmLayout;
-pub use crate::enclave::{is_within_enclave, is_within_host, EnclaveRange};
+pub use crate::enclave::{
+    check_enclave_ptrs, is_within_enclave, is_within_enclave_slice, is_within_enclave_val,
+    is_within_host, EnclaveRange,
+};
 pub use crate::error::abort;
 pub use crate::feature::Version;
 
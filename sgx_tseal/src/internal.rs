@@ -28,7 +28,8 @@ use sgx_trts::trts::{is_within_enclave, is_within_host, EnclaveRange};
 use sgx_tse::{EnclaveKey, EnclaveReport};
 use sgx_types::error::{SgxResult, SgxStatus};
 use sgx_types::types::{
-    Attributes, AttributesFlags, CSealedData, KeyId, KeyName, KeyPolicy, KeyRequest, Report,
+    Attributes, AttributesFlags, CSealedData, Key128bit, KeyId, KeyName, KeyPolicy, KeyRequest,
+    Report,
 };
 use sgx_types::types::{SEAL_TAG_SIZE, TSEAL_DEFAULT_MISCMASK};
 
@@ -120,6 +121,38 @@ impl InnerSealedData {
     }
 
     pub fn from_slice(raw: &[u8]) -> SgxResult<InnerSealedData> {
+        let (raw_data, ciphertext, aad) = Self::split_raw(raw)?;
+
+        Ok(InnerSealedData {
+            key_request: raw_data.key_request,
+            payload: Payload {
+                len: raw_data.aes_data.payload_size,
+                tag: raw_data.aes_data.payload_tag,
+                ciphertext: Box::from(ciphertext),
+                aad: Box::from(aad),
+            },
+        })
+    }
+
+    pub fn peek_aad(raw: &[u8]) -> SgxResult<Vec<u8>> {
+        let (raw_data, ciphertext, aad) = Self::split_raw(raw)?;
+
+        let key_request = raw_data.key_request;
+        let mut key = key_request.get_align_key().map_err(|e| match e {
+            SgxStatus::InvalidCpusvn | SgxStatus::InvalidIsvsvn | SgxStatus::OutOfMemory => e,
+            _ => SgxStatus::MacMismatch,
+        })?;
+
+        lfence();
+
+        let result = Self::verify_tag(&key.key, ciphertext, aad, &raw_data.aes_data.payload_tag);
+        key.as_mut().fill(0);
+        result?;
+
+        Ok(aad.to_vec())
+    }
+
+    fn split_raw(raw: &[u8]) -> SgxResult<(&CSealedData, &[u8], &[u8])> {
         ensure!(raw.is_enclave_range(), SgxStatus::InvalidParameter);
         ensure!(
             raw.len() >= mem::size_of::<CSealedData>(),
@@ -144,19 +177,8 @@ impl InnerSealedData {
         ensure!(raw.len() == raw_len as usize, SgxStatus::InvalidParameter);
 
         let header_len = mem::size_of::<CSealedData>();
-        let ciphertext: Box<[u8]> =
-            Box::from(&raw[header_len..header_len + ciphertext_len as usize]);
-        let aad: Box<[u8]> = Box::from(&raw[header_len + ciphertext_len as usize..]);
-
-        Ok(InnerSealedData {
-            key_request: raw_data.key_request,
-            payload: Payload {
-                len: raw_data.aes_data.payload_size,
-                tag: raw_data.aes_data.payload_tag,
-                ciphertext,
-                aad,
-            },
-        })
+        let (ciphertext, aad) = raw[header_len..].split_at(ciphertext_len as usize);
+        Ok((raw_data, ciphertext, aad))
     }
 
     pub fn aad_len(&self) -> u32 {
@@ -406,6 +428,31 @@ impl InnerSealedData {
         })
     }
 
+    // Runs the ciphertext through GCM in small pieces so the tag can be
+    // checked without keeping any of the plaintext around.
+    fn verify_tag(
+        key: &Key128bit,
+        ciphertext: &[u8],
+        aad: &[u8],
+        tag: &[u8; SEAL_TAG_SIZE],
+    ) -> SgxResult {
+        const SCRATCH_SIZE: usize = 4096;
+
+        let mut aes = AesGcm::new(key, Nonce::zeroed(), Aad::from(aad))?;
+        if ciphertext.is_empty() {
+            return aes.verify_mac(tag);
+        }
+
+        let mut scratch = vec![0_u8; ciphertext.len().min(SCRATCH_SIZE)];
+        for chunk in ciphertext.chunks(scratch.len()) {
+            let out = &mut scratch[..chunk.len()];
+            let result = unsafe { aes.dec_update(chunk, out) };
+            out.fill(0);
+            result?;
+        }
+        aes.dec_verify_mac(tag)
+    }
+
     fn mac_data_helper(aad: &[u8], key_request: &KeyRequest) -> SgxResult<InnerSealedData> {
         let mut key = key_request.get_align_key()?;
         let mut aes = AesGcm::new(&key.key, Nonce::zeroed(), Aad::from(aad))?;
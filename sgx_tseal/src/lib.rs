@@ -33,6 +33,7 @@ extern crate sgx_serialize;
 
 pub mod aad;
 pub mod seal;
+pub mod stream;
 
 mod internal;
 
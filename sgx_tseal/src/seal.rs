@@ -27,11 +27,14 @@ use core::slice;
 use sgx_trts::trts::{is_within_enclave, is_within_host, EnclaveRange};
 use sgx_types::error::{SgxResult, SgxStatus};
 use sgx_types::marker::ContiguousMemory;
-use sgx_types::types::{Attributes, KeyPolicy, Mac};
+use sgx_types::types::{Attributes, AttributesFlags, KeyPolicy, Mac, TSEAL_DEFAULT_MISCMASK};
 
 #[cfg(feature = "serialize")]
 use sgx_serialize::{Deserialize, Serialize};
 
+#[cfg(feature = "unit_test")]
+mod tests;
+
 /// The structure about the sealed data.
 #[derive(Clone, Debug, Default)]
 #[cfg_attr(feature = "serialize", derive(Deserialize, Serialize))]
@@ -70,6 +73,64 @@ impl<T: ContiguousMemory + ?Sized> SealedData<T> {
             })
     }
 
+    /// Seals `data` under `key_policy` with the default attribute and misc
+    /// masks. `aad` is authenticated but stored in the clear; its length is
+    /// kept in the sealed header and `unseal` hands it back through
+    /// `UnsealedData::to_aad`, failing with `MacMismatch` if it was altered.
+    pub fn seal_with_aad(data: &T, aad: &[u8], key_policy: KeyPolicy) -> SgxResult<SealedData<T>> {
+        let attribute_mask = Attributes {
+            flags: AttributesFlags::DEFAULT_MASK,
+            xfrm: 0,
+        };
+        Self::seal_with_key_policy(
+            key_policy,
+            attribute_mask,
+            TSEAL_DEFAULT_MISCMASK,
+            data,
+            Some(aad),
+        )
+    }
+
+    /// Unseals `blob` with the current enclave's key and seals the plaintext
+    /// again under `new_policy`, keeping the AAD and the original attribute
+    /// and misc masks. The intermediate plaintext is cleared before returning.
+    pub fn reseal(blob: &[u8], new_policy: KeyPolicy) -> SgxResult<Vec<u8>> {
+        let sealed_data = InnerSealedData::from_slice(blob)?;
+        let mut unsealed_data = sealed_data.unseal()?;
+
+        let aad = if unsealed_data.aad.is_empty() {
+            None
+        } else {
+            Some(unsealed_data.aad.as_ref())
+        };
+        let result = InnerSealedData::seal_with_key_policy(
+            new_policy,
+            sealed_data.key_request.attribute_mask,
+            sealed_data.key_request.misc_mask,
+            &unsealed_data.plaintext,
+            aad,
+        )
+        .and_then(|inner| inner.into_bytes());
+
+        unsealed_data.plaintext.fill(0);
+        result
+    }
+
+    /// Returns the AAD of a sealed blob once its tag has been verified. The
+    /// ciphertext is authenticated but none of the plaintext is kept, and
+    /// the blob is not copied, so this stays cheap on large payloads.
+    #[inline]
+    pub fn peek_aad(blob: &[u8]) -> SgxResult<Vec<u8>> {
+        InnerSealedData::peek_aad(blob)
+    }
+
+    /// Exact size of the sealed blob for the given AAD and payload lengths,
+    /// as written by `to_bytes`, or `None` if it does not fit in a `u32`.
+    #[inline]
+    pub fn calc_sealed_size(aad_len: u32, payload_len: u32) -> Option<u32> {
+        InnerSealedData::raw_sealed_data_size(aad_len, payload_len)
+    }
+
     #[inline]
     pub fn into_bytes(self) -> SgxResult<Vec<u8>> {
         self.inner.into_bytes()
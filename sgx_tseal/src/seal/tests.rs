@@ -0,0 +1,174 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License..
+use super::{SealedData, UnsealedData};
+use core::mem;
+use sgx_test_utils::test_case;
+use sgx_types::error::SgxStatus;
+use sgx_types::types::{CSealedData, KeyPolicy};
+
+const HEADER_LEN: u32 = mem::size_of::<CSealedData>() as u32;
+
+#[test_case]
+fn calc_sealed_size_adds_header() {
+    assert_eq!(SealedData::<[u8]>::calc_sealed_size(0, 0), Some(HEADER_LEN));
+    assert_eq!(
+        SealedData::<[u8]>::calc_sealed_size(7, 33),
+        Some(HEADER_LEN + 40)
+    );
+}
+
+#[test_case]
+fn calc_sealed_size_overflow() {
+    let max = u32::MAX;
+    assert_eq!(SealedData::<[u8]>::calc_sealed_size(max, 1), None);
+    assert_eq!(SealedData::<[u8]>::calc_sealed_size(1, max), None);
+    assert_eq!(SealedData::<[u8]>::calc_sealed_size(max, max), None);
+    assert_eq!(
+        SealedData::<[u8]>::calc_sealed_size(0, max - HEADER_LEN),
+        Some(max)
+    );
+    assert_eq!(
+        SealedData::<[u8]>::calc_sealed_size(max - HEADER_LEN, 0),
+        Some(max)
+    );
+    assert_eq!(
+        SealedData::<[u8]>::calc_sealed_size(0, max - HEADER_LEN + 1),
+        None
+    );
+    assert_eq!(
+        SealedData::<[u8]>::calc_sealed_size(1, max - HEADER_LEN),
+        None
+    );
+}
+
+#[test_case]
+fn calc_sealed_size_matches_to_bytes() {
+    let data = [0x42_u8; 37];
+    let aad = [0x24_u8; 11];
+    let sealed = SealedData::<[u8]>::seal(&data, Some(&aad)).unwrap();
+    let bytes = sealed.to_bytes().unwrap();
+    assert_eq!(
+        SealedData::<[u8]>::calc_sealed_size(aad.len() as u32, data.len() as u32),
+        Some(bytes.len() as u32)
+    );
+}
+
+#[test_case]
+fn reseal_mrenclave_to_mrsigner() {
+    let data = [0x5a_u8; 48];
+    let aad = b"reseal aad";
+    let sealed = SealedData::<[u8]>::seal_with_aad(&data, aad, KeyPolicy::MRENCLAVE).unwrap();
+    assert!(sealed
+        .inner
+        .key_request
+        .key_policy
+        .contains(KeyPolicy::MRENCLAVE));
+    let blob = sealed.to_bytes().unwrap();
+
+    let resealed = SealedData::<[u8]>::reseal(&blob, KeyPolicy::MRSIGNER).unwrap();
+    let sealed = SealedData::<[u8]>::from_slice(&resealed).unwrap();
+    assert_eq!(sealed.inner.key_request.key_policy, KeyPolicy::MRSIGNER);
+
+    let unsealed = sealed.unseal().unwrap();
+    assert_eq!(unsealed.to_plaintext(), &data[..]);
+    assert_eq!(unsealed.to_aad(), &aad[..]);
+}
+
+#[test_case]
+fn reseal_rejects_bad_blob() {
+    let data = [0xa5_u8; 16];
+    let mut blob = SealedData::<[u8]>::seal(&data, None)
+        .unwrap()
+        .to_bytes()
+        .unwrap();
+
+    let last = blob.len() - 1;
+    blob[last] ^= 1;
+    assert_eq!(
+        SealedData::<[u8]>::reseal(&blob, KeyPolicy::MRSIGNER),
+        Err(SgxStatus::MacMismatch)
+    );
+    assert!(SealedData::<[u8]>::reseal(&blob[..HEADER_LEN as usize], KeyPolicy::MRSIGNER).is_err());
+}
+
+#[test_case]
+fn peek_aad_verifies_tag() {
+    let data = [0x11_u8; 5000];
+    let aad = b"record index";
+    let blob = SealedData::<[u8]>::seal(&data, Some(aad))
+        .unwrap()
+        .to_bytes()
+        .unwrap();
+    assert_eq!(SealedData::<[u8]>::peek_aad(&blob).unwrap(), &aad[..]);
+
+    let mut bad = blob.clone();
+    bad[HEADER_LEN as usize + 4096] ^= 1;
+    assert_eq!(
+        SealedData::<[u8]>::peek_aad(&bad),
+        Err(SgxStatus::MacMismatch)
+    );
+
+    let mut bad = blob.clone();
+    let last = bad.len() - 1;
+    bad[last] ^= 1;
+    assert_eq!(
+        SealedData::<[u8]>::peek_aad(&bad),
+        Err(SgxStatus::MacMismatch)
+    );
+
+    let empty = SealedData::<[u8]>::seal(&data, None)
+        .unwrap()
+        .to_bytes()
+        .unwrap();
+    assert!(SealedData::<[u8]>::peek_aad(&empty).unwrap().is_empty());
+    assert_eq!(
+        SealedData::<[u8]>::peek_aad(&blob[..blob.len() - 1]),
+        Err(SgxStatus::InvalidParameter)
+    );
+}
+
+#[test_case]
+fn seal_with_aad_round_trip() {
+    let data = [0x3c_u8; 64];
+    let aad = b"seal_with_aad";
+    let sealed = SealedData::<[u8]>::seal_with_aad(&data, aad, KeyPolicy::MRSIGNER).unwrap();
+    assert_eq!(sealed.inner.key_request.key_policy, KeyPolicy::MRSIGNER);
+    let blob = sealed.to_bytes().unwrap();
+
+    let unsealed = UnsealedData::<[u8]>::unseal_from_slice(&blob).unwrap();
+    assert_eq!(unsealed.to_plaintext(), &data[..]);
+    assert_eq!(unsealed.to_aad(), &aad[..]);
+}
+
+#[test_case]
+fn seal_with_aad_rejects_tampered_aad() {
+    let data = [0x3c_u8; 64];
+    let aad = b"seal_with_aad";
+    let mut blob = SealedData::<[u8]>::seal_with_aad(&data, aad, KeyPolicy::MRENCLAVE)
+        .unwrap()
+        .to_bytes()
+        .unwrap();
+
+    // The AAD is stored in the clear after the ciphertext.
+    let last = blob.len() - 1;
+    assert_eq!(blob[last], aad[aad.len() - 1]);
+    blob[last] ^= 1;
+    assert_eq!(
+        UnsealedData::<[u8]>::unseal_from_slice(&blob).err(),
+        Some(SgxStatus::MacMismatch)
+    );
+}
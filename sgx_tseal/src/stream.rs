@@ -0,0 +1,368 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License..
+
+//! Chunked sealing for payloads that do not fit in the enclave heap.
+//!
+//! A stream is a fixed-size header followed by a sequence of AES-GCM chunks:
+//!
+//! ```text
+//! header: version (1) | reserved (3) | chunk_size (u32 LE) | KeyRequest (512)
+//! chunk:  ciphertext (chunk_size bytes, fewer for the last one) | tag (16)
+//! ```
+//!
+//! Every chunk is encrypted with the key derived from the header's
+//! `KeyRequest`, the header as AAD, and a nonce made of the chunk index and
+//! a last-chunk flag, so chunks cannot be reordered, dropped or mixed between
+//! streams. The last chunk is always shorter than `chunk_size` (possibly
+//! empty), and a stream that ends without it is rejected.
+//!
+//! Single-shot blobs produced by [`SealedData`] keep their own layout and are
+//! still read through [`SealedData::from_bytes`] and `unseal`. They begin
+//! with a `KeyRequest` whose key name is `KeyName::Seal` (4), so they never
+//! carry a valid stream version in their first byte.
+//!
+//! [`SealedData`]: crate::seal::SealedData
+//! [`SealedData::from_bytes`]: crate::seal::SealedData::from_bytes
+
+use alloc::vec::Vec;
+use core::alloc::Allocator;
+use core::mem;
+use core::ptr;
+use sgx_crypto::aes::gcm::{Aad, AesGcm, Nonce};
+use sgx_trts::rand::rand;
+use sgx_tse::{EnclaveKey, EnclaveReport};
+use sgx_types::error::{SgxResult, SgxStatus};
+use sgx_types::types::{
+    AlignKey128bit, Attributes, AttributesFlags, KeyId, KeyName, KeyPolicy, KeyRequest, Mac,
+    Report, AESGCM_IV_SIZE, SEAL_TAG_SIZE, TSEAL_DEFAULT_MISCMASK,
+};
+
+#[cfg(feature = "unit_test")]
+mod tests;
+
+pub const STREAM_FORMAT_VERSION: u8 = 1;
+pub const STREAM_HEADER_SIZE: usize = 8 + mem::size_of::<KeyRequest>();
+pub const DEFAULT_CHUNK_SIZE: u32 = 64 * 1024;
+pub const MAX_CHUNK_SIZE: u32 = 16 * 1024 * 1024;
+
+/// Where a [`SealedReader`] pulls sealed bytes from. `read` returns the
+/// number of bytes written into `buf`, and 0 only at the end of the stream.
+pub trait Source {
+    fn read(&mut self, buf: &mut [u8]) -> SgxResult<usize>;
+}
+
+/// Where a [`SealedWriter`] pushes sealed bytes to.
+pub trait Sink {
+    fn write_all(&mut self, buf: &[u8]) -> SgxResult;
+}
+
+impl Source for &[u8] {
+    fn read(&mut self, buf: &mut [u8]) -> SgxResult<usize> {
+        let n = buf.len().min(self.len());
+        let (head, tail) = self.split_at(n);
+        buf[..n].copy_from_slice(head);
+        *self = tail;
+        Ok(n)
+    }
+}
+
+impl<R: Source + ?Sized> Source for &mut R {
+    #[inline]
+    fn read(&mut self, buf: &mut [u8]) -> SgxResult<usize> {
+        (**self).read(buf)
+    }
+}
+
+impl<A: Allocator> Sink for Vec<u8, A> {
+    #[inline]
+    fn write_all(&mut self, buf: &[u8]) -> SgxResult {
+        self.extend_from_slice(buf);
+        Ok(())
+    }
+}
+
+impl<W: Sink + ?Sized> Sink for &mut W {
+    #[inline]
+    fn write_all(&mut self, buf: &[u8]) -> SgxResult {
+        (**self).write_all(buf)
+    }
+}
+
+/// Seals a stream chunk by chunk. Nothing is readable until [`finish`]
+/// writes the last chunk.
+///
+/// [`finish`]: SealedWriter::finish
+pub struct SealedWriter<W: Sink> {
+    sink: W,
+    key: AlignKey128bit,
+    header: [u8; STREAM_HEADER_SIZE],
+    chunk_size: usize,
+    index: u64,
+    plaintext: Vec<u8>,
+    ciphertext: Vec<u8>,
+}
+
+impl<W: Sink> SealedWriter<W> {
+    #[inline]
+    pub fn new(sink: W, key_policy: KeyPolicy) -> SgxResult<SealedWriter<W>> {
+        Self::with_chunk_size(sink, key_policy, DEFAULT_CHUNK_SIZE)
+    }
+
+    /// Derives a fresh seal key for `key_policy` and writes the stream
+    /// header to `sink`.
+    pub fn with_chunk_size(
+        mut sink: W,
+        key_policy: KeyPolicy,
+        chunk_size: u32,
+    ) -> SgxResult<SealedWriter<W>> {
+        ensure!(
+            (1..=MAX_CHUNK_SIZE).contains(&chunk_size),
+            SgxStatus::InvalidParameter
+        );
+        ensure!(key_policy.is_valid(), SgxStatus::InvalidParameter);
+        ensure!(
+            key_policy.intersects(KeyPolicy::MRENCLAVE | KeyPolicy::MRSIGNER),
+            SgxStatus::InvalidParameter
+        );
+
+        let mut key_id = KeyId::default();
+        rand(key_id.as_mut())?;
+        let report = Report::get_self();
+        let key_request = KeyRequest {
+            key_name: KeyName::Seal,
+            key_policy,
+            isv_svn: report.body.isv_svn,
+            cpu_svn: report.body.cpu_svn,
+            attribute_mask: Attributes {
+                flags: AttributesFlags::DEFAULT_MASK,
+                xfrm: 0,
+            },
+            key_id,
+            misc_mask: TSEAL_DEFAULT_MISCMASK,
+            config_svn: report.body.config_svn,
+            ..Default::default()
+        };
+        key_id.as_mut().fill(0);
+
+        let key = key_request.get_align_key()?;
+
+        let mut header = [0_u8; STREAM_HEADER_SIZE];
+        header[0] = STREAM_FORMAT_VERSION;
+        header[4..8].copy_from_slice(&chunk_size.to_le_bytes());
+        header[8..].copy_from_slice(key_request.as_ref());
+        sink.write_all(&header)?;
+
+        Ok(SealedWriter {
+            sink,
+            key,
+            header,
+            chunk_size: chunk_size as usize,
+            index: 0,
+            plaintext: Vec::with_capacity(chunk_size as usize),
+            ciphertext: Vec::with_capacity(chunk_size as usize),
+        })
+    }
+
+    pub fn write(&mut self, mut data: &[u8]) -> SgxResult {
+        while !data.is_empty() {
+            let n = (self.chunk_size - self.plaintext.len()).min(data.len());
+            self.plaintext.extend_from_slice(&data[..n]);
+            data = &data[n..];
+
+            if self.plaintext.len() == self.chunk_size {
+                self.seal_chunk(false)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Seals the buffered tail as the last chunk. A stream whose writer is
+    /// dropped without calling this cannot be read back.
+    pub fn finish(mut self) -> SgxResult {
+        self.seal_chunk(true)
+    }
+
+    fn seal_chunk(&mut self, last: bool) -> SgxResult {
+        let mut aes = AesGcm::new(
+            &self.key.key,
+            chunk_nonce(self.index, last),
+            Aad::from(&self.header),
+        )?;
+
+        self.ciphertext.resize(self.plaintext.len(), 0);
+        let result = aes.encrypt(&self.plaintext, &mut self.ciphertext);
+        self.plaintext.fill(0);
+        self.plaintext.clear();
+        let tag = result?;
+
+        self.sink.write_all(&self.ciphertext)?;
+        self.sink.write_all(&tag)?;
+        self.index = self.index.checked_add(1).ok_or(SgxStatus::Unexpected)?;
+        Ok(())
+    }
+}
+
+impl<W: Sink> Drop for SealedWriter<W> {
+    fn drop(&mut self) {
+        self.key.key.fill(0);
+        self.plaintext.fill(0);
+    }
+}
+
+/// Unseals a stream chunk by chunk. Plaintext is only handed out once the
+/// tag of the chunk that holds it has been verified.
+pub struct SealedReader<R: Source> {
+    source: R,
+    key: AlignKey128bit,
+    header: [u8; STREAM_HEADER_SIZE],
+    chunk_size: usize,
+    index: u64,
+    chunk: Vec<u8>,
+    plaintext: Vec<u8>,
+    pos: usize,
+    finished: bool,
+    poisoned: bool,
+}
+
+impl<R: Source> SealedReader<R> {
+    /// Parses the first [`STREAM_HEADER_SIZE`] bytes of a stream and derives
+    /// its key. The chunks that follow are pulled from `source`.
+    pub fn open(blob_header: &[u8], source: R) -> SgxResult<SealedReader<R>> {
+        ensure!(
+            blob_header.len() == STREAM_HEADER_SIZE,
+            SgxStatus::InvalidParameter
+        );
+        ensure!(
+            blob_header[0] == STREAM_FORMAT_VERSION,
+            SgxStatus::InvalidVersion
+        );
+        ensure!(blob_header[1..4] == [0_u8; 3], SgxStatus::InvalidParameter);
+
+        let chunk_size = u32::from_le_bytes(blob_header[4..8].try_into().unwrap());
+        ensure!(
+            (1..=MAX_CHUNK_SIZE).contains(&chunk_size),
+            SgxStatus::InvalidParameter
+        );
+
+        let raw_request = &blob_header[8..];
+        ensure!(
+            raw_request[..2] == (KeyName::Seal as u16).to_le_bytes(),
+            SgxStatus::InvalidParameter
+        );
+        let key_request = unsafe { ptr::read_unaligned(raw_request.as_ptr() as *const KeyRequest) };
+        ensure!(
+            key_request.key_policy.is_valid(),
+            SgxStatus::InvalidParameter
+        );
+
+        let key = key_request.get_align_key().map_err(|e| match e {
+            SgxStatus::InvalidCpusvn | SgxStatus::InvalidIsvsvn | SgxStatus::OutOfMemory => e,
+            _ => SgxStatus::MacMismatch,
+        })?;
+
+        let mut header = [0_u8; STREAM_HEADER_SIZE];
+        header.copy_from_slice(blob_header);
+
+        Ok(SealedReader {
+            source,
+            key,
+            header,
+            chunk_size: chunk_size as usize,
+            index: 0,
+            chunk: Vec::with_capacity(chunk_size as usize + SEAL_TAG_SIZE),
+            plaintext: Vec::with_capacity(chunk_size as usize),
+            pos: 0,
+            finished: false,
+            poisoned: false,
+        })
+    }
+
+    /// Copies verified plaintext into `buf`, returning 0 once the last chunk
+    /// has been consumed. After an error every further call fails with
+    /// `InvalidState`.
+    pub fn read(&mut self, buf: &mut [u8]) -> SgxResult<usize> {
+        ensure!(!self.poisoned, SgxStatus::InvalidState);
+        if buf.is_empty() {
+            return Ok(0);
+        }
+
+        while self.pos == self.plaintext.len() {
+            if self.finished {
+                return Ok(0);
+            }
+            if let Err(e) = self.unseal_chunk() {
+                self.poisoned = true;
+                return Err(e);
+            }
+        }
+
+        let n = buf.len().min(self.plaintext.len() - self.pos);
+        buf[..n].copy_from_slice(&self.plaintext[self.pos..self.pos + n]);
+        self.pos += n;
+        Ok(n)
+    }
+
+    fn unseal_chunk(&mut self) -> SgxResult {
+        self.chunk.resize(self.chunk_size + SEAL_TAG_SIZE, 0);
+        let mut len = 0;
+        while len < self.chunk.len() {
+            let n = self.source.read(&mut self.chunk[len..])?;
+            if n == 0 {
+                break;
+            }
+            len += n;
+        }
+        ensure!(len >= SEAL_TAG_SIZE, SgxStatus::InvalidParameter);
+
+        let last = len < self.chunk.len();
+        let (ciphertext, tag) = self.chunk[..len].split_at(len - SEAL_TAG_SIZE);
+        let tag: Mac = tag.try_into().unwrap();
+
+        self.plaintext.fill(0);
+        self.plaintext.resize(ciphertext.len(), 0);
+        self.pos = 0;
+
+        let mut aes = AesGcm::new(
+            &self.key.key,
+            chunk_nonce(self.index, last),
+            Aad::from(&self.header),
+        )?;
+        if let Err(e) = aes.decrypt(ciphertext, &mut self.plaintext, &tag) {
+            self.plaintext.fill(0);
+            self.plaintext.clear();
+            return Err(e);
+        }
+
+        self.index = self.index.checked_add(1).ok_or(SgxStatus::Unexpected)?;
+        self.finished = last;
+        Ok(())
+    }
+}
+
+impl<R: Source> Drop for SealedReader<R> {
+    fn drop(&mut self) {
+        self.key.key.fill(0);
+        self.plaintext.fill(0);
+    }
+}
+
+fn chunk_nonce(index: u64, last: bool) -> Nonce {
+    let mut nonce = [0_u8; AESGCM_IV_SIZE];
+    nonce[..8].copy_from_slice(&index.to_le_bytes());
+    nonce[AESGCM_IV_SIZE - 1] = last as u8;
+    Nonce::from(nonce)
+}
@@ -0,0 +1,134 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License..
+use super::{SealedReader, SealedWriter, STREAM_FORMAT_VERSION, STREAM_HEADER_SIZE};
+use crate::seal::SealedData;
+use alloc::vec::Vec;
+use sgx_test_utils::test_case;
+use sgx_types::error::SgxStatus;
+use sgx_types::types::{KeyPolicy, SEAL_TAG_SIZE};
+
+const CHUNK_SIZE: u32 = 16;
+
+fn seal_stream(data: &[u8]) -> Vec<u8> {
+    let mut blob = Vec::new();
+    let mut writer =
+        SealedWriter::with_chunk_size(&mut blob, KeyPolicy::MRSIGNER, CHUNK_SIZE).unwrap();
+    // Uneven writes so that chunk boundaries fall inside a single call.
+    for part in data.chunks(7) {
+        writer.write(part).unwrap();
+    }
+    writer.finish().unwrap();
+    blob
+}
+
+fn unseal_stream(blob: &[u8], buf_len: usize) -> Result<Vec<u8>, SgxStatus> {
+    let (header, chunks) = blob.split_at(STREAM_HEADER_SIZE);
+    let mut reader = SealedReader::open(header, chunks)?;
+    let mut buf = vec![0_u8; buf_len];
+    let mut out = Vec::new();
+    loop {
+        let n = reader.read(&mut buf)?;
+        if n == 0 {
+            return Ok(out);
+        }
+        out.extend_from_slice(&buf[..n]);
+    }
+}
+
+fn message(len: usize) -> Vec<u8> {
+    (0..len).map(|i| i as u8).collect()
+}
+
+#[test_case]
+fn stream_round_trip() {
+    for len in [0, 1, 15, 16, 17, 32, 100] {
+        let data = message(len);
+        let blob = seal_stream(&data);
+
+        let chunks = len / CHUNK_SIZE as usize + 1;
+        assert_eq!(
+            blob.len(),
+            STREAM_HEADER_SIZE + len + chunks * SEAL_TAG_SIZE
+        );
+        assert_eq!(blob[0], STREAM_FORMAT_VERSION);
+
+        for buf_len in [1, 5, 16, 64] {
+            assert_eq!(unseal_stream(&blob, buf_len).unwrap(), data);
+        }
+    }
+}
+
+#[test_case]
+fn stream_rejects_tampering() {
+    let data = message(40);
+    let blob = seal_stream(&data);
+    let chunk_len = CHUNK_SIZE as usize + SEAL_TAG_SIZE;
+
+    // Only the verified first chunk is handed out before the error.
+    let mut bad = blob.clone();
+    bad[STREAM_HEADER_SIZE + chunk_len] ^= 1;
+    let (header, chunks) = bad.split_at(STREAM_HEADER_SIZE);
+    let mut reader = SealedReader::open(header, chunks).unwrap();
+    let mut buf = [0_u8; 64];
+    assert_eq!(reader.read(&mut buf), Ok(CHUNK_SIZE as usize));
+    assert_eq!(&buf[..CHUNK_SIZE as usize], &data[..CHUNK_SIZE as usize]);
+    assert_eq!(reader.read(&mut buf), Err(SgxStatus::MacMismatch));
+    assert_eq!(reader.read(&mut buf), Err(SgxStatus::InvalidState));
+
+    let mut bad = blob.clone();
+    bad[10] ^= 1;
+    assert!(unseal_stream(&bad, 64).is_err());
+
+    let mut swapped = blob[..STREAM_HEADER_SIZE].to_vec();
+    swapped.extend_from_slice(&blob[STREAM_HEADER_SIZE + chunk_len..][..chunk_len]);
+    swapped.extend_from_slice(&blob[STREAM_HEADER_SIZE..][..chunk_len]);
+    swapped.extend_from_slice(&blob[STREAM_HEADER_SIZE + 2 * chunk_len..]);
+    assert_eq!(unseal_stream(&swapped, 64), Err(SgxStatus::MacMismatch));
+}
+
+#[test_case]
+fn stream_rejects_truncation() {
+    let data = message(32);
+    let blob = seal_stream(&data);
+    let chunk_len = CHUNK_SIZE as usize + SEAL_TAG_SIZE;
+
+    // Dropping the empty last chunk leaves only full chunks behind.
+    let cut = &blob[..STREAM_HEADER_SIZE + 2 * chunk_len];
+    assert_eq!(unseal_stream(cut, 64), Err(SgxStatus::InvalidParameter));
+
+    let cut = &blob[..STREAM_HEADER_SIZE + chunk_len + SEAL_TAG_SIZE + 4];
+    assert_eq!(unseal_stream(cut, 64), Err(SgxStatus::MacMismatch));
+}
+
+#[test_case]
+fn single_shot_blob_is_not_a_stream() {
+    let data = message(STREAM_HEADER_SIZE);
+    let blob = SealedData::<[u8]>::seal(&data, None)
+        .unwrap()
+        .to_bytes()
+        .unwrap();
+
+    assert_eq!(
+        SealedReader::open(&blob[..STREAM_HEADER_SIZE], &blob[STREAM_HEADER_SIZE..]).err(),
+        Some(SgxStatus::InvalidVersion)
+    );
+    let unsealed = SealedData::<[u8]>::from_slice(&blob)
+        .unwrap()
+        .unseal()
+        .unwrap();
+    assert_eq!(unsealed.to_plaintext(), &data[..]);
+}
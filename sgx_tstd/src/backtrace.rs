@@ -45,6 +45,11 @@
 //! platform supports capturing backtraces you can consult the `BacktraceStatus`
 //! enum as a result of `Backtrace::status`.
 //!
+//! Inside an enclave, frames are found by walking the enclave stack and are
+//! symbolized against the image at `Enclave::get_path`. When that isn't
+//! available, `BacktraceFrame::enclave_offset` gives addresses relative to
+//! the enclave base for offline symbolization.
+//!
 //! Like above with accuracy platform support is done on a best effort basis.
 //! Sometimes libraries might not be available at runtime or something may go
 //! wrong which would cause a backtrace to not be captured. Please feel free to
@@ -97,6 +102,8 @@ use crate::sys_common::backtrace::{
 };
 use crate::vec::Vec;
 
+use sgx_trts::trts::MmLayout;
+
 /// A captured OS thread stack backtrace.
 ///
 /// This type represents a stack backtrace for an OS thread captured at a
@@ -191,6 +198,24 @@ impl fmt::Debug for Backtrace {
     }
 }
 
+impl BacktraceFrame {
+    /// Returns this frame's instruction pointer as an offset from the
+    /// enclave image base.
+    ///
+    /// Symbols can be resolved offline by looking the offset up in the
+    /// signed enclave image, e.g. with `addr2line -e enclave.signed.so`.
+    #[must_use]
+    pub fn enclave_offset(&self) -> usize {
+        match &self.frame {
+            RawFrame::Actual(frame) => {
+                (frame.ip() as usize).wrapping_sub(MmLayout::image_base())
+            }
+            #[cfg(feature = "unit_test")]
+            RawFrame::Fake => 0,
+        }
+    }
+}
+
 impl fmt::Debug for BacktraceFrame {
     fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
         let mut dbg = fmt.debug_list();
@@ -280,9 +305,6 @@ impl Backtrace {
     /// parts of code.
     #[inline(never)] // want to make sure there's a frame here to remove
     pub fn force_capture() -> Backtrace {
-        if !Backtrace::enabled() {
-            return Backtrace { inner: Inner::Disabled };
-        }
         Backtrace::create(Backtrace::force_capture as usize)
     }
 
@@ -118,3 +118,19 @@ fn backtrace_unwind_safe() {
     fn assert_unwind_safe<T: UnwindSafe + RefUnwindSafe>() {}
     assert_unwind_safe::<Backtrace>();
 }
+
+#[test_case]
+fn force_capture_frames_within_enclave() {
+    let backtrace = Backtrace::force_capture();
+    assert_eq!(backtrace.status(), BacktraceStatus::Captured);
+
+    let frames = backtrace.frames();
+    assert!(!frames.is_empty());
+
+    let image_size = MmLayout::image_size();
+    let mut ips = frames.iter().filter(|frame| match &frame.frame {
+        RawFrame::Actual(frame) => !frame.ip().is_null(),
+        RawFrame::Fake => false,
+    });
+    assert!(ips.all(|frame| frame.enclave_offset() < image_size));
+}
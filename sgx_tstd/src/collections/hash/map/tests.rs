@@ -1110,3 +1110,20 @@ fn const_with_hasher() {
     const X: HashMap<(), (), ()> = HashMap::with_hasher(());
     assert_eq!(X.len(), 0);
 }
+
+#[test_case]
+fn test_random_state_seeded_per_thread() {
+    use crate::hash::BuildHasher;
+    use crate::thread;
+
+    // Each thread draws fresh keys from the enclave RNG, so maps built on
+    // different threads should hash the same key differently.
+    let hash_on_new_thread = || {
+        thread::spawn(|| HashMap::<u32, u32>::new().hasher().hash_one(0xdead_beef_u32))
+            .join()
+            .unwrap()
+    };
+    let a = hash_on_new_thread();
+    let b = hash_on_new_thread();
+    assert_ne!(a, b);
+}
@@ -275,6 +275,35 @@ fn _var_os(key: &OsStr) -> Option<OsString> {
         .unwrap_or_else(|e| panic!("failed to get environment variable `{key:?}`: {e}"))
 }
 
+/// Makes the listed keys visible to [`var`], [`var_os`], [`vars`] and
+/// [`vars_os`].
+///
+/// The environment is supplied by the untrusted host, so variables not in
+/// the allowlist are reported as not present, as are values longer than
+/// 4096 bytes. Until a key is registered no variable is visible.
+/// Registrations accumulate, so several crates may each list the keys they
+/// read.
+///
+/// # Examples
+///
+/// ```
+/// use std::env;
+///
+/// std::sgx_env_allow!("MY_CONFIG", "RUST_BACKTRACE");
+/// assert_eq!(env::var("PATH"), Err(env::VarError::NotPresent));
+/// ```
+#[macro_export]
+macro_rules! sgx_env_allow {
+    ($($key:literal),+ $(,)?) => {
+        $crate::env::__allow_vars(&[$($key),+])
+    };
+}
+
+#[doc(hidden)]
+pub fn __allow_vars(keys: &'static [&'static str]) {
+    os_imp::allow_env(keys)
+}
+
 /// The error type for operations interacting with environment variables.
 /// Possibly returned from [`env::var()`].
 ///
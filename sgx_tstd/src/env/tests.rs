@@ -100,3 +100,31 @@ fn vars_os_debug() {
         format!("{:?}", vars_os())
     );
 }
+
+#[test_case]
+fn env_allowlist() {
+    set_var("SGX_ENV_ALLOWED", "yes");
+    set_var("SGX_ENV_BLOCKED", "no");
+    set_var("SGX_ENV_LONG", "x".repeat(os_imp::ENV_VALUE_MAX + 1));
+
+    os_imp::clear_env_allowlist();
+    assert_eq!(var("SGX_ENV_ALLOWED"), Err(VarError::NotPresent));
+    assert_eq!(vars().count(), 0);
+
+    crate::sgx_env_allow!("SGX_ENV_ALLOWED", "SGX_ENV_LONG");
+
+    assert_eq!(var("SGX_ENV_ALLOWED"), Ok("yes".to_string()));
+    assert_eq!(var("SGX_ENV_BLOCKED"), Err(VarError::NotPresent));
+    assert_eq!(var("SGX_ENV_LONG"), Err(VarError::NotPresent));
+    assert!(var_os("SGX_ENV_BLOCKED").is_none());
+
+    let keys: Vec<String> = vars().map(|(k, _)| k).collect();
+    assert_eq!(keys, ["SGX_ENV_ALLOWED"]);
+
+    os_imp::clear_env_allowlist();
+    assert_eq!(var("SGX_ENV_ALLOWED"), Err(VarError::NotPresent));
+
+    remove_var("SGX_ENV_ALLOWED");
+    remove_var("SGX_ENV_BLOCKED");
+    remove_var("SGX_ENV_LONG");
+}
@@ -142,6 +142,35 @@ fn file_test_io_non_positional_read() {
     assert_eq!(read_str, message);
 }
 
+#[test_case]
+fn file_test_io_vectored() {
+    use crate::io::{IoSlice, IoSliceMut};
+
+    let tmpdir = tmpdir();
+    let filename = &tmpdir.join("file_rt_io_file_test_vectored.txt");
+    {
+        let mut file = check!(File::create(filename));
+        assert!(file.is_write_vectored());
+        let bufs = [IoSlice::new(b"head-"), IoSlice::new(b"body-"), IoSlice::new(b"tail")];
+        assert_eq!(check!(file.write_vectored(&bufs)), 14);
+    }
+    {
+        let mut file = check!(File::open(filename));
+        let mut contents = String::new();
+        check!(file.read_to_string(&mut contents));
+        assert_eq!(contents, "head-body-tail");
+    }
+    {
+        let mut file = check!(File::open(filename));
+        assert!(file.is_read_vectored());
+        let (mut a, mut b) = ([0; 5], [0; 9]);
+        let mut bufs = [IoSliceMut::new(&mut a), IoSliceMut::new(&mut b)];
+        assert_eq!(check!(file.read_vectored(&mut bufs)), 14);
+        assert_eq!(&a, b"head-");
+        assert_eq!(&b, b"body-tail");
+    }
+}
+
 #[allow(clippy::unnecessary_cast)]
 #[test_case]
 fn file_test_io_seek_and_tell_smoke_test() {
@@ -361,7 +361,10 @@ pub enum ErrorKind {
     #[doc(hidden)]
     Uncategorized,
 
-    /// SGX error status
+    /// An SGX error status with no closer `ErrorKind`.
+    ///
+    /// Statuses such as `SgxStatus::InvalidParameter` or
+    /// `SgxStatus::OutOfMemory` report the matching kind instead.
     SgxError,
 }
 
@@ -914,7 +917,7 @@ impl Error {
             ErrorData::Custom(c) => c.kind,
             ErrorData::Simple(kind) => kind,
             ErrorData::SimpleMessage(m) => m.kind,
-            ErrorData::SgxStatus(..) => ErrorKind::SgxError,
+            ErrorData::SgxStatus(status) => sys::decode_sgx_error_kind(status),
         }
     }
 
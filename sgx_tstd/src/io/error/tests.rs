@@ -212,3 +212,19 @@ fn test_std_io_error_downcast() {
     assert_eq!(SIMPLE_MESSAGE.kind, io_error.kind());
     assert_eq!(SIMPLE_MESSAGE.message, format!("{io_error}"));
 }
+
+#[test_case]
+fn test_sgx_status_kind() {
+    use sgx_types::error::SgxStatus;
+
+    fn sgx_kind(status: SgxStatus) -> ErrorKind {
+        let error: Error = status.into();
+        assert_eq!(error.raw_sgx_error(), Some(status));
+        error.kind()
+    }
+
+    assert_eq!(sgx_kind(SgxStatus::InvalidParameter), ErrorKind::InvalidInput);
+    assert_eq!(sgx_kind(SgxStatus::OutOfMemory), ErrorKind::OutOfMemory);
+    assert_eq!(sgx_kind(SgxStatus::MacMismatch), ErrorKind::InvalidData);
+    assert_eq!(sgx_kind(SgxStatus::EnclaveLost), ErrorKind::SgxError);
+}
@@ -900,3 +900,38 @@ fn connect_timeout_valid() {
     let addr = listener.local_addr().unwrap();
     TcpStream::connect_timeout(&addr, Duration::from_secs(2)).unwrap();
 }
+
+#[test_case]
+fn echo_round_trip() {
+    each_ip(&mut |addr| {
+        let srv = t!(TcpListener::bind(addr));
+        let t = thread::spawn(move || {
+            let mut cl = t!(srv.accept()).0;
+            let mut buf = [0; 4096];
+            loop {
+                let n = t!(cl.read(&mut buf));
+                if n == 0 {
+                    break;
+                }
+                t!(cl.write_all(&buf[..n]));
+            }
+        });
+
+        // Large enough that both directions go through several send/recv
+        // ocalls and host buffer copies.
+        let data: Vec<u8> = (0..256 * 1024).map(|i| (i % 251) as u8).collect();
+        let mut s = t!(TcpStream::connect(addr));
+        let mut reader = t!(s.try_clone());
+        let expected = data.clone();
+        let r = thread::spawn(move || {
+            let mut echoed = vec![0; expected.len()];
+            t!(reader.read_exact(&mut echoed));
+            assert!(echoed == expected);
+        });
+
+        t!(s.write_all(&data));
+        t!(s.shutdown(Shutdown::Write));
+        r.join().unwrap();
+        t.join().unwrap();
+    })
+}
@@ -378,3 +378,22 @@ fn set_nonblocking() {
         }
     })
 }
+
+#[test_case]
+fn recv_from_truncates_to_buffer() {
+    each_ip(&mut |server_ip, client_ip| {
+        let server = t!(UdpSocket::bind(server_ip));
+        let client = t!(UdpSocket::bind(client_ip));
+
+        let datagram: Vec<u8> = (0..1200).map(|i| i as u8).collect();
+        t!(client.send_to(&datagram, server_ip));
+
+        // The host copy is bounded by the enclave buffer, the rest of the
+        // datagram is discarded as with a native socket.
+        let mut buf = [0; 100];
+        let (n, src) = t!(server.recv_from(&mut buf));
+        assert_eq!(n, buf.len());
+        assert_eq!(&buf[..], &datagram[..100]);
+        assert_eq!(src, client_ip);
+    })
+}
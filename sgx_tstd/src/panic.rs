@@ -286,10 +286,27 @@ pub fn set_backtrace_style(style: BacktraceStyle) {
 /// * Other values are currently `BacktraceStyle::Short`, but this may change in
 ///   the future
 ///
-/// Returns `None` if backtraces aren't currently supported.
+/// Returns `None` if backtraces aren't currently supported, or if neither
+/// a style nor `RUST_BACKTRACE` has been set.
 #[cfg(feature = "backtrace")]
 pub fn get_backtrace_style() -> Option<BacktraceStyle> {
-    BacktraceStyle::from_usize(SHOULD_CAPTURE.load(Ordering::Acquire))
+    if let Some(style) = BacktraceStyle::from_usize(SHOULD_CAPTURE.load(Ordering::Acquire)) {
+        return Some(style);
+    }
+
+    // The environment may be uninitialized or filtered by `sgx_env_allow!`,
+    // so a missing variable leaves the style unset rather than failing.
+    let format = crate::sys::os::getenv(crate::ffi::OsStr::new("RUST_BACKTRACE")).ok().flatten().map(|x| {
+        if &x == "0" {
+            BacktraceStyle::Off
+        } else if &x == "full" {
+            BacktraceStyle::Full
+        } else {
+            BacktraceStyle::Short
+        }
+    })?;
+    set_backtrace_style(format);
+    Some(format)
 }
 
 #[cfg(feature = "unit_test")]
@@ -73,3 +73,37 @@ fn panic_safety_traits() {
         assert::<Arc<AssertUnwindSafe<T>>>();
     }
 }
+
+#[test_case]
+fn panic_hook_observes_message_and_location() {
+    use crate::panic::{self, PanicInfo};
+    use crate::thread;
+
+    static SEEN: Mutex<Option<(String, String, u32)>> = Mutex::new(None);
+
+    panic::set_hook(Box::new(|info: &PanicInfo<'_>| {
+        let payload = info.payload();
+        let msg = match payload.downcast_ref::<&str>() {
+            Some(s) => s.to_string(),
+            None => payload.downcast_ref::<String>().cloned().unwrap_or_default(),
+        };
+        let location = info.location().unwrap();
+        *SEEN.lock().unwrap() = Some((msg, location.file().to_string(), location.line()));
+    }));
+
+    let line = thread::spawn(|| {
+        let line = line!() + 1;
+        let result = panic::catch_unwind(|| panic!("hooked panic {}", 42));
+        assert!(result.is_err());
+        line
+    })
+    .join()
+    .unwrap();
+
+    drop(panic::take_hook());
+
+    let (msg, file, seen_line) = SEEN.lock().unwrap().take().unwrap();
+    assert_eq!(msg, "hooked panic 42");
+    assert_eq!(file, file!());
+    assert_eq!(seen_line, line);
+}
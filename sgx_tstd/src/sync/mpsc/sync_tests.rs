@@ -690,3 +690,37 @@ fn drop_unreceived() {
     assert!(weak.upgrade().is_none());
     drop(tx);
 }
+
+#[test_case]
+fn send_blocks_until_drained() {
+    use crate::sync::atomic::{AtomicBool, Ordering};
+    use crate::sync::Arc;
+
+    let (tx, rx) = sync_channel::<i32>(1);
+    let sent = Arc::new(AtomicBool::new(false));
+    let sent2 = sent.clone();
+    let t = thread::spawn(move || {
+        tx.send(1).unwrap();
+        tx.send(2).unwrap();
+        sent2.store(true, Ordering::SeqCst);
+    });
+
+    // The first message fills the buffer; the second send must park.
+    thread::sleep(Duration::from_millis(100));
+    assert!(!sent.load(Ordering::SeqCst));
+
+    assert_eq!(rx.recv().unwrap(), 1);
+    assert_eq!(rx.recv().unwrap(), 2);
+    t.join().unwrap();
+    assert!(sent.load(Ordering::SeqCst));
+}
+
+#[test_case]
+fn try_send_full_at_capacity() {
+    let (tx, rx) = sync_channel::<i32>(1);
+    tx.try_send(1).unwrap();
+    assert_eq!(tx.try_send(2), Err(TrySendError::Full(2)));
+    assert_eq!(rx.recv().unwrap(), 1);
+    tx.try_send(3).unwrap();
+    assert_eq!(rx.recv().unwrap(), 3);
+}
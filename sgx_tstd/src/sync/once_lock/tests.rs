@@ -216,3 +216,26 @@ fn dropck() {
         cell.set(&s).unwrap();
     }
 }
+
+#[test_case]
+fn get_or_init_races_run_initializer_once() {
+    static CELL: OnceLock<usize> = OnceLock::new();
+    static INIT_CALLS: AtomicUsize = AtomicUsize::new(0);
+
+    let threads: Vec<_> = (0..8)
+        .map(|_| {
+            thread::spawn(|| {
+                *CELL.get_or_init(|| {
+                    thread::yield_now();
+                    INIT_CALLS.fetch_add(1, SeqCst) + 42
+                })
+            })
+        })
+        .collect();
+
+    for t in threads {
+        assert_eq!(t.join().unwrap(), 42);
+    }
+    assert_eq!(INIT_CALLS.load(SeqCst), 1);
+    assert_eq!(CELL.get(), Some(&42));
+}
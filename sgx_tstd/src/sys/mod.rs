@@ -21,6 +21,7 @@ use crate::io::ErrorKind;
 use sgx_oc::ocall::{self, OCallResult};
 use sgx_oc as libc;
 use sgx_trts::error::abort;
+use sgx_types::error::SgxStatus;
 
 pub use self::rand::hashmap_random_keys;
 
@@ -125,6 +126,18 @@ pub fn decode_error_kind(errno: i32) -> ErrorKind {
     }
 }
 
+pub fn decode_sgx_error_kind(status: SgxStatus) -> ErrorKind {
+    use ErrorKind::*;
+    match status {
+        SgxStatus::InvalidParameter => InvalidInput,
+        SgxStatus::OutOfMemory => OutOfMemory,
+        SgxStatus::UnsupportedFeature => Unsupported,
+        SgxStatus::MacMismatch => InvalidData,
+        SgxStatus::ServiceTimeout => TimedOut,
+        _ => SgxError,
+    }
+}
+
 pub type RawOsError = i32;
 
 #[doc(hidden)]
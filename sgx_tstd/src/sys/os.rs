@@ -26,6 +26,7 @@ use crate::iter;
 use crate::path;
 use crate::path::PathBuf;
 use crate::slice;
+use crate::sync::RwLock;
 #[cfg(feature = "env")]
 use crate::sys::common::small_c_string::run_path_with_cstr;
 use crate::sys::common::small_c_string::run_with_cstr;
@@ -41,6 +42,11 @@ use sgx_types::metadata::SE_PAGE_SIZE;
 const TMPBUF_SZ: usize = 128;
 const PATH_SEPARATOR: u8 = b':';
 
+/// Longest environment value, in bytes, handed out once an allowlist is set.
+pub const ENV_VALUE_MAX: usize = 4096;
+
+static ENV_ALLOWLIST: RwLock<Vec<&'static str>> = RwLock::new(Vec::new());
+
 #[inline]
 pub fn errno() -> i32 {
     trts_error::errno()
@@ -221,8 +227,10 @@ pub fn env() -> Env {
         let mut result = Vec::new();
 
         for var in environ {
-            if let Some(key_value) = parse(var.as_bytes()) {
-                result.push(key_value);
+            if let Some((key, value)) = parse(var.as_bytes()) {
+                if env_filter(&key, &value) {
+                    result.push((key, value));
+                }
             }
         }
         return Env { iter: result.into_iter() };
@@ -252,8 +260,30 @@ pub fn getenv(k: &OsStr) -> io::Result<Option<OsString>> {
     let k = CString::new(k.as_bytes())?;
     unsafe {
         let result_opt = cvt_ocall(libc::getenv(&k));
-        result_opt.map(|opt| opt.map(|v| OsString::from_vec(v.into_bytes())))
+        result_opt.map(|opt| {
+            opt.map(|v| OsString::from_vec(v.into_bytes()))
+                .filter(|v| env_filter(OsStr::from_bytes(k.as_bytes()), v))
+        })
+    }
+}
+
+pub fn allow_env(keys: &'static [&'static str]) {
+    ENV_ALLOWLIST.write().unwrap_or_else(|e| e.into_inner()).extend_from_slice(keys);
+}
+
+#[cfg(feature = "unit_test")]
+pub fn clear_env_allowlist() {
+    ENV_ALLOWLIST.write().unwrap_or_else(|e| e.into_inner()).clear();
+}
+
+// Only listed keys with values no longer than `ENV_VALUE_MAX` pass, so
+// with no allowlist registered no variable is visible.
+fn env_filter(key: &OsStr, value: &OsStr) -> bool {
+    if value.len() > ENV_VALUE_MAX {
+        return false;
     }
+    let allowlist = ENV_ALLOWLIST.read().unwrap_or_else(|e| e.into_inner());
+    allowlist.iter().any(|k| k.as_bytes() == key.as_bytes())
 }
 
 pub fn setenv(k: &OsStr, v: &OsStr) -> io::Result<()> {
@@ -15,6 +15,8 @@
 // specific language governing permissions and limitations
 // under the License..
 
+use crate::cell::Cell;
+use crate::cmp;
 use crate::fmt;
 use crate::time::Duration;
 
@@ -174,9 +176,25 @@ pub struct Instant {
     t: Timespec,
 }
 
+// The monotonic clock is read from the host, which may run it backwards.
+// Each thread remembers the latest reading it has handed out and clamps to it.
+#[thread_local]
+static LAST_INSTANT: Cell<Timespec> = Cell::new(Timespec::zero());
+
 impl Instant {
     pub fn now() -> Instant {
-        Instant { t: Timespec::now(libc::CLOCK_MONOTONIC) }
+        Instant::from_host(Timespec::now(libc::CLOCK_MONOTONIC))
+    }
+
+    fn from_host(t: Timespec) -> Instant {
+        let t = cmp::max(t, LAST_INSTANT.get());
+        LAST_INSTANT.set(t);
+        Instant { t }
+    }
+
+    #[cfg(feature = "unit_test")]
+    pub(crate) fn from_host_reading(tv_sec: i64, tv_nsec: i64) -> Instant {
+        Instant::from_host(Timespec::new(tv_sec, tv_nsec))
     }
 
     pub fn checked_sub_instant(&self, other: &Instant) -> Option<Duration> {
@@ -139,6 +139,12 @@
 //! * The thread name is provided to the OS where applicable (e.g., `pthread_setname_np` in
 //!   unix-like platforms).
 //!
+//! ## Stack size
+//!
+//! Every enclave thread runs on the stack of the TCS it is bound to, whose size is fixed by
+//! `StackMaxSize` in the enclave configuration. [`Builder::stack_size`] cannot grow it: a
+//! request up to that size is accepted, and a larger one makes [`Builder::spawn`] fail.
+//!
 //! Note that the stack size of the main thread is *not* determined by Rust.
 //!
 //! [channels]: crate::sync::mpsc
@@ -265,6 +271,8 @@ pub mod local_impl {
 pub struct Builder {
     // A name for the thread-to-be, for identification in panic messages
     name: Option<String>,
+    // The minimum stack size the thread-to-be needs
+    stack_size: Option<usize>,
 }
 
 #[cfg(feature = "thread")]
@@ -288,7 +296,7 @@ impl Builder {
     /// ```
     pub fn new() -> Builder {
         assert!(tcs_policy() == TcsPolicy::Bind, "The TCS policy must be Bound!");
-        Builder { name: None }
+        Builder { name: None, stack_size: None }
     }
 
     /// Names the thread-to-be. Currently the name is used for identification
@@ -320,10 +328,28 @@ impl Builder {
         self
     }
 
-    /// Sets the size of the stack (in bytes) for the new thread.
-    /// just for API compatibility.
-    #[deprecated(note = "Setting the stack size for SGX thread is pointless.")]
-    pub fn stack_size(self, _size: usize) -> Builder {
+    /// Sets the size of the stack (in bytes) the new thread needs.
+    ///
+    /// The stack of an enclave thread comes from its TCS and is sized by the
+    /// enclave configuration, so this only checks the request: [`spawn`]
+    /// fails with [`io::ErrorKind::InvalidInput`] if `size` is larger than
+    /// the configured thread stack.
+    ///
+    /// For more information about the stack size for threads, see
+    /// [this module-level documentation][stack-size].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::thread;
+    ///
+    /// let builder = thread::Builder::new().stack_size(32 * 1024);
+    /// ```
+    ///
+    /// [`spawn`]: Builder::spawn
+    /// [stack-size]: ./index.html#stack-size
+    pub fn stack_size(mut self, size: usize) -> Builder {
+        self.stack_size = Some(size);
         self
     }
 
@@ -449,7 +475,14 @@ impl Builder {
         T: Send + 'a,
         'scope: 'a,
     {
-        let Builder { name } = self;
+        let Builder { name, stack_size } = self;
+
+        if stack_size.is_some_and(|size| size > sgx_trts::tcs::stack_size()) {
+            return Err(io::const_io_error!(
+                io::ErrorKind::InvalidInput,
+                "the requested stack size exceeds the enclave thread stack",
+            ));
+        }
 
         let my_thread = Thread::new(name.map(|name| {
             CString::new(name).expect("thread name may not contain interior null bytes")
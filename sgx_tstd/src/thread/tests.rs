@@ -19,6 +19,7 @@
 #![allow(clippy::redundant_closure)]
 
 use super::Builder;
+use crate::hint::black_box;
 use crate::mem;
 use crate::sync::{
     atomic::{AtomicBool, Ordering},
@@ -57,6 +58,40 @@ fn test_named_thread() {
         .unwrap();
 }
 
+#[test_case]
+fn test_named_thread_with_stack_size() {
+    fn recurse(depth: usize) -> usize {
+        let frame = black_box([depth as u8; 256]);
+        if depth == 0 { frame[0] as usize } else { recurse(depth - 1) + frame[255] as usize }
+    }
+
+    // Stay well inside the configured stack while still recursing a few
+    // hundred frames deep.
+    let stack_size = sgx_trts::tcs::stack_size() / 2;
+    let depth = stack_size / 1024;
+    let sum = Builder::new()
+        .name("deep recursion".to_string())
+        .stack_size(stack_size)
+        .spawn(move || {
+            assert_eq!(thread::current().name(), Some("deep recursion"));
+            recurse(depth)
+        })
+        .unwrap()
+        .join()
+        .unwrap();
+    assert_eq!(sum, (1..=depth).map(|d| d as u8 as usize).sum::<usize>());
+}
+
+#[test_case]
+fn test_stack_size_too_large() {
+    let err = Builder::new()
+        .stack_size(sgx_trts::tcs::stack_size() + 1)
+        .spawn(|| {})
+        .err()
+        .unwrap();
+    assert_eq!(err.kind(), crate::io::ErrorKind::InvalidInput);
+}
+
 #[test_case]
 fn test_invalid_named_thread() {
     should_panic!(Builder::new().name("ada l\0velace".to_string()).spawn(|| {}));
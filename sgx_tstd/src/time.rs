@@ -159,6 +159,11 @@ pub use core::time::TryFromFloatSecsError;
 /// This workaround obscures programming errors where earlier and later instants are accidentally
 /// swapped. For this reason future rust versions may reintroduce panics.
 ///
+/// Inside an enclave the clock is read from the untrusted host, which can move it backwards.
+/// Each thread remembers the latest `Instant` it has returned and never returns an earlier one,
+/// so instants taken on one thread are monotonic. This does not make the clock accurate: the
+/// host still decides how fast it ticks, and can hold it still.
+///
 /// [tier 1]: https://doc.rust-lang.org/rustc/platform-support.html
 /// [`duration_since`]: Instant::duration_since
 /// [`elapsed`]: Instant::elapsed
@@ -281,3 +281,27 @@ bench_instant_threaded!(instant_contention_02_threads, 1);
 bench_instant_threaded!(instant_contention_04_threads, 3);
 bench_instant_threaded!(instant_contention_08_threads, 7);
 bench_instant_threaded!(instant_contention_16_threads, 15);
+
+#[test_case]
+fn instant_clamps_host_clock() {
+    use crate::sys::time::Instant as HostInstant;
+
+    // Run on a fresh thread so the readings below do not race with the
+    // real clock values other tests have seen.
+    crate::thread::spawn(|| {
+        let a = Instant(HostInstant::from_host_reading(1000, 500));
+        let b = Instant(HostInstant::from_host_reading(10, 0));
+        assert_eq!(b, a);
+        assert_eq!(b.checked_duration_since(a), Some(Duration::ZERO));
+
+        let c = Instant(HostInstant::from_host_reading(1002, 500));
+        assert_eq!(c.checked_duration_since(b), Some(Duration::from_secs(2)));
+
+        let d = Instant(HostInstant::from_host_reading(999, 999_999_999));
+        assert_eq!(d, c);
+        assert!(Instant::_now() >= d);
+        assert!(d.elapsed() >= Duration::ZERO);
+    })
+    .join()
+    .unwrap();
+}
@@ -107,6 +107,7 @@ impl_enum! {
         AttKeyUninitialized     = 0x0000_8003,      /* The platform quoting infrastructure does not have the attestation key available to generate quote. */
         InvaliedAttKeyCertData  = 0x0000_8004,      /* TThe data returned by the platform library's sgx_get_quote_config() is invalid. */
         INvaliedPlatfromCert    = 0x0000_8005,      /* The PCK Cert for the platform is not available. */
+        TcbOutOfDate            = 0x0000_8006,      /* The quote verified but the platform TCB level is out of date. */
 
         EnclaveCreateInterrupted = 0x0000_F001,     /* The ioctl for enclave_create unexpectedly failed with EINTR. */
     }
@@ -200,6 +201,7 @@ impl SgxStatus {
             SgxStatus::AttKeyUninitialized => "The platform quoting infrastructure does not have the attestation key available to generate quote.",
             SgxStatus::InvaliedAttKeyCertData => "The data returned by the platform library is invalid.",
             SgxStatus::INvaliedPlatfromCert => "The PCK Cert for the platform is not available.",
+            SgxStatus::TcbOutOfDate => "The quote verified but the platform TCB level is out of date.",
 
             SgxStatus::EnclaveCreateInterrupted => "The ioctl for enclave_create unexpectedly failed with EINTR.",
         }
@@ -285,6 +287,7 @@ impl SgxStatus {
             SgxStatus::AttKeyUninitialized => "AttKeyUninitialized",
             SgxStatus::InvaliedAttKeyCertData => "InvaliedAttKeyCertData",
             SgxStatus::INvaliedPlatfromCert => "INvaliedPlatfromCert",
+            SgxStatus::TcbOutOfDate => "TcbOutOfDate",
 
             SgxStatus::EnclaveCreateInterrupted => "EnclaveCreateInterrupted",
         }
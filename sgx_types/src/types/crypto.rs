@@ -25,6 +25,7 @@ use core::ops::{Deref, DerefMut};
 pub const SHA1_HASH_SIZE: usize = 20;
 pub const SHA256_HASH_SIZE: usize = 32;
 pub const SHA384_HASH_SIZE: usize = 48;
+pub const SHA512_HASH_SIZE: usize = 64;
 pub const SM3_HASH_SIZE: usize = 32;
 
 pub type ShaHandle = *mut c_void;
@@ -95,6 +96,12 @@ impl_copy_clone! {
     pub struct Sha384Hash {
         pub hash: [u8; SHA384_HASH_SIZE],
     }
+
+    #[repr(C)]
+    #[derive(Debug, Eq, PartialEq)]
+    pub struct Sha512Hash {
+        pub hash: [u8; SHA512_HASH_SIZE],
+    }
 }
 
 macro_rules! impl_hash_eq {
@@ -119,39 +126,45 @@ impl_hash_eq! {
     Sha1Hash;
     Sha256Hash;
     Sha384Hash;
+    Sha512Hash;
     Sm3Hash;
 }
 
 impl_struct_default! {
     Sha384Hash; //48
+    Sha512Hash; //64
 }
 
 impl_struct_ContiguousMemory! {
     Sha384Hash;
+    Sha512Hash;
 }
 
 impl_asref_array! {
     Sha1Hash;
     Sha256Hash;
     Sha384Hash;
+    Sha512Hash;
     Sm3Hash;
 }
 impl_asmut_array! {
     Sha1Hash;
     Sha256Hash;
     Sha384Hash;
+    Sha512Hash;
     Sm3Hash;
 }
 impl_from_array! {
     Sha1Hash;
     Sha256Hash;
     Sha384Hash;
+    Sha512Hash;
     Sm3Hash;
 }
 
 impl_unsafe_marker_for! {
     BytewiseEquality,
-    Sha1Hash Sha256Hash Sha384Hash Sm3Hash
+    Sha1Hash Sha256Hash Sha384Hash Sha512Hash Sm3Hash
 }
 
 macro_rules! impl_hash_deref {
@@ -188,6 +201,7 @@ impl_hash_deref! {
     Sha1Hash;
     Sha256Hash;
     Sha384Hash;
+    Sha512Hash;
     Sm3Hash;
 }
 
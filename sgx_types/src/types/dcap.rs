@@ -17,6 +17,7 @@
 
 use super::*;
 
+use crate::error::SgxStatus;
 use core::mem;
 use core::slice;
 
@@ -495,6 +496,60 @@ impl fmt::Display for QlQvResult {
     }
 }
 
+/// Maps a quote verification verdict to the closest `SgxStatus`:
+///
+/// | `QlQvResult`                 | `SgxStatus`         |
+/// |------------------------------|---------------------|
+/// | `Ok`                         | `Success`           |
+/// | `ConfigNeeded`               | `UnsupportedConfig` |
+/// | `OutOfDate`                  | `TcbOutOfDate`      |
+/// | `OutOfDateConfigNeeded`      | `TcbOutOfDate`      |
+/// | `InvalidSignature`           | `InvalidSignature`  |
+/// | `Revoked`                    | `InvalidSignature`  |
+/// | `SWHardeningNeeded`          | `UpdateNeeded`      |
+/// | `ConfigAndSWHardeningNeeded` | `UpdateNeeded`      |
+/// | `Unspecified`, `Max`         | `Unexpected`        |
+impl From<QlQvResult> for SgxStatus {
+    fn from(result: QlQvResult) -> SgxStatus {
+        match result {
+            QlQvResult::Ok => SgxStatus::Success,
+            QlQvResult::ConfigNeeded => SgxStatus::UnsupportedConfig,
+            QlQvResult::OutOfDate | QlQvResult::OutOfDateConfigNeeded => SgxStatus::TcbOutOfDate,
+            QlQvResult::InvalidSignature | QlQvResult::Revoked => SgxStatus::InvalidSignature,
+            QlQvResult::SWHardeningNeeded | QlQvResult::ConfigAndSWHardeningNeeded => {
+                SgxStatus::UpdateNeeded
+            }
+            QlQvResult::Unspecified | QlQvResult::Max => SgxStatus::Unexpected,
+        }
+    }
+}
+
+/// The inverse of `From<QlQvResult>` for statuses that name one verdict:
+///
+/// | `SgxStatus`         | `QlQvResult`        |
+/// |---------------------|---------------------|
+/// | `Success`           | `Ok`                |
+/// | `UnsupportedConfig` | `ConfigNeeded`      |
+/// | `TcbOutOfDate`      | `OutOfDate`         |
+/// | `InvalidSignature`  | `InvalidSignature`  |
+/// | `UpdateNeeded`      | `SWHardeningNeeded` |
+///
+/// Any other status is returned as the error.
+impl TryFrom<SgxStatus> for QlQvResult {
+    type Error = SgxStatus;
+
+    fn try_from(status: SgxStatus) -> Result<QlQvResult, SgxStatus> {
+        match status {
+            SgxStatus::Success => Ok(QlQvResult::Ok),
+            SgxStatus::UnsupportedConfig => Ok(QlQvResult::ConfigNeeded),
+            SgxStatus::TcbOutOfDate => Ok(QlQvResult::OutOfDate),
+            SgxStatus::InvalidSignature => Ok(QlQvResult::InvalidSignature),
+            SgxStatus::UpdateNeeded => Ok(QlQvResult::SWHardeningNeeded),
+            _ => Err(status),
+        }
+    }
+}
+
 /* intel DCAP 1.6 */
 //
 // sgx_dcap_quoteverify.h
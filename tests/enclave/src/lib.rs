@@ -21,6 +21,14 @@
 #[macro_use]
 extern crate sgx_tstd as std;
 extern crate sgx_types;
+extern crate sgx_crypto;
+extern crate sgx_dcap_ra_msg;
+extern crate sgx_dcap_tkey_exchange;
+extern crate sgx_dcap_tvl;
+extern crate sgx_tprotected_fs;
+extern crate sgx_rand;
+extern crate sgx_trts;
+extern crate sgx_tseal;
 
 use std::backtrace::{self, PrintFormat};
 use sgx_types::error::SgxStatus;